@@ -0,0 +1,165 @@
+//! Procedural macros of the [asyncapiv3](https://docs.rs/asyncapiv3) crate, re-exported by it
+//! behind the `macros` feature.
+mod shape;
+
+use proc_macro::TokenStream;
+use quote::quote;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use syn::{parse_macro_input, LitStr};
+
+/// Includes an AsyncAPI document, in JSON or YAML, parsed and validated at compile time.
+///
+/// The path is resolved relatively to the file invoking the macro, like `include_str!`, and the
+/// crate is rebuilt when the document changes. The macro expands to a
+/// `LazyLock<AsyncApiV3Spec>`. Documents which cannot be read, are not AsyncAPI 3.0 documents,
+/// miss a required field, have a field of the wrong type, e.g. an unknown operation `action`,
+/// or contain unresolved local references fail the build with the validation report.
+///
+/// ```ignore
+/// use asyncapiv3::asyncapi_include;
+/// use asyncapiv3::spec::AsyncApiV3Spec;
+/// use std::sync::LazyLock;
+///
+/// static ORDERS: LazyLock<AsyncApiV3Spec> = asyncapi_include!("specs/orders.yaml");
+/// ```
+#[proc_macro]
+pub fn asyncapi_include(input: TokenStream) -> TokenStream {
+    let literal = parse_macro_input!(input as LitStr);
+    let path = resolve_path(&literal);
+    match load(&path) {
+        Ok(json) => {
+            let path = path.to_string_lossy();
+            quote! {
+                ::std::sync::LazyLock::<::asyncapiv3::spec::AsyncApiV3Spec>::new(|| {
+                    // Rebuilds the crate when the document changes.
+                    const _: &str = ::core::include_str!(#path);
+                    let ::asyncapiv3::spec::AsyncApiSpec::V3_0_0(spec) = #json
+                        .parse::<::asyncapiv3::spec::AsyncApiSpec>()
+                        .unwrap_or_else(|err| ::core::panic!("AsyncAPI document `{}` checked at compile time: {}", #path, err));
+                    spec
+                })
+            }
+            .into()
+        }
+        Err(report) => syn::Error::new(
+            literal.span(),
+            format!("invalid AsyncAPI document `{}`:{report}", literal.value()),
+        )
+        .to_compile_error()
+        .into(),
+    }
+}
+
+/// Resolves the path relatively to the file invoking the macro, like `include_str!` does.
+fn resolve_path(literal: &LitStr) -> PathBuf {
+    let path = Path::new(&literal.value()).to_path_buf();
+    if path.is_absolute() {
+        return path;
+    }
+    let base = proc_macro::Span::call_site()
+        .local_file()
+        .and_then(|file| file.parent().map(Path::to_path_buf))
+        .or_else(|| std::env::var_os("CARGO_MANIFEST_DIR").map(PathBuf::from))
+        .unwrap_or_default();
+    // The file of the invocation is relative to the directory rustc runs in.
+    std::env::current_dir()
+        .map(|dir| dir.join(&base))
+        .unwrap_or(base)
+        .join(path)
+}
+
+/// Reads, parses and validates the document, returning its JSON representation or the
+/// validation report.
+fn load(path: &Path) -> Result<String, String> {
+    let source = std::fs::read_to_string(path).map_err(|err| format!(" {err}"))?;
+    // JSON being a subset of YAML, both formats are handled by the YAML parser.
+    let document = serde_yaml::from_str::<Value>(&source)
+        .map_err(|err| format!("\n  - Parsing error: {err}"))?;
+
+    let mut errors = Vec::new();
+    check_structure(&document, &mut errors);
+    collect_unresolved_references(&document, &document, &mut errors);
+    if errors.is_empty() {
+        Ok(document.to_string())
+    } else {
+        Err(errors
+            .into_iter()
+            .map(|err| format!("\n  - {err}"))
+            .collect())
+    }
+}
+
+/// Checks the AsyncAPI version of the document, then the fields of its objects and their type.
+fn check_structure(document: &Value, errors: &mut Vec<String>) {
+    let Some(fields) = document.as_object() else {
+        errors.push(String::from("The document is not a mapping"));
+        return;
+    };
+    match fields.get("asyncapi").and_then(Value::as_str) {
+        Some(version) if version.starts_with("3.0.") => {}
+        Some(version) => errors.push(format!("Unsupported AsyncAPI version '{version}'")),
+        None => errors.push(String::from("Missing field '/asyncapi'")),
+    }
+    shape::DOCUMENT.check(document, "", errors);
+}
+
+fn collect_unresolved_references(document: &Value, value: &Value, unresolved: &mut Vec<String>) {
+    match value {
+        Value::Object(fields) => {
+            if let Some(Value::String(reference)) = fields.get("$ref") {
+                let resolved = match reference.strip_prefix('#') {
+                    Some(pointer) => resolve(document, pointer).is_some(),
+                    // External references cannot be checked.
+                    None => true,
+                };
+                if !resolved {
+                    unresolved.push(format!("Unresolved reference '{reference}'"));
+                }
+            }
+            for value in fields.values() {
+                collect_unresolved_references(document, value, unresolved);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                collect_unresolved_references(document, item, unresolved);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Evaluates a JSON pointer, percent-encoded as in a URI fragment.
+fn resolve<'a>(document: &'a Value, pointer: &str) -> Option<&'a Value> {
+    if pointer.is_empty() {
+        return Some(document);
+    }
+    pointer
+        .strip_prefix('/')?
+        .split('/')
+        .map(|segment| percent_decode(&segment.replace("~1", "/").replace("~0", "~")))
+        .try_fold(document, |current, segment| match current {
+            Value::Object(fields) => fields.get(&segment?),
+            Value::Array(items) => items.get(segment?.parse::<usize>().ok()?),
+            _ => None,
+        })
+}
+
+fn percent_decode(segment: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(segment.len());
+    let mut rest = segment.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        match (byte, tail.get(..2)) {
+            (b'%', Some(digits)) => {
+                bytes.push(u8::from_str_radix(std::str::from_utf8(digits).ok()?, 16).ok()?);
+                rest = &tail[2..];
+            }
+            _ => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
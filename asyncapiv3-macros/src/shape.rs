@@ -0,0 +1,530 @@
+//! The shape of an AsyncAPI 3.0 document as read by asyncapiv3: the fields of each object and
+//! the type of their values.
+//!
+//! This crate cannot depend on the document model of asyncapiv3, which re-exports it, so the
+//! model is mirrored here. Fields the model does not read, e.g. extensions, are not checked.
+use serde_json::Value;
+
+/// The expected type of a value.
+pub enum Shape {
+    /// Any value, e.g. a message example.
+    Any,
+    String,
+    /// A string or a number, e.g. a protocol version.
+    StringOrNumber,
+    /// An integer between 1 and 65535, e.g. an HTTP status code.
+    StatusCode,
+    /// A schema, an object or a boolean.
+    Schema,
+    /// One of the listed strings.
+    Enum(&'static [&'static str]),
+    List(&'static Shape),
+    /// An object with values of the same shape, keyed by name.
+    Map(&'static Shape),
+    Object(&'static [Field]),
+    /// A reference object.
+    Reference,
+    /// A reference object or a value of the shape.
+    RefOr(&'static Shape),
+    /// A security scheme, whose fields depend on its `type`.
+    SecurityScheme,
+}
+
+/// A field of an object.
+pub struct Field {
+    name: &'static str,
+    shape: Shape,
+    presence: Presence,
+}
+
+enum Presence {
+    Required,
+    /// May be absent or `null`.
+    Optional,
+    /// May be absent, but not `null`.
+    Defaulted,
+}
+
+const fn required(name: &'static str, shape: Shape) -> Field {
+    Field {
+        name,
+        shape,
+        presence: Presence::Required,
+    }
+}
+
+const fn optional(name: &'static str, shape: Shape) -> Field {
+    Field {
+        name,
+        shape,
+        presence: Presence::Optional,
+    }
+}
+
+const fn defaulted(name: &'static str, shape: Shape) -> Field {
+    Field {
+        name,
+        shape,
+        presence: Presence::Defaulted,
+    }
+}
+
+const EXTERNAL_DOCS: Shape = Shape::Object(&[
+    optional("description", Shape::String),
+    required("url", Shape::String),
+]);
+
+const TAG: Shape = Shape::Object(&[
+    required("name", Shape::String),
+    optional("description", Shape::String),
+    optional("externalDocs", Shape::RefOr(&EXTERNAL_DOCS)),
+]);
+
+const INFO: Shape = Shape::Object(&[
+    required("title", Shape::String),
+    required("version", Shape::String),
+    optional("description", Shape::String),
+    optional("termsOfService", Shape::String),
+    optional(
+        "contact",
+        Shape::Object(&[
+            optional("name", Shape::String),
+            optional("url", Shape::String),
+            optional("email", Shape::String),
+        ]),
+    ),
+    optional(
+        "license",
+        Shape::Object(&[
+            required("name", Shape::String),
+            optional("url", Shape::String),
+        ]),
+    ),
+    defaulted("tags", Shape::List(&Shape::RefOr(&TAG))),
+    optional("externalDocs", Shape::RefOr(&EXTERNAL_DOCS)),
+]);
+
+/// A binding without modelled fields.
+const BINDING: Shape = Shape::Object(&[]);
+
+const SERVER_BINDINGS: Shape = Shape::Object(&[
+    optional("ws", BINDING),
+    optional("nats", BINDING),
+    optional("http", BINDING),
+]);
+
+const SERVER_VARIABLE: Shape = Shape::Object(&[
+    defaulted("enum", Shape::List(&Shape::String)),
+    optional("default", Shape::String),
+    optional("description", Shape::String),
+    defaulted("examples", Shape::List(&Shape::String)),
+]);
+
+const SERVER: Shape = Shape::Object(&[
+    required("host", Shape::String),
+    required("protocol", Shape::String),
+    optional("protocolVersion", Shape::StringOrNumber),
+    optional("pathname", Shape::String),
+    optional("description", Shape::String),
+    optional("title", Shape::String),
+    optional("summary", Shape::String),
+    defaulted("variables", Shape::Map(&Shape::RefOr(&SERVER_VARIABLE))),
+    defaulted("security", Shape::List(&Shape::SecurityScheme)),
+    defaulted("tags", Shape::List(&TAG)),
+    optional("externalDocs", Shape::RefOr(&EXTERNAL_DOCS)),
+    optional("bindings", Shape::RefOr(&SERVER_BINDINGS)),
+]);
+
+const PARAMETER: Shape = Shape::Object(&[
+    defaulted("enum", Shape::List(&Shape::String)),
+    optional("default", Shape::String),
+    optional("description", Shape::String),
+    defaulted("examples", Shape::List(&Shape::String)),
+    optional("location", Shape::String),
+]);
+
+const CHANNEL_BINDINGS: Shape = Shape::Object(&[
+    optional(
+        "ws",
+        Shape::Object(&[
+            optional("method", Shape::Enum(&["GET", "POST"])),
+            optional("query", Shape::Schema),
+            optional("headers", Shape::Schema),
+            optional("bindingVersion", Shape::String),
+        ]),
+    ),
+    optional("nats", BINDING),
+    optional("http", BINDING),
+]);
+
+const CHANNEL: Shape = Shape::Object(&[
+    optional("address", Shape::String),
+    required("messages", Shape::Map(&Shape::RefOr(&MESSAGE))),
+    optional("title", Shape::String),
+    optional("summary", Shape::String),
+    optional("description", Shape::String),
+    defaulted("servers", Shape::List(&Shape::Reference)),
+    defaulted("parameters", Shape::Map(&Shape::RefOr(&PARAMETER))),
+    defaulted("tags", Shape::List(&TAG)),
+    optional("externalDocs", Shape::RefOr(&EXTERNAL_DOCS)),
+    optional("bindings", Shape::RefOr(&CHANNEL_BINDINGS)),
+]);
+
+const OPERATION_BINDINGS: Shape = Shape::Object(&[
+    optional("ws", BINDING),
+    optional(
+        "nats",
+        Shape::Object(&[
+            required("queue", Shape::String),
+            optional("bindingVersion", Shape::String),
+        ]),
+    ),
+    optional(
+        "http",
+        Shape::Object(&[
+            required(
+                "method",
+                Shape::Enum(&[
+                    "GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS", "CONNECT", "TRACE",
+                ]),
+            ),
+            optional("query", Shape::Schema),
+            optional("bindingVersion", Shape::String),
+        ]),
+    ),
+]);
+
+const OPERATION_TRAIT: Shape = Shape::Object(&[
+    optional("title", Shape::String),
+    optional("summary", Shape::String),
+    optional("description", Shape::String),
+    optional("security", Shape::SecurityScheme),
+    defaulted("tags", Shape::List(&TAG)),
+    optional("externalDocs", Shape::RefOr(&EXTERNAL_DOCS)),
+    optional("bindings", Shape::RefOr(&OPERATION_BINDINGS)),
+]);
+
+const REPLY_ADDRESS: Shape = Shape::Object(&[
+    optional("description", Shape::String),
+    required("location", Shape::String),
+]);
+
+const REPLY: Shape = Shape::Object(&[
+    optional("address", Shape::RefOr(&REPLY_ADDRESS)),
+    optional("channel", Shape::Reference),
+    defaulted("messages", Shape::List(&Shape::Reference)),
+]);
+
+const OPERATION: Shape = Shape::Object(&[
+    required("action", Shape::Enum(&["send", "receive"])),
+    required("channel", Shape::Reference),
+    optional("title", Shape::String),
+    optional("summary", Shape::String),
+    optional("description", Shape::String),
+    defaulted("security", Shape::List(&Shape::SecurityScheme)),
+    defaulted("tags", Shape::List(&TAG)),
+    optional("externalDocs", Shape::RefOr(&EXTERNAL_DOCS)),
+    optional("bindings", Shape::RefOr(&OPERATION_BINDINGS)),
+    defaulted("traits", Shape::List(&Shape::RefOr(&OPERATION_TRAIT))),
+    optional("messages", Shape::List(&Shape::Reference)),
+    optional("reply", Shape::RefOr(&REPLY)),
+]);
+
+const CORRELATION_ID: Shape = Shape::Object(&[
+    optional("description", Shape::String),
+    required("location", Shape::String),
+]);
+
+const MESSAGE_BINDINGS: Shape = Shape::Object(&[
+    optional("ws", BINDING),
+    optional("nats", BINDING),
+    optional(
+        "http",
+        Shape::Object(&[
+            optional("headers", Shape::Schema),
+            optional("statusCode", Shape::StatusCode),
+            optional("bindingVersion", Shape::String),
+        ]),
+    ),
+]);
+
+const MESSAGE_EXAMPLE: Shape = Shape::Object(&[
+    defaulted("headers", Shape::Map(&Shape::Any)),
+    optional("payload", Shape::Any),
+    optional("name", Shape::String),
+    optional("summary", Shape::String),
+]);
+
+const MESSAGE_TRAIT: Shape = Shape::Object(&[
+    optional("headers", Shape::Schema),
+    optional("correlationId", Shape::RefOr(&CORRELATION_ID)),
+    optional("contentType", Shape::String),
+    optional("name", Shape::String),
+    optional("title", Shape::String),
+    optional("summary", Shape::String),
+    optional("description", Shape::String),
+    defaulted("tags", Shape::List(&TAG)),
+    optional("externalDocs", Shape::RefOr(&EXTERNAL_DOCS)),
+    optional("bindings", Shape::RefOr(&MESSAGE_BINDINGS)),
+    defaulted("examples", Shape::List(&MESSAGE_EXAMPLE)),
+]);
+
+const MESSAGE: Shape = Shape::Object(&[
+    optional("headers", Shape::Schema),
+    optional("payload", Shape::Schema),
+    optional("correlationId", Shape::RefOr(&CORRELATION_ID)),
+    optional("contentType", Shape::String),
+    optional("name", Shape::String),
+    optional("title", Shape::String),
+    optional("summary", Shape::String),
+    optional("description", Shape::String),
+    defaulted("tags", Shape::List(&TAG)),
+    optional("externalDocs", Shape::RefOr(&EXTERNAL_DOCS)),
+    optional("bindings", Shape::RefOr(&MESSAGE_BINDINGS)),
+    defaulted("examples", Shape::List(&MESSAGE_EXAMPLE)),
+    defaulted("traits", Shape::List(&Shape::RefOr(&MESSAGE_TRAIT))),
+]);
+
+const COMPONENTS: Shape = Shape::Object(&[
+    defaulted("schemas", Shape::Map(&Shape::Schema)),
+    defaulted("servers", Shape::Map(&Shape::RefOr(&SERVER))),
+    defaulted("channels", Shape::Map(&Shape::RefOr(&CHANNEL))),
+    defaulted("operations", Shape::Map(&Shape::RefOr(&OPERATION))),
+    defaulted("messages", Shape::Map(&Shape::RefOr(&MESSAGE))),
+    defaulted("securitySchemes", Shape::Map(&Shape::SecurityScheme)),
+    defaulted(
+        "serverVariables",
+        Shape::Map(&Shape::RefOr(&SERVER_VARIABLE)),
+    ),
+    defaulted("parameters", Shape::Map(&Shape::RefOr(&PARAMETER))),
+    defaulted("correlationIds", Shape::Map(&Shape::RefOr(&CORRELATION_ID))),
+    defaulted("replies", Shape::Map(&Shape::RefOr(&REPLY))),
+    defaulted("replyAddresses", Shape::Map(&Shape::RefOr(&REPLY_ADDRESS))),
+    defaulted("externalDocs", Shape::Map(&Shape::RefOr(&EXTERNAL_DOCS))),
+    defaulted("tags", Shape::Map(&Shape::RefOr(&TAG))),
+    defaulted(
+        "operationTraits",
+        Shape::Map(&Shape::RefOr(&OPERATION_TRAIT)),
+    ),
+    defaulted("messageTraits", Shape::Map(&Shape::RefOr(&MESSAGE_TRAIT))),
+    defaulted(
+        "serverBindings",
+        Shape::Map(&Shape::RefOr(&SERVER_BINDINGS)),
+    ),
+    defaulted(
+        "channelBindings",
+        Shape::Map(&Shape::RefOr(&CHANNEL_BINDINGS)),
+    ),
+    defaulted(
+        "operationBindings",
+        Shape::Map(&Shape::RefOr(&OPERATION_BINDINGS)),
+    ),
+    defaulted(
+        "messageBindings",
+        Shape::Map(&Shape::RefOr(&MESSAGE_BINDINGS)),
+    ),
+]);
+
+/// The shape of a whole document, but its `asyncapi` version.
+pub const DOCUMENT: Shape = Shape::Object(&[
+    optional("id", Shape::String),
+    required("info", INFO),
+    defaulted("servers", Shape::Map(&Shape::RefOr(&SERVER))),
+    optional("defaultContentType", Shape::String),
+    defaulted("channels", Shape::Map(&Shape::RefOr(&CHANNEL))),
+    defaulted("operations", Shape::Map(&Shape::RefOr(&OPERATION))),
+    defaulted("components", COMPONENTS),
+]);
+
+const REFERENCE: Shape = Shape::Object(&[
+    required("$ref", Shape::String),
+    optional("summary", Shape::String),
+    optional("description", Shape::String),
+]);
+
+const DESCRIBED_SCHEME: Shape = Shape::Object(&[optional("description", Shape::String)]);
+
+const SCOPES: Shape = Shape::Map(&Shape::String);
+
+const OAUTH2_FLOWS: Shape = Shape::Object(&[
+    optional(
+        "implicit",
+        Shape::Object(&[
+            required("authorizationUrl", Shape::String),
+            optional("refreshUrl", Shape::String),
+            required("availableScopes", SCOPES),
+        ]),
+    ),
+    optional(
+        "password",
+        Shape::Object(&[
+            required("tokenUrl", Shape::String),
+            optional("refreshUrl", Shape::String),
+            required("availableScopes", SCOPES),
+        ]),
+    ),
+    optional(
+        "clientCredentials",
+        Shape::Object(&[
+            required("tokenUrl", Shape::String),
+            optional("refreshUrl", Shape::String),
+            required("availableScopes", SCOPES),
+        ]),
+    ),
+    optional(
+        "authorizationCode",
+        Shape::Object(&[
+            required("authorizationUrl", Shape::String),
+            required("tokenUrl", Shape::String),
+            optional("refreshUrl", Shape::String),
+            required("availableScopes", SCOPES),
+        ]),
+    ),
+]);
+
+/// The shape of the security schemes of each known `type`. Schemes of other types are kept as
+/// written by the model, so they are not checked.
+const SECURITY_SCHEMES: &[(&str, Shape)] = &[
+    ("userPassword", DESCRIBED_SCHEME),
+    (
+        "apiKey",
+        Shape::Object(&[
+            optional("description", Shape::String),
+            required("in", Shape::Enum(&["user", "password"])),
+        ]),
+    ),
+    ("X509", DESCRIBED_SCHEME),
+    ("symmetricEncryption", DESCRIBED_SCHEME),
+    ("asymmetricEncryption", DESCRIBED_SCHEME),
+    (
+        "httpApiKey",
+        Shape::Object(&[
+            optional("description", Shape::String),
+            required("name", Shape::String),
+            required("in", Shape::Enum(&["query", "header", "cookie"])),
+        ]),
+    ),
+    (
+        "http",
+        Shape::Object(&[
+            optional("description", Shape::String),
+            required("scheme", Shape::String),
+            optional("bearerFormat", Shape::String),
+        ]),
+    ),
+    (
+        "oauth2",
+        Shape::Object(&[
+            optional("description", Shape::String),
+            required("flows", OAUTH2_FLOWS),
+            defaulted("scopes", Shape::List(&Shape::String)),
+        ]),
+    ),
+    (
+        "openIdConnect",
+        Shape::Object(&[
+            optional("description", Shape::String),
+            required("openIdConnectUrl", Shape::String),
+            defaulted("scopes", Shape::List(&Shape::String)),
+        ]),
+    ),
+    ("plain", DESCRIBED_SCHEME),
+    ("scramSha256", DESCRIBED_SCHEME),
+    ("scramSha512", DESCRIBED_SCHEME),
+    ("gssapi", DESCRIBED_SCHEME),
+];
+
+impl Shape {
+    /// Checks that `value`, found at `pointer`, has this shape, adding an error per mismatch.
+    pub fn check(&self, value: &Value, pointer: &str, errors: &mut Vec<String>) {
+        let mut mismatch = |expected: &str| {
+            errors.push(format!(
+                "Invalid value at '{pointer}': expected {expected}, found {}",
+                describe(value)
+            ))
+        };
+        match (self, value) {
+            (Shape::Any, _)
+            | (Shape::String, Value::String(_))
+            | (Shape::StringOrNumber, Value::String(_) | Value::Number(_))
+            | (Shape::Schema, Value::Object(_) | Value::Bool(_)) => {}
+            (Shape::String, _) => mismatch("a string"),
+            (Shape::StringOrNumber, _) => mismatch("a string or a number"),
+            (Shape::Schema, _) => mismatch("a schema"),
+            (Shape::StatusCode, Value::Number(number))
+                if number
+                    .as_u64()
+                    .is_some_and(|code| (1..=65535).contains(&code)) => {}
+            (Shape::StatusCode, _) => mismatch("an integer between 1 and 65535"),
+            (Shape::Enum(values), Value::String(string)) if values.contains(&string.as_str()) => {}
+            (Shape::Enum(values), _) => mismatch(&format!(
+                "one of {}",
+                values
+                    .iter()
+                    .map(|value| format!("`{value}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+            (Shape::List(item), Value::Array(items)) => {
+                for (index, value) in items.iter().enumerate() {
+                    item.check(value, &format!("{pointer}/{index}"), errors);
+                }
+            }
+            (Shape::List(_), _) => mismatch("a list"),
+            (Shape::Map(item), Value::Object(fields)) => {
+                for (key, value) in fields {
+                    item.check(value, &child(pointer, key), errors);
+                }
+            }
+            (Shape::Object(expected), Value::Object(fields)) => {
+                for field in *expected {
+                    let pointer = child(pointer, field.name);
+                    match (fields.get(field.name), &field.presence) {
+                        (None, Presence::Required) => {
+                            errors.push(format!("Missing field '{pointer}'"))
+                        }
+                        (None, _) | (Some(Value::Null), Presence::Optional) => {}
+                        (Some(value), _) => field.shape.check(value, &pointer, errors),
+                    }
+                }
+            }
+            (Shape::Map(_) | Shape::Object(_), _) => mismatch("a mapping"),
+            (Shape::Reference, _) => REFERENCE.check(value, pointer, errors),
+            (Shape::RefOr(_), Value::Object(fields)) if fields.contains_key("$ref") => {
+                REFERENCE.check(value, pointer, errors)
+            }
+            (Shape::RefOr(shape), _) => shape.check(value, pointer, errors),
+            (Shape::SecurityScheme, Value::Object(fields)) if fields.contains_key("$ref") => {
+                REFERENCE.check(value, pointer, errors)
+            }
+            (Shape::SecurityScheme, Value::Object(fields)) => match fields.get("type") {
+                Some(Value::String(type_)) => {
+                    if let Some((_, shape)) =
+                        SECURITY_SCHEMES.iter().find(|(known, _)| known == type_)
+                    {
+                        shape.check(value, pointer, errors);
+                    }
+                }
+                Some(value) => Shape::String.check(value, &child(pointer, "type"), errors),
+                None => errors.push(format!("Missing field '{}'", child(pointer, "type"))),
+            },
+            (Shape::SecurityScheme, _) => mismatch("a mapping"),
+        }
+    }
+}
+
+/// Returns the pointer of a field, escaped as in a JSON pointer.
+fn child(pointer: &str, name: &str) -> String {
+    format!("{pointer}/{}", name.replace('~', "~0").replace('/', "~1"))
+}
+
+fn describe(value: &Value) -> String {
+    match value {
+        Value::Null => String::from("null"),
+        Value::Bool(_) => String::from("a boolean"),
+        Value::Number(number) => format!("`{number}`"),
+        Value::String(string) => format!("`{string}`"),
+        Value::Array(_) => String::from("a list"),
+        Value::Object(_) => String::from("a mapping"),
+    }
+}
@@ -0,0 +1,25 @@
+use asyncapiv3::borrowed::AsyncApiRef;
+use asyncapiv3::spec::AsyncApiSpec;
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::hint::black_box;
+
+fn fixture_json() -> String {
+    let file = std::fs::File::open("test-res/3.0.0/streetlights-kafka-asyncapi.yml").unwrap();
+    let value: serde_json::Value = serde_yaml::from_reader(file).unwrap();
+    serde_json::to_string(&value).unwrap()
+}
+
+fn parse(c: &mut Criterion) {
+    let json = fixture_json();
+    let mut group = c.benchmark_group("parse");
+    group.bench_function("owned", |b| {
+        b.iter(|| serde_json::from_str::<AsyncApiSpec>(black_box(&json)).unwrap())
+    });
+    group.bench_function("borrowed", |b| {
+        b.iter(|| serde_json::from_str::<AsyncApiRef>(black_box(&json)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
@@ -0,0 +1,42 @@
+use asyncapiv3::spec::common::ReferenceObject;
+use asyncapiv3::spec::index::DocumentIndex;
+use asyncapiv3::spec::message::Message;
+use asyncapiv3::spec::AsyncApiSpec;
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::hint::black_box;
+
+const LOOKUPS: usize = 10_000;
+
+const POINTERS: [&str; 4] = [
+    "#/channels/lightingMeasured/messages/lightMeasured",
+    "#/channels/lightTurnOn/messages/turnOn",
+    "#/channels/lightsDim/messages/dimLight",
+    "#/components/messages/lightMeasured",
+];
+
+fn resolve(c: &mut Criterion) {
+    let file = std::fs::File::open("test-res/3.0.0/streetlights-kafka-asyncapi.yml").unwrap();
+    let AsyncApiSpec::V3_0_0(spec) = serde_yaml::from_reader(file).unwrap();
+    let references = POINTERS.map(ReferenceObject::new);
+    let index = DocumentIndex::build(&spec);
+
+    let mut group = c.benchmark_group("resolve_messages");
+    group.bench_function("per_call", |b| {
+        b.iter(|| {
+            for reference in references.iter().cycle().take(LOOKUPS) {
+                black_box(reference.resolve::<Message>(&spec).unwrap());
+            }
+        })
+    });
+    group.bench_function("index", |b| {
+        b.iter(|| {
+            for pointer in POINTERS.iter().cycle().take(LOOKUPS) {
+                black_box(index.message(pointer).unwrap());
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, resolve);
+criterion_main!(benches);
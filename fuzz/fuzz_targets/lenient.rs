@@ -0,0 +1,3 @@
+#![no_main]
+
+libfuzzer_sys::fuzz_target!(|data: &[u8]| asyncapiv3::fuzzing::fuzz_lenient(data));
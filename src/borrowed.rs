@@ -0,0 +1,262 @@
+//! Borrowed, read-only view over the commonly read subset of a document.
+//!
+//! [AsyncApiRef] mirrors the owned [AsyncApiV3Spec](crate::spec::AsyncApiV3Spec) for the info
+//! object, the servers, the channel keys and the operation actions, borrowing strings from the
+//! input instead of allocating them. Everything else is skipped without being materialized.
+//!
+//! Borrowing requires a deserializer able to hand out borrowed strings, such as
+//! `serde_json::from_str`; strings containing escape sequences are still allocated.
+//!
+//! ```
+//! # use asyncapiv3::borrowed::AsyncApiRef;
+//! let document = r#"{
+//!     "asyncapi": "3.0.0",
+//!     "info": { "title": "Lights", "version": "1.0.0" },
+//!     "channels": { "lights": { "address": "lights" } }
+//! }"#;
+//! let spec: AsyncApiRef = serde_json::from_str(document).unwrap();
+//! assert_eq!(spec.info.title, "Lights");
+//! ```
+use crate::spec::operation::OperationAction;
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use core::fmt;
+use core::marker::PhantomData;
+use core::ops::Deref;
+use serde::de::{IgnoredAny, Visitor};
+use serde::{Deserialize, Deserializer};
+
+/// A string borrowed from the input whenever possible.
+///
+/// Unlike a plain `Cow<str>`, it borrows in any position, e.g. as a map key or inside an
+/// `Option`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Str<'a>(pub Cow<'a, str>);
+
+impl Str<'_> {
+    /// Returns `true` if the string is borrowed from the input.
+    pub fn is_borrowed(&self) -> bool {
+        matches!(self.0, Cow::Borrowed(_))
+    }
+}
+
+impl Deref for Str<'_> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq<str> for Str<'_> {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Str<'_> {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl fmt::Display for Str<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for Str<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct StrVisitor<'a>(PhantomData<Str<'a>>);
+
+        impl<'de: 'a, 'a> Visitor<'de> for StrVisitor<'a> {
+            type Value = Str<'a>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a string")
+            }
+
+            fn visit_borrowed_str<E>(self, value: &'de str) -> Result<Self::Value, E> {
+                Ok(Str(Cow::Borrowed(value)))
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> {
+                Ok(Str(Cow::Owned(String::from(value))))
+            }
+
+            fn visit_string<E>(self, value: String) -> Result<Self::Value, E> {
+                Ok(Str(Cow::Owned(value)))
+            }
+        }
+
+        deserializer.deserialize_str(StrVisitor(PhantomData))
+    }
+}
+
+/// Borrowed view over an AsyncAPI 3 document.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AsyncApiRef<'a> {
+    /// The AsyncAPI version of the document.
+    #[serde(borrow)]
+    pub asyncapi: Str<'a>,
+    /// Identifier of the application the AsyncAPI document is defining.
+    #[serde(borrow, default)]
+    pub id: Option<Str<'a>>,
+    /// Metadata about the API.
+    #[serde(borrow)]
+    pub info: InfoRef<'a>,
+    /// Connection details of servers, keyed by server name.
+    #[serde(borrow, default)]
+    pub servers: BTreeMap<Str<'a>, ServerRef<'a>>,
+    /// The channels of the document, keyed by channel name. Their content is skipped.
+    #[serde(borrow, default)]
+    pub channels: BTreeMap<Str<'a>, IgnoredAny>,
+    /// The operations of the document, keyed by operation name.
+    #[serde(borrow, default)]
+    pub operations: BTreeMap<Str<'a>, OperationRef<'a>>,
+}
+
+impl AsyncApiRef<'_> {
+    /// Iterates over the channel keys.
+    pub fn channel_keys(&self) -> impl Iterator<Item = &str> {
+        self.channels.keys().map(|key| &**key)
+    }
+}
+
+/// Borrowed view over the [Info](crate::spec::info::Info) object.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InfoRef<'a> {
+    /// The title of the application.
+    #[serde(borrow)]
+    pub title: Str<'a>,
+    /// The version of the application API.
+    #[serde(borrow)]
+    pub version: Str<'a>,
+    /// A short description of the application.
+    #[serde(borrow, default)]
+    pub description: Option<Str<'a>>,
+}
+
+/// Borrowed view over a [Server](crate::spec::server::Server) or a reference to it.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerRef<'a> {
+    /// The reference, when the server is defined elsewhere.
+    #[serde(borrow, default, rename = "$ref")]
+    pub reference: Option<Str<'a>>,
+    /// The server host name.
+    #[serde(borrow, default)]
+    pub host: Option<Str<'a>>,
+    /// The protocol this server supports for connection.
+    #[serde(borrow, default)]
+    pub protocol: Option<Str<'a>>,
+    /// The version of the protocol used for connection.
+    #[serde(borrow, default)]
+    pub protocol_version: Option<Str<'a>>,
+    /// The path to a resource in the host.
+    #[serde(borrow, default)]
+    pub pathname: Option<Str<'a>>,
+}
+
+/// Borrowed view over an [Operation](crate::spec::operation::Operation) or a reference to it.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationRef<'a> {
+    /// The reference, when the operation is defined elsewhere.
+    #[serde(borrow, default, rename = "$ref")]
+    pub reference: Option<Str<'a>>,
+    /// The action of the operation.
+    #[serde(default)]
+    pub action: Option<OperationAction>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::spec::common::RefOr;
+    use crate::spec::{AsyncApiSpec, AsyncApiV3Spec};
+
+    fn fixture_json(name: &str) -> String {
+        let file = std::fs::File::open(format!("test-res/3.0.0/{name}")).unwrap();
+        let value: serde_json::Value = serde_yaml::from_reader(file).unwrap();
+        serde_json::to_string(&value).unwrap()
+    }
+
+    fn assert_agrees(borrowed: &AsyncApiRef, owned: &AsyncApiV3Spec) {
+        assert_eq!(borrowed.asyncapi, "3.0.0");
+        assert_eq!(borrowed.id.as_deref(), owned.id.as_deref());
+        assert_eq!(borrowed.info.title, owned.info.title.as_str());
+        assert_eq!(borrowed.info.version, owned.info.version.as_str());
+        assert_eq!(
+            borrowed.info.description.as_deref(),
+            owned.info.description.as_deref()
+        );
+
+        let mut channel_keys = owned
+            .channels
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        channel_keys.sort_unstable();
+        assert_eq!(borrowed.channel_keys().collect::<Vec<_>>(), channel_keys);
+
+        assert_eq!(borrowed.servers.len(), owned.servers.len());
+        for (name, server) in &borrowed.servers {
+            match &owned.servers[&**name] {
+                RefOr::Right(owned) => {
+                    assert_eq!(server.host.as_deref(), Some(owned.host.as_str()));
+                    assert_eq!(server.protocol.as_deref(), Some(owned.protocol.as_str()));
+                    assert_eq!(
+                        server.protocol_version.as_deref(),
+                        owned.protocol_version.as_deref()
+                    );
+                    assert_eq!(server.pathname.as_deref(), owned.pathname.as_deref());
+                }
+                RefOr::Left(reference) => {
+                    assert_eq!(server.reference.as_deref(), Some(&*reference.reference))
+                }
+            }
+        }
+
+        assert_eq!(borrowed.operations.len(), owned.operations.len());
+        for (name, operation) in &borrowed.operations {
+            match &owned.operations[&**name] {
+                RefOr::Right(owned) => assert_eq!(operation.action, Some(owned.action)),
+                RefOr::Left(reference) => {
+                    assert_eq!(operation.reference.as_deref(), Some(&*reference.reference))
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn borrowed_view_agrees_with_owned_parse() {
+        for entry in std::fs::read_dir("test-res/3.0.0").unwrap() {
+            let name = entry.unwrap().file_name().into_string().unwrap();
+            let json = fixture_json(&name);
+
+            let borrowed: AsyncApiRef = serde_json::from_str(&json).unwrap();
+            let AsyncApiSpec::V3_0_0(owned) = serde_json::from_str(&json).unwrap();
+
+            assert_agrees(&borrowed, &owned);
+        }
+    }
+
+    #[test]
+    fn strings_are_borrowed_from_input() {
+        let json = fixture_json("streetlights-kafka-asyncapi.yml");
+
+        let borrowed: AsyncApiRef = serde_json::from_str(&json).unwrap();
+
+        assert!(borrowed.info.title.is_borrowed());
+        assert!(borrowed.servers.keys().all(Str::is_borrowed));
+        assert!(borrowed
+            .servers
+            .values()
+            .all(|server| server.host.as_ref().is_some_and(Str::is_borrowed)));
+    }
+}
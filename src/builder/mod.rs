@@ -7,6 +7,7 @@ use crate::spec::server::{Server, Servers};
 use crate::spec::{AsyncApiSpec, AsyncApiV3Spec};
 
 pub mod operation;
+pub mod server;
 
 pub struct AsyncApiV3Builder {
     spec: AsyncApiV3Spec,
@@ -49,7 +50,7 @@ impl AsyncApiV3Builder {
     pub fn register_server(&mut self, name: &str, server: Server) {
         self.spec
             .servers
-            .insert(String::from(name), RefOr::Right(server));
+            .insert(String::from(name), RefOr::item(server));
     }
 
     pub fn register_server_ref(&mut self, name: &str, reference: ReferenceObject) {
@@ -65,7 +66,7 @@ impl AsyncApiV3Builder {
     pub fn register_channel(&mut self, name: &str, channel: Channel) {
         self.spec
             .channels
-            .insert(String::from(name), RefOr::Right(channel));
+            .insert(String::from(name), RefOr::item(channel));
     }
 
     pub fn register_channel_ref(&mut self, name: &str, reference: ReferenceObject) {
@@ -81,7 +82,7 @@ impl AsyncApiV3Builder {
     pub fn register_operation(&mut self, name: &str, operation: Operation) {
         self.spec
             .operations
-            .insert(String::from(name), RefOr::Right(operation));
+            .insert(String::from(name), RefOr::item(operation));
     }
 
     pub fn register_operation_ref(&mut self, name: &str, reference: ReferenceObject) {
@@ -4,7 +4,7 @@ use schemars::Schema;
 
 use crate::error::Error;
 use crate::spec::channel::Channel;
-use crate::spec::common::{Either, RefOr, ReferenceObject};
+use crate::spec::common::{Either, Maybe, RefOr, ReferenceObject};
 use crate::spec::message::Message;
 use crate::spec::operation::{Operation, OperationAction, OperationReply, OperationReplyAddress};
 
@@ -63,9 +63,9 @@ impl AsyncApiV3Builder {
 
         self.spec.channels.insert(
             String::from(&operation.name),
-            RefOr::Right(Channel {
-                address: Some(String::from(&operation.address)),
-                messages: [(String::from(&req_name), RefOr::Right(req.message))]
+            RefOr::item(Channel {
+                address: Maybe::Value(String::from(&operation.address)),
+                messages: [(String::from(&req_name), RefOr::item(req.message))]
                     .into_iter()
                     .collect(),
                 title: None,
@@ -76,6 +76,7 @@ impl AsyncApiV3Builder {
                 tags: vec![],
                 external_docs: None,
                 bindings: None,
+                extensions: Default::default(),
             }),
         );
 
@@ -83,7 +84,7 @@ impl AsyncApiV3Builder {
 
         self.spec.operations.insert(
             String::from(&operation.name),
-            RefOr::Right(Operation {
+            RefOr::item(Operation {
                 action: OperationAction::Send,
                 channel: ReferenceObject::new_channel(&operation.name),
                 title: None,
@@ -98,9 +99,9 @@ impl AsyncApiV3Builder {
                     &operation.name,
                     &req_name,
                 )]),
-                reply: Some(RefOr::Right(OperationReply {
+                reply: Some(RefOr::item(OperationReply {
                     channel: Some(ReferenceObject::new_channel(&reply_info.channel_name)),
-                    address: Some(RefOr::Right(OperationReplyAddress {
+                    address: Some(RefOr::item(OperationReplyAddress {
                         description: None,
                         location: reply_info.reply_address_location,
                     })),
@@ -108,7 +109,9 @@ impl AsyncApiV3Builder {
                         &reply_info.channel_name,
                         &res_name,
                     )],
+                    extensions: Default::default(),
                 })),
+                extensions: Default::default(),
             }),
         );
 
@@ -128,7 +131,7 @@ impl AsyncApiV3Builder {
         };
         channel
             .messages
-            .insert(String::from(res_name), RefOr::Right(message));
+            .insert(String::from(res_name), RefOr::item(message));
         Ok(())
     }
     fn merge_schema_components(&mut self, partial: HashMap<String, Schema>) -> Result<(), Error> {
@@ -139,7 +142,7 @@ impl AsyncApiV3Builder {
     }
 
     fn insert_schema_component(&mut self, key: String, schema: Schema) -> Result<(), Error> {
-        let wrapped = RefOr::Right(Either::Left(schema));
+        let wrapped = RefOr::item(Either::Left(schema));
         if let Some(current_schema) = self.spec.components.schemas.get(&key) {
             if !current_schema.eq(&wrapped) {
                 log::warn!("Trying to add two schema components with the same name but different content {current_schema:?} - {wrapped:?}");
@@ -158,18 +161,18 @@ mod test {
     use crate::spec::info::Info;
     use schemars::JsonSchema;
 
-    #[derive(JsonSchema)]
+    #[derive(JsonSchema, serde::Serialize)]
     struct CommonData {
         number: u32,
         text: String,
     }
 
-    #[derive(JsonSchema)]
+    #[derive(JsonSchema, serde::Serialize)]
     struct Req {
         data: CommonData,
     }
 
-    #[derive(JsonSchema)]
+    #[derive(JsonSchema, serde::Serialize)]
     struct Res {
         data: CommonData,
     }
@@ -195,7 +198,7 @@ mod test {
         let req_message = MessageFullSpec {
             message: Message {
                 headers: None,
-                payload: Some(RefOr::Right(Either::Left(req_schema))),
+                payload: Some(RefOr::item(Either::Left(req_schema))),
                 correlation_id: None,
                 content_type: None,
                 name: None,
@@ -207,6 +210,7 @@ mod test {
                 bindings: None,
                 examples: vec![],
                 traits: vec![],
+                extensions: Default::default(),
             },
             definitions: req_schema_gen
                 .take_definitions(false)
@@ -220,7 +224,7 @@ mod test {
         let res_message = MessageFullSpec {
             message: Message {
                 headers: None,
-                payload: Some(RefOr::Right(Either::Left(res_schema))),
+                payload: Some(RefOr::item(Either::Left(res_schema))),
                 correlation_id: None,
                 content_type: None,
                 name: None,
@@ -232,6 +236,7 @@ mod test {
                 bindings: None,
                 examples: vec![],
                 traits: vec![],
+                extensions: Default::default(),
             },
             definitions: res_schema_gen
                 .take_definitions(false)
@@ -243,7 +248,7 @@ mod test {
         spec.register_channel(
             "DemoRepl",
             Channel {
-                address: None,
+                address: Maybe::Absent,
                 messages: Default::default(),
                 title: None,
                 summary: None,
@@ -253,6 +258,7 @@ mod test {
                 tags: vec![],
                 external_docs: None,
                 bindings: None,
+                extensions: Default::default(),
             },
         );
         spec.register_simple_req_rep_operation(
@@ -0,0 +1,282 @@
+//! Servers generated from templates, see [ServerMatrix].
+use crate::error::Error;
+use crate::spec::common::RefOr;
+use crate::spec::component::is_valid_key;
+use crate::spec::server::{Server, Servers, Variable};
+
+use super::AsyncApiV3Builder;
+
+/// How a [ServerMatrix] describes the combinations of its axes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MatrixMode {
+    /// One server per combination, named after the base name and the values of the
+    /// combination, e.g. `kafka-eu-prod`, with the placeholders of the axes substituted.
+    #[default]
+    Expand,
+    /// A single templated server named after the base name, with a variable per axis listing
+    /// its values and defaulting to the first one.
+    Templated,
+}
+
+/// Servers generated from a template for every combination of a set of axes, e.g. regions and
+/// environments.
+///
+/// ```
+/// # use asyncapiv3::builder::server::ServerMatrix;
+/// # use asyncapiv3::spec::server::Server;
+/// # fn example(template: Server) {
+/// let servers = ServerMatrix::new(template)
+///     .axis("region", ["eu", "us"])
+///     .axis("env", ["prod", "staging"])
+///     .build("kafka")
+///     .unwrap();
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ServerMatrix {
+    template: Server,
+    axes: Vec<(String, Vec<String>)>,
+    mode: MatrixMode,
+}
+
+impl ServerMatrix {
+    /// Creates a matrix without axes, which generates the template alone, in the
+    /// [Expand](MatrixMode::Expand) mode.
+    pub fn new(template: Server) -> Self {
+        Self {
+            template,
+            axes: Vec::new(),
+            mode: MatrixMode::default(),
+        }
+    }
+
+    /// Adds an axis, whose values replace the `{name}` placeholders of the host and the pathname
+    /// of the template.
+    pub fn axis<V: Into<String>>(
+        mut self,
+        name: &str,
+        values: impl IntoIterator<Item = V>,
+    ) -> Self {
+        self.axes.push((
+            String::from(name),
+            values.into_iter().map(Into::into).collect(),
+        ));
+        self
+    }
+
+    /// Sets whether a server is generated per combination or a single templated one.
+    pub fn mode(mut self, mode: MatrixMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Generates the servers, named after `base_name`.
+    ///
+    /// Fails with [Error::InvalidKey] if a name is not a valid server key, i.e. is not made of
+    /// letters, digits, `.`, `-` and `_`, or with [Error::DuplicateDefinition] if two
+    /// combinations are given the same name.
+    pub fn build(&self, base_name: &str) -> Result<Servers, Error> {
+        let mut servers = Servers::new();
+        match self.mode {
+            MatrixMode::Expand => {
+                for combination in self.combinations() {
+                    let mut name = String::from(base_name);
+                    let mut server = self.template.clone();
+                    for (axis, value) in &combination {
+                        name = format!("{name}-{value}");
+                        let placeholder = format!("{{{axis}}}");
+                        server.host = server.host.replace(&placeholder, value);
+                        server.pathname = server
+                            .pathname
+                            .map(|pathname| pathname.replace(&placeholder, value));
+                        server.variables.remove(*axis);
+                    }
+                    insert(&mut servers, name, server)?;
+                }
+            }
+            MatrixMode::Templated => {
+                let mut server = self.template.clone();
+                for (axis, values) in &self.axes {
+                    let variable = server.variables.entry(axis.clone()).or_insert_with(|| {
+                        RefOr::item(Variable {
+                            enum_values: None,
+                            default: None,
+                            description: None,
+                            examples: Vec::new(),
+                        })
+                    });
+                    match variable {
+                        RefOr::Right(variable) => {
+                            variable.enum_values = Some(values.clone());
+                            variable.default = values.first().cloned();
+                        }
+                        // Referenced variables are shared, so they are replaced rather than
+                        // modified.
+                        RefOr::Left(_) => {
+                            *variable = RefOr::item(Variable {
+                                enum_values: Some(values.clone()),
+                                default: values.first().cloned(),
+                                description: None,
+                                examples: Vec::new(),
+                            })
+                        }
+                    }
+                }
+                insert(&mut servers, String::from(base_name), server)?;
+            }
+        }
+        Ok(servers)
+    }
+
+    /// Returns every combination of one value per axis, the axes in the order they were added.
+    fn combinations(&self) -> Vec<Vec<(&str, &str)>> {
+        self.axes
+            .iter()
+            .fold(vec![Vec::new()], |combinations, (axis, values)| {
+                combinations
+                    .iter()
+                    .flat_map(|combination| {
+                        values.iter().map(move |value| {
+                            let mut combination = combination.clone();
+                            combination.push((axis.as_str(), value.as_str()));
+                            combination
+                        })
+                    })
+                    .collect()
+            })
+    }
+}
+
+fn insert(servers: &mut Servers, name: String, server: Server) -> Result<(), Error> {
+    if !is_valid_key(&name) {
+        return Err(Error::InvalidKey { key: name });
+    }
+    if servers.contains_key(&name) {
+        return Err(Error::DuplicateDefinition { key: name });
+    }
+    servers.insert(name, RefOr::item(server));
+    Ok(())
+}
+
+impl AsyncApiV3Builder {
+    /// Registers the servers generated by a matrix, see [ServerMatrix::build].
+    ///
+    /// Fails with [Error::DuplicateDefinition] if a generated name is already the name of a
+    /// server, in which case no server is registered.
+    pub fn register_server_matrix(
+        &mut self,
+        base_name: &str,
+        matrix: &ServerMatrix,
+    ) -> Result<(), Error> {
+        let servers = matrix.build(base_name)?;
+        if let Some(name) = servers
+            .keys()
+            .find(|name| self.spec.servers.contains_key(*name))
+        {
+            return Err(Error::DuplicateDefinition { key: name.clone() });
+        }
+        self.spec.servers.extend(servers);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::spec::info::Info;
+    use std::collections::BTreeMap;
+
+    fn template() -> Server {
+        Server {
+            host: String::from("{region}.{env}.broker.example.com:9092"),
+            protocol: String::from("kafka"),
+            protocol_version: None,
+            pathname: None,
+            description: None,
+            title: None,
+            summary: None,
+            variables: BTreeMap::new(),
+            security: vec![],
+            tags: vec![],
+            external_docs: None,
+            bindings: None,
+        }
+    }
+
+    fn matrix() -> ServerMatrix {
+        ServerMatrix::new(template())
+            .axis("region", ["eu", "us"])
+            .axis("env", ["prod", "staging"])
+    }
+
+    fn builder() -> AsyncApiV3Builder {
+        AsyncApiV3Builder::new(Info {
+            title: String::from("Brokers"),
+            version: String::from("1.0.0"),
+            description: None,
+            terms_of_service: None,
+            contact: None,
+            license: None,
+            tags: vec![],
+            external_docs: None,
+        })
+    }
+
+    #[test]
+    fn expand_every_combination() {
+        let servers = matrix().build("kafka").unwrap();
+
+        let hosts = servers
+            .iter()
+            .map(|(name, server)| match server {
+                RefOr::Right(server) => (name.as_str(), server.host.as_str()),
+                RefOr::Left(_) => panic!("servers are inline"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            hosts,
+            [
+                ("kafka-eu-prod", "eu.prod.broker.example.com:9092"),
+                ("kafka-eu-staging", "eu.staging.broker.example.com:9092"),
+                ("kafka-us-prod", "us.prod.broker.example.com:9092"),
+                ("kafka-us-staging", "us.staging.broker.example.com:9092"),
+            ]
+        );
+    }
+
+    #[test]
+    fn single_templated_server() {
+        let servers = matrix().mode(MatrixMode::Templated).build("kafka").unwrap();
+
+        let RefOr::Right(server) = &servers["kafka"] else {
+            panic!("the server is inline");
+        };
+        assert_eq!(servers.len(), 1);
+        assert_eq!(server.host, template().host);
+        let RefOr::Right(env) = &server.variables["env"] else {
+            panic!("the variable is inline");
+        };
+        assert_eq!(
+            env.enum_values.as_deref(),
+            Some(&[String::from("prod"), String::from("staging")][..])
+        );
+        assert_eq!(env.default.as_deref(), Some("prod"));
+    }
+
+    #[test]
+    fn reject_colliding_and_invalid_names() {
+        let mut builder = builder();
+        builder.register_server("kafka-us-staging", template());
+
+        assert!(matches!(
+            builder.register_server_matrix("kafka", &matrix()),
+            Err(Error::DuplicateDefinition { key }) if key == "kafka-us-staging"
+        ));
+        assert!(matches!(
+            builder.register_server_matrix("kafka", &matrix().axis("zone", ["a/b"])),
+            Err(Error::InvalidKey { key }) if key == "kafka-eu-prod-a/b"
+        ));
+        let crate::spec::AsyncApiSpec::V3_0_0(spec) = builder.build();
+        assert_eq!(spec.servers.len(), 1);
+    }
+}
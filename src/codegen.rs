@@ -0,0 +1,250 @@
+//! Generation of Rust source code from a document.
+use crate::error::Error;
+use crate::spec::operation::{Operation, OperationAction};
+use crate::spec::schema::json_schema;
+use crate::spec::AsyncApiV3Spec;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Write;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Options of [conformance_tests].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConformanceGenOptions {
+    /// Path of the trait implemented by the consumer under test, from the generated module. It
+    /// has a method per receive operation, named after the operation key in snake case, taking
+    /// `&mut self` and the payload as a `&serde_json::Value`.
+    pub handler_trait: String,
+    /// Expression building the consumer under test, from the generated module.
+    pub handler: String,
+    /// Marks the generated tests `#[ignore]`, to run them on demand.
+    pub ignore: bool,
+}
+
+impl Default for ConformanceGenOptions {
+    fn default() -> Self {
+        Self {
+            handler_trait: String::from("crate::Handler"),
+            handler: String::from("crate::handler()"),
+            ignore: true,
+        }
+    }
+}
+
+/// Generates contract tests for the consumers of the document: a module per channel, with a
+/// test per receive operation of the root operations.
+///
+/// The test of an operation goes over the examples of its messages with a payload. It
+/// validates each payload against the payload schema of its message, when it is a JSON
+/// schema, and hands it to the method of the handler trait named after the operation. The
+/// generated code depends on `asyncapiv3` and `serde_json`, and embeds the document to resolve
+/// the references of the schemas.
+///
+/// Fails if the document cannot be serialized or an example payload cannot be resolved, with
+/// [Error::NotRootChannel] if a receive operation does not reference a root channel, or with
+/// [Error::ConflictingIdentifier] if the keys of two channels or of two receive operations have
+/// the same snake case identifier, e.g. `lightOn` and `light_on`.
+pub fn conformance_tests(
+    spec: &AsyncApiV3Spec,
+    options: &ConformanceGenOptions,
+) -> Result<String, Error> {
+    let mut channels = BTreeMap::<&str, Vec<(&str, &Operation)>>::new();
+    for (key, operation) in &spec.operations {
+        let operation = operation.resolve(spec)?;
+        if operation.action != OperationAction::Receive {
+            continue;
+        }
+        let pointer = operation.channel.local_pointer();
+        let channel = match pointer.as_ref().map(|pointer| pointer.segments()) {
+            Some([section, channel]) if section == "channels" => channel,
+            _ => {
+                return Err(Error::NotRootChannel {
+                    operation: key.clone(),
+                    reference: operation.channel.reference.clone(),
+                })
+            }
+        };
+        let Some((channel, _)) = spec.channels.get_key_value(channel) else {
+            return Err(Error::UnresolvedReference {
+                reference: operation.channel.reference.clone(),
+            });
+        };
+        channels
+            .entry(channel.as_str())
+            .or_default()
+            .push((key.as_str(), operation));
+    }
+    check_identifiers(channels.keys().copied())?;
+    check_identifiers(channels.values().flatten().map(|(key, _)| *key))?;
+
+    let mut code = String::from(
+        "// Generated by asyncapiv3::codegen::conformance_tests, do not edit.\n\
+         use asyncapiv3::spec::schema::SchemaValidator;\n\
+         use asyncapiv3::spec::AsyncApiV3Spec;\n\n",
+    );
+    let document = to_json(spec)?;
+    let _ = writeln!(code, "const DOCUMENT: &str = {};\n", raw_string(&document));
+    code.push_str(
+        "fn spec() -> AsyncApiV3Spec {\n    \
+             serde_json::from_str(DOCUMENT).unwrap()\n\
+         }\n",
+    );
+
+    for (channel, operations) in channels {
+        let _ = write!(
+            code,
+            "\n/// Consumers of the channel `{channel}`.\nmod {} {{\n    use super::*;\n",
+            identifier(channel)
+        );
+        for (key, operation) in operations {
+            let mut examples = Vec::new();
+            for message in operation.resolved_messages(spec) {
+                let schema = message
+                    .payload
+                    .as_ref()
+                    .and_then(|payload| json_schema(payload, spec))
+                    .unwrap_or(&Value::Bool(true));
+                for example in &message.examples {
+                    if let Some(payload) = example.resolve_payload(spec)? {
+                        examples.push((to_json(schema)?, to_json(payload.as_ref())?));
+                    }
+                }
+            }
+
+            let method = identifier(key);
+            let _ = write!(
+                code,
+                "\n    /// Receives the examples of the operation `{key}`.\n    #[test]\n"
+            );
+            if options.ignore {
+                code.push_str("    #[ignore = \"generated conformance test\"]\n");
+            }
+            let _ = writeln!(code, "    fn {method}() {{");
+            let _ = writeln!(
+                code,
+                "        let examples: [(&str, &str); {}] = [",
+                examples.len()
+            );
+            for (schema, payload) in &examples {
+                let _ = writeln!(
+                    code,
+                    "            ({}, {}),",
+                    raw_string(schema),
+                    raw_string(payload)
+                );
+            }
+            let _ = write!(
+                code,
+                "        ];\n        \
+                 let spec = spec();\n        \
+                 let validator = SchemaValidator::new(&spec);\n        \
+                 let mut handler = {handler};\n        \
+                 for (schema, payload) in examples {{\n            \
+                     let schema = serde_json::from_str(schema).unwrap();\n            \
+                     let payload = serde_json::from_str(payload).unwrap();\n            \
+                     let violations = validator.validate(&schema, &payload);\n            \
+                     assert!(violations.is_empty(), \"{{violations:?}}\");\n            \
+                     {handler_trait}::{method}(&mut handler, &payload);\n        \
+                 }}\n    \
+                 }}\n",
+                handler = options.handler,
+                handler_trait = options.handler_trait,
+            );
+        }
+        code.push_str("}\n");
+    }
+    Ok(code)
+}
+
+fn to_json<T: Serialize + ?Sized>(value: &T) -> Result<String, Error> {
+    serde_json::to_string(value).map_err(|err| Error::InvalidDocument {
+        reason: err.to_string(),
+        hints: Vec::new(),
+    })
+}
+
+/// Fails with [Error::ConflictingIdentifier] if several keys have the same identifier.
+fn check_identifiers<'k>(keys: impl Iterator<Item = &'k str>) -> Result<(), Error> {
+    let mut identifiers = BTreeMap::<String, Vec<String>>::new();
+    for key in keys {
+        identifiers
+            .entry(identifier(key))
+            .or_default()
+            .push(String::from(key));
+    }
+    match identifiers.into_iter().find(|(_, keys)| keys.len() > 1) {
+        Some((identifier, keys)) => Err(Error::ConflictingIdentifier { identifier, keys }),
+        None => Ok(()),
+    }
+}
+
+/// Returns a raw string literal holding `content`, with enough `#` to contain it.
+fn raw_string(content: &str) -> String {
+    let mut hashes = 0;
+    let mut run = None;
+    for c in content.chars() {
+        run = match (c, run) {
+            ('"', _) => Some(0),
+            ('#', Some(length)) => Some(length + 1),
+            _ => None,
+        };
+        hashes = hashes.max(run.map_or(0, |length| length + 1));
+    }
+    let hashes = "#".repeat(hashes);
+    format!("r{hashes}\"{content}\"{hashes}")
+}
+
+/// Returns a snake case identifier for a key, e.g. `light_measured` for `lightMeasured` or
+/// `light_on` for `light.on`.
+fn identifier(key: &str) -> String {
+    let mut identifier = String::new();
+    let mut previous = None::<char>;
+    for c in key.chars() {
+        if c.is_ascii_alphanumeric() {
+            if c.is_ascii_uppercase()
+                && previous.is_some_and(|previous| {
+                    previous.is_ascii_lowercase() || previous.is_ascii_digit()
+                })
+            {
+                identifier.push('_');
+            }
+            identifier.push(c.to_ascii_lowercase());
+        } else if !identifier.is_empty() && !identifier.ends_with('_') {
+            identifier.push('_');
+        }
+        previous = Some(c);
+    }
+    let identifier = identifier.trim_end_matches('_');
+    match identifier.chars().next() {
+        None => String::from("_"),
+        Some(first) if first.is_ascii_digit() => format!("_{identifier}"),
+        _ if KEYWORDS.contains(&identifier) => format!("{identifier}_"),
+        _ => String::from(identifier),
+    }
+}
+
+/// The keywords of Rust which cannot be used as identifiers.
+const KEYWORDS: [&str; 38] = [
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use",
+    "where", "while", "yield",
+];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn identifiers_and_raw_strings() {
+        assert_eq!(identifier("onLightMeasured"), "on_light_measured");
+        assert_eq!(identifier("lights.on-v2"), "lights_on_v2");
+        assert_eq!(identifier("2fa"), "_2fa");
+        assert_eq!(identifier("type"), "type_");
+        assert_eq!(raw_string("plain"), "r\"plain\"");
+        assert_eq!(raw_string("a \"#quote\"##"), "r###\"a \"#quote\"##\"###");
+    }
+}
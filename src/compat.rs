@@ -0,0 +1,236 @@
+//! Detection of AsyncAPI 2 constructs left in documents declaring version 3.
+//!
+//! Half-migrated documents usually fail deserialization with errors which do not point at the
+//! actual cause. [detect_v2_constructs] inspects the raw document and names each leftover along
+//! with its v3 equivalent.
+use crate::spec::common::JsonPointer;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use serde_json::Value;
+
+/// The kinds of AsyncAPI 2 constructs recognized by [detect_v2_constructs].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum V2ConstructKind {
+    /// A `publish` operation declared inside a channel.
+    ChannelPublish,
+    /// A `subscribe` operation declared inside a channel.
+    ChannelSubscribe,
+    /// A server described by its `url`.
+    ServerUrl,
+    /// A security requirement written as a map of scheme names to scopes.
+    SecurityRequirementMap,
+    /// A `schemaFormat` declared directly on a message.
+    MessageSchemaFormat,
+}
+
+impl V2ConstructKind {
+    /// Describes how the construct is expressed in AsyncAPI 3.
+    pub fn suggestion(&self) -> &'static str {
+        match self {
+            V2ConstructKind::ChannelPublish => {
+                "declare a `receive` operation in `operations` referencing the channel"
+            }
+            V2ConstructKind::ChannelSubscribe => {
+                "declare a `send` operation in `operations` referencing the channel"
+            }
+            V2ConstructKind::ServerUrl => "split the url into `host`, `pathname` and `protocol`",
+            V2ConstructKind::SecurityRequirementMap => {
+                "reference the security scheme with `$ref` and move the scopes into the scheme"
+            }
+            V2ConstructKind::MessageSchemaFormat => {
+                "wrap the payload into a Multi Format Schema Object with `schemaFormat` and `schema`"
+            }
+        }
+    }
+}
+
+/// An AsyncAPI 2 construct found in a document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct V2Construct {
+    /// The kind of construct.
+    pub kind: V2ConstructKind,
+    /// Location of the construct in the document.
+    pub pointer: JsonPointer,
+}
+
+impl fmt::Display for V2Construct {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self
+            .pointer
+            .segments()
+            .last()
+            .map(String::as_str)
+            .unwrap_or_default();
+        write!(
+            f,
+            "{}: AsyncAPI 2 `{name}`, {}",
+            self.pointer,
+            self.kind.suggestion()
+        )
+    }
+}
+
+/// Scans a raw document for AsyncAPI 2 constructs, returning them sorted by location.
+pub fn detect_v2_constructs(document: &Value) -> Vec<V2Construct> {
+    let mut constructs = Vec::new();
+    let root = JsonPointer::default();
+
+    for (section, servers) in [
+        (root.join("servers"), document.get("servers")),
+        (
+            root.join("components").join("servers"),
+            document.pointer("/components/servers"),
+        ),
+    ] {
+        for (name, server) in entries(servers) {
+            let server_pointer = section.join(name.as_str());
+            if server.get("url").is_some() {
+                constructs.push(V2Construct {
+                    kind: V2ConstructKind::ServerUrl,
+                    pointer: server_pointer.join("url"),
+                });
+            }
+            detect_security_maps(server, &server_pointer, &mut constructs);
+        }
+    }
+
+    for (section, channels) in [
+        (root.join("channels"), document.get("channels")),
+        (
+            root.join("components").join("channels"),
+            document.pointer("/components/channels"),
+        ),
+    ] {
+        for (name, channel) in entries(channels) {
+            let channel_pointer = section.join(name.as_str());
+            for (field, kind) in [
+                ("publish", V2ConstructKind::ChannelPublish),
+                ("subscribe", V2ConstructKind::ChannelSubscribe),
+            ] {
+                if let Some(operation) = channel.get(field) {
+                    let operation_pointer = channel_pointer.join(field);
+                    detect_security_maps(operation, &operation_pointer, &mut constructs);
+                    constructs.push(V2Construct {
+                        kind,
+                        pointer: operation_pointer,
+                    });
+                }
+            }
+        }
+    }
+
+    for (section, operations) in [
+        (root.join("operations"), document.get("operations")),
+        (
+            root.join("components").join("operations"),
+            document.pointer("/components/operations"),
+        ),
+    ] {
+        for (name, operation) in entries(operations) {
+            detect_security_maps(operation, &section.join(name.as_str()), &mut constructs);
+        }
+    }
+
+    let messages_pointer = root.join("components").join("messages");
+    for (name, message) in entries(document.pointer("/components/messages")) {
+        if message.get("schemaFormat").is_some() {
+            constructs.push(V2Construct {
+                kind: V2ConstructKind::MessageSchemaFormat,
+                pointer: messages_pointer.join(name.as_str()).join("schemaFormat"),
+            });
+        }
+    }
+
+    constructs.sort_by(|a, b| a.pointer.cmp(&b.pointer));
+    constructs
+}
+
+fn entries(section: Option<&Value>) -> impl Iterator<Item = (&String, &Value)> {
+    section.and_then(Value::as_object).into_iter().flatten()
+}
+
+/// A v2 security requirement is an object without `$ref` whose values are lists of scopes.
+fn detect_security_maps(owner: &Value, pointer: &JsonPointer, constructs: &mut Vec<V2Construct>) {
+    let requirements = owner.get("security").and_then(Value::as_array);
+    for (index, requirement) in requirements.into_iter().flatten().enumerate() {
+        let Some(fields) = requirement.as_object() else {
+            continue;
+        };
+        if !fields.is_empty()
+            && !fields.contains_key("$ref")
+            && fields.values().all(Value::is_array)
+        {
+            constructs.push(V2Construct {
+                kind: V2ConstructKind::SecurityRequirementMap,
+                pointer: pointer.join("security").join(index.to_string()),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn detect_server_url_and_channel_publish() {
+        let document = json!({
+            "asyncapi": "3.0.0",
+            "info": { "title": "Legacy", "version": "1.0.0" },
+            "servers": {
+                "production": {
+                    "url": "mqtt://broker.example.com",
+                    "protocol": "mqtt",
+                    "security": [{ "oauth": ["write:lights"] }]
+                }
+            },
+            "channels": {
+                "lights": {
+                    "publish": { "message": { "payload": { "type": "string" } } }
+                }
+            }
+        });
+
+        let constructs = detect_v2_constructs(&document);
+
+        let found = constructs
+            .iter()
+            .map(|construct| (construct.kind, construct.pointer.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    V2ConstructKind::ChannelPublish,
+                    String::from("/channels/lights/publish")
+                ),
+                (
+                    V2ConstructKind::SecurityRequirementMap,
+                    String::from("/servers/production/security/0")
+                ),
+                (
+                    V2ConstructKind::ServerUrl,
+                    String::from("/servers/production/url")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn no_constructs_in_v3_document() {
+        let document = json!({
+            "servers": {
+                "production": {
+                    "host": "broker.example.com",
+                    "protocol": "mqtt",
+                    "security": [{ "$ref": "#/components/securitySchemes/oauth" }]
+                }
+            },
+            "channels": { "lights": { "address": "lights" } }
+        });
+
+        assert!(detect_v2_constructs(&document).is_empty());
+    }
+}
@@ -0,0 +1,329 @@
+//! Conformance runs over a corpus of documents, e.g. the examples published by the AsyncAPI
+//! specification project.
+//!
+//! [run] parses every document of a directory, [validates](crate::validation::validate) it,
+//! [round-trips](crate::fidelity::check) it and maps the features of the specification the
+//! corpus exercises to the documents using them.
+use crate::error::Error;
+use crate::fidelity::{self, Difference};
+use crate::spec::common::RefOr;
+use crate::spec::message::Message;
+use crate::spec::security::SecuritySchemeKind;
+use crate::spec::{AsyncApiSpec, AsyncApiV3Spec};
+use crate::validation::{validate, Severity, ValidationIssue};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::Path;
+
+/// A feature of the specification exercised by a document.
+#[derive(
+    Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum SpecFeature {
+    /// Bindings of the given protocol, on any object.
+    Binding(String),
+    /// Schemas written in another format than the AsyncAPI one, through a `schemaFormat`.
+    MultiFormatSchema,
+    /// Operations with a reply.
+    RequestReply,
+    OperationTraits,
+    MessageTraits,
+    /// Messages with a correlation ID.
+    CorrelationId,
+    /// Channel addresses with parameters.
+    ChannelParameters,
+    /// Server URLs with variables.
+    ServerVariables,
+    /// Security schemes of the given kind.
+    Security(SecuritySchemeKind),
+}
+
+impl fmt::Display for SpecFeature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Binding(protocol) => write!(f, "{protocol} bindings"),
+            Self::MultiFormatSchema => f.write_str("multi-format schemas"),
+            Self::RequestReply => f.write_str("request/reply"),
+            Self::OperationTraits => f.write_str("operation traits"),
+            Self::MessageTraits => f.write_str("message traits"),
+            Self::CorrelationId => f.write_str("correlation IDs"),
+            Self::ChannelParameters => f.write_str("channel parameters"),
+            Self::ServerVariables => f.write_str("server variables"),
+            Self::Security(kind) => write!(f, "{kind} security"),
+        }
+    }
+}
+
+/// Result of the conformance run of a document.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileReport {
+    /// Why the document could not be read or parsed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The issues found by the validation, including warnings.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub issues: Vec<ValidationIssue>,
+    /// The round-trip differences not explained by a [known
+    /// normalization](fidelity::KNOWN_NORMALIZATIONS).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub differences: Vec<Difference>,
+}
+
+impl FileReport {
+    /// Returns `true` if the document parses, has no validation error and round-trips.
+    pub fn passed(&self) -> bool {
+        self.error.is_none()
+            && self.differences.is_empty()
+            && self
+                .issues
+                .iter()
+                .all(|issue| issue.severity != Severity::Error)
+    }
+}
+
+/// Result of a conformance [run].
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConformanceReport {
+    /// Reports keyed by the path of the documents relative to the corpus directory, with `/`
+    /// separators.
+    pub files: BTreeMap<String, FileReport>,
+    /// The documents exercising each feature.
+    pub coverage: BTreeMap<SpecFeature, BTreeSet<String>>,
+}
+
+impl ConformanceReport {
+    /// Returns the documents which passed.
+    pub fn passed(&self) -> impl Iterator<Item = &str> {
+        self.files
+            .iter()
+            .filter(|(_, report)| report.passed())
+            .map(|(file, _)| file.as_str())
+    }
+
+    /// Returns the documents of a baseline, e.g. the ones which passed a previous run, which
+    /// are part of the corpus and no longer pass.
+    pub fn regressions<'a>(&self, baseline: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+        baseline
+            .into_iter()
+            .filter(|file| self.files.get(*file).is_some_and(|report| !report.passed()))
+            .collect()
+    }
+}
+
+/// Lists the failures, leaving out validation warnings, then the coverage.
+impl fmt::Display for ConformanceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{}/{} documents passed",
+            self.passed().count(),
+            self.files.len()
+        )?;
+        for (file, report) in &self.files {
+            if let Some(error) = &report.error {
+                writeln!(f, "  {file}: {error}")?;
+            }
+            let errors = report
+                .issues
+                .iter()
+                .filter(|issue| issue.severity == Severity::Error);
+            for issue in errors {
+                writeln!(f, "  {file}: {issue}")?;
+            }
+            for difference in &report.differences {
+                writeln!(
+                    f,
+                    "  {file}: {:?} at {}",
+                    difference.kind, difference.pointer
+                )?;
+            }
+        }
+        writeln!(f, "coverage:")?;
+        for (feature, files) in &self.coverage {
+            let files = files.iter().map(String::as_str).collect::<Vec<_>>();
+            writeln!(f, "  {feature}: {}", files.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs every `.json`, `.yaml` and `.yml` document of a directory and its subdirectories.
+pub fn run(dir: impl AsRef<Path>) -> Result<ConformanceReport, Error> {
+    let dir = dir.as_ref();
+    let mut paths = Vec::new();
+    collect_documents(dir, &mut paths)?;
+
+    let mut report = ConformanceReport::default();
+    for path in paths {
+        let file = path
+            .strip_prefix(dir)
+            .unwrap_or(&path)
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let (file_report, features) = match run_document(&path) {
+            Ok(found) => found,
+            Err(err) => (
+                FileReport {
+                    error: Some(err.to_string()),
+                    ..FileReport::default()
+                },
+                BTreeSet::new(),
+            ),
+        };
+        for feature in features {
+            report
+                .coverage
+                .entry(feature)
+                .or_default()
+                .insert(file.clone());
+        }
+        report.files.insert(file, file_report);
+    }
+    Ok(report)
+}
+
+fn io_error(path: &Path, err: std::io::Error) -> Error {
+    Error::Parse {
+        reason: format!("cannot read {}: {err}", path.display()),
+    }
+}
+
+fn collect_documents(dir: &Path, paths: &mut Vec<std::path::PathBuf>) -> Result<(), Error> {
+    let entries = std::fs::read_dir(dir).map_err(|err| io_error(dir, err))?;
+    for entry in entries {
+        let path = entry.map_err(|err| io_error(dir, err))?.path();
+        if path.is_dir() {
+            collect_documents(&path, paths)?;
+        } else if path.extension().is_some_and(|extension| {
+            ["json", "yaml", "yml"].contains(&&*extension.to_string_lossy())
+        }) {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(())
+}
+
+fn run_document(path: &Path) -> Result<(FileReport, BTreeSet<SpecFeature>), Error> {
+    let document = std::fs::read_to_string(path).map_err(|err| io_error(path, err))?;
+    let parse_error = |reason: String| Error::Parse { reason };
+    // The round trip is checked on JSON, keeping the field order of the YAML document.
+    let json = if path
+        .extension()
+        .is_some_and(|extension| extension == "json")
+    {
+        document
+    } else {
+        let value = serde_yaml::from_str::<serde_yaml::Value>(&document)
+            .map_err(|err| parse_error(err.to_string()))?;
+        serde_json::to_string(&value).map_err(|err| parse_error(err.to_string()))?
+    };
+    let AsyncApiSpec::V3_0_0(spec) = json.parse::<AsyncApiSpec>()?;
+
+    let report = FileReport {
+        error: None,
+        issues: validate(&spec),
+        differences: fidelity::check(&json)?.unexplained().cloned().collect(),
+    };
+    Ok((report, features(&spec)?))
+}
+
+fn features(spec: &AsyncApiV3Spec) -> Result<BTreeSet<SpecFeature>, Error> {
+    let mut features = BTreeSet::new();
+    let document = serde_json::to_value(spec).map_err(|err| Error::Parse {
+        reason: err.to_string(),
+    })?;
+    value_features(&document, &mut features);
+
+    let operations = spec
+        .operations
+        .values()
+        .chain(spec.components.operations.values())
+        .filter_map(|operation| operation.resolve(spec).ok());
+    for operation in operations {
+        if operation.reply.is_some() {
+            features.insert(SpecFeature::RequestReply);
+        }
+        if !operation.traits.is_empty() {
+            features.insert(SpecFeature::OperationTraits);
+        }
+    }
+    if !spec.components.replies.is_empty() {
+        features.insert(SpecFeature::RequestReply);
+    }
+
+    let channels = spec
+        .channels
+        .values()
+        .chain(spec.components.channels.values())
+        .filter_map(|channel| channel.resolve(spec).ok())
+        .collect::<Vec<_>>();
+    if channels
+        .iter()
+        .any(|channel| !channel.parameters.is_empty())
+    {
+        features.insert(SpecFeature::ChannelParameters);
+    }
+    let messages = channels
+        .iter()
+        .flat_map(|channel| channel.messages.values())
+        .chain(spec.components.messages.values())
+        .filter_map(|message: &RefOr<Message>| message.resolve(spec).ok());
+    for message in messages {
+        if !message.traits.is_empty() {
+            features.insert(SpecFeature::MessageTraits);
+        }
+        if message.correlation_id.is_some() {
+            features.insert(SpecFeature::CorrelationId);
+        }
+    }
+
+    let servers = spec
+        .servers
+        .values()
+        .chain(spec.components.servers.values())
+        .filter_map(|server| server.resolve(spec).ok());
+    if servers
+        .into_iter()
+        .any(|server| !server.variables.is_empty())
+    {
+        features.insert(SpecFeature::ServerVariables);
+    }
+    features.extend(
+        spec.security_scheme_kinds()
+            .into_iter()
+            .map(SpecFeature::Security),
+    );
+    Ok(features)
+}
+
+/// Collects the features visible in the serialized document: bindings and multi-format schemas
+/// can appear on many objects.
+fn value_features(value: &Value, features: &mut BTreeSet<SpecFeature>) {
+    match value {
+        Value::Object(fields) => {
+            if fields.contains_key("schemaFormat") && fields.contains_key("schema") {
+                features.insert(SpecFeature::MultiFormatSchema);
+            }
+            if let Some(Value::Object(bindings)) = fields.get("bindings") {
+                features.extend(
+                    bindings
+                        .keys()
+                        .filter(|protocol| *protocol != "$ref")
+                        .map(|protocol| SpecFeature::Binding(protocol.clone())),
+                );
+            }
+            fields
+                .values()
+                .for_each(|value| value_features(value, features));
+        }
+        Value::Array(items) => items.iter().for_each(|item| value_features(item, features)),
+        _ => {}
+    }
+}
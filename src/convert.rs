@@ -0,0 +1,301 @@
+//! Conversion of documents to the formats of other tools.
+use crate::error::Error;
+use crate::spec::common::{Either, JsonPointer, RefOr};
+use crate::spec::message::Message;
+use crate::spec::AsyncApiV3Spec;
+use alloc::collections::btree_map::Entry;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Tag of the channels whose messages are left out of the CloudEvents catalog.
+pub const INTERNAL_TAG: &str = "internal";
+
+/// Options of [to_cloudevents_catalog].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CloudEventsOptions {
+    /// Reverse-DNS prefix of the event types, e.g. `com.example.orders`, joined to the message
+    /// names with a dot. Event types are the bare message names when unset.
+    pub type_prefix: Option<String>,
+    /// URI of the document, prefixed to the `dataschema` references within the document to make
+    /// them absolute, e.g. `https://example.com/orders.yaml`.
+    pub document_uri: Option<String>,
+}
+
+/// The CloudEvents attributes of the events carrying a message.
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct CloudEventTypeDescriptor {
+    /// The `type` attribute: the name of the message, or its key when it has none, prefixed by
+    /// [CloudEventsOptions::type_prefix].
+    #[serde(rename = "type")]
+    pub event_type: String,
+    /// Patterns of the `source` attribute: the addresses of the channels of the message, with
+    /// their parameters as `{name}`. Channels without address are not listed.
+    pub sources: Vec<String>,
+    /// The `datacontenttype` attribute: the effective content type of the message.
+    #[serde(rename = "datacontenttype", skip_serializing_if = "Option::is_none")]
+    pub data_content_type: Option<String>,
+    /// The `dataschema` attribute: the reference to the payload schema of the message, or the
+    /// location of the payload in the document when the schema is inline, e.g.
+    /// `#/components/messages/orderPlaced/payload`, prefixed by
+    /// [CloudEventsOptions::document_uri].
+    #[serde(rename = "dataschema", skip_serializing_if = "Option::is_none")]
+    pub data_schema: Option<String>,
+}
+
+/// Describes the messages of the root channels as CloudEvents types, sorted by type. A message
+/// used by several channels is described once, with a source per channel. The messages of
+/// channels tagged [internal](INTERNAL_TAG) are skipped.
+///
+/// The channels of the components are described only through the root channels referencing
+/// them: they are reusable definitions rather than channels of the application.
+///
+/// Fails with [Error::ConflictingEventType] when different messages map to the same type, or
+/// with the error of a channel or a message which does not resolve.
+pub fn to_cloudevents_catalog(
+    spec: &AsyncApiV3Spec,
+    options: &CloudEventsOptions,
+) -> Result<Vec<CloudEventTypeDescriptor>, Error> {
+    let mut catalog = BTreeMap::<String, (&Message, String, CloudEventTypeDescriptor)>::new();
+    for (channel_name, channel_entry) in &spec.channels {
+        let channel = channel_entry.resolve(spec)?;
+        let channel_uri = defined_at(channel_entry, ["channels", channel_name.as_str()]);
+        if channel.tags.iter().any(|tag| tag.name == INTERNAL_TAG) {
+            continue;
+        }
+        for (message_name, message_entry) in &channel.messages {
+            let location = JsonPointer::from_iter([
+                "channels",
+                channel_name.as_str(),
+                "messages",
+                message_name.as_str(),
+            ])
+            .to_string();
+            let message = message_entry.resolve(spec)?;
+            let name = message.name.as_deref().unwrap_or(message_name);
+            let event_type = match options.type_prefix.as_deref() {
+                Some(prefix) => format!("{}.{name}", prefix.trim_end_matches('.')),
+                None => String::from(name),
+            };
+
+            let (described, described_at, descriptor) = match catalog.entry(event_type.clone()) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    let message_uri = match message_entry {
+                        Either::Left(reference) => reference.reference.clone(),
+                        Either::Right(_) => nested(&channel_uri, ["messages", message_name]),
+                    };
+                    let data_schema = message.payload.as_ref().map(|payload| {
+                        let uri = schema_uri(payload)
+                            .unwrap_or_else(|| nested(&message_uri, ["payload"]));
+                        match (uri.starts_with('#'), options.document_uri.as_deref()) {
+                            (true, Some(document)) => format!("{document}{uri}"),
+                            _ => uri,
+                        }
+                    });
+                    let descriptor = CloudEventTypeDescriptor {
+                        event_type: event_type.clone(),
+                        sources: Vec::new(),
+                        data_content_type: message.effective_content_type(spec).map(String::from),
+                        data_schema,
+                    };
+                    entry.insert((message, location.clone(), descriptor))
+                }
+            };
+            if !core::ptr::eq(*described, message) {
+                return Err(Error::ConflictingEventType {
+                    event_type,
+                    messages: Vec::from([described_at.clone(), location]),
+                });
+            }
+            if let Some(address) = channel.address.as_option() {
+                if !descriptor.sources.contains(address) {
+                    descriptor.sources.push(address.clone());
+                }
+            }
+        }
+    }
+    Ok(catalog
+        .into_values()
+        .map(|(_, _, descriptor)| descriptor)
+        .collect())
+}
+
+/// Returns the URI of an object: its reference, or its location in the document when inline.
+fn defined_at<T, const N: usize>(object: &RefOr<T>, location: [&str; N]) -> String {
+    match object {
+        Either::Left(reference) => reference.reference.clone(),
+        Either::Right(_) => format!("#{}", JsonPointer::from_iter(location)),
+    }
+}
+
+/// Returns the URI of a value nested in the object at `uri`.
+fn nested<const N: usize>(uri: &str, segments: [&str; N]) -> String {
+    let separator = if uri.contains('#') { "" } else { "#" };
+    format!("{uri}{separator}{}", JsonPointer::from_iter(segments))
+}
+
+/// Returns the reference to a payload schema, if it is one, including the schema of a multi
+/// format schema made of a `$ref` only.
+fn schema_uri(
+    payload: &RefOr<Either<schemars::Schema, crate::spec::message::MultiFormatSchema>>,
+) -> Option<String> {
+    match payload {
+        Either::Left(reference) => Some(reference.reference.clone()),
+        Either::Right(schema) => {
+            let schema = match schema {
+                Either::Left(schema) => schema
+                    .as_object()
+                    .filter(|fields| fields.contains_key("schemaFormat"))
+                    .and_then(|fields| fields.get("schema"))?,
+                Either::Right(schema) => &schema.schema,
+            };
+            match schema.as_object() {
+                Some(fields) if fields.len() == 1 => fields
+                    .get("$ref")
+                    .and_then(serde_json::Value::as_str)
+                    .map(String::from),
+                _ => None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::parse_spec;
+
+    const SPEC: &str = r#"
+asyncapi: 3.0.0
+info:
+  title: Orders
+  version: 1.0.0
+defaultContentType: application/json
+channels:
+  orders:
+    address: 'orders/{region}'
+    messages:
+      orderPlaced:
+        $ref: '#/components/messages/orderPlaced'
+  archive:
+    address: archive
+    messages:
+      orderPlaced:
+        $ref: '#/components/messages/orderPlaced'
+  audit:
+    address: audit
+    tags:
+      - name: internal
+    messages:
+      orderAudited:
+        payload:
+          type: object
+components:
+  messages:
+    orderPlaced:
+      name: order.placed
+      payload:
+        type: object
+  schemas:
+    audit:
+      type: object
+"#;
+
+    #[test]
+    fn catalog_with_type_prefix() {
+        let spec = parse_spec(SPEC);
+        let options = CloudEventsOptions {
+            type_prefix: Some(String::from("com.example")),
+            ..CloudEventsOptions::default()
+        };
+
+        let catalog = to_cloudevents_catalog(&spec, &options).unwrap();
+
+        assert_eq!(
+            catalog,
+            [CloudEventTypeDescriptor {
+                event_type: String::from("com.example.order.placed"),
+                sources: Vec::from([String::from("archive"), String::from("orders/{region}")]),
+                data_content_type: Some(String::from("application/json")),
+                data_schema: Some(String::from("#/components/messages/orderPlaced/payload")),
+            }]
+        );
+        assert_eq!(
+            serde_json::to_value(&catalog[0]).unwrap()["datacontenttype"],
+            "application/json"
+        );
+    }
+
+    #[test]
+    fn internal_channels_are_skipped() {
+        let spec = parse_spec(SPEC);
+        let catalog = to_cloudevents_catalog(&spec, &CloudEventsOptions::default()).unwrap();
+        assert_eq!(catalog.len(), 1);
+        assert_eq!(catalog[0].event_type, "order.placed");
+
+        let spec = parse_spec(&SPEC.replace("name: internal", "name: audit"));
+        let catalog = to_cloudevents_catalog(&spec, &CloudEventsOptions::default()).unwrap();
+        assert_eq!(catalog[0].event_type, "order.placed");
+        assert_eq!(catalog[1].event_type, "orderAudited");
+    }
+
+    #[test]
+    fn data_schema_references() {
+        let spec = parse_spec(&SPEC.replace("name: internal", "name: audit").replace(
+            "      orderAudited:\n        payload:\n          type: object",
+            "      orderAudited:\n        payload:\n          $ref: '#/components/schemas/audit'\n      \
+             orderArchived:\n        payload:\n          schemaFormat: application/vnd.apache.avro;version=1.9.0\n          \
+             schema:\n            $ref: 'archive.avsc'",
+        ));
+        let options = CloudEventsOptions {
+            document_uri: Some(String::from("https://example.com/orders.yaml")),
+            ..CloudEventsOptions::default()
+        };
+
+        let catalog = to_cloudevents_catalog(&spec, &options).unwrap();
+
+        let data_schemas = catalog
+            .iter()
+            .map(|descriptor| {
+                (
+                    descriptor.event_type.as_str(),
+                    descriptor.data_schema.as_deref().unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            data_schemas,
+            [
+                (
+                    "order.placed",
+                    "https://example.com/orders.yaml#/components/messages/orderPlaced/payload"
+                ),
+                ("orderArchived", "archive.avsc"),
+                (
+                    "orderAudited",
+                    "https://example.com/orders.yaml#/components/schemas/audit"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn conflicting_event_types() {
+        let spec = parse_spec(
+            &SPEC
+                .replace("orderAudited:", "orderAudited:\n        name: order.placed")
+                .replace("name: internal", "name: audit"),
+        );
+
+        let err = to_cloudevents_catalog(&spec, &CloudEventsOptions::default()).unwrap_err();
+
+        assert!(matches!(
+            err,
+            Error::ConflictingEventType { event_type, messages }
+                if event_type == "order.placed"
+                    && messages == ["/channels/archive/messages/orderPlaced", "/channels/audit/messages/orderAudited"]
+        ));
+    }
+}
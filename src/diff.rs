@@ -0,0 +1,687 @@
+//! Differences between two versions of a document, entity by entity.
+//!
+//! Entities of the two versions are matched by their [stable
+//! identifier](crate::spec::uid::UID_EXTENSION) first, so a renamed entity is reported as
+//! [renamed](EntityChange::Renamed) rather than removed and added, then by key.
+//! [AsyncApiV3Spec::breaking_changes] classifies the differences by their compatibility, and
+//! [DocumentDiff] holds both.
+use crate::spec::common::JsonPointer;
+use crate::spec::message::Message;
+use crate::spec::schema::{json_schema, WalkProperties};
+use crate::spec::uid::UID_EXTENSION;
+use crate::spec::AsyncApiV3Spec;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use serde_json::Value;
+
+/// The kinds of entities compared by [diff].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum EntityKind {
+    Channel,
+    Operation,
+    Message,
+}
+
+/// A change of an entity between two versions of a document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EntityChange {
+    /// The entity only exists in the new version.
+    Added {
+        kind: EntityKind,
+        pointer: JsonPointer,
+    },
+    /// The entity only exists in the old version.
+    Removed {
+        kind: EntityKind,
+        pointer: JsonPointer,
+    },
+    /// The entity moved to another key, keeping its identifier. Its content may have changed
+    /// as well, which is reported by a [Modified](EntityChange::Modified) change at the new
+    /// location.
+    Renamed {
+        kind: EntityKind,
+        from: JsonPointer,
+        to: JsonPointer,
+    },
+    /// The content of the entity changed.
+    Modified {
+        kind: EntityKind,
+        pointer: JsonPointer,
+    },
+}
+
+/// The sections whose entries are compared, with the kind of their entries.
+const SECTIONS: [(&[&str], EntityKind); 5] = [
+    (&["channels"], EntityKind::Channel),
+    (&["operations"], EntityKind::Operation),
+    (&["components", "channels"], EntityKind::Channel),
+    (&["components", "operations"], EntityKind::Operation),
+    (&["components", "messages"], EntityKind::Message),
+];
+
+/// Compares the root channels and operations, the component channels, operations and
+/// messages, and the messages of the channels found in both versions of a document. Entities
+/// are compared as written: a reference changes only when its target does. Changes are listed
+/// section by section, in key order, the changes of the messages of a channel following the
+/// changes of the channels of its section.
+pub fn diff(old: &AsyncApiV3Spec, new: &AsyncApiV3Spec) -> Vec<EntityChange> {
+    let (Ok(old), Ok(new)) = (serde_json::to_value(old), serde_json::to_value(new)) else {
+        return Vec::new();
+    };
+    let mut changes = Vec::new();
+    for (section, kind) in SECTIONS {
+        let section = JsonPointer::from_iter(section.iter().copied());
+        let matched = diff_entries(kind, (&section, &old), (&section, &new), &mut changes);
+        if kind != EntityKind::Channel {
+            continue;
+        }
+        for (old_key, new_key) in matched {
+            let messages = |key: &str| section.join(key).join("messages");
+            diff_entries(
+                EntityKind::Message,
+                (&messages(&old_key), &old),
+                (&messages(&new_key), &new),
+                &mut changes,
+            );
+        }
+    }
+    changes
+}
+
+/// Compares the entries of a section of both versions, returning the keys of the entries
+/// found in both, in the old and the new version.
+fn diff_entries(
+    kind: EntityKind,
+    (old_section, old): (&JsonPointer, &Value),
+    (new_section, new): (&JsonPointer, &Value),
+    changes: &mut Vec<EntityChange>,
+) -> Vec<(String, String)> {
+    let entries = |section: &JsonPointer, document| match section.evaluate(document) {
+        Some(Value::Object(entries)) => entries.iter().collect::<BTreeMap<_, _>>(),
+        _ => BTreeMap::new(),
+    };
+    let mut old_entries = entries(old_section, old);
+    let mut new_entries = entries(new_section, new);
+    let mut matched = Vec::new();
+
+    let renames = old_entries
+        .iter()
+        .filter_map(|(old_key, old_entry)| {
+            let (new_key, new_entry) = new_entries.iter().find(|(_, new_entry)| {
+                uid(new_entry).is_some_and(|new_uid| Some(new_uid) == uid(old_entry))
+            })?;
+            Some((*old_key, *new_key, *old_entry != *new_entry))
+        })
+        .collect::<Vec<_>>();
+    for (old_key, new_key, modified) in renames {
+        old_entries.remove(old_key);
+        new_entries.remove(new_key);
+        if old_key != new_key {
+            changes.push(EntityChange::Renamed {
+                kind,
+                from: old_section.join(old_key.as_str()),
+                to: new_section.join(new_key.as_str()),
+            });
+        }
+        if modified {
+            changes.push(EntityChange::Modified {
+                kind,
+                pointer: new_section.join(new_key.as_str()),
+            });
+        }
+        matched.push((old_key.clone(), new_key.clone()));
+    }
+
+    for (key, old_entry) in &old_entries {
+        match new_entries.remove(key) {
+            Some(new_entry) => {
+                if new_entry != *old_entry {
+                    changes.push(EntityChange::Modified {
+                        kind,
+                        pointer: new_section.join(key.as_str()),
+                    });
+                }
+                matched.push(((*key).clone(), (*key).clone()));
+            }
+            None => changes.push(EntityChange::Removed {
+                kind,
+                pointer: old_section.join(key.as_str()),
+            }),
+        }
+    }
+    changes.extend(new_entries.keys().map(|key| EntityChange::Added {
+        kind,
+        pointer: new_section.join(key.as_str()),
+    }));
+    matched.sort();
+    matched
+}
+
+fn uid(entry: &Value) -> Option<&str> {
+    entry.get(UID_EXTENSION).and_then(Value::as_str)
+}
+
+/// Returns the location in the old version of what is at `pointer` in the new one, following
+/// the renames of `changes`, e.g. the messages of a renamed channel.
+fn old_location(changes: &[EntityChange], pointer: &JsonPointer) -> JsonPointer {
+    let mut location = pointer.clone();
+    // A message of a renamed channel may have been renamed itself, the longest rename of the
+    // location is applied first.
+    let mut renames = changes
+        .iter()
+        .filter_map(|change| match change {
+            EntityChange::Renamed { from, to, .. } => Some((from, to)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    renames.sort_by_key(|(_, to)| core::cmp::Reverse(to.segments().len()));
+    for (from, to) in renames {
+        if location.starts_with(to) {
+            let rest = &location.segments()[to.segments().len()..];
+            location = from.segments().iter().chain(rest).cloned().collect();
+        }
+    }
+    location
+}
+
+/// The properties of channels, operations and messages whose change breaks applications
+/// written against the old version.
+fn breaking_properties(kind: EntityKind) -> &'static [&'static str] {
+    match kind {
+        EntityKind::Channel => &["address"],
+        EntityKind::Operation => &["action", "channel"],
+        EntityKind::Message => &["contentType"],
+    }
+}
+
+/// A change of a field of the payload of a message, see [AsyncApiV3Spec::breaking_changes].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldChange {
+    /// Location of the message in the new version.
+    pub message: JsonPointer,
+    /// Dotted path of the field, e.g. `address.city`.
+    pub field: String,
+    /// Whether the field is required in the old version, `None` if it does not exist there.
+    pub old_required: Option<bool>,
+    /// Whether the field is required in the new version, `None` if it does not exist there.
+    pub new_required: Option<bool>,
+    /// The types the field accepts in the old version, empty if it accepts any type or does
+    /// not exist there.
+    pub old_types: Vec<String>,
+    /// The types the field accepts in the new version, empty if it accepts any type or does
+    /// not exist there.
+    pub new_types: Vec<String>,
+}
+
+/// A change of a property of a channel, an operation or a message, e.g. the `action` of an
+/// operation, see [AsyncApiV3Spec::breaking_changes].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PropertyChange {
+    pub kind: EntityKind,
+    /// Location of the entity in the new version.
+    pub pointer: JsonPointer,
+    /// Name of the property, e.g. `action`.
+    pub property: String,
+    /// Value of the property in the old version, `None` if it is not set there.
+    pub old: Option<Value>,
+    /// Value of the property in the new version, `None` if it is not set there.
+    pub new: Option<Value>,
+}
+
+/// A change between two versions of a document, see [AsyncApiV3Spec::breaking_changes].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Change {
+    Entity(EntityChange),
+    Property(PropertyChange),
+    Field(FieldChange),
+}
+
+/// A change classified by its compatibility.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BreakingChange {
+    pub change: Change,
+    /// Whether applications written against the old version may break with the new one.
+    pub breaking: bool,
+}
+
+/// The differences between two versions of a document: the [changes of their entities](diff)
+/// and the [classification](AsyncApiV3Spec::breaking_changes) of the changes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DocumentDiff {
+    /// The channels, operations and messages added, removed, renamed or modified.
+    pub entities: Vec<EntityChange>,
+    /// The changes classified by their compatibility.
+    pub changes: Vec<BreakingChange>,
+}
+
+impl DocumentDiff {
+    /// Compares an older version of a document with a newer one.
+    pub fn new(old: &AsyncApiV3Spec, new: &AsyncApiV3Spec) -> Self {
+        Self {
+            entities: diff(old, new),
+            changes: new.breaking_changes(old),
+        }
+    }
+}
+
+/// The shape of a field of a payload compared by [AsyncApiV3Spec::breaking_changes].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Field {
+    required: bool,
+    types: Vec<String>,
+}
+
+impl AsyncApiV3Spec {
+    /// Lists the changes from an older version of the document, classified by their
+    /// compatibility for publishers and subscribers, e.g. to decide the next version number.
+    ///
+    /// Removed channels, operations and messages, including the messages of a channel, are
+    /// breaking, added ones are not. Renamed entities are not either, since keys are not
+    /// exchanged between applications. Modified entities are reported through the changes of
+    /// their properties and of their payload fields:
+    /// - changing the `address` of a channel, the `action` or `channel` of an operation or the
+    ///   `contentType` of a message is breaking.
+    /// - the payloads of the messages of both versions are compared field by field: a field
+    ///   becoming required, or a required field being removed or becoming optional, is
+    ///   breaking, as publishers of the old version miss the first and subscribers rely on the
+    ///   others. So is a field accepting other types, narrowed types rejecting what publishers
+    ///   of the old version send and widened ones surprising its subscribers. Adding or
+    ///   removing an optional field is not breaking.
+    ///
+    /// Payloads written in a format other than JSON schema are not compared.
+    pub fn breaking_changes(&self, old: &AsyncApiV3Spec) -> Vec<BreakingChange> {
+        let entity_changes = diff(old, self);
+        let (Ok(old_document), Ok(new_document)) =
+            (serde_json::to_value(old), serde_json::to_value(self))
+        else {
+            return Vec::new();
+        };
+        let mut changes = Vec::new();
+        for change in &entity_changes {
+            let breaking = match change {
+                EntityChange::Removed { .. } => true,
+                EntityChange::Added { .. } | EntityChange::Renamed { .. } => false,
+                EntityChange::Modified { kind, pointer } => {
+                    let old_entry = old_location(&entity_changes, pointer).evaluate(&old_document);
+                    let new_entry = pointer.evaluate(&new_document);
+                    for property in breaking_properties(*kind) {
+                        let value = |entry: Option<&Value>| entry?.get(*property).cloned();
+                        let (old, new) = (value(old_entry), value(new_entry));
+                        if old != new {
+                            changes.push(BreakingChange {
+                                change: Change::Property(PropertyChange {
+                                    kind: *kind,
+                                    pointer: pointer.clone(),
+                                    property: String::from(*property),
+                                    old,
+                                    new,
+                                }),
+                                breaking: true,
+                            });
+                        }
+                    }
+                    continue;
+                }
+            };
+            changes.push(BreakingChange {
+                change: Change::Entity(change.clone()),
+                breaking,
+            });
+        }
+
+        let old_messages = old.defined_messages();
+        for (pointer, message) in self.defined_messages() {
+            let old_pointer = old_location(&entity_changes, &pointer);
+            let Some((_, old_message)) = old_messages
+                .iter()
+                .find(|(candidate, _)| *candidate == old_pointer)
+            else {
+                continue;
+            };
+            let (Some(old_fields), Some(new_fields)) = (
+                payload_fields(old_message, old),
+                payload_fields(message, self),
+            ) else {
+                continue;
+            };
+            let paths = old_fields
+                .keys()
+                .chain(new_fields.keys())
+                .collect::<BTreeSet<_>>();
+            for path in paths {
+                let (old_field, new_field) = (old_fields.get(path), new_fields.get(path));
+                if old_field == new_field {
+                    continue;
+                }
+                let retyped = matches!(
+                    (old_field, new_field),
+                    (Some(old), Some(new)) if old.types != new.types
+                );
+                let required = |field: Option<&Field>| field.is_some_and(|field| field.required);
+                let types = |field: Option<&Field>| {
+                    field.map(|field| field.types.clone()).unwrap_or_default()
+                };
+                changes.push(BreakingChange {
+                    breaking: retyped || required(old_field) != required(new_field),
+                    change: Change::Field(FieldChange {
+                        message: pointer.clone(),
+                        field: path.clone(),
+                        old_required: old_field.map(|field| field.required),
+                        new_required: new_field.map(|field| field.required),
+                        old_types: types(old_field),
+                        new_types: types(new_field),
+                    }),
+                });
+            }
+        }
+        changes
+    }
+}
+
+/// Returns the fields of the payload of a message by dotted path, or `None` if the payload is
+/// not a JSON schema.
+fn payload_fields(message: &Message, spec: &AsyncApiV3Spec) -> Option<BTreeMap<String, Field>> {
+    let payload = json_schema(message.payload.as_ref()?, spec)?;
+    let payload = <&schemars::Schema>::try_from(payload).ok()?;
+    let required = |schema: &Value| -> Vec<String> {
+        let schema = schema.get("items").unwrap_or(schema);
+        match schema.get("required") {
+            Some(Value::Array(names)) => names
+                .iter()
+                .filter_map(Value::as_str)
+                .map(String::from)
+                .collect(),
+            _ => Vec::new(),
+        }
+    };
+    let mut required_paths = required(payload.as_value());
+    let mut fields = Vec::new();
+    payload.walk_properties(&mut |path, schema| {
+        let mut types = match schema.get("type") {
+            Some(Value::String(ty)) => vec![ty.clone()],
+            Some(Value::Array(types)) => types
+                .iter()
+                .filter_map(Value::as_str)
+                .map(String::from)
+                .collect(),
+            _ => Vec::new(),
+        };
+        types.sort();
+        types.dedup();
+        fields.push((String::from(path), types));
+        let nested = required(schema.as_value());
+        required_paths.extend(nested.into_iter().map(|name| format!("{path}.{name}")));
+    });
+    Some(
+        fields
+            .into_iter()
+            .map(|(path, types)| {
+                let required = required_paths.contains(&path);
+                (path, Field { required, types })
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::parse_spec;
+    use alloc::vec;
+
+    const ORDERS: &str = r#"
+asyncapi: 3.0.0
+info:
+  title: Orders
+  version: 1.0.0
+channels:
+  orders:
+    address: orders
+    messages:
+      orderPlaced:
+        $ref: '#/components/messages/orderPlaced'
+operations:
+  placeOrder:
+    action: send
+    channel:
+      $ref: '#/channels/orders'
+  cancelOrder:
+    action: send
+    channel:
+      $ref: '#/channels/orders'
+components:
+  messages:
+    orderPlaced:
+      payload:
+        $ref: '#/components/schemas/order'
+  schemas:
+    order:
+      type: object
+      required: [id]
+      properties:
+        id:
+          type: string
+"#;
+
+    fn pointer(pointer: &str) -> JsonPointer {
+        JsonPointer::parse(pointer).unwrap()
+    }
+
+    #[test]
+    fn renamed_message_keeping_its_uid() {
+        let old = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Orders
+  version: 1.0.0
+components:
+  messages:
+    orderPlaced:
+      x-uid: order-placed
+      payload:
+        type: object
+    orderShipped:
+      payload:
+        type: object
+"#,
+        );
+        let new = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Orders
+  version: 1.1.0
+components:
+  messages:
+    orderCreated:
+      x-uid: order-placed
+      payload:
+        type: object
+    orderDelivered:
+      payload:
+        type: object
+"#,
+        );
+
+        let changes = diff(&old, &new);
+
+        assert_eq!(
+            changes,
+            vec![
+                EntityChange::Renamed {
+                    kind: EntityKind::Message,
+                    from: pointer("/components/messages/orderPlaced"),
+                    to: pointer("/components/messages/orderCreated"),
+                },
+                EntityChange::Removed {
+                    kind: EntityKind::Message,
+                    pointer: pointer("/components/messages/orderShipped"),
+                },
+                EntityChange::Added {
+                    kind: EntityKind::Message,
+                    pointer: pointer("/components/messages/orderDelivered"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn removed_operation_is_breaking() {
+        let old = parse_spec(ORDERS);
+        let mut new = old.clone();
+        new.operations.remove("cancelOrder");
+
+        let changes = new.breaking_changes(&old);
+
+        assert_eq!(
+            changes,
+            [BreakingChange {
+                change: Change::Entity(EntityChange::Removed {
+                    kind: EntityKind::Operation,
+                    pointer: pointer("/operations/cancelOrder"),
+                }),
+                breaking: true,
+            }]
+        );
+    }
+
+    #[test]
+    fn added_optional_field_is_not_breaking() {
+        let old = parse_spec(ORDERS);
+        let new = parse_spec(&ORDERS.replace(
+            "        id:\n          type: string\n",
+            "        id:\n          type: string\n        note:\n          type: string\n",
+        ));
+
+        let changes = new.breaking_changes(&old);
+
+        assert_eq!(
+            changes,
+            [BreakingChange {
+                change: Change::Field(FieldChange {
+                    message: pointer("/components/messages/orderPlaced"),
+                    field: String::from("note"),
+                    old_required: None,
+                    new_required: Some(false),
+                    old_types: vec![],
+                    new_types: vec![String::from("string")],
+                }),
+                breaking: false,
+            }]
+        );
+    }
+
+    const LIGHTS: &str = r#"
+asyncapi: 3.0.0
+info:
+  title: Lights
+  version: 1.0.0
+channels:
+  lights:
+    address: lights
+    messages:
+      lightOn:
+        payload:
+          type: object
+          properties:
+            level:
+              type: [integer, string]
+      lightOff:
+        payload:
+          type: object
+operations:
+  switchLight:
+    action: send
+    channel:
+      $ref: '#/channels/lights'
+"#;
+
+    fn breaking(changes: &[BreakingChange]) -> Vec<&Change> {
+        changes
+            .iter()
+            .filter(|change| change.breaking)
+            .map(|change| &change.change)
+            .collect()
+    }
+
+    #[test]
+    fn removed_inline_message_is_breaking() {
+        let old = parse_spec(LIGHTS);
+        let new = parse_spec(&LIGHTS.replace(
+            "      lightOff:\n        payload:\n          type: object\n",
+            "",
+        ));
+
+        let changes = new.breaking_changes(&old);
+
+        assert_eq!(
+            breaking(&changes),
+            [&Change::Entity(EntityChange::Removed {
+                kind: EntityKind::Message,
+                pointer: pointer("/channels/lights/messages/lightOff"),
+            })]
+        );
+    }
+
+    #[test]
+    fn changed_action_is_breaking() {
+        let old = parse_spec(LIGHTS);
+        let new = parse_spec(&LIGHTS.replace("action: send", "action: receive"));
+
+        let changes = new.breaking_changes(&old);
+
+        assert_eq!(
+            breaking(&changes),
+            [&Change::Property(PropertyChange {
+                kind: EntityKind::Operation,
+                pointer: pointer("/operations/switchLight"),
+                property: String::from("action"),
+                old: Some(Value::from("send")),
+                new: Some(Value::from("receive")),
+            })]
+        );
+    }
+
+    #[test]
+    fn changed_address_is_breaking() {
+        let old = parse_spec(LIGHTS);
+        let new = parse_spec(&LIGHTS.replace("address: lights", "address: lights.v2"));
+
+        let changes = new.breaking_changes(&old);
+
+        assert_eq!(
+            breaking(&changes),
+            [&Change::Property(PropertyChange {
+                kind: EntityKind::Channel,
+                pointer: pointer("/channels/lights"),
+                property: String::from("address"),
+                old: Some(Value::from("lights")),
+                new: Some(Value::from("lights.v2")),
+            })]
+        );
+    }
+
+    #[test]
+    fn narrowed_field_type_is_breaking() {
+        let old = parse_spec(LIGHTS);
+        let new = parse_spec(&LIGHTS.replace("type: [integer, string]", "type: integer"));
+
+        let changes = new.breaking_changes(&old);
+
+        assert_eq!(
+            breaking(&changes),
+            [&Change::Field(FieldChange {
+                message: pointer("/channels/lights/messages/lightOn"),
+                field: String::from("level"),
+                old_required: Some(false),
+                new_required: Some(false),
+                old_types: vec![String::from("integer"), String::from("string")],
+                new_types: vec![String::from("integer")],
+            })]
+        );
+    }
+}
@@ -0,0 +1,9 @@
+//! Human-readable documents generated from AsyncAPI documents.
+//!
+//! [changelog] describes the differences between two versions of a document in prose, and
+//! [markdown] renders a reference page of a document, badging its deprecated entities.
+mod changelog;
+mod markdown;
+
+pub use changelog::{changelog, ChangelogFormat, ChangelogOptions};
+pub use markdown::markdown;
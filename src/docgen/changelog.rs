@@ -0,0 +1,512 @@
+use crate::diff::{BreakingChange, Change, DocumentDiff, EntityChange, FieldChange};
+use crate::spec::common::JsonPointer;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use serde_json::Value;
+
+/// The output format of a [changelog].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ChangelogFormat {
+    /// Markdown, with a heading per section and links to the changed objects.
+    #[default]
+    Markdown,
+    /// Plain text, with the pointers of the changed objects.
+    PlainText,
+}
+
+/// Options of [changelog].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChangelogOptions {
+    /// Whether to render Markdown or plain text.
+    pub format: ChangelogFormat,
+    /// Location of the new version of the document, e.g. `orders.yaml`. Markdown entries link to
+    /// the changed objects in it, with their JSON pointer as fragment, and only show the
+    /// pointer when it is not set.
+    pub document_url: Option<String>,
+    /// Location of the old version of the document, e.g. `orders-1.0.0.yaml`. Markdown entries
+    /// of removed entities link to them in it, as they no longer exist in the new version, and
+    /// only show the pointer when it is not set.
+    pub previous_document_url: Option<String>,
+}
+
+/// The wording of a changelog. The `{placeholders}` of the templates are replaced by
+/// [fill], so translating a changelog only takes another wording.
+struct Wording {
+    added: &'static str,
+    changed: &'static str,
+    removed: &'static str,
+    breaking: &'static str,
+    channel: &'static str,
+    component_channel: &'static str,
+    operation: &'static str,
+    component_operation: &'static str,
+    message: &'static str,
+    channel_message: &'static str,
+    entity_added: &'static str,
+    entity_removed: &'static str,
+    entity_renamed: &'static str,
+    entity_changed: &'static str,
+    property_changed: &'static str,
+    field_added: &'static str,
+    required_field_added: &'static str,
+    field_removed: &'static str,
+    required_field_removed: &'static str,
+    field_required: &'static str,
+    field_optional: &'static str,
+    field_retyped: &'static str,
+    unset: &'static str,
+    any_type: &'static str,
+}
+
+const ENGLISH: Wording = Wording {
+    added: "Added",
+    changed: "Changed",
+    removed: "Removed",
+    breaking: "Breaking",
+    channel: "channel `{name}`",
+    component_channel: "component channel `{name}`",
+    operation: "operation `{name}`",
+    component_operation: "component operation `{name}`",
+    message: "message `{name}`",
+    channel_message: "message `{name}` of {channel}",
+    entity_added: "{entity} added",
+    entity_removed: "{entity} removed",
+    entity_renamed: "{entity} renamed to {renamed}",
+    entity_changed: "{entity} changed",
+    property_changed: "{entity}: `{property}` changed from {old} to {new}",
+    field_added: "{entity}: payload field `{field}` added",
+    required_field_added: "{entity}: required payload field `{field}` added",
+    field_removed: "{entity}: payload field `{field}` removed",
+    required_field_removed: "{entity}: required payload field `{field}` removed",
+    field_required: "{entity}: payload field `{field}` is now required",
+    field_optional: "{entity}: payload field `{field}` is now optional",
+    field_retyped: "{entity}: payload field `{field}` type changed from {old} to {new}",
+    unset: "unset",
+    any_type: "any type",
+};
+
+/// The sections of a changelog, in order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Section {
+    Added,
+    Changed,
+    Removed,
+    Breaking,
+}
+
+const SECTIONS: [Section; 4] = [
+    Section::Added,
+    Section::Changed,
+    Section::Removed,
+    Section::Breaking,
+];
+
+/// An entry of a changelog, describing a change of the object at `pointer`.
+struct Entry {
+    section: Section,
+    text: String,
+    pointer: JsonPointer,
+    /// Whether the object only exists in the old version of the document.
+    removed: bool,
+}
+
+/// Renders the differences between two versions of a document as a changelog, with an entry
+/// per change grouped under the Added, Changed, Removed and Breaking headings, e.g.
+/// ``message `orderCreated`: payload field `discount` is now required``.
+///
+/// Breaking changes are listed under Breaking only. Renamed entities, and modified ones for
+/// which no more precise change is listed, are listed under Changed. Empty sections are left
+/// out, so identical documents give an empty changelog.
+///
+/// ```
+/// # use asyncapiv3::diff::DocumentDiff;
+/// # use asyncapiv3::docgen::{changelog, ChangelogOptions};
+/// # use asyncapiv3::spec::{AsyncApiSpec, AsyncApiV3Spec};
+/// let parse = |operations: &str| -> AsyncApiV3Spec {
+///     let document = format!(
+///         r#"{{
+///             "asyncapi": "3.0.0",
+///             "info": {{ "title": "Lights", "version": "1.0.0" }},
+///             "channels": {{ "lights": {{ "address": "lights", "messages": {{}} }} }},
+///             "operations": {{ {operations} }}
+///         }}"#
+///     );
+///     let AsyncApiSpec::V3_0_0(spec) = document.parse().unwrap();
+///     spec
+/// };
+/// let old = parse(r##""turnOn": { "action": "send", "channel": { "$ref": "#/channels/lights" } }"##);
+/// let new = parse("");
+///
+/// let changelog = changelog(&DocumentDiff::new(&old, &new), &ChangelogOptions::default());
+///
+/// assert_eq!(
+///     changelog,
+///     "## Breaking\n\n- operation `turnOn` removed (`/operations/turnOn`)\n"
+/// );
+/// ```
+pub fn changelog(diff: &DocumentDiff, options: &ChangelogOptions) -> String {
+    let wording = &ENGLISH;
+    let entries = entries(diff, wording);
+    let mut changelog = String::new();
+    for section in SECTIONS {
+        let entries = entries
+            .iter()
+            .filter(|entry| entry.section == section)
+            .collect::<Vec<_>>();
+        if entries.is_empty() {
+            continue;
+        }
+        if !changelog.is_empty() {
+            changelog.push('\n');
+        }
+        let heading = match section {
+            Section::Added => wording.added,
+            Section::Changed => wording.changed,
+            Section::Removed => wording.removed,
+            Section::Breaking => wording.breaking,
+        };
+        match options.format {
+            ChangelogFormat::Markdown => changelog.push_str(&format!("## {heading}\n\n")),
+            ChangelogFormat::PlainText => changelog.push_str(&format!("{heading}:\n")),
+        }
+        for entry in entries {
+            let url = if entry.removed {
+                &options.previous_document_url
+            } else {
+                &options.document_url
+            };
+            let line = match (options.format, url) {
+                (ChangelogFormat::Markdown, Some(url)) => format!(
+                    "- {} ([{}]({url}#{}))\n",
+                    entry.text,
+                    entry.pointer,
+                    fragment(&entry.pointer)
+                ),
+                (ChangelogFormat::Markdown, None) => {
+                    format!("- {} (`{}`)\n", entry.text, entry.pointer)
+                }
+                (ChangelogFormat::PlainText, _) => {
+                    format!("  - {} ({})\n", entry.text, entry.pointer)
+                }
+            };
+            changelog.push_str(&line);
+        }
+    }
+    changelog
+}
+
+fn entries(diff: &DocumentDiff, wording: &Wording) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    for BreakingChange { change, breaking } in &diff.changes {
+        let section = |section| {
+            if *breaking {
+                Section::Breaking
+            } else {
+                section
+            }
+        };
+        match change {
+            Change::Entity(EntityChange::Added { pointer, .. }) => entries.push(Entry {
+                section: section(Section::Added),
+                text: fill(
+                    wording.entity_added,
+                    &[("entity", &entity(pointer, wording))],
+                ),
+                pointer: pointer.clone(),
+                removed: false,
+            }),
+            Change::Entity(EntityChange::Removed { pointer, .. }) => entries.push(Entry {
+                section: section(Section::Removed),
+                text: fill(
+                    wording.entity_removed,
+                    &[("entity", &entity(pointer, wording))],
+                ),
+                pointer: pointer.clone(),
+                removed: true,
+            }),
+            Change::Entity(EntityChange::Renamed { from, to, .. }) => entries.push(Entry {
+                section: section(Section::Changed),
+                text: fill(
+                    wording.entity_renamed,
+                    &[
+                        ("entity", &entity(from, wording)),
+                        ("renamed", &entity(to, wording)),
+                    ],
+                ),
+                pointer: to.clone(),
+                removed: false,
+            }),
+            Change::Entity(EntityChange::Modified { pointer, .. }) => entries.push(Entry {
+                section: section(Section::Changed),
+                text: fill(
+                    wording.entity_changed,
+                    &[("entity", &entity(pointer, wording))],
+                ),
+                pointer: pointer.clone(),
+                removed: false,
+            }),
+            Change::Property(property) => {
+                let value = |value: &Option<Value>| match value {
+                    Some(value) => format!("`{}`", display_value(value)),
+                    None => String::from(wording.unset),
+                };
+                entries.push(Entry {
+                    section: section(Section::Changed),
+                    text: fill(
+                        wording.property_changed,
+                        &[
+                            ("entity", &entity(&property.pointer, wording)),
+                            ("property", &property.property),
+                            ("old", &value(&property.old)),
+                            ("new", &value(&property.new)),
+                        ],
+                    ),
+                    pointer: property.pointer.clone(),
+                    removed: false,
+                });
+            }
+            Change::Field(field) => {
+                for (default_section, text) in field_entries(field, wording) {
+                    entries.push(Entry {
+                        section: section(default_section),
+                        text,
+                        pointer: field.message.clone(),
+                        removed: false,
+                    });
+                }
+            }
+        }
+    }
+
+    // Modified entities are only listed when no more precise change describes them.
+    for change in &diff.entities {
+        let EntityChange::Modified { pointer, .. } = change else {
+            continue;
+        };
+        if entries
+            .iter()
+            .any(|entry| entry.pointer.starts_with(pointer))
+        {
+            continue;
+        }
+        entries.push(Entry {
+            section: Section::Changed,
+            text: fill(
+                wording.entity_changed,
+                &[("entity", &entity(pointer, wording))],
+            ),
+            pointer: pointer.clone(),
+            removed: false,
+        });
+    }
+    entries
+}
+
+/// Describes the change of a payload field, with the section it belongs to when it is not
+/// breaking.
+fn field_entries(field: &FieldChange, wording: &Wording) -> Vec<(Section, String)> {
+    let message = entity(&field.message, wording);
+    let fill = |template, more: &[(&str, &str)]| {
+        let mut values = Vec::from([("entity", message.as_str()), ("field", &field.field)]);
+        values.extend_from_slice(more);
+        fill(template, &values)
+    };
+    match (field.old_required, field.new_required) {
+        (None, Some(true)) => {
+            Vec::from([(Section::Added, fill(wording.required_field_added, &[]))])
+        }
+        (None, _) => Vec::from([(Section::Added, fill(wording.field_added, &[]))]),
+        (Some(true), None) => {
+            Vec::from([(Section::Removed, fill(wording.required_field_removed, &[]))])
+        }
+        (Some(_), None) => Vec::from([(Section::Removed, fill(wording.field_removed, &[]))]),
+        (Some(old_required), Some(new_required)) => {
+            let mut entries = Vec::new();
+            if field.old_types != field.new_types {
+                let types = |types: &[String]| match types {
+                    [] => String::from(wording.any_type),
+                    types => types
+                        .iter()
+                        .map(|ty| format!("`{ty}`"))
+                        .collect::<Vec<_>>()
+                        .join(" | "),
+                };
+                entries.push((
+                    Section::Changed,
+                    fill(
+                        wording.field_retyped,
+                        &[
+                            ("old", &types(&field.old_types)),
+                            ("new", &types(&field.new_types)),
+                        ],
+                    ),
+                ));
+            }
+            if old_required != new_required {
+                let template = if new_required {
+                    wording.field_required
+                } else {
+                    wording.field_optional
+                };
+                entries.push((Section::Changed, fill(template, &[])));
+            }
+            entries
+        }
+    }
+}
+
+/// Describes the channel, operation or message at `pointer`.
+fn entity(pointer: &JsonPointer, wording: &Wording) -> String {
+    let segments = pointer
+        .segments()
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    match segments.as_slice() {
+        ["channels", name] => fill(wording.channel, &[("name", name)]),
+        ["operations", name] => fill(wording.operation, &[("name", name)]),
+        ["components", "channels", name] => fill(wording.component_channel, &[("name", name)]),
+        ["components", "operations", name] => fill(wording.component_operation, &[("name", name)]),
+        ["components", "messages", name] => fill(wording.message, &[("name", name)]),
+        [channel @ .., "messages", name] => fill(
+            wording.channel_message,
+            &[
+                ("name", name),
+                (
+                    "channel",
+                    &entity(&channel.iter().copied().collect(), wording),
+                ),
+            ],
+        ),
+        _ => pointer.to_string(),
+    }
+}
+
+/// Shows a property value: references as their target, strings as is and other values as
+/// JSON.
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        Value::Object(fields) => match fields.get("$ref") {
+            Some(Value::String(reference)) => reference.clone(),
+            _ => value.to_string(),
+        },
+        _ => value.to_string(),
+    }
+}
+
+/// Replaces the `{name}` placeholders of a template in a single pass, so the braces of the
+/// values, e.g. of a channel named `{field}`, are kept as is.
+fn fill(template: &str, values: &[(&str, &str)]) -> String {
+    let mut text = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        text.push_str(&rest[..start]);
+        let placeholder = &rest[start..];
+        let value = placeholder.find('}').and_then(|end| {
+            values
+                .iter()
+                .find(|(name, _)| *name == &placeholder[1..end])
+                .map(|(_, value)| (end, value))
+        });
+        match value {
+            Some((end, value)) => {
+                text.push_str(value);
+                rest = &placeholder[end + 1..];
+            }
+            None => {
+                text.push('{');
+                rest = &placeholder[1..];
+            }
+        }
+    }
+    text.push_str(rest);
+    text
+}
+
+/// Percent-encodes a pointer for a URI fragment.
+fn fragment(pointer: &JsonPointer) -> String {
+    pointer
+        .to_string()
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                String::from(byte as char)
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::spec::{AsyncApiSpec, AsyncApiV3Spec};
+
+    fn read_spec(path: &str) -> AsyncApiV3Spec {
+        let document = std::fs::read_to_string(path).unwrap();
+        let AsyncApiSpec::V3_0_0(spec) = serde_yaml::from_str(&document).unwrap();
+        spec
+    }
+
+    fn diff() -> DocumentDiff {
+        DocumentDiff::new(
+            &read_spec("test-res/changelog/orders-1.0.0.yaml"),
+            &read_spec("test-res/changelog/orders-1.1.0.yaml"),
+        )
+    }
+
+    #[test]
+    fn markdown_changelog() {
+        let options = ChangelogOptions {
+            format: ChangelogFormat::Markdown,
+            document_url: Some(String::from("orders.yaml")),
+            previous_document_url: Some(String::from("orders-1.0.0.yaml")),
+        };
+
+        let changelog = changelog(&diff(), &options);
+
+        let expected = std::fs::read_to_string("test-res/snapshots/orders-changelog.md").unwrap();
+        assert_eq!(changelog, expected);
+    }
+
+    #[test]
+    fn plain_text_changelog() {
+        let options = ChangelogOptions {
+            format: ChangelogFormat::PlainText,
+            document_url: None,
+            previous_document_url: None,
+        };
+
+        let changelog = changelog(&diff(), &options);
+
+        let expected = std::fs::read_to_string("test-res/snapshots/orders-changelog.txt").unwrap();
+        assert_eq!(changelog, expected);
+    }
+
+    #[test]
+    fn removed_entities_without_previous_document() {
+        let options = ChangelogOptions {
+            format: ChangelogFormat::Markdown,
+            document_url: Some(String::from("orders.yaml")),
+            previous_document_url: None,
+        };
+
+        let changelog = changelog(&diff(), &options);
+
+        assert!(changelog
+            .lines()
+            .any(|line| line == "- operation `auditOrders` removed (`/operations/auditOrders`)"));
+    }
+
+    #[test]
+    fn placeholders_of_names_are_kept() {
+        let pointer = JsonPointer::parse("/channels/orders/messages/{channel}").unwrap();
+
+        assert_eq!(
+            entity(&pointer, &ENGLISH),
+            "message `{channel}` of channel `orders`"
+        );
+    }
+}
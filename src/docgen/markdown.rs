@@ -0,0 +1,188 @@
+use crate::spec::common::{Extensions, RefOr};
+use crate::spec::operation::OperationAction;
+use crate::spec::AsyncApiV3Spec;
+use alloc::format;
+use alloc::string::String;
+
+/// Renders a reference page of a document in Markdown: its title, version and description,
+/// then a section per kind of entity listing the root channels, the root operations and the
+/// component messages, with their address, action and channel, summary and description.
+///
+/// Entities marked with the [x-deprecated](crate::spec::deprecation::DEPRECATED_EXTENSION)
+/// extension carry a **Deprecated** badge, followed by their sunset date when
+/// [x-sunset](crate::spec::deprecation::SUNSET_EXTENSION) is set. Referenced entities are
+/// listed with their reference only, and empty sections are left out.
+///
+/// ```
+/// # use asyncapiv3::docgen::markdown;
+/// # use asyncapiv3::spec::{AsyncApiSpec, AsyncApiV3Spec};
+/// let document = r#"{
+///     "asyncapi": "3.0.0",
+///     "info": { "title": "Lights", "version": "1.0.0" },
+///     "channels": {
+///         "lights": { "address": "lights", "messages": {}, "x-deprecated": true }
+///     }
+/// }"#;
+/// let AsyncApiSpec::V3_0_0(spec) = document.parse().unwrap();
+///
+/// assert_eq!(
+///     markdown(&spec),
+///     "# Lights 1.0.0\n\n## Channels\n\n### `lights` **Deprecated**\n\nAddress: `lights`\n"
+/// );
+/// ```
+pub fn markdown(spec: &AsyncApiV3Spec) -> String {
+    let mut page = format!("# {} {}\n", spec.info.title, spec.info.version);
+    paragraph(&mut page, spec.info.description.as_deref());
+
+    if !spec.channels.is_empty() {
+        page.push_str("\n## Channels\n");
+    }
+    for (name, channel) in &spec.channels {
+        let RefOr::Right(channel) = channel else {
+            heading(&mut page, name, None);
+            paragraph(&mut page, reference(channel).as_deref());
+            continue;
+        };
+        heading(&mut page, name, Some(&channel.extensions));
+        if let Some(address) = channel.address.as_option() {
+            paragraph(&mut page, Some(&format!("Address: `{address}`")));
+        }
+        paragraph(&mut page, channel.summary.as_deref());
+        paragraph(&mut page, channel.description.as_deref());
+    }
+
+    if !spec.operations.is_empty() {
+        page.push_str("\n## Operations\n");
+    }
+    for (name, operation) in &spec.operations {
+        let RefOr::Right(operation) = operation else {
+            heading(&mut page, name, None);
+            paragraph(&mut page, reference(operation).as_deref());
+            continue;
+        };
+        heading(&mut page, name, Some(&operation.extensions));
+        let action = match operation.action {
+            OperationAction::Send => "send",
+            OperationAction::Receive => "receive",
+        };
+        paragraph(
+            &mut page,
+            Some(&format!(
+                "Action: `{action}`, channel: `{}`",
+                operation.channel.reference
+            )),
+        );
+        paragraph(&mut page, operation.summary.as_deref());
+        paragraph(&mut page, operation.description.as_deref());
+    }
+
+    if !spec.components.messages.is_empty() {
+        page.push_str("\n## Messages\n");
+    }
+    for (name, message) in &spec.components.messages {
+        let RefOr::Right(message) = message else {
+            heading(&mut page, name, None);
+            paragraph(&mut page, reference(message).as_deref());
+            continue;
+        };
+        heading(&mut page, name, Some(&message.extensions));
+        paragraph(&mut page, message.summary.as_deref());
+        paragraph(&mut page, message.description.as_deref());
+    }
+    page
+}
+
+/// Writes the heading of an entity, with the deprecation badge of its `extensions`.
+fn heading(page: &mut String, name: &str, extensions: Option<&Extensions>) {
+    page.push_str(&format!("\n### `{name}`"));
+    if let Some(extensions) = extensions.filter(|extensions| extensions.deprecated()) {
+        page.push_str(" **Deprecated**");
+        if let Some(sunset) = extensions.sunset() {
+            page.push_str(&format!(" (sunset {sunset})"));
+        }
+    }
+    page.push('\n');
+}
+
+/// Writes a paragraph, if there is any text.
+fn paragraph(page: &mut String, text: Option<&str>) {
+    if let Some(text) = text.filter(|text| !text.is_empty()) {
+        page.push_str(&format!("\n{text}\n"));
+    }
+}
+
+/// Describes the target of a referenced entity.
+fn reference<T>(entity: &RefOr<T>) -> Option<String> {
+    entity
+        .as_reference()
+        .map(|reference| format!("See `{}`", reference.reference))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::parse_spec;
+
+    #[test]
+    fn deprecation_badges() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Lights
+  version: 1.0.0
+  description: Controls the lights.
+channels:
+  lights:
+    address: lights
+    description: Light measurements.
+    messages:
+      lightMeasured:
+        $ref: '#/components/messages/lightMeasured'
+operations:
+  measure:
+    action: send
+    summary: Measures the lights.
+    x-deprecated: true
+    x-sunset: '2027-01-01'
+    channel:
+      $ref: '#/channels/lights'
+components:
+  messages:
+    lightMeasured:
+      description: A measurement.
+      x-deprecated: true
+"#,
+        );
+
+        assert_eq!(
+            markdown(&spec),
+            "# Lights 1.0.0
+
+Controls the lights.
+
+## Channels
+
+### `lights`
+
+Address: `lights`
+
+Light measurements.
+
+## Operations
+
+### `measure` **Deprecated** (sunset 2027-01-01)
+
+Action: `send`, channel: `#/channels/lights`
+
+Measures the lights.
+
+## Messages
+
+### `lightMeasured` **Deprecated**
+
+A measurement.
+"
+        );
+    }
+}
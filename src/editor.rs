@@ -0,0 +1,1153 @@
+//! Mutations of a document which keep its references intact.
+//!
+//! Removing an object through the public fields of a document leaves the references to it
+//! dangling. A [DocumentEditor] applies the removal along with a [RemovalPolicy] for these
+//! references, so the document always passes the `unresolved-reference` rule of
+//! [validate](crate::validation::validate), and reports what it changed.
+use crate::error::Error;
+use crate::spec::channel::{Channel, ChannelBindings, Parameter};
+use crate::spec::common::{
+    local_pointer, Either, ExternalDocumentation, JsonPointer, RefOr, ReferenceObject, Tag,
+};
+use crate::spec::message::{
+    CorrelationId, Message, MessageBindings, MessageExample, MessageTrait, MultiFormatSchema,
+};
+use crate::spec::operation::{
+    Operation, OperationBindings, OperationReply, OperationReplyAddress, OperationTrait,
+};
+use crate::spec::resolve::Referenceable;
+use crate::spec::security::SecurityScheme;
+use crate::spec::server::{Server, ServerBindings, Variable};
+use crate::spec::AsyncApiV3Spec;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use serde_json::{Map, Value};
+
+/// Number of rounds of cascading removals or inlined copies after which the references are
+/// considered cyclic.
+const MAX_ROUNDS: usize = 32;
+
+/// What to do with the references to an object being removed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RemovalPolicy {
+    /// Remove the objects holding the references as well, and the ones referencing them in
+    /// turn. A reference its holder cannot do without, the `channel` of an operation or a
+    /// reply, removes the holder.
+    Cascade,
+    /// Fail with [Error::StillReferenced] listing the references.
+    Reject,
+    /// Replace the references with copies of the removed object. The references the
+    /// specification requires to stay references, the `channel` of an operation or a reply and
+    /// the items of `messages` and `servers` lists, fail with [Error::StillReferenced].
+    Detach,
+}
+
+/// A change applied by a [DocumentEditor].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Change {
+    /// An object was added at the pointer.
+    Added { pointer: JsonPointer },
+    /// The object at the pointer was removed.
+    Removed { pointer: JsonPointer },
+    /// The reference at the pointer was replaced with a copy of the object it pointed at.
+    Inlined {
+        /// Location of the former reference.
+        pointer: JsonPointer,
+        /// The replaced reference, e.g. `#/components/messages/lightMeasured`.
+        reference: String,
+    },
+}
+
+/// Checked mutations of a document, see the [module documentation](self).
+///
+/// Every operation either applies completely and returns the list of changes, or fails and
+/// leaves the document untouched.
+pub struct DocumentEditor<'a> {
+    spec: &'a mut AsyncApiV3Spec,
+}
+
+impl<'a> DocumentEditor<'a> {
+    /// Creates an editor of a document, which is only modified by operations that succeed.
+    pub fn new(spec: &'a mut AsyncApiV3Spec) -> Self {
+        Self { spec }
+    }
+
+    /// Returns the edited document.
+    pub fn spec(&self) -> &AsyncApiV3Spec {
+        self.spec
+    }
+
+    /// Removes a root channel, handling the references to it and to its messages according
+    /// to the policy.
+    pub fn remove_channel(
+        &mut self,
+        key: &str,
+        policy: RemovalPolicy,
+    ) -> Result<Vec<Change>, Error> {
+        if !self.spec.channels.contains_key(key) {
+            return Err(Error::ChannelNotFound {
+                name: String::from(key),
+            });
+        }
+        self.remove(JsonPointer::from_iter(["channels", key]), policy)
+    }
+
+    /// Removes an entry of a components section, named as in the document, e.g.
+    /// `securitySchemes`.
+    pub fn remove_component(
+        &mut self,
+        section: &str,
+        key: &str,
+        policy: RemovalPolicy,
+    ) -> Result<Vec<Change>, Error> {
+        self.remove(JsonPointer::from_iter(["components", section, key]), policy)
+    }
+
+    /// Adds a message to a root channel, or to the channel it references. The references of the
+    /// message must resolve, and the key must not be used by another message of the channel.
+    pub fn add_message_to_channel(
+        &mut self,
+        channel: &str,
+        key: &str,
+        mut message: RefOr<Message>,
+    ) -> Result<Vec<Change>, Error> {
+        let Some(entry) = self.spec.channels.get(channel) else {
+            return Err(Error::ChannelNotFound {
+                name: String::from(channel),
+            });
+        };
+        entry.resolve(self.spec)?;
+        let mut pointer = JsonPointer::from_iter(["channels", channel]);
+        while let Some(Either::Left(reference)) = Channel::lookup(self.spec, &pointer) {
+            pointer = reference
+                .local_pointer()
+                .ok_or_else(|| Error::UnresolvedReference {
+                    reference: reference.reference.clone(),
+                })?;
+        }
+
+        let mut pass = Pass::new(self.spec, Selection::Unresolved, Action::List);
+        pass.slot(&mut message, &pointer.join("messages").join(key));
+        if let Some((_, reference)) = pass.found.into_iter().next() {
+            return Err(Error::UnresolvedReference { reference });
+        }
+
+        let channel = match pointer.segments() {
+            [channels, name] if channels == "channels" => self.spec.channels.get_mut(name),
+            [components, channels, name]
+                if components == "components" && channels == "channels" =>
+            {
+                self.spec.components.channels.get_mut(name)
+            }
+            _ => None,
+        };
+        let Some(Either::Right(channel)) = channel else {
+            return Err(Error::UnresolvedReference {
+                reference: format!("#{pointer}"),
+            });
+        };
+        if channel.messages.contains_key(key) {
+            return Err(Error::DuplicateDefinition {
+                key: String::from(key),
+            });
+        }
+        channel.messages.insert(String::from(key), message);
+        Ok(Vec::from([Change::Added {
+            pointer: pointer.join("messages").join(key),
+        }]))
+    }
+
+    fn remove(&mut self, target: JsonPointer, policy: RemovalPolicy) -> Result<Vec<Change>, Error> {
+        let mut spec = self.spec.clone();
+        if !take_entry(&mut spec, &target) {
+            return Err(Error::UnresolvedReference {
+                reference: format!("#{target}"),
+            });
+        }
+        let action = match policy {
+            RemovalPolicy::Cascade => Action::Remove,
+            RemovalPolicy::Reject => Action::List,
+            RemovalPolicy::Detach => Action::Inline,
+        };
+        let mut changes = Vec::from([Change::Removed {
+            pointer: target.clone(),
+        }]);
+        let mut removed = Vec::from([target.clone()]);
+
+        for _ in 0..MAX_ROUNDS {
+            let mut pass = Pass::new(self.spec, Selection::Removed(&removed), action);
+            pass.document(&mut spec);
+            let Pass {
+                found,
+                changes: applied,
+                ..
+            } = pass;
+            if !found.is_empty() {
+                return Err(Error::StillReferenced {
+                    pointer: target.to_string(),
+                    references: found
+                        .iter()
+                        .map(|(location, _)| location.to_string())
+                        .collect(),
+                });
+            }
+            if applied.is_empty() {
+                *self.spec = spec;
+                return Ok(changes);
+            }
+            removed.extend(applied.iter().filter_map(|change| match change {
+                Change::Removed { pointer } => Some(pointer.clone()),
+                _ => None,
+            }));
+            changes.extend(applied);
+        }
+        Err(Error::CyclicReference {
+            reference: format!("#{target}"),
+        })
+    }
+}
+
+/// Removes a root channel or an entry of a components section, returning `false` if there is
+/// no such entry.
+fn take_entry(spec: &mut AsyncApiV3Spec, pointer: &JsonPointer) -> bool {
+    let components = &mut spec.components;
+    match pointer.segments() {
+        [channels, key] if channels == "channels" => spec.channels.remove(key).is_some(),
+        [root, section, key] if root == "components" => match section.as_str() {
+            "schemas" => components.schemas.remove(key).is_some(),
+            "servers" => components.servers.remove(key).is_some(),
+            "channels" => components.channels.remove(key).is_some(),
+            "operations" => components.operations.remove(key).is_some(),
+            "messages" => components.messages.remove(key).is_some(),
+            "securitySchemes" => components.security_schemes.remove(key).is_some(),
+            "serverVariables" => components.server_variables.remove(key).is_some(),
+            "parameters" => components.parameters.remove(key).is_some(),
+            "correlationIds" => components.correlation_ids.remove(key).is_some(),
+            "replies" => components.replies.remove(key).is_some(),
+            "replyAddresses" => components.reply_addresses.remove(key).is_some(),
+            "externalDocs" => components.external_docs.remove(key).is_some(),
+            "tags" => components.tags.remove(key).is_some(),
+            "operationTraits" => components.operation_traits.remove(key).is_some(),
+            "messageTraits" => components.message_traits.remove(key).is_some(),
+            "serverBindings" => components.server_bindings.remove(key).is_some(),
+            "channelBindings" => components.channel_bindings.remove(key).is_some(),
+            "operationBindings" => components.operation_bindings.remove(key).is_some(),
+            "messageBindings" => components.message_bindings.remove(key).is_some(),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// What a [Pass] does with the references it selects.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Action {
+    /// List them.
+    List,
+    /// Replace them with copies of their targets, listing the ones which must stay references.
+    Inline,
+    /// Remove the objects holding them.
+    Remove,
+}
+
+/// The references a [Pass] selects.
+#[derive(Clone, Copy, Debug)]
+enum Selection<'r> {
+    /// The local references to the removed objects, or inside them, which resolved before the
+    /// edit. References which were dangling before the edit are not its concern.
+    Removed(&'r [JsonPointer]),
+    /// The local references which do not resolve.
+    Unresolved,
+}
+
+/// A walk through the references of the data model, applying an [Action] to the selected ones.
+struct Pass<'o> {
+    /// The document before the edit, holding the targets of the references.
+    original: &'o AsyncApiV3Spec,
+    selection: Selection<'o>,
+    action: Action,
+    /// The location and the `$ref` of the selected references which are left in place.
+    found: Vec<(JsonPointer, String)>,
+    changes: Vec<Change>,
+}
+
+/// What replaces a reference after a [Pass] handled it.
+enum Outcome<T> {
+    Keep,
+    Remove,
+    Replace(T),
+}
+
+impl<'o> Pass<'o> {
+    fn new(original: &'o AsyncApiV3Spec, selection: Selection<'o>, action: Action) -> Self {
+        Self {
+            original,
+            selection,
+            action,
+            found: Vec::new(),
+            changes: Vec::new(),
+        }
+    }
+
+    /// Applies the action to a reference if it is selected. `resolves` tells whether a pointer
+    /// resolves in the original document and `copy` gives the object to inline.
+    fn handle<T>(
+        &mut self,
+        reference: &str,
+        location: &JsonPointer,
+        resolves: impl FnOnce(&JsonPointer) -> bool,
+        copy: impl FnOnce(&JsonPointer) -> Option<T>,
+    ) -> Outcome<T> {
+        let Some(target) = local_pointer(reference) else {
+            return Outcome::Keep;
+        };
+        let selected = match self.selection {
+            Selection::Removed(removed) => {
+                removed.iter().any(|removed| target.starts_with(removed)) && resolves(&target)
+            }
+            Selection::Unresolved => !resolves(&target),
+        };
+        if !selected {
+            return Outcome::Keep;
+        }
+        match self.action {
+            Action::Remove => return Outcome::Remove,
+            Action::Inline => {
+                if let Some(copy) = copy(&target) {
+                    self.changes.push(Change::Inlined {
+                        pointer: location.clone(),
+                        reference: String::from(reference),
+                    });
+                    return Outcome::Replace(copy);
+                }
+            }
+            Action::List => {}
+        }
+        self.found.push((location.clone(), String::from(reference)));
+        Outcome::Keep
+    }
+
+    /// Handles a reference which must stay one, e.g. the `channel` of an operation. Returns
+    /// `true` if it is to be removed.
+    fn reference<T: Referenceable>(
+        &mut self,
+        reference: &ReferenceObject,
+        location: &JsonPointer,
+    ) -> bool {
+        let original = self.original;
+        let outcome = self.handle::<()>(
+            &reference.reference,
+            location,
+            |target| T::lookup(original, target).is_some(),
+            |_| None,
+        );
+        matches!(outcome, Outcome::Remove)
+    }
+
+    /// Handles a list of references which must stay ones, e.g. the `messages` of an operation.
+    fn references<T: Referenceable>(
+        &mut self,
+        references: &mut Vec<ReferenceObject>,
+        location: &JsonPointer,
+    ) {
+        let mut index = 0;
+        references.retain(|reference| {
+            let item = location.join(index.to_string());
+            index += 1;
+            !self.removes::<T>(reference, item)
+        });
+    }
+
+    fn removes<T: Referenceable>(
+        &mut self,
+        reference: &ReferenceObject,
+        location: JsonPointer,
+    ) -> bool {
+        let remove = self.reference::<T>(reference, &location);
+        if remove {
+            self.changes.push(Change::Removed { pointer: location });
+        }
+        remove
+    }
+
+    /// Handles a reference or an object. Returns `true` if it is to be removed.
+    fn slot<T: Walk>(&mut self, slot: &mut RefOr<T>, location: &JsonPointer) -> bool {
+        let reference = match slot {
+            Either::Left(reference) => reference,
+            Either::Right(item) => return item.walk(self, location),
+        };
+        let original = self.original;
+        match self.handle(
+            &reference.reference,
+            location,
+            |target| T::lookup(original, target).is_some(),
+            |target| T::lookup(original, target).cloned(),
+        ) {
+            Outcome::Keep => false,
+            Outcome::Remove => true,
+            Outcome::Replace(copy) => {
+                *slot = copy;
+                false
+            }
+        }
+    }
+
+    fn option<T: Walk>(&mut self, slot: &mut Option<RefOr<T>>, location: JsonPointer) {
+        if slot.as_mut().is_some_and(|item| self.slot(item, &location)) {
+            *slot = None;
+            self.changes.push(Change::Removed { pointer: location });
+        }
+    }
+
+    fn list<T: Walk>(&mut self, items: &mut Vec<RefOr<T>>, location: &JsonPointer) {
+        let mut index = 0;
+        items.retain_mut(|item| {
+            let location = location.join(index.to_string());
+            index += 1;
+            let remove = self.slot(item, &location);
+            if remove {
+                self.changes.push(Change::Removed { pointer: location });
+            }
+            !remove
+        });
+    }
+
+    fn map<T: Walk>(&mut self, entries: &mut BTreeMap<String, RefOr<T>>, location: &JsonPointer) {
+        entries.retain(|key, item| {
+            let location = location.join(key.as_str());
+            let remove = self.slot(item, &location);
+            if remove {
+                self.changes.push(Change::Removed { pointer: location });
+            }
+            !remove
+        });
+    }
+
+    fn tags(&mut self, tags: &mut [Tag], location: &JsonPointer) {
+        for (index, tag) in tags.iter_mut().enumerate() {
+            tag.walk(self, &location.join(index.to_string()));
+        }
+    }
+
+    /// Handles the `$ref`s inside a JSON value. Returns `true` if the value is itself a
+    /// reference to be removed.
+    fn value(&mut self, value: &mut Value, location: &JsonPointer) -> bool {
+        match value {
+            Value::Object(fields) => self.object(fields, location),
+            Value::Array(items) => {
+                let mut index = 0;
+                items.retain_mut(|item| {
+                    let location = location.join(index.to_string());
+                    index += 1;
+                    self.keep_value(item, location)
+                });
+                false
+            }
+            _ => false,
+        }
+    }
+
+    fn keep_value(&mut self, value: &mut Value, location: JsonPointer) -> bool {
+        let remove = self.value(value, &location);
+        if remove {
+            self.changes.push(Change::Removed { pointer: location });
+        }
+        !remove
+    }
+
+    /// Handles the `$ref`s inside a JSON object, which is replaced by an object it references
+    /// when inlining. Returns `true` if the object is a reference to be removed.
+    fn object(&mut self, fields: &mut Map<String, Value>, location: &JsonPointer) -> bool {
+        if let Some(reference) = fields.get("$ref").and_then(Value::as_str) {
+            let reference = String::from(reference);
+            let original = self.original;
+            return match self.handle(
+                &reference,
+                location,
+                |target| resolves(original, target),
+                |target| match value_at(original, target) {
+                    Some(Value::Object(copy)) => Some(copy),
+                    _ => None,
+                },
+            ) {
+                Outcome::Keep => false,
+                Outcome::Remove => true,
+                Outcome::Replace(copy) => {
+                    *fields = copy;
+                    false
+                }
+            };
+        }
+        fields.retain(|key, field| self.keep_value(field, location.join(key.as_str())));
+        false
+    }
+
+    fn values<'v>(
+        &mut self,
+        values: impl Iterator<Item = (&'v String, &'v mut Value)>,
+        location: &JsonPointer,
+    ) {
+        for (key, value) in values {
+            let location = location.join(key.as_str());
+            if self.value(value, &location) {
+                *value = Value::Null;
+                self.changes.push(Change::Removed { pointer: location });
+            }
+        }
+    }
+
+    fn schema(&mut self, schema: &mut schemars::Schema, location: &JsonPointer) -> bool {
+        schema
+            .as_object_mut()
+            .is_some_and(|fields| self.object(fields, location))
+    }
+
+    /// Handles a schema of a binding, which is not [Referenceable].
+    fn binding_schema(
+        &mut self,
+        slot: &mut Option<RefOr<schemars::Schema>>,
+        location: JsonPointer,
+    ) {
+        let remove = match slot {
+            None => false,
+            Some(Either::Right(schema)) => self.schema(schema, &location),
+            Some(Either::Left(reference)) => {
+                let original = self.original;
+                match self.handle(
+                    &reference.reference,
+                    &location,
+                    |target| resolves(original, target),
+                    |target| schemars::Schema::try_from(value_at(original, target)?).ok(),
+                ) {
+                    Outcome::Keep => false,
+                    Outcome::Remove => true,
+                    Outcome::Replace(copy) => {
+                        *slot = Some(Either::Right(copy));
+                        false
+                    }
+                }
+            }
+        };
+        if remove {
+            *slot = None;
+            self.changes.push(Change::Removed { pointer: location });
+        }
+    }
+
+    fn examples(&mut self, examples: &mut [MessageExample], location: &JsonPointer) {
+        for (index, example) in examples.iter_mut().enumerate() {
+            let location = location.join(index.to_string());
+            let headers = location.join("headers");
+            example
+                .headers
+                .retain(|key, value| self.keep_value(value, headers.join(key.as_str())));
+            let location = location.join("payload");
+            let remove = match &mut example.payload {
+                None => false,
+                Some(Either::Right(payload)) => self.value(payload, &location),
+                Some(Either::Left(reference)) => {
+                    let original = self.original;
+                    match self.handle(
+                        &reference.reference,
+                        &location,
+                        |target| resolves(original, target),
+                        |target| value_at(original, target),
+                    ) {
+                        Outcome::Keep => false,
+                        Outcome::Remove => true,
+                        Outcome::Replace(copy) => {
+                            example.payload = Some(Either::Right(copy));
+                            false
+                        }
+                    }
+                }
+            };
+            if remove {
+                example.payload = None;
+                self.changes.push(Change::Removed { pointer: location });
+            }
+        }
+    }
+
+    fn document(&mut self, spec: &mut AsyncApiV3Spec) {
+        let root = JsonPointer::default();
+        let info = root.join("info");
+        self.list(&mut spec.info.tags, &info.join("tags"));
+        self.option(&mut spec.info.external_docs, info.join("externalDocs"));
+        self.map(&mut spec.servers, &root.join("servers"));
+        self.map(&mut spec.channels, &root.join("channels"));
+        self.map(&mut spec.operations, &root.join("operations"));
+
+        let components = &mut spec.components;
+        let location = root.join("components");
+        self.map(&mut components.schemas, &location.join("schemas"));
+        self.map(&mut components.servers, &location.join("servers"));
+        self.map(&mut components.channels, &location.join("channels"));
+        self.map(&mut components.operations, &location.join("operations"));
+        self.map(&mut components.messages, &location.join("messages"));
+        self.map(
+            &mut components.security_schemes,
+            &location.join("securitySchemes"),
+        );
+        self.map(
+            &mut components.server_variables,
+            &location.join("serverVariables"),
+        );
+        self.map(&mut components.parameters, &location.join("parameters"));
+        self.map(
+            &mut components.correlation_ids,
+            &location.join("correlationIds"),
+        );
+        self.map(&mut components.replies, &location.join("replies"));
+        self.map(
+            &mut components.reply_addresses,
+            &location.join("replyAddresses"),
+        );
+        self.map(
+            &mut components.external_docs,
+            &location.join("externalDocs"),
+        );
+        self.map(&mut components.tags, &location.join("tags"));
+        self.map(
+            &mut components.operation_traits,
+            &location.join("operationTraits"),
+        );
+        self.map(
+            &mut components.message_traits,
+            &location.join("messageTraits"),
+        );
+        self.map(
+            &mut components.server_bindings,
+            &location.join("serverBindings"),
+        );
+        self.map(
+            &mut components.channel_bindings,
+            &location.join("channelBindings"),
+        );
+        self.map(
+            &mut components.operation_bindings,
+            &location.join("operationBindings"),
+        );
+        self.map(
+            &mut components.message_bindings,
+            &location.join("messageBindings"),
+        );
+    }
+}
+
+/// Objects of the data model holding references.
+trait Walk: Referenceable + Clone {
+    /// Goes through the references of the object at `location`. Returns `true` if the object
+    /// cannot do without a reference the pass removes, and is to be removed as well.
+    fn walk(&mut self, pass: &mut Pass, location: &JsonPointer) -> bool;
+}
+
+impl Walk for Server {
+    fn walk(&mut self, pass: &mut Pass, location: &JsonPointer) -> bool {
+        pass.map(&mut self.variables, &location.join("variables"));
+        pass.list(&mut self.security, &location.join("security"));
+        pass.tags(&mut self.tags, &location.join("tags"));
+        pass.option(&mut self.external_docs, location.join("externalDocs"));
+        pass.option(&mut self.bindings, location.join("bindings"));
+        false
+    }
+}
+
+impl Walk for Channel {
+    fn walk(&mut self, pass: &mut Pass, location: &JsonPointer) -> bool {
+        pass.map(&mut self.messages, &location.join("messages"));
+        pass.references::<Server>(&mut self.servers, &location.join("servers"));
+        pass.map(&mut self.parameters, &location.join("parameters"));
+        pass.tags(&mut self.tags, &location.join("tags"));
+        pass.option(&mut self.external_docs, location.join("externalDocs"));
+        pass.option(&mut self.bindings, location.join("bindings"));
+        false
+    }
+}
+
+impl Walk for Operation {
+    fn walk(&mut self, pass: &mut Pass, location: &JsonPointer) -> bool {
+        if pass.reference::<Channel>(&self.channel, &location.join("channel")) {
+            return true;
+        }
+        pass.list(&mut self.security, &location.join("security"));
+        pass.tags(&mut self.tags, &location.join("tags"));
+        pass.option(&mut self.external_docs, location.join("externalDocs"));
+        pass.option(&mut self.bindings, location.join("bindings"));
+        pass.list(&mut self.traits, &location.join("traits"));
+        if let Some(messages) = &mut self.messages {
+            pass.references::<Message>(messages, &location.join("messages"));
+        }
+        pass.option(&mut self.reply, location.join("reply"));
+        false
+    }
+}
+
+impl Walk for OperationReply {
+    fn walk(&mut self, pass: &mut Pass, location: &JsonPointer) -> bool {
+        if let Some(channel) = &self.channel {
+            if pass.reference::<Channel>(channel, &location.join("channel")) {
+                return true;
+            }
+        }
+        pass.option(&mut self.address, location.join("address"));
+        pass.references::<Message>(&mut self.messages, &location.join("messages"));
+        false
+    }
+}
+
+impl Walk for OperationTrait {
+    fn walk(&mut self, pass: &mut Pass, location: &JsonPointer) -> bool {
+        pass.option(&mut self.security, location.join("security"));
+        pass.tags(&mut self.tags, &location.join("tags"));
+        pass.option(&mut self.external_docs, location.join("externalDocs"));
+        pass.option(&mut self.bindings, location.join("bindings"));
+        false
+    }
+}
+
+impl Walk for Message {
+    fn walk(&mut self, pass: &mut Pass, location: &JsonPointer) -> bool {
+        pass.option(&mut self.headers, location.join("headers"));
+        pass.option(&mut self.payload, location.join("payload"));
+        pass.option(&mut self.correlation_id, location.join("correlationId"));
+        pass.tags(&mut self.tags, &location.join("tags"));
+        pass.option(&mut self.external_docs, location.join("externalDocs"));
+        pass.option(&mut self.bindings, location.join("bindings"));
+        pass.examples(&mut self.examples, &location.join("examples"));
+        pass.list(&mut self.traits, &location.join("traits"));
+        false
+    }
+}
+
+impl Walk for MessageTrait {
+    fn walk(&mut self, pass: &mut Pass, location: &JsonPointer) -> bool {
+        pass.option(&mut self.headers, location.join("headers"));
+        pass.option(&mut self.correlation_id, location.join("correlationId"));
+        pass.tags(&mut self.tags, &location.join("tags"));
+        pass.option(&mut self.external_docs, location.join("externalDocs"));
+        pass.option(&mut self.bindings, location.join("bindings"));
+        pass.examples(&mut self.examples, &location.join("examples"));
+        false
+    }
+}
+
+impl Walk for Tag {
+    fn walk(&mut self, pass: &mut Pass, location: &JsonPointer) -> bool {
+        pass.option(&mut self.external_docs, location.join("externalDocs"));
+        false
+    }
+}
+
+impl Walk for Either<schemars::Schema, MultiFormatSchema> {
+    fn walk(&mut self, pass: &mut Pass, location: &JsonPointer) -> bool {
+        match self {
+            Either::Left(schema) => pass.schema(schema, location),
+            Either::Right(schema) => {
+                let location = location.join("schema");
+                if pass.value(&mut schema.schema, &location) {
+                    schema.schema = Value::Null;
+                    pass.changes.push(Change::Removed { pointer: location });
+                }
+                false
+            }
+        }
+    }
+}
+
+impl Walk for ServerBindings {
+    fn walk(&mut self, pass: &mut Pass, location: &JsonPointer) -> bool {
+        let typed = [
+            ("ws", self.ws.as_mut().map(|binding| &mut binding.extra)),
+            ("nats", self.nats.as_mut().map(|binding| &mut binding.extra)),
+            ("http", self.http.as_mut().map(|binding| &mut binding.extra)),
+        ];
+        for (protocol, extra) in typed {
+            if let Some(extra) = extra {
+                pass.values(extra.iter_mut(), &location.join(protocol));
+            }
+        }
+        pass.values(self.other.iter_mut(), location);
+        false
+    }
+}
+
+impl Walk for ChannelBindings {
+    fn walk(&mut self, pass: &mut Pass, location: &JsonPointer) -> bool {
+        if let Some(ws) = &mut self.ws {
+            let location = location.join("ws");
+            pass.binding_schema(&mut ws.query, location.join("query"));
+            pass.binding_schema(&mut ws.headers, location.join("headers"));
+            pass.values(ws.extra.iter_mut(), &location);
+        }
+        let typed = [
+            ("nats", self.nats.as_mut().map(|binding| &mut binding.extra)),
+            ("http", self.http.as_mut().map(|binding| &mut binding.extra)),
+        ];
+        for (protocol, extra) in typed {
+            if let Some(extra) = extra {
+                pass.values(extra.iter_mut(), &location.join(protocol));
+            }
+        }
+        pass.values(self.other.iter_mut(), location);
+        false
+    }
+}
+
+impl Walk for OperationBindings {
+    fn walk(&mut self, pass: &mut Pass, location: &JsonPointer) -> bool {
+        if let Some(http) = &mut self.http {
+            let location = location.join("http");
+            pass.binding_schema(&mut http.query, location.join("query"));
+            pass.values(http.extra.iter_mut(), &location);
+        }
+        let typed = [
+            ("ws", self.ws.as_mut().map(|binding| &mut binding.extra)),
+            ("nats", self.nats.as_mut().map(|binding| &mut binding.extra)),
+        ];
+        for (protocol, extra) in typed {
+            if let Some(extra) = extra {
+                pass.values(extra.iter_mut(), &location.join(protocol));
+            }
+        }
+        pass.values(self.other.iter_mut(), location);
+        false
+    }
+}
+
+impl Walk for MessageBindings {
+    fn walk(&mut self, pass: &mut Pass, location: &JsonPointer) -> bool {
+        if let Some(http) = &mut self.http {
+            let location = location.join("http");
+            pass.binding_schema(&mut http.headers, location.join("headers"));
+            pass.values(http.extra.iter_mut(), &location);
+        }
+        let typed = [
+            ("ws", self.ws.as_mut().map(|binding| &mut binding.extra)),
+            ("nats", self.nats.as_mut().map(|binding| &mut binding.extra)),
+        ];
+        for (protocol, extra) in typed {
+            if let Some(extra) = extra {
+                pass.values(extra.iter_mut(), &location.join(protocol));
+            }
+        }
+        pass.values(self.other.iter_mut(), location);
+        false
+    }
+}
+
+macro_rules! without_references {
+    ($($ty:ty),*) => {
+        $(
+            impl Walk for $ty {
+                fn walk(&mut self, _pass: &mut Pass, _location: &JsonPointer) -> bool {
+                    false
+                }
+            }
+        )*
+    };
+}
+
+without_references!(
+    Parameter,
+    SecurityScheme,
+    Variable,
+    CorrelationId,
+    OperationReplyAddress,
+    ExternalDocumentation
+);
+
+/// Returns `true` if a pointer addresses an object or a schema value of the document.
+fn resolves(spec: &AsyncApiV3Spec, pointer: &JsonPointer) -> bool {
+    value_at(spec, pointer).is_some()
+        || Server::lookup(spec, pointer).is_some()
+        || Channel::lookup(spec, pointer).is_some()
+        || Operation::lookup(spec, pointer).is_some()
+        || Message::lookup(spec, pointer).is_some()
+        || Parameter::lookup(spec, pointer).is_some()
+        || OperationReply::lookup(spec, pointer).is_some()
+        || SecurityScheme::lookup(spec, pointer).is_some()
+        || Variable::lookup(spec, pointer).is_some()
+        || CorrelationId::lookup(spec, pointer).is_some()
+        || OperationReplyAddress::lookup(spec, pointer).is_some()
+        || ExternalDocumentation::lookup(spec, pointer).is_some()
+        || Tag::lookup(spec, pointer).is_some()
+        || OperationTrait::lookup(spec, pointer).is_some()
+        || MessageTrait::lookup(spec, pointer).is_some()
+        || ServerBindings::lookup(spec, pointer).is_some()
+        || ChannelBindings::lookup(spec, pointer).is_some()
+        || OperationBindings::lookup(spec, pointer).is_some()
+        || MessageBindings::lookup(spec, pointer).is_some()
+}
+
+/// Returns the value at a pointer inside a schema: a component schema, or the payload or the
+/// headers of a message, e.g. `/components/schemas/order/properties/id`.
+fn value_at(spec: &AsyncApiV3Spec, pointer: &JsonPointer) -> Option<Value> {
+    let segments = pointer.segments();
+    let (schema, rest) = match segments {
+        [components, schemas, _, rest @ ..]
+            if components == "components" && schemas == "schemas" =>
+        {
+            let root = JsonPointer::from_iter(&segments[..3]);
+            (
+                <Either<schemars::Schema, MultiFormatSchema>>::lookup(spec, &root)?,
+                rest,
+            )
+        }
+        _ => {
+            let index = segments
+                .iter()
+                .position(|segment| segment == "payload" || segment == "headers")?;
+            let message = Message::lookup(spec, &JsonPointer::from_iter(&segments[..index]))?
+                .resolve(spec)
+                .ok()?;
+            let schema = match segments[index].as_str() {
+                "payload" => message.payload.as_ref()?,
+                _ => message.headers.as_ref()?,
+            };
+            (schema, &segments[index + 1..])
+        }
+    };
+    let value = match schema.resolve(spec).ok()? {
+        Either::Left(schema) => JsonPointer::from_iter(rest).evaluate(schema.as_value()),
+        Either::Right(schema) => match rest {
+            [field, rest @ ..] if field == "schema" => {
+                JsonPointer::from_iter(rest).evaluate(&schema.schema)
+            }
+            _ => None,
+        },
+    };
+    value.cloned()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::parse_spec;
+    #[cfg(feature = "validation")]
+    use crate::validation::validate;
+    #[cfg(feature = "validation")]
+    use alloc::vec;
+
+    const SPEC: &str = r#"
+asyncapi: 3.0.0
+info:
+  title: Orders
+  version: 1.0.0
+channels:
+  orders:
+    $ref: '#/components/channels/orders'
+  audit:
+    address: audit
+    messages: {}
+operations:
+  placeOrder:
+    action: send
+    channel:
+      $ref: '#/channels/orders'
+  watchOrders:
+    action: receive
+    channel:
+      $ref: '#/channels/orders'
+  recordAudit:
+    action: send
+    channel:
+      $ref: '#/channels/audit'
+components:
+  channels:
+    orders:
+      address: orders
+      messages:
+        orderPlaced:
+          $ref: '#/components/messages/orderPlaced'
+  messages:
+    orderPlaced:
+      payload:
+        type: object
+"#;
+
+    #[cfg(feature = "validation")]
+    fn pointer(pointer: &str) -> JsonPointer {
+        JsonPointer::parse(pointer).unwrap()
+    }
+
+    #[cfg(feature = "validation")]
+    fn assert_no_dangling_references(spec: &AsyncApiV3Spec) {
+        let issues = validate(spec);
+        assert!(
+            issues
+                .iter()
+                .all(|issue| issue.code != "unresolved-reference"),
+            "{issues:?}"
+        );
+    }
+
+    #[test]
+    fn reject_referenced_channel() {
+        let mut spec = parse_spec(SPEC);
+
+        let error = DocumentEditor::new(&mut spec)
+            .remove_channel("orders", RemovalPolicy::Reject)
+            .unwrap_err();
+
+        assert!(matches!(
+            error,
+            Error::StillReferenced { pointer, references }
+                if pointer == "/channels/orders"
+                    && references == ["/operations/placeOrder/channel", "/operations/watchOrders/channel"]
+        ));
+        assert_eq!(spec, parse_spec(SPEC));
+    }
+
+    #[test]
+    #[cfg(feature = "validation")]
+    fn cascade_removes_referencing_operations() {
+        let mut spec = parse_spec(SPEC);
+
+        let changes = DocumentEditor::new(&mut spec)
+            .remove_channel("orders", RemovalPolicy::Cascade)
+            .unwrap();
+
+        assert_eq!(
+            changes,
+            vec![
+                Change::Removed {
+                    pointer: pointer("/channels/orders")
+                },
+                Change::Removed {
+                    pointer: pointer("/operations/placeOrder")
+                },
+                Change::Removed {
+                    pointer: pointer("/operations/watchOrders")
+                },
+            ]
+        );
+        assert_eq!(spec.operations.keys().collect::<Vec<_>>(), ["recordAudit"]);
+        assert_no_dangling_references(&spec);
+    }
+
+    #[test]
+    #[cfg(feature = "validation")]
+    fn detach_inlines_removed_channel() {
+        let mut spec = parse_spec(SPEC);
+
+        let error = DocumentEditor::new(&mut spec)
+            .remove_channel("orders", RemovalPolicy::Detach)
+            .unwrap_err();
+        assert!(
+            matches!(error, Error::StillReferenced { references, .. } if references.len() == 2)
+        );
+
+        let changes = DocumentEditor::new(&mut spec)
+            .remove_component("channels", "orders", RemovalPolicy::Detach)
+            .unwrap();
+
+        assert_eq!(
+            changes,
+            vec![
+                Change::Removed {
+                    pointer: pointer("/components/channels/orders")
+                },
+                Change::Inlined {
+                    pointer: pointer("/channels/orders"),
+                    reference: String::from("#/components/channels/orders"),
+                },
+            ]
+        );
+        let Either::Right(channel) = &spec.channels["orders"] else {
+            panic!("the channel is inlined");
+        };
+        assert_eq!(channel.address.as_deref(), Some("orders"));
+        assert_eq!(spec.operations.len(), 3);
+        assert_no_dangling_references(&spec);
+    }
+
+    #[test]
+    #[cfg(feature = "validation")]
+    fn schema_references() {
+        let yaml = SPEC.replace(
+            "      payload:\n        type: object\n",
+            "      payload:\n        $ref: '#/components/schemas/order'\n  \
+             schemas:\n    order:\n      type: object\n    \
+             receipt:\n      type: object\n      properties:\n        \
+             order:\n          $ref: '#/components/schemas/order'\n",
+        );
+
+        let mut spec = parse_spec(&yaml);
+        let changes = DocumentEditor::new(&mut spec)
+            .remove_component("schemas", "order", RemovalPolicy::Cascade)
+            .unwrap();
+        assert_eq!(
+            changes,
+            vec![
+                Change::Removed {
+                    pointer: pointer("/components/schemas/order")
+                },
+                Change::Removed {
+                    pointer: pointer("/components/schemas/receipt/properties/order")
+                },
+                Change::Removed {
+                    pointer: pointer("/components/messages/orderPlaced/payload")
+                },
+            ]
+        );
+        assert_no_dangling_references(&spec);
+
+        let mut spec = parse_spec(&yaml);
+        DocumentEditor::new(&mut spec)
+            .remove_component("schemas", "order", RemovalPolicy::Detach)
+            .unwrap();
+        let Some(Either::Right(Either::Left(receipt))) = spec.components.schemas.get("receipt")
+        else {
+            panic!("the receipt schema is inline");
+        };
+        assert_eq!(
+            receipt.as_value()["properties"]["order"],
+            serde_json::json!({ "type": "object" })
+        );
+        let Either::Right(message) = &spec.components.messages["orderPlaced"] else {
+            panic!("the message is inline");
+        };
+        assert!(matches!(message.payload, Some(Either::Right(_))));
+        assert_no_dangling_references(&spec);
+    }
+
+    #[test]
+    #[cfg(feature = "validation")]
+    fn add_message_through_channel_reference() {
+        let mut spec = parse_spec(SPEC);
+        let mut editor = DocumentEditor::new(&mut spec);
+        let reference =
+            |target: &str| RefOr::Left(crate::spec::common::ReferenceObject::new(target));
+
+        let changes = editor
+            .add_message_to_channel(
+                "orders",
+                "orderCancelled",
+                reference("#/components/messages/orderPlaced"),
+            )
+            .unwrap();
+        let missing = editor.add_message_to_channel(
+            "audit",
+            "auditEntry",
+            reference("#/components/messages/auditEntry"),
+        );
+        let duplicate = editor.add_message_to_channel(
+            "orders",
+            "orderPlaced",
+            reference("#/components/messages/orderPlaced"),
+        );
+
+        assert_eq!(
+            changes,
+            vec![Change::Added {
+                pointer: pointer("/components/channels/orders/messages/orderCancelled")
+            }]
+        );
+        assert!(matches!(missing, Err(Error::UnresolvedReference { .. })));
+        assert!(matches!(duplicate, Err(Error::DuplicateDefinition { .. })));
+        assert_no_dangling_references(editor.spec());
+    }
+}
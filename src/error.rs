@@ -1,11 +1,99 @@
+use crate::compat::V2Construct;
+use crate::query::QueryError;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("Duplicate definition entry '{key}'")]
     DuplicateDefinition { key: String },
+    #[error("Invalid key '{key}'")]
+    InvalidKey { key: String },
+    #[error("Invalid extension key '{key}', extension keys start with 'x-'")]
+    InvalidExtensionKey { key: String },
+    #[error("Validation rule code '{code}' is already used")]
+    DuplicateRule { code: String },
+    #[error("The selected components also require {}", .required.join(", "))]
+    IncompleteSelection { required: Vec<String> },
     #[error("Duplicate operation entry '{name}'")]
     DuplicateOperation { name: String },
     #[error("Channel not found '{name}'")]
     ChannelNotFound { name: String },
+    #[error("Server not found '{name}'")]
+    ServerNotFound { name: String },
+    #[error("Unresolved reference '{reference}'")]
+    UnresolvedReference { reference: String },
+    #[error("Document '{name}' is outside of the root directory")]
+    OutsideRoot { name: String },
+    #[error("Reference '{reference}' points at {found} instead of {expected}")]
+    WrongCategory {
+        reference: String,
+        expected: String,
+        found: String,
+    },
+    #[error("'{pointer}' is still referenced by {}", references.join(", "))]
+    StillReferenced {
+        pointer: String,
+        references: Vec<String>,
+    },
+    #[error("Cyclic reference '{reference}'")]
+    CyclicReference { reference: String },
+    #[error("Invalid runtime expression '{expression}'")]
+    InvalidRuntimeExpression { expression: String },
+    #[error("Runtime expression '{expression}' does not match any value of the message")]
+    UnmatchedRuntimeExpression { expression: String },
+    #[error("Invalid {location}: {reason}")]
+    InvalidInstance { location: String, reason: String },
+    #[error("Value does not match the content type '{content_type}'")]
+    ContentTypeMismatch { content_type: String },
+    #[error("Value '{value}' of server variable '{name}' is not one of {}", allowed.join(", "))]
+    InvalidVariableValue {
+        name: String,
+        value: String,
+        allowed: Vec<String>,
+    },
+    #[error("Conflicting SASL mechanisms: {}", mechanisms.join(", "))]
+    ConflictingSaslMechanisms { mechanisms: Vec<String> },
+    #[error("Invalid binding defaults for '{protocol}': {reason}")]
+    InvalidBindingDefaults { protocol: String, reason: String },
+    #[error("Invalid patch of the '{protocol}' binding: {reason}")]
+    InvalidBindingPatch { protocol: String, reason: String },
+    #[error("Conflicting CloudEvents type '{event_type}' of {}", messages.join(", "))]
+    ConflictingEventType {
+        event_type: String,
+        messages: Vec<String>,
+    },
+    #[error("Conflicting identifier '{identifier}' of {}", keys.join(", "))]
+    ConflictingIdentifier {
+        identifier: String,
+        keys: Vec<String>,
+    },
+    #[error("Operation '{operation}' uses '{reference}', which is not a root channel")]
+    NotRootChannel {
+        operation: String,
+        reference: String,
+    },
+    #[error("Invalid query: {error}")]
+    InvalidQuery { error: QueryError },
+    #[error("Cannot parse document: {reason}")]
+    Parse { reason: String },
+    #[error("Invalid document: {reason}{}", display_hints(.hints))]
+    InvalidDocument {
+        reason: String,
+        /// AsyncAPI 2 constructs found in the document, likely causing the failure.
+        hints: Vec<V2Construct>,
+    },
+}
+
+fn display_hints(hints: &[V2Construct]) -> String {
+    if hints.is_empty() {
+        return String::new();
+    }
+    let hints = hints
+        .iter()
+        .map(|hint| format!("\n  - {hint}"))
+        .collect::<String>();
+    format!("\nThe document contains AsyncAPI 2 constructs:{hints}")
 }
@@ -0,0 +1,402 @@
+//! Round-trip fidelity checks, exposing the constructs the data model cannot represent.
+//!
+//! [check] parses a document, serializes it back and compares both structurally. Every
+//! difference is classified and, when it is one of the [KNOWN_NORMALIZATIONS] which do not
+//! alter the meaning of a document, explained.
+use crate::error::Error;
+use crate::spec::common::JsonPointer;
+use crate::spec::AsyncApiSpec;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use serde::de::{Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+
+/// The kinds of differences between a document and its round-tripped version.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum DifferenceKind {
+    /// The fields of an object are serialized in another order.
+    ReorderedKeys,
+    /// A field of the document is not serialized back.
+    DroppedField,
+    /// A field holding an empty list or map is added or removed.
+    NormalizedDefault,
+    /// A value is serialized back differently.
+    ChangedValue,
+}
+
+/// A difference between a document and its round-tripped version.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Difference {
+    /// The kind of difference.
+    pub kind: DifferenceKind,
+    /// Location of the difference in the document.
+    pub pointer: JsonPointer,
+    /// Reason of the [known normalization](KNOWN_NORMALIZATIONS) explaining the difference.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<String>,
+}
+
+/// A difference known to be introduced by a round trip without altering the document meaning.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KnownNormalization {
+    /// The kind of difference.
+    pub kind: DifferenceKind,
+    /// Pointer to the locations concerned, where `*` matches any segment and a trailing `**`
+    /// any number of segments.
+    pub pattern: &'static str,
+    /// Why the difference is acceptable.
+    pub reason: &'static str,
+}
+
+impl KnownNormalization {
+    /// Returns `true` if the normalization explains the given difference.
+    pub fn explains(&self, kind: DifferenceKind, pointer: &JsonPointer) -> bool {
+        if kind != self.kind {
+            return false;
+        }
+        let pattern = self.pattern.split('/').skip(1).collect::<Vec<_>>();
+        let segments = pointer.segments();
+        match pattern.split_last() {
+            Some((&"**", prefix)) => {
+                segments.len() >= prefix.len() && segments_match(prefix, &segments[..prefix.len()])
+            }
+            _ => segments.len() == pattern.len() && segments_match(&pattern, segments),
+        }
+    }
+}
+
+fn segments_match(pattern: &[&str], segments: &[String]) -> bool {
+    pattern
+        .iter()
+        .zip(segments)
+        .all(|(pattern, segment)| *pattern == "*" || pattern == segment)
+}
+
+/// The differences known to be introduced by a round trip.
+pub const KNOWN_NORMALIZATIONS: &[KnownNormalization] = &[
+    KnownNormalization {
+        kind: DifferenceKind::ReorderedKeys,
+        pattern: "/**",
+        reason: "fields are serialized in declaration order and maps sorted by key",
+    },
+    KnownNormalization {
+        kind: DifferenceKind::NormalizedDefault,
+        pattern: "/**",
+        reason: "empty lists and maps are omitted",
+    },
+];
+
+/// Result of a round-trip [check].
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FidelityReport {
+    /// The differences found, in document order.
+    pub differences: Vec<Difference>,
+}
+
+impl FidelityReport {
+    /// Returns `true` if the document is serialized back identically.
+    pub fn is_lossless(&self) -> bool {
+        self.differences.is_empty()
+    }
+
+    /// Returns the differences not explained by a [known normalization](KNOWN_NORMALIZATIONS).
+    pub fn unexplained(&self) -> impl Iterator<Item = &Difference> {
+        self.differences
+            .iter()
+            .filter(|difference| difference.explanation.is_none())
+    }
+}
+
+/// Parses a JSON document, serializes it back and reports the differences between both.
+///
+/// Fails with [Error::Parse] if the input is not a JSON document and with
+/// [Error::InvalidDocument] if the parsed document cannot be serialized back.
+pub fn check(input: &str) -> Result<FidelityReport, Error> {
+    let parse_error = |err: serde_json::Error| Error::Parse {
+        reason: err.to_string(),
+    };
+    let original = serde_json::from_str::<Node>(input).map_err(parse_error)?;
+    let spec = input.parse::<AsyncApiSpec>()?;
+    let output = serde_json::to_string(&spec)
+        .and_then(|output| serde_json::from_str::<Node>(&output))
+        .map_err(|err| Error::InvalidDocument {
+            reason: err.to_string(),
+            hints: Vec::new(),
+        })?;
+
+    let mut report = FidelityReport::default();
+    compare(&original, &output, &JsonPointer::default(), &mut report);
+    Ok(report)
+}
+
+fn compare(original: &Node, output: &Node, pointer: &JsonPointer, report: &mut FidelityReport) {
+    match (original, output) {
+        (Node::Object(original), Node::Object(output)) => {
+            let position = |fields: &[(String, Node)], key: &str| {
+                fields.iter().position(|(field, _)| field == key)
+            };
+            let mut common = Vec::new();
+            for (key, value) in original {
+                let child = pointer.join(key.as_str());
+                match position(output, key) {
+                    Some(index) => {
+                        common.push(index);
+                        compare(value, &output[index].1, &child, report);
+                    }
+                    None if value.is_default() => {
+                        report.push(DifferenceKind::NormalizedDefault, child)
+                    }
+                    None => report.push(DifferenceKind::DroppedField, child),
+                }
+            }
+            for (key, value) in output {
+                if position(original, key).is_none() {
+                    let kind = if value.is_default() {
+                        DifferenceKind::NormalizedDefault
+                    } else {
+                        DifferenceKind::ChangedValue
+                    };
+                    report.push(kind, pointer.join(key.as_str()));
+                }
+            }
+            if common.windows(2).any(|pair| pair[0] > pair[1]) {
+                report.push(DifferenceKind::ReorderedKeys, pointer.clone());
+            }
+        }
+        (Node::Array(original), Node::Array(output)) if original.len() == output.len() => {
+            for (index, (original, output)) in original.iter().zip(output).enumerate() {
+                compare(original, output, &pointer.join(index.to_string()), report);
+            }
+        }
+        (Node::Number(original), Node::Number(output)) if original.as_f64() == output.as_f64() => {}
+        (original, output) if original == output => {}
+        _ => report.push(DifferenceKind::ChangedValue, pointer.clone()),
+    }
+}
+
+impl FidelityReport {
+    fn push(&mut self, kind: DifferenceKind, pointer: JsonPointer) {
+        let explanation = KNOWN_NORMALIZATIONS
+            .iter()
+            .find(|normalization| normalization.explains(kind, &pointer))
+            .map(|normalization| String::from(normalization.reason));
+        self.differences.push(Difference {
+            kind,
+            pointer,
+            explanation,
+        });
+    }
+}
+
+/// A JSON value keeping the order of the object fields.
+#[derive(Debug, PartialEq)]
+enum Node {
+    Null,
+    Bool(bool),
+    Number(serde_json::Number),
+    String(String),
+    Array(Vec<Node>),
+    Object(Vec<(String, Node)>),
+}
+
+impl Node {
+    /// Returns `true` for the empty sequences and mappings, which are not serialized back.
+    /// Nulls are not defaults: `address: null` means something else than no address.
+    fn is_default(&self) -> bool {
+        match self {
+            Node::Array(items) => items.is_empty(),
+            Node::Object(fields) => fields.is_empty(),
+            _ => false,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Node {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(NodeVisitor)
+    }
+}
+
+struct NodeVisitor;
+
+impl<'de> Visitor<'de> for NodeVisitor {
+    type Value = Node;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a JSON value")
+    }
+
+    fn visit_unit<E>(self) -> Result<Node, E> {
+        Ok(Node::Null)
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<Node, E> {
+        Ok(Node::Bool(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Node, E> {
+        Ok(Node::Number(value.into()))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Node, E> {
+        Ok(Node::Number(value.into()))
+    }
+
+    fn visit_f64<E: serde::de::Error>(self, value: f64) -> Result<Node, E> {
+        serde_json::Number::from_f64(value)
+            .map(Node::Number)
+            .ok_or_else(|| E::custom("invalid number"))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Node, E> {
+        Ok(Node::String(String::from(value)))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Node, A::Error> {
+        let mut items = Vec::new();
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(Node::Array(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Node, A::Error> {
+        let mut fields = Vec::new();
+        while let Some(field) = map.next_entry()? {
+            fields.push(field);
+        }
+        Ok(Node::Object(fields))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs;
+
+    /// Converts a YAML document to JSON, keeping the order of the fields.
+    fn yaml_to_json(yaml: &str) -> String {
+        let value = serde_yaml::from_str::<serde_yaml::Value>(yaml).unwrap();
+        serde_json::to_string(&value).unwrap()
+    }
+
+    #[test]
+    fn fixtures_round_trip_without_unexplained_differences() {
+        let mut paths = fs::read_dir("./test-res/3.0.0")
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect::<Vec<_>>();
+        paths.sort();
+
+        for path in paths {
+            let input = yaml_to_json(&fs::read_to_string(&path).unwrap());
+            let report = check(&input).unwrap();
+            let unexplained = report.unexplained().collect::<Vec<_>>();
+            assert!(unexplained.is_empty(), "{path:?}: {unexplained:#?}");
+        }
+    }
+
+    #[test]
+    fn classify_differences() {
+        let input = r#"{
+            "info": { "version": "1.0.0", "title": "Lights", "tags": [] },
+            "asyncapi": "3.0.0",
+            "servers": {
+                "production": {
+                    "host": "broker.example.com",
+                    "protocol": "mqtt",
+                    "tags": [],
+                    "unknown": true
+                }
+            },
+            "channels": {},
+            "operations": {},
+            "components": {}
+        }"#;
+
+        let report = check(input).unwrap();
+
+        let differences = report
+            .differences
+            .iter()
+            .map(|difference| {
+                (
+                    difference.kind,
+                    difference.pointer.to_string(),
+                    difference.explanation.is_some(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            differences,
+            vec![
+                (DifferenceKind::ReorderedKeys, String::from("/info"), true),
+                (
+                    DifferenceKind::NormalizedDefault,
+                    String::from("/servers/production/tags"),
+                    true
+                ),
+                (
+                    DifferenceKind::DroppedField,
+                    String::from("/servers/production/unknown"),
+                    false
+                ),
+                (DifferenceKind::ReorderedKeys, String::new(), true),
+            ]
+        );
+        assert!(!report.is_lossless());
+        assert_eq!(report.unexplained().count(), 1);
+    }
+
+    #[test]
+    fn dropped_nulls_are_not_explained() {
+        let report = check(
+            r#"{
+                "asyncapi": "3.0.0",
+                "info": { "title": "Lights", "version": "1.0.0", "description": null },
+                "channels": {},
+                "operations": {},
+                "components": {}
+            }"#,
+        )
+        .unwrap();
+
+        let unexplained = report.unexplained().collect::<Vec<_>>();
+        assert_eq!(unexplained.len(), 1);
+        assert_eq!(unexplained[0].kind, DifferenceKind::DroppedField);
+        assert_eq!(unexplained[0].pointer.to_string(), "/info/description");
+    }
+
+    #[test]
+    fn report_serialization() {
+        let report = check(
+            r#"{
+                "asyncapi": "3.0.0",
+                "info": { "title": "Lights", "version": "1.0.0", "tags": [] },
+                "servers": { "a": { "host": "h", "protocol": "mqtt", "extra": 1 } },
+                "channels": {},
+                "operations": {},
+                "components": {}
+            }"#,
+        )
+        .unwrap();
+
+        let serialized = serde_json::to_value(&report).unwrap();
+
+        assert_eq!(
+            serialized,
+            serde_json::json!({
+                "differences": [{ "kind": "droppedField", "pointer": "/servers/a/extra" }]
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<FidelityReport>(serialized).unwrap(),
+            report
+        );
+    }
+}
@@ -0,0 +1,457 @@
+//! Canonical formatting of documents, shared by a team through a [FormatProfile].
+//!
+//! A profile bundles the formatting choices applied by [apply] and is serialized next to the
+//! documents it formats, e.g. as `asyncapi-format.json`. The data model serializes fields in
+//! declaration order and sorts the keys of every map, so the [KeyOrder] of the sections of a
+//! profile is only applied by [apply_yaml], which formats the text of a document.
+use crate::error::Error;
+use crate::spec::common::JsonPointer;
+use crate::spec::AsyncApiV3Spec;
+use crate::transform::DATA_FIELDS;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use serde_json::Value;
+
+/// How the local references of a document are written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReferenceStyle {
+    /// References are left as written.
+    #[default]
+    Keep,
+    /// Local references are rewritten as `#` followed by a JSON pointer escaping `~` as `~0` and
+    /// `/` as `~1` and nothing else, e.g. `#/components/schemas/order id` for
+    /// `#/components/schemas/order%20id` and `#/channels/orders~1placed` for
+    /// `#/channels/orders%2Fplaced`. References to other documents and local references which
+    /// are not a valid JSON pointer, e.g. with a `~2` escape, are left as written.
+    Canonical,
+}
+
+/// The order of the keys of a map.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KeyOrder {
+    /// Keys are left in the order of the formatted document.
+    #[default]
+    Keep,
+    /// Keys are sorted alphabetically.
+    Sorted,
+}
+
+/// The order of the keys of each section of a document.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SectionOrder {
+    /// The order of the servers.
+    pub servers: KeyOrder,
+    /// The order of the channels.
+    pub channels: KeyOrder,
+    /// The order of the operations.
+    pub operations: KeyOrder,
+    /// The order of the keys of every map of the components.
+    pub components: KeyOrder,
+}
+
+impl SectionOrder {
+    /// Uses the same order for every section.
+    pub fn all(order: KeyOrder) -> Self {
+        Self {
+            servers: order,
+            channels: order,
+            operations: order,
+            components: order,
+        }
+    }
+}
+
+/// Formatting choices applied by [apply].
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FormatProfile {
+    /// Trim the whitespace at the end of every line of the descriptions, and the blank lines
+    /// ending them.
+    pub trim_descriptions: bool,
+    /// Sort the tags of every object by name.
+    pub sort_tags: bool,
+    /// How the local references are written.
+    pub reference_style: ReferenceStyle,
+    /// The order of the keys of each section, applied by [apply_yaml].
+    pub key_order: SectionOrder,
+}
+
+impl FormatProfile {
+    /// Normalizes everything a profile can: trimmed descriptions, tags sorted by name, canonical
+    /// references and sections sorted by key.
+    pub fn canonical() -> Self {
+        Self {
+            trim_descriptions: true,
+            sort_tags: true,
+            reference_style: ReferenceStyle::Canonical,
+            key_order: SectionOrder::all(KeyOrder::Sorted),
+        }
+    }
+
+    /// Trims descriptions but keeps the order of the tags and of the keys of the sections, and
+    /// the references as written.
+    pub fn preserve_order() -> Self {
+        Self {
+            trim_descriptions: true,
+            sort_tags: false,
+            reference_style: ReferenceStyle::Keep,
+            key_order: SectionOrder::all(KeyOrder::Keep),
+        }
+    }
+}
+
+/// Formats a document according to a profile, returning the location of every changed value.
+///
+/// Applying a profile to a document it already formatted changes nothing. Descriptions, tags
+/// and references inside examples, default values and specification extensions are left
+/// untouched.
+///
+/// Fails with [Error::InvalidDocument] if the document cannot be serialized or the formatted
+/// document cannot be read back, in which case the document is left untouched.
+pub fn apply(
+    spec: &mut AsyncApiV3Spec,
+    profile: &FormatProfile,
+) -> Result<Vec<JsonPointer>, Error> {
+    let invalid_document = |err: serde_json::Error| Error::InvalidDocument {
+        reason: err.to_string(),
+        hints: Vec::new(),
+    };
+    let mut document = serde_json::to_value(&*spec).map_err(invalid_document)?;
+    let mut changed = Vec::new();
+    format_value(
+        &mut document,
+        &JsonPointer::default(),
+        profile,
+        &mut changed,
+    );
+    if !changed.is_empty() {
+        *spec = serde_json::from_value(document).map_err(invalid_document)?;
+    }
+    Ok(changed)
+}
+
+/// Formats a YAML document according to a profile, like [apply], and orders the keys of its
+/// sections as the profile tells. The sections keeping their order list their keys in the order
+/// of `document`.
+///
+/// Aliases are expanded, and comments and the quoting of strings are not kept. Fails with
+/// [Error::Parse] or [Error::InvalidDocument] if `document` is not an AsyncAPI 3.0 document.
+#[cfg(feature = "yaml")]
+#[cfg_attr(docsrs, doc(cfg(feature = "yaml")))]
+pub fn apply_yaml(document: &str, profile: &FormatProfile) -> Result<String, Error> {
+    let parse_error = |err: serde_yaml::Error| Error::Parse {
+        reason: err.to_string(),
+    };
+    let invalid_document = |err: serde_yaml::Error| Error::InvalidDocument {
+        reason: err.to_string(),
+        hints: Vec::new(),
+    };
+    let original = serde_yaml::from_str::<serde_yaml::Value>(document).map_err(parse_error)?;
+    let crate::spec::AsyncApiSpec::V3_0_0(mut spec) =
+        crate::spec::AsyncApiSpec::from_yaml(document)?;
+    apply(&mut spec, profile)?;
+    let mut formatted =
+        serde_yaml::to_value(crate::spec::AsyncApiSpec::V3_0_0(spec)).map_err(invalid_document)?;
+    let order = &profile.key_order;
+    for (section, order) in [
+        ("servers", order.servers),
+        ("channels", order.channels),
+        ("operations", order.operations),
+    ] {
+        if let (KeyOrder::Keep, Some(formatted)) = (order, formatted.get_mut(section)) {
+            keep_order(&original[section], formatted);
+        }
+    }
+    if let (KeyOrder::Keep, Some(components)) = (
+        order.components,
+        formatted
+            .get_mut("components")
+            .and_then(serde_yaml::Value::as_mapping_mut),
+    ) {
+        for (name, section) in components.iter_mut() {
+            keep_order(&original["components"][name], section);
+        }
+    }
+    serde_yaml::to_string(&formatted).map_err(invalid_document)
+}
+
+/// Orders the keys of `formatted` as in `original`, the keys missing from `original` last.
+#[cfg(feature = "yaml")]
+fn keep_order(original: &serde_yaml::Value, formatted: &mut serde_yaml::Value) {
+    let (Some(original), Some(formatted)) = (original.as_mapping(), formatted.as_mapping_mut())
+    else {
+        return;
+    };
+    let mut entries = core::mem::take(formatted).into_iter().collect::<Vec<_>>();
+    entries.sort_by_key(|(key, _)| {
+        original
+            .keys()
+            .position(|original| original == key)
+            .unwrap_or(original.len())
+    });
+    formatted.extend(entries);
+}
+
+fn format_value(
+    value: &mut Value,
+    pointer: &JsonPointer,
+    profile: &FormatProfile,
+    changed: &mut Vec<JsonPointer>,
+) {
+    match value {
+        Value::Object(fields) => {
+            for (key, value) in fields.iter_mut() {
+                if DATA_FIELDS.contains(&key.as_str()) || key.starts_with("x-") {
+                    continue;
+                }
+                let field_pointer = pointer.join(key.as_str());
+                let formatted = match (key.as_str(), &mut *value) {
+                    ("description", Value::String(description)) if profile.trim_descriptions => {
+                        trim_description(description)
+                    }
+                    ("$ref", Value::String(reference)) => {
+                        format_reference(reference, profile.reference_style)
+                    }
+                    ("tags", Value::Array(tags)) if profile.sort_tags => sort_tags(tags),
+                    _ => false,
+                };
+                if formatted {
+                    changed.push(field_pointer.clone());
+                }
+                format_value(value, &field_pointer, profile, changed);
+            }
+        }
+        Value::Array(items) => {
+            for (index, item) in items.iter_mut().enumerate() {
+                format_value(item, &pointer.join(index.to_string()), profile, changed);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn trim_description(description: &mut String) -> bool {
+    let trimmed = description
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n");
+    let trimmed = trimmed.trim_end();
+    if trimmed == description {
+        return false;
+    }
+    *description = String::from(trimmed);
+    true
+}
+
+fn format_reference(reference: &mut String, style: ReferenceStyle) -> bool {
+    let ReferenceStyle::Canonical = style else {
+        return false;
+    };
+    let Some(canonical) = reference
+        .strip_prefix('#')
+        .and_then(|pointer| JsonPointer::parse(pointer).ok())
+        .map(|pointer| format!("#{pointer}"))
+    else {
+        return false;
+    };
+    if canonical == *reference {
+        return false;
+    }
+    *reference = canonical;
+    true
+}
+
+/// Sorts tags by name, keeping the order of tags without a name or with the same name.
+fn sort_tags(tags: &mut [Value]) -> bool {
+    let name = |tag: &Value| tag.get("name").and_then(Value::as_str).map(String::from);
+    if tags.windows(2).all(|pair| name(&pair[0]) <= name(&pair[1])) {
+        return false;
+    }
+    tags.sort_by_key(name);
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::parse_spec;
+
+    const MESSY_SPEC: &str = r#"
+asyncapi: 3.0.0
+info:
+  title: Orders
+  version: 1.0.0
+  description: "Order events.  \nPublished by the shop.   \n\n"
+  tags:
+    - name: shop
+    - name: orders
+channels:
+  orders:
+    address: orders
+    messages:
+      orderPlaced:
+        $ref: '#/components/messages/order%20placed'
+  audit:
+    address: audit
+    messages:
+      entry:
+        $ref: '#/components/messages/audit%2Fentry'
+operations:
+  placeOrder:
+    action: send
+    description: "Places an order. "
+    channel:
+      $ref: '#/channels/orders'
+    tags:
+      - name: write
+      - name: orders
+components:
+  messages:
+    order placed:
+      payload:
+        type: object
+    audit/entry:
+      payload:
+        type: string
+"#;
+
+    fn tag_names(tags: &[crate::spec::common::RefOr<crate::spec::common::Tag>]) -> Vec<&str> {
+        tags.iter()
+            .filter_map(|tag| match tag {
+                crate::spec::common::Either::Right(tag) => Some(tag.name.as_str()),
+                crate::spec::common::Either::Left(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn canonical_profile_converges() {
+        let mut spec = parse_spec(MESSY_SPEC);
+
+        let changed = apply(&mut spec, &FormatProfile::canonical()).unwrap();
+
+        assert_eq!(
+            changed.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            [
+                "/channels/audit/messages/entry/$ref",
+                "/channels/orders/messages/orderPlaced/$ref",
+                "/info/description",
+                "/info/tags",
+                "/operations/placeOrder/description",
+                "/operations/placeOrder/tags",
+            ]
+        );
+        assert_eq!(
+            spec.info.description.as_deref(),
+            Some("Order events.\nPublished by the shop.")
+        );
+        assert_eq!(tag_names(&spec.info.tags), ["orders", "shop"]);
+        let crate::spec::common::Either::Left(reference) =
+            &spec.channels["orders"].resolve(&spec).unwrap().messages["orderPlaced"]
+        else {
+            panic!("the message is a reference");
+        };
+        assert_eq!(reference.reference, "#/components/messages/order placed");
+        let crate::spec::common::Either::Left(reference) =
+            &spec.channels["audit"].resolve(&spec).unwrap().messages["entry"]
+        else {
+            panic!("the message is a reference");
+        };
+        assert_eq!(reference.reference, "#/components/messages/audit~1entry");
+
+        let formatted = spec.clone();
+        assert!(apply(&mut spec, &FormatProfile::canonical())
+            .unwrap()
+            .is_empty());
+        assert_eq!(spec, formatted);
+    }
+
+    #[test]
+    fn preserve_order_profile_converges() {
+        let mut spec = parse_spec(MESSY_SPEC);
+
+        apply(&mut spec, &FormatProfile::preserve_order()).unwrap();
+
+        assert_eq!(
+            spec.info.description.as_deref(),
+            Some("Order events.\nPublished by the shop.")
+        );
+        assert_eq!(tag_names(&spec.info.tags), ["shop", "orders"]);
+        let formatted = spec.clone();
+        assert!(apply(&mut spec, &FormatProfile::preserve_order())
+            .unwrap()
+            .is_empty());
+        assert_eq!(spec, formatted);
+    }
+
+    #[cfg(feature = "yaml")]
+    fn keys(document: &str, path: &[&str]) -> Vec<String> {
+        let document = serde_yaml::from_str::<serde_yaml::Value>(document).unwrap();
+        let section = path.iter().fold(&document, |value, key| &value[key]);
+        section
+            .as_mapping()
+            .unwrap()
+            .keys()
+            .map(|key| String::from(key.as_str().unwrap()))
+            .collect()
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn section_key_order() {
+        let canonical = apply_yaml(MESSY_SPEC, &FormatProfile::canonical()).unwrap();
+        let preserved = apply_yaml(MESSY_SPEC, &FormatProfile::preserve_order()).unwrap();
+
+        assert_eq!(keys(&canonical, &["channels"]), ["audit", "orders"]);
+        assert_eq!(keys(&preserved, &["channels"]), ["orders", "audit"]);
+        assert_eq!(
+            keys(&canonical, &["components", "messages"]),
+            ["audit/entry", "order placed"]
+        );
+        assert_eq!(
+            keys(&preserved, &["components", "messages"]),
+            ["order placed", "audit/entry"]
+        );
+        assert_eq!(
+            apply_yaml(&canonical, &FormatProfile::canonical()).unwrap(),
+            canonical
+        );
+        assert_eq!(
+            apply_yaml(&preserved, &FormatProfile::preserve_order()).unwrap(),
+            preserved
+        );
+    }
+
+    #[test]
+    fn profile_serialization() {
+        let profile = serde_json::to_value(FormatProfile::canonical()).unwrap();
+
+        assert_eq!(
+            profile,
+            serde_json::json!({
+                "trimDescriptions": true,
+                "sortTags": true,
+                "referenceStyle": "canonical",
+                "keyOrder": {
+                    "servers": "sorted",
+                    "channels": "sorted",
+                    "operations": "sorted",
+                    "components": "sorted"
+                }
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<FormatProfile>(serde_json::json!({ "sortTags": true }))
+                .unwrap(),
+            FormatProfile {
+                sort_tags: true,
+                ..FormatProfile::default()
+            }
+        );
+    }
+}
@@ -0,0 +1,477 @@
+//! Differential checks between the strict and the lenient parsers, for fuzzing.
+//!
+//! [check_round_trip] checks that a document serialized by this crate parses back strictly,
+//! and [check_lenient] that the lenient [parse_partial](AsyncApiV3Spec::parse_partial) does not
+//! panic, and that the documents it fully reads round trip as well. [fuzz_documents] and
+//! [fuzz_lenient] run them on documents generated from raw bytes, so that they can be called
+//! from a `cargo fuzz` target or a `proptest` test:
+//!
+//! ```
+//! use proptest::prelude::*;
+//!
+//! proptest!(|(data in proptest::collection::vec(any::<u8>(), 0..1024))| {
+//!     asyncapiv3::fuzzing::fuzz_documents(&data);
+//! });
+//! ```
+//!
+//! Generated documents are available as [ArbitraryDocument], to check properties of other
+//! operations.
+use crate::error::Error;
+use crate::spec::common::JsonPointer;
+use crate::spec::partial::ParseOptions;
+use crate::spec::{AsyncApiSpec, AsyncApiV3Spec};
+use arbitrary::{Arbitrary, Unstructured};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// Maximum nesting of the generated schemas and example values.
+const MAX_DEPTH: usize = 3;
+
+/// Protocols of the generated servers, along with arbitrary ones.
+const PROTOCOLS: [&str; 6] = ["amqp", "http", "kafka", "mqtt", "nats", "ws"];
+
+/// Content types of the generated messages, along with arbitrary ones.
+const CONTENT_TYPES: [&str; 3] = [
+    "application/json",
+    "application/avro",
+    "text/plain; charset=utf-8",
+];
+
+/// A document generated from raw bytes: servers, channels with their messages, operations
+/// and components referencing each other, with arbitrary names, descriptions and examples.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArbitraryDocument(pub AsyncApiV3Spec);
+
+impl<'a> Arbitrary<'a> for ArbitraryDocument {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let document = document(u)?;
+        AsyncApiV3Spec::deserialize(&document)
+            .map(ArbitraryDocument)
+            .map_err(|_| arbitrary::Error::IncorrectFormat)
+    }
+}
+
+/// Checks that `spec`, serialized by this crate as JSON and, with the `yaml` feature, as
+/// YAML, parses back strictly into the same document.
+///
+/// Fails with [Error::InvalidDocument] if `spec` cannot be serialized or changes when parsed
+/// back, or with the error of the parser.
+pub fn check_round_trip(spec: &AsyncApiV3Spec) -> Result<(), Error> {
+    let document = AsyncApiSpec::V3_0_0(spec.clone());
+    let json = serde_json::to_string(&document).map_err(serialization_error)?;
+    compare(spec, json.parse::<AsyncApiSpec>()?, "JSON")?;
+
+    #[cfg(feature = "yaml")]
+    {
+        let yaml = serde_yaml::to_string(&document).map_err(|err| Error::InvalidDocument {
+            reason: err.to_string(),
+            hints: Vec::new(),
+        })?;
+        compare(spec, AsyncApiSpec::from_yaml(&yaml)?, "YAML")?;
+    }
+    Ok(())
+}
+
+fn serialization_error(err: serde_json::Error) -> Error {
+    Error::InvalidDocument {
+        reason: err.to_string(),
+        hints: Vec::new(),
+    }
+}
+
+fn compare(spec: &AsyncApiV3Spec, parsed: AsyncApiSpec, format: &str) -> Result<(), Error> {
+    let AsyncApiSpec::V3_0_0(parsed) = parsed;
+    let to_value = |spec: &AsyncApiV3Spec| serde_json::to_value(spec).map_err(serialization_error);
+    if to_value(&parsed)? == to_value(spec)? {
+        Ok(())
+    } else {
+        Err(Error::InvalidDocument {
+            reason: format!("the document changes when parsed back from {format}"),
+            hints: Vec::new(),
+        })
+    }
+}
+
+/// Parses `input` leniently then, if it is read without placeholders, checks that it
+/// [round trips](check_round_trip). Strict parsing must accept whatever lenient parsing reads
+/// without issues, so the strict parse of `input` must succeed as well in that case.
+pub fn check_lenient(input: &str) -> Result<(), Error> {
+    let (document, issues) = AsyncApiV3Spec::parse_partial(input, &ParseOptions::default());
+    let Ok(spec) = AsyncApiV3Spec::try_from(document) else {
+        return Ok(());
+    };
+    if issues.is_empty() {
+        input.parse::<AsyncApiSpec>()?;
+    }
+    check_round_trip(&spec)
+}
+
+/// Generates an [ArbitraryDocument] from `data` and checks it
+/// [round trips](check_round_trip), panicking otherwise. Data from which no document can be
+/// generated is ignored.
+pub fn fuzz_documents(data: &[u8]) {
+    let mut u = Unstructured::new(data);
+    if let Ok(ArbitraryDocument(spec)) = ArbitraryDocument::arbitrary(&mut u) {
+        if let Err(err) = check_round_trip(&spec) {
+            panic!("{err}\n{spec:#?}");
+        }
+    }
+}
+
+/// Runs [check_lenient] on `data` read as text, and on a generated document with arbitrary
+/// nodes replaced by arbitrary values, panicking when a check fails.
+pub fn fuzz_lenient(data: &[u8]) {
+    let inputs = [
+        core::str::from_utf8(data).ok().map(String::from),
+        mutated_document(&mut Unstructured::new(data))
+            .ok()
+            .map(|document| document.to_string()),
+    ];
+    for input in inputs.into_iter().flatten() {
+        if let Err(err) = check_lenient(&input) {
+            panic!("{err}\n{input}");
+        }
+    }
+}
+
+fn mutated_document(u: &mut Unstructured) -> arbitrary::Result<Value> {
+    let mut document = document(u)?;
+    for _ in 0..u.int_in_range(1..=4)? {
+        mutate(u, &mut document)?;
+    }
+    Ok(document)
+}
+
+/// Replaces a node of `value`, picked by walking down from it, with an arbitrary value.
+fn mutate(u: &mut Unstructured, value: &mut Value) -> arbitrary::Result<()> {
+    let child = match value {
+        Value::Object(fields) if !fields.is_empty() && u.arbitrary()? => {
+            let index = u.choose_index(fields.len())?;
+            fields.values_mut().nth(index)
+        }
+        Value::Array(items) if !items.is_empty() && u.arbitrary()? => {
+            let index = u.choose_index(items.len())?;
+            items.get_mut(index)
+        }
+        _ => None,
+    };
+    match child {
+        Some(child) => mutate(u, child),
+        None => {
+            *value = json(u, 0)?;
+            Ok(())
+        }
+    }
+}
+
+fn document(u: &mut Unstructured) -> arbitrary::Result<Value> {
+    let mut document = Map::new();
+    document.insert(String::from("asyncapi"), Value::from("3.0.0"));
+    if u.ratio(1, 4)? {
+        document.insert(String::from("id"), Value::from(text(u)?));
+    }
+    let mut info = Map::new();
+    info.insert(String::from("title"), Value::from(text(u)?));
+    info.insert(String::from("version"), Value::from(text(u)?));
+    optional(u, &mut info, "description", |u| text(u).map(Value::from))?;
+    document.insert(String::from("info"), Value::Object(info));
+    optional(u, &mut document, "defaultContentType", content_type)?;
+
+    let schemas = names(u)?;
+    let messages = names(u)?;
+    let servers = names(u)?;
+    let channels = names(u)?;
+
+    let mut components = Map::new();
+    let mut schema_entries = Map::new();
+    for name in &schemas {
+        schema_entries.insert(name.clone(), schema(u, &schemas, 0)?);
+    }
+    components.insert(String::from("schemas"), Value::Object(schema_entries));
+    let mut message_entries = Map::new();
+    for name in &messages {
+        message_entries.insert(name.clone(), message(u, &schemas)?);
+    }
+    components.insert(String::from("messages"), Value::Object(message_entries));
+    let mut scheme_entries = Map::new();
+    for name in names(u)? {
+        scheme_entries.insert(name, security_scheme(u)?);
+    }
+    components.insert(
+        String::from("securitySchemes"),
+        Value::Object(scheme_entries),
+    );
+    let schemes = components["securitySchemes"]
+        .as_object()
+        .map(|schemes| schemes.keys().cloned().collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    let mut server_entries = Map::new();
+    for name in &servers {
+        let mut server = Map::new();
+        server.insert(String::from("host"), Value::from(text(u)?));
+        server.insert(String::from("protocol"), Value::from(protocol(u)?));
+        optional(u, &mut server, "pathname", |u| {
+            text(u).map(|path| Value::from(format!("/{path}")))
+        })?;
+        optional(u, &mut server, "description", |u| text(u).map(Value::from))?;
+        optional(u, &mut server, "security", |u| {
+            references(u, "securitySchemes", &schemes)
+        })?;
+        server_entries.insert(name.clone(), Value::Object(server));
+    }
+    document.insert(String::from("servers"), Value::Object(server_entries));
+
+    let mut channel_entries = Map::new();
+    let mut channel_messages = Vec::new();
+    for name in &channels {
+        let mut channel = Map::new();
+        match u.int_in_range(0..=2)? {
+            0 => {}
+            1 => {
+                channel.insert(String::from("address"), Value::Null);
+            }
+            _ => {
+                channel.insert(String::from("address"), Value::from(text(u)?));
+            }
+        }
+        let mut entries = Map::new();
+        for message_name in names(u)? {
+            let entry = if !messages.is_empty() && u.arbitrary()? {
+                reference("messages", u.choose(&messages)?)
+            } else {
+                message(u, &schemas)?
+            };
+            channel_messages.push((name.clone(), message_name.clone()));
+            entries.insert(message_name, entry);
+        }
+        channel.insert(String::from("messages"), Value::Object(entries));
+        optional(u, &mut channel, "servers", |u| {
+            let mut list = Vec::new();
+            for _ in 0..u.int_in_range(0..=servers.len())? {
+                list.push(root_reference("servers", u.choose(&servers)?));
+            }
+            Ok(Value::Array(list))
+        })?;
+        optional(u, &mut channel, "description", |u| text(u).map(Value::from))?;
+        if u.ratio(1, 4)? {
+            channel.insert(format!("x-{}", text(u)?), json(u, 0)?);
+        }
+        channel_entries.insert(name.clone(), Value::Object(channel));
+    }
+    document.insert(String::from("channels"), Value::Object(channel_entries));
+
+    let mut operation_entries = Map::new();
+    if !channels.is_empty() {
+        for name in names(u)? {
+            let channel = u.choose(&channels)?;
+            let mut operation = Map::new();
+            let action = if u.arbitrary()? { "send" } else { "receive" };
+            operation.insert(String::from("action"), Value::from(action));
+            operation.insert(String::from("channel"), root_reference("channels", channel));
+            let keys = channel_messages
+                .iter()
+                .filter(|(owner, _)| owner == channel)
+                .map(|(_, key)| key)
+                .collect::<Vec<_>>();
+            if !keys.is_empty() && u.arbitrary()? {
+                let key = u.choose(&keys)?;
+                let pointer = JsonPointer::from_iter(["channels", channel, "messages", key]);
+                operation.insert(
+                    String::from("messages"),
+                    serde_json::json!([{ "$ref": format!("#{pointer}") }]),
+                );
+            }
+            optional(u, &mut operation, "summary", |u| text(u).map(Value::from))?;
+            optional(u, &mut operation, "security", |u| {
+                references(u, "securitySchemes", &schemes)
+            })?;
+            operation_entries.insert(name, Value::Object(operation));
+        }
+    }
+    document.insert(String::from("operations"), Value::Object(operation_entries));
+    document.insert(String::from("components"), Value::Object(components));
+    Ok(Value::Object(document))
+}
+
+fn message(u: &mut Unstructured, schemas: &[String]) -> arbitrary::Result<Value> {
+    let mut message = Map::new();
+    optional(u, &mut message, "payload", |u| {
+        if u.ratio(1, 4)? {
+            Ok(serde_json::json!({
+                "schemaFormat": "application/vnd.apache.avro;version=1.9.0",
+                "schema": { "type": "record", "name": text(u)?, "fields": [] },
+            }))
+        } else {
+            schema(u, schemas, 0)
+        }
+    })?;
+    optional(u, &mut message, "contentType", content_type)?;
+    optional(u, &mut message, "name", |u| text(u).map(Value::from))?;
+    optional(u, &mut message, "summary", |u| text(u).map(Value::from))?;
+    optional(u, &mut message, "examples", |u| {
+        let mut examples = Vec::new();
+        for _ in 0..u.int_in_range(0..=2)? {
+            examples.push(serde_json::json!({ "payload": json(u, 0)? }));
+        }
+        Ok(Value::Array(examples))
+    })?;
+    Ok(Value::Object(message))
+}
+
+fn schema(u: &mut Unstructured, schemas: &[String], depth: usize) -> arbitrary::Result<Value> {
+    if !schemas.is_empty() && u.ratio(1, 4)? {
+        return Ok(reference("schemas", u.choose(schemas)?));
+    }
+    let types = ["string", "number", "integer", "boolean", "array", "object"];
+    let ty = *u.choose(&types)?;
+    let mut schema = Map::new();
+    schema.insert(String::from("type"), Value::from(ty));
+    optional(u, &mut schema, "description", |u| text(u).map(Value::from))?;
+    match ty {
+        "array" if depth < MAX_DEPTH => {
+            schema.insert(String::from("items"), self::schema(u, schemas, depth + 1)?);
+        }
+        "object" if depth < MAX_DEPTH => {
+            let mut properties = Map::new();
+            for name in names(u)? {
+                properties.insert(name, self::schema(u, schemas, depth + 1)?);
+            }
+            schema.insert(String::from("properties"), Value::Object(properties));
+        }
+        "string" => optional(u, &mut schema, "maxLength", |u| {
+            u.int_in_range(0..=64u8).map(Value::from)
+        })?,
+        _ => {}
+    }
+    Ok(Value::Object(schema))
+}
+
+fn security_scheme(u: &mut Unstructured) -> arbitrary::Result<Value> {
+    Ok(match u.int_in_range(0..=6)? {
+        0 => serde_json::json!({ "type": "userPassword" }),
+        1 => serde_json::json!({ "type": "X509" }),
+        2 => serde_json::json!({ "type": "scramSha256" }),
+        3 => serde_json::json!({
+            "type": "httpApiKey",
+            "name": text(u)?,
+            "in": *u.choose(&["query", "header", "cookie"])?,
+        }),
+        4 => serde_json::json!({ "type": "http", "scheme": "bearer" }),
+        5 => serde_json::json!({
+            "type": "oauth2",
+            "flows": {
+                "clientCredentials": {
+                    "tokenUrl": format!("https://{}", text(u)?),
+                    "availableScopes": {},
+                },
+            },
+        }),
+        _ => serde_json::json!({ "type": text(u)?, "x-raw": json(u, 0)? }),
+    })
+}
+
+/// An arbitrary JSON value, numbers being integers so they round trip through text.
+fn json(u: &mut Unstructured, depth: usize) -> arbitrary::Result<Value> {
+    let kinds = if depth < MAX_DEPTH { 6 } else { 4 };
+    Ok(match u.choose_index(kinds)? {
+        0 => Value::Null,
+        1 => Value::Bool(u.arbitrary()?),
+        2 => Value::from(u.arbitrary::<i64>()?),
+        3 => Value::from(text(u)?),
+        4 => {
+            let mut items = Vec::new();
+            for _ in 0..u.int_in_range(0..=3)? {
+                items.push(json(u, depth + 1)?);
+            }
+            Value::Array(items)
+        }
+        _ => {
+            let mut fields = Map::new();
+            for _ in 0..u.int_in_range(0..=3)? {
+                fields.insert(text(u)?, json(u, depth + 1)?);
+            }
+            Value::Object(fields)
+        }
+    })
+}
+
+/// Up to four distinct keys.
+fn names(u: &mut Unstructured) -> arbitrary::Result<Vec<String>> {
+    let mut names = Vec::new();
+    for _ in 0..u.int_in_range(0..=4)? {
+        let name = text(u)?;
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    Ok(names)
+}
+
+/// Either a word usual in documents or an arbitrary string.
+fn text(u: &mut Unstructured) -> arbitrary::Result<String> {
+    const WORDS: [&str; 8] = [
+        "orders",
+        "lights.on",
+        "user/{userId}",
+        "a~b",
+        "3.0.0",
+        "yes",
+        "null",
+        "",
+    ];
+    if u.arbitrary()? {
+        Ok(String::from(*u.choose(&WORDS)?))
+    } else {
+        u.arbitrary()
+    }
+}
+
+fn protocol(u: &mut Unstructured) -> arbitrary::Result<String> {
+    if u.ratio(3, 4)? {
+        Ok(String::from(*u.choose(&PROTOCOLS)?))
+    } else {
+        text(u)
+    }
+}
+
+fn content_type(u: &mut Unstructured) -> arbitrary::Result<Value> {
+    if u.ratio(3, 4)? {
+        Ok(Value::from(*u.choose(&CONTENT_TYPES)?))
+    } else {
+        text(u).map(Value::from)
+    }
+}
+
+/// Inserts `field` with a generated value, or leaves it out.
+fn optional(
+    u: &mut Unstructured,
+    object: &mut Map<String, Value>,
+    field: &str,
+    value: impl FnOnce(&mut Unstructured) -> arbitrary::Result<Value>,
+) -> arbitrary::Result<()> {
+    if u.arbitrary()? {
+        object.insert(String::from(field), value(u)?);
+    }
+    Ok(())
+}
+
+fn references(u: &mut Unstructured, section: &str, keys: &[String]) -> arbitrary::Result<Value> {
+    let mut list = Vec::new();
+    if !keys.is_empty() {
+        for _ in 0..u.int_in_range(0..=2)? {
+            list.push(reference(section, u.choose(keys)?));
+        }
+    }
+    Ok(Value::Array(list))
+}
+
+fn reference(section: &str, key: &str) -> Value {
+    let pointer = JsonPointer::from_iter(["components", section, key]);
+    serde_json::json!({ "$ref": format!("#{pointer}") })
+}
+
+fn root_reference(section: &str, key: &str) -> Value {
+    let pointer = JsonPointer::from_iter([section, key]);
+    serde_json::json!({ "$ref": format!("#{pointer}") })
+}
@@ -1,10 +1,55 @@
 #![doc = include_str!("../README.md")]
 #![cfg_attr(docsrs, feature(doc_cfg))]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
+extern crate alloc;
+
+#[cfg(feature = "borrowed")]
+#[cfg_attr(docsrs, doc(cfg(feature = "borrowed")))]
+pub mod borrowed;
 #[cfg(feature = "writer")]
 #[cfg_attr(docsrs, doc(cfg(feature = "writer")))]
 pub mod builder;
-#[cfg(feature = "writer")]
-#[cfg_attr(docsrs, doc(cfg(feature = "writer")))]
+#[cfg(feature = "codegen")]
+#[cfg_attr(docsrs, doc(cfg(feature = "codegen")))]
+pub mod codegen;
+pub mod compat;
+#[cfg(feature = "conformance")]
+#[cfg_attr(docsrs, doc(cfg(feature = "conformance")))]
+pub mod conformance;
+pub mod convert;
+pub mod diff;
+#[cfg(feature = "docgen")]
+#[cfg_attr(docsrs, doc(cfg(feature = "docgen")))]
+pub mod docgen;
+pub mod editor;
 pub mod error;
+pub mod fidelity;
+pub mod format;
+#[cfg(feature = "fuzzing")]
+#[cfg_attr(docsrs, doc(cfg(feature = "fuzzing")))]
+pub mod fuzzing;
+pub mod limits;
+#[cfg(feature = "protobuf")]
+#[cfg_attr(docsrs, doc(cfg(feature = "protobuf")))]
+pub mod protobuf;
+pub mod publish;
+pub mod query;
+#[cfg(any(feature = "axum", feature = "actix"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "axum", feature = "actix"))))]
+pub mod serve;
 pub mod spec;
+#[cfg(feature = "streaming")]
+#[cfg_attr(docsrs, doc(cfg(feature = "streaming")))]
+pub mod streaming;
+#[cfg(test)]
+mod test_util;
+pub mod transform;
+#[cfg(feature = "validation")]
+#[cfg_attr(docsrs, doc(cfg(feature = "validation")))]
+pub mod validation;
+pub mod workspace;
+
+#[cfg(feature = "macros")]
+#[cfg_attr(docsrs, doc(cfg(feature = "macros")))]
+pub use asyncapiv3_macros::asyncapi_include;
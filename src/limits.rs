@@ -0,0 +1,325 @@
+//! Size limits of documents, checked before handing a document to a system with hard limits,
+//! e.g. an API gateway or a schema registry.
+use crate::spec::common::{JsonPointer, RefOr};
+use crate::spec::schema::subschemas;
+use crate::spec::AsyncApiV3Spec;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::fmt;
+use serde_json::Value;
+
+/// The limits checked by [check]. A limit set to `None` is not checked.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Limits {
+    /// Maximum size in bytes of the document serialized as compact JSON.
+    pub max_document_size: Option<usize>,
+    /// Maximum number of entries of the `channels` section.
+    pub max_channels: Option<usize>,
+    /// Maximum number of entries of the `operations` section.
+    pub max_operations: Option<usize>,
+    /// Maximum number of messages: those of the root and component channels, counted once per
+    /// channel using them, and the component messages which no channel or operation uses.
+    pub max_messages: Option<usize>,
+    /// Maximum nesting of a schema, a schema without subschemas having a depth of 1.
+    pub max_schema_depth: Option<usize>,
+    /// Maximum number of values of the `enum` of a schema.
+    pub max_enum_length: Option<usize>,
+}
+
+impl Limits {
+    /// Limits commonly enforced by API gateways and schema registries: documents of 1 MiB,
+    /// 500 channels, 1000 operations and messages, schemas nested 32 levels deep and enums of
+    /// 500 values.
+    pub fn gateway_defaults() -> Self {
+        Self {
+            max_document_size: Some(1024 * 1024),
+            max_channels: Some(500),
+            max_operations: Some(1000),
+            max_messages: Some(1000),
+            max_schema_depth: Some(32),
+            max_enum_length: Some(500),
+        }
+    }
+}
+
+/// The measures bounded by [Limits].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Limit {
+    DocumentSize,
+    Channels,
+    Operations,
+    Messages,
+    SchemaDepth,
+    EnumLength,
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::DocumentSize => "document size",
+            Self::Channels => "number of channels",
+            Self::Operations => "number of operations",
+            Self::Messages => "number of messages",
+            Self::SchemaDepth => "schema depth",
+            Self::EnumLength => "enum length",
+        })
+    }
+}
+
+/// A measure of a document exceeding its limit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LimitViolation {
+    pub limit: Limit,
+    /// Location of the offending value: the document for the size and the counts, the deepest
+    /// schema for the depth and the `enum` for its length.
+    pub pointer: JsonPointer,
+    pub actual: usize,
+    pub maximum: usize,
+}
+
+impl fmt::Display for LimitViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} {} exceeds the limit of {}",
+            self.pointer, self.limit, self.actual, self.maximum
+        )
+    }
+}
+
+/// Checks a document against limits, returning the violations in the order of the fields of
+/// [Limits]. The schema depth is reported once, at the deepest schema of the document.
+///
+/// Schemas are measured as written at the [sites](AsyncApiV3Spec::schemas) of the document,
+/// without following references.
+pub fn check(spec: &AsyncApiV3Spec, limits: &Limits) -> Vec<LimitViolation> {
+    let mut violations = Vec::new();
+    let mut exceeds = |limit, pointer: JsonPointer, actual, maximum: Option<usize>| {
+        if let Some(maximum) = maximum.filter(|maximum| actual > *maximum) {
+            violations.push(LimitViolation {
+                limit,
+                pointer,
+                actual,
+                maximum,
+            });
+        }
+    };
+    let Ok(document) = serde_json::to_value(spec) else {
+        return Vec::new();
+    };
+
+    let root = JsonPointer::default();
+    let size = document.to_string().len();
+    exceeds(
+        Limit::DocumentSize,
+        root.clone(),
+        size,
+        limits.max_document_size,
+    );
+    exceeds(
+        Limit::Channels,
+        root.clone(),
+        spec.channels.len(),
+        limits.max_channels,
+    );
+    exceeds(
+        Limit::Operations,
+        root.clone(),
+        spec.operations.len(),
+        limits.max_operations,
+    );
+    // Root channels referencing a component channel are counted with the components.
+    let channel_messages = spec
+        .channels
+        .values()
+        .chain(spec.components.channels.values())
+        .filter_map(RefOr::as_item)
+        .map(|channel| channel.messages.len())
+        .sum::<usize>();
+    let messages = channel_messages + spec.unreachable_messages().len();
+    exceeds(Limit::Messages, root, messages, limits.max_messages);
+
+    let mut deepest = None::<(usize, JsonPointer)>;
+    let mut enums = Vec::new();
+    for site in spec.schemas() {
+        let Some(schema) = site.pointer.evaluate(&document) else {
+            continue;
+        };
+        let (schema, pointer) = match (schema.get("schemaFormat"), schema.get("schema")) {
+            (Some(_), Some(schema)) => (schema, site.pointer.join("schema")),
+            _ => (schema, site.pointer),
+        };
+        let (depth, deepest_pointer) = measure(schema, &pointer, &mut enums);
+        if deepest.as_ref().is_none_or(|(max, _)| depth > *max) {
+            deepest = Some((depth, deepest_pointer));
+        }
+    }
+    if let Some((depth, pointer)) = deepest {
+        exceeds(Limit::SchemaDepth, pointer, depth, limits.max_schema_depth);
+    }
+    for (pointer, length) in enums {
+        exceeds(Limit::EnumLength, pointer, length, limits.max_enum_length);
+    }
+    violations
+}
+
+/// Returns the depth of a schema with the location of its deepest subschema, collecting the
+/// location and the length of its enums along the way.
+fn measure(
+    schema: &Value,
+    pointer: &JsonPointer,
+    enums: &mut Vec<(JsonPointer, usize)>,
+) -> (usize, JsonPointer) {
+    let Value::Object(keywords) = schema else {
+        return (1, pointer.clone());
+    };
+    if let Some(Value::Array(values)) = keywords.get("enum") {
+        enums.push((pointer.join("enum"), values.len()));
+    }
+    subschemas(schema, pointer)
+        .into_iter()
+        .map(|(subschema, pointer)| {
+            let (depth, deepest) = measure(subschema, &pointer, enums);
+            (depth + 1, deepest)
+        })
+        .fold((1, pointer.clone()), |deepest, measured| {
+            if measured.0 > deepest.0 {
+                measured
+            } else {
+                deepest
+            }
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::parse_spec;
+    use alloc::string::String;
+
+    const SPEC: &str = r#"
+asyncapi: 3.0.0
+info:
+  title: Orders
+  version: 1.0.0
+channels:
+  orders:
+    address: orders
+    messages:
+      orderPlaced:
+        $ref: '#/components/messages/orderPlaced'
+operations:
+  placeOrder:
+    action: send
+    channel:
+      $ref: '#/channels/orders'
+components:
+  messages:
+    orderPlaced:
+      payload:
+        $ref: '#/components/schemas/order'
+  schemas:
+    order:
+      type: object
+      properties:
+        lines:
+          type: array
+          items:
+            type: object
+            properties:
+              status:
+                type: string
+                enum: [pending, shipped]
+"#;
+
+    #[test]
+    fn document_under_all_limits() {
+        let spec = parse_spec(SPEC);
+
+        assert!(check(&spec, &Limits::gateway_defaults()).is_empty());
+    }
+
+    #[test]
+    fn schema_deeper_than_the_limit() {
+        let spec = parse_spec(SPEC);
+        let limits = Limits {
+            max_schema_depth: Some(3),
+            ..Limits::gateway_defaults()
+        };
+
+        let violations = check(&spec, &limits);
+
+        assert_eq!(
+            violations,
+            [LimitViolation {
+                limit: Limit::SchemaDepth,
+                pointer: JsonPointer::parse(
+                    "/components/schemas/order/properties/lines/items/properties/status"
+                )
+                .unwrap(),
+                actual: 4,
+                maximum: 3,
+            }]
+        );
+        assert_eq!(
+            violations[0].to_string(),
+            String::from(
+                "/components/schemas/order/properties/lines/items/properties/status: \
+                 schema depth 4 exceeds the limit of 3"
+            )
+        );
+    }
+
+    #[test]
+    fn messages_of_component_channels_and_unused_component_messages() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Orders
+  version: 1.0.0
+channels:
+  orders:
+    $ref: '#/components/channels/orders'
+components:
+  channels:
+    orders:
+      address: orders
+      messages:
+        orderPlaced:
+          $ref: '#/components/messages/orderPlaced'
+        orderCancelled:
+          payload:
+            type: object
+    archive:
+      address: archive
+      messages:
+        orderPlaced:
+          $ref: '#/components/messages/orderPlaced'
+  messages:
+    orderPlaced:
+      payload:
+        type: object
+    orderAudited:
+      payload:
+        type: object
+"#,
+        );
+        let limits = Limits {
+            max_messages: Some(3),
+            ..Limits::default()
+        };
+
+        let violations = check(&spec, &limits);
+
+        assert_eq!(
+            violations,
+            [LimitViolation {
+                limit: Limit::Messages,
+                pointer: JsonPointer::default(),
+                actual: 4,
+                maximum: 3,
+            }]
+        );
+    }
+}
@@ -0,0 +1,274 @@
+//! Extraction of basic message information from Protobuf schemas embedded in a
+//! [Multi Format Schema](MultiFormatSchema).
+//!
+//! The schema can either be the `.proto` source text or a JSON `DescriptorProto` (or a
+//! `FileDescriptorProto` wrapping one in its `messageType` list).
+use crate::spec::message::MultiFormatSchema;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use serde_json::Value;
+
+/// Name and fields of the first message declared by a Protobuf schema.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProtobufDescriptor {
+    /// The name of the message.
+    pub message_name: String,
+    /// The names of the message fields in declaration order, including the ones declared
+    /// inside `oneof` groups.
+    pub field_names: Vec<String>,
+}
+
+impl MultiFormatSchema {
+    /// Parses the embedded Protobuf schema and returns the descriptor of its first message.
+    ///
+    /// Returns `None` if the schema format is not Protobuf or the schema does not declare any
+    /// message.
+    pub fn protobuf_descriptor(&self) -> Option<ProtobufDescriptor> {
+        if !self.is_protobuf() {
+            return None;
+        }
+        match &self.schema {
+            Value::String(source) => parse_proto_source(source),
+            Value::Object(_) => parse_descriptor_json(&self.schema),
+            _ => None,
+        }
+    }
+}
+
+fn parse_descriptor_json(descriptor: &Value) -> Option<ProtobufDescriptor> {
+    if let Some(message) = descriptor
+        .get("messageType")
+        .or_else(|| descriptor.get("message_type"))
+        .and_then(Value::as_array)
+        .and_then(|messages| messages.first())
+    {
+        return parse_descriptor_json(message);
+    }
+
+    let message_name = descriptor.get("name")?.as_str()?.to_string();
+    let field_names = descriptor
+        .get("field")
+        .and_then(Value::as_array)
+        .map(|fields| {
+            fields
+                .iter()
+                .filter_map(|field| field.get("name").and_then(Value::as_str))
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Some(ProtobufDescriptor {
+        message_name,
+        field_names,
+    })
+}
+
+fn parse_proto_source(source: &str) -> Option<ProtobufDescriptor> {
+    let tokens = tokenize(source);
+    let start = tokens
+        .windows(3)
+        .position(|window| window[0] == "message" && window[2] == "{")?;
+
+    let message_name = tokens[start + 1].clone();
+    let mut position = start + 3;
+    let mut field_names = Vec::new();
+    parse_message_body(&tokens, &mut position, &mut field_names);
+
+    Some(ProtobufDescriptor {
+        message_name,
+        field_names,
+    })
+}
+
+/// Collects field names until the closing brace of the current block.
+fn parse_message_body(tokens: &[String], position: &mut usize, field_names: &mut Vec<String>) {
+    let mut statement: Vec<&str> = Vec::new();
+    while let Some(token) = tokens.get(*position) {
+        *position += 1;
+        match token.as_str() {
+            "}" => return,
+            "{" => {
+                if statement.first() == Some(&"oneof") {
+                    parse_message_body(tokens, position, field_names);
+                } else {
+                    skip_block(tokens, position);
+                }
+                statement.clear();
+            }
+            ";" => {
+                let is_field = !matches!(
+                    statement.first(),
+                    Some(&"option") | Some(&"reserved") | Some(&"extensions") | None
+                );
+                if is_field {
+                    if let Some(eq) = statement.iter().position(|token| *token == "=") {
+                        if eq > 0 {
+                            field_names.push(statement[eq - 1].to_string());
+                        }
+                    }
+                }
+                statement.clear();
+            }
+            other => statement.push(other),
+        }
+    }
+}
+
+fn skip_block(tokens: &[String], position: &mut usize) {
+    let mut depth = 1;
+    while let Some(token) = tokens.get(*position) {
+        *position += 1;
+        match token.as_str() {
+            "{" => depth += 1,
+            "}" => {
+                depth -= 1;
+                if depth == 0 {
+                    return;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = '\0';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            '"' | '\'' => {
+                let mut literal = String::from(c);
+                let mut escaped = false;
+                for next in chars.by_ref() {
+                    literal.push(next);
+                    if next == c && !escaped {
+                        break;
+                    }
+                    escaped = next == '\\' && !escaped;
+                }
+                tokens.push(literal);
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '.' => {
+                let mut identifier = String::from(c);
+                while let Some(&next) = chars.peek() {
+                    if next.is_alphanumeric() || next == '_' || next == '.' {
+                        identifier.push(next);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(identifier);
+            }
+            c if c.is_whitespace() => {}
+            c => tokens.push(String::from(c)),
+        }
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    const PROTOBUF_FORMAT: &str = "application/vnd.google.protobuf;version=3";
+
+    #[test]
+    fn parse_proto_source_message() {
+        let schema = MultiFormatSchema {
+            schema_format: String::from(PROTOBUF_FORMAT),
+            schema: Value::String(String::from(
+                r#"
+                syntax = "proto3";
+                package demo;
+
+                // A user signed up.
+                message UserSignedUp {
+                    option deprecated = false;
+                    string user_id = 1;
+                    repeated string roles = 2 [packed = true];
+                    map<string, int32> counters = 3;
+                    /* nested types are not fields */
+                    message Address {
+                        string city = 1;
+                    }
+                    oneof contact {
+                        string email = 4;
+                        string phone = 5;
+                    }
+                    reserved 6, 7;
+                }
+                "#,
+            )),
+        };
+
+        assert_eq!(
+            schema.protobuf_descriptor(),
+            Some(ProtobufDescriptor {
+                message_name: String::from("UserSignedUp"),
+                field_names: vec![
+                    String::from("user_id"),
+                    String::from("roles"),
+                    String::from("counters"),
+                    String::from("email"),
+                    String::from("phone"),
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn parse_descriptor_json_message() {
+        let schema = MultiFormatSchema {
+            schema_format: String::from(PROTOBUF_FORMAT),
+            schema: json!({
+                "name": "demo.proto",
+                "messageType": [{
+                    "name": "UserSignedUp",
+                    "field": [
+                        { "name": "user_id", "number": 1, "type": "TYPE_STRING" },
+                        { "name": "email", "number": 2, "type": "TYPE_STRING" }
+                    ]
+                }]
+            }),
+        };
+
+        assert_eq!(
+            schema.protobuf_descriptor(),
+            Some(ProtobufDescriptor {
+                message_name: String::from("UserSignedUp"),
+                field_names: vec![String::from("user_id"), String::from("email")],
+            })
+        );
+    }
+
+    #[test]
+    fn ignore_non_protobuf_formats() {
+        let schema = MultiFormatSchema {
+            schema_format: String::from("application/vnd.apache.avro;version=1.9.0"),
+            schema: Value::String(String::from("message Foo { string bar = 1; }")),
+        };
+
+        assert!(!schema.is_protobuf());
+        assert_eq!(schema.protobuf_descriptor(), None);
+    }
+}
@@ -0,0 +1,376 @@
+//! Gate-keeping of documents before their publication.
+//!
+//! A [Gate] runs a set of checks over a document and collects their findings. The document
+//! passes the gate when no check reports anything. Every gate checks that the document has no
+//! validation error, with the `validation` feature, and no breaking change from the previously
+//! published version, unless told otherwise.
+//!
+//! ```
+//! # use asyncapiv3::publish::Gate;
+//! # use asyncapiv3::spec::AsyncApiV3Spec;
+//! # fn example(spec: &AsyncApiV3Spec) {
+//! let gate = Gate::new()
+//!     .require_tls()
+//!     .require_operation_descriptions()
+//!     .check("contact", |spec| {
+//!         if spec.info.contact.is_none() {
+//!             vec![String::from("the document has no contact")]
+//!         } else {
+//!             Vec::new()
+//!         }
+//!     });
+//! let result = gate.evaluate(spec, None);
+//! # }
+//! ```
+use crate::diff::{Change, EntityChange};
+use crate::spec::common::JsonPointer;
+use crate::spec::AsyncApiV3Spec;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Protocols whose connections are encrypted with TLS.
+pub const TLS_PROTOCOLS: [&str; 7] = [
+    "amqps",
+    "https",
+    "kafka-secure",
+    "mqtts",
+    "secure-mqtt",
+    "stomps",
+    "wss",
+];
+
+type Check = Box<dyn Fn(&AsyncApiV3Spec, Option<&AsyncApiV3Spec>) -> Vec<Finding> + Send + Sync>;
+
+/// A composable set of checks a document must pass to be published.
+pub struct Gate {
+    checks: Vec<Check>,
+    #[cfg(feature = "validation")]
+    validation: bool,
+    compatibility: bool,
+}
+
+impl Default for Gate {
+    fn default() -> Self {
+        Self {
+            checks: Vec::new(),
+            #[cfg(feature = "validation")]
+            validation: true,
+            compatibility: true,
+        }
+    }
+}
+
+impl Gate {
+    /// Creates a gate rejecting the documents with [validation](crate::validation::validate)
+    /// errors, with the `validation` feature, and the documents
+    /// [breaking](AsyncApiV3Spec::breaking_changes) applications written against the previous
+    /// version.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lets documents with validation errors through.
+    #[cfg(feature = "validation")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "validation")))]
+    pub fn skip_validation(mut self) -> Self {
+        self.validation = false;
+        self
+    }
+
+    /// Lets documents with breaking changes through, e.g. for a new major version.
+    pub fn allow_breaking_changes(mut self) -> Self {
+        self.compatibility = false;
+        self
+    }
+
+    /// Requires every root server to use a [TLS protocol](TLS_PROTOCOLS).
+    pub fn require_tls(self) -> Self {
+        self.with(|spec, _| {
+            spec.servers
+                .iter()
+                .filter_map(|(name, server)| {
+                    let server = server.resolve(spec).ok()?;
+                    let protocol = server.protocol.to_ascii_lowercase();
+                    (!TLS_PROTOCOLS.contains(&protocol.as_str())).then(|| Finding {
+                        check: String::from("tls"),
+                        message: format!(
+                            "server `{name}` uses `{}`, which is not a TLS protocol",
+                            server.protocol
+                        ),
+                        pointer: Some(JsonPointer::from_iter(["servers", name.as_str()])),
+                    })
+                })
+                .collect()
+        })
+    }
+
+    /// Requires every root operation to have a description.
+    pub fn require_operation_descriptions(self) -> Self {
+        self.with(|spec, _| {
+            spec.operations
+                .iter()
+                .filter_map(|(name, operation)| {
+                    let operation = operation.resolve(spec).ok()?;
+                    let documented = operation
+                        .description
+                        .as_deref()
+                        .is_some_and(|description| !description.trim().is_empty());
+                    (!documented).then(|| Finding {
+                        check: String::from("operation-description"),
+                        message: format!("operation `{name}` has no description"),
+                        pointer: Some(JsonPointer::from_iter(["operations", name.as_str()])),
+                    })
+                })
+                .collect()
+        })
+    }
+
+    /// Adds a custom check, reporting each issue it returns as a finding of the named check.
+    pub fn check<F>(self, name: &str, check: F) -> Self
+    where
+        F: Fn(&AsyncApiV3Spec) -> Vec<String> + Send + Sync + 'static,
+    {
+        let name = String::from(name);
+        self.with(move |spec, _| {
+            check(spec)
+                .into_iter()
+                .map(|message| Finding {
+                    check: name.clone(),
+                    message,
+                    pointer: None,
+                })
+                .collect()
+        })
+    }
+
+    fn with<F>(mut self, check: F) -> Self
+    where
+        F: Fn(&AsyncApiV3Spec, Option<&AsyncApiV3Spec>) -> Vec<Finding> + Send + Sync + 'static,
+    {
+        self.checks.push(Box::new(check));
+        self
+    }
+
+    /// Runs every check over a document, given the previously published version if any, the
+    /// validation and the compatibility checks first.
+    pub fn evaluate(&self, spec: &AsyncApiV3Spec, previous: Option<&AsyncApiV3Spec>) -> GateResult {
+        let mut findings = Vec::new();
+        #[cfg(feature = "validation")]
+        if self.validation {
+            findings.extend(validation_findings(spec));
+        }
+        if let (true, Some(previous)) = (self.compatibility, previous) {
+            findings.extend(breaking_findings(spec, previous));
+        }
+        findings.extend(self.checks.iter().flat_map(|check| check(spec, previous)));
+        GateResult {
+            passed: findings.is_empty(),
+            findings,
+        }
+    }
+}
+
+/// Reports the validation errors of a document, as findings of the `validation` check.
+#[cfg(feature = "validation")]
+fn validation_findings(spec: &AsyncApiV3Spec) -> Vec<Finding> {
+    crate::validation::validate(spec)
+        .into_iter()
+        .filter(|issue| issue.severity == crate::validation::Severity::Error)
+        .map(|issue| Finding {
+            check: String::from("validation"),
+            message: format!("{}: {}", issue.code, issue.message),
+            pointer: Some(issue.pointer),
+        })
+        .collect()
+}
+
+/// Reports the breaking changes from the previous version, as findings of the
+/// `breaking-change` check.
+fn breaking_findings(spec: &AsyncApiV3Spec, previous: &AsyncApiV3Spec) -> Vec<Finding> {
+    spec.breaking_changes(previous)
+        .into_iter()
+        .filter(|change| change.breaking)
+        .map(|change| {
+            let (message, pointer) = match change.change {
+                Change::Entity(EntityChange::Removed { pointer, .. }) => {
+                    (format!("`{pointer}` is removed"), pointer)
+                }
+                Change::Entity(EntityChange::Renamed { from, to, .. }) => {
+                    (format!("`{from}` is renamed to `{to}`"), to)
+                }
+                Change::Entity(
+                    EntityChange::Added { pointer, .. } | EntityChange::Modified { pointer, .. },
+                ) => (format!("`{pointer}` changes"), pointer),
+                Change::Property(change) => (
+                    format!("the `{}` of `{}` changes", change.property, change.pointer),
+                    change.pointer,
+                ),
+                Change::Field(change) => (
+                    format!(
+                        "the field `{}` of `{}` changes",
+                        change.field, change.message
+                    ),
+                    change.message,
+                ),
+            };
+            Finding {
+                check: String::from("breaking-change"),
+                message,
+                pointer: Some(pointer),
+            }
+        })
+        .collect()
+}
+
+/// An issue reported by a check of a [Gate].
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Finding {
+    /// Name of the check reporting the issue.
+    pub check: String,
+    /// Description of the issue.
+    pub message: String,
+    /// Location of the issue in the document, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pointer: Option<JsonPointer>,
+}
+
+/// The outcome of a [Gate] evaluation, serializable for audit logs.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GateResult {
+    /// Whether the document can be published.
+    pub passed: bool,
+    /// The issues reported by the checks, in the order the checks were added.
+    pub findings: Vec<Finding>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::parse_spec;
+
+    const SPEC: &str = r#"
+asyncapi: 3.0.0
+info:
+  title: Lights
+  version: 1.0.0
+servers:
+  production:
+    host: broker.example.com
+    protocol: secure-mqtt
+channels:
+  lights:
+    address: lights
+    messages: {}
+operations:
+  turnOn:
+    action: send
+    description: Turns the lights on.
+    channel:
+      $ref: '#/channels/lights'
+"#;
+
+    fn gate() -> Gate {
+        Gate::new().require_tls().require_operation_descriptions()
+    }
+
+    #[test]
+    fn passing_document() {
+        let spec = parse_spec(SPEC);
+
+        let result = gate().evaluate(&spec, Some(&spec));
+
+        assert_eq!(
+            result,
+            GateResult {
+                passed: true,
+                findings: Vec::new()
+            }
+        );
+    }
+
+    #[test]
+    fn tls_violation() {
+        let spec = parse_spec(&SPEC.replace("protocol: secure-mqtt", "protocol: mqtt"));
+
+        let result = gate().evaluate(&spec, None);
+
+        assert!(!result.passed);
+        assert_eq!(
+            serde_json::to_value(&result).unwrap(),
+            serde_json::json!({
+                "passed": false,
+                "findings": [{
+                    "check": "tls",
+                    "message": "server `production` uses `mqtt`, which is not a TLS protocol",
+                    "pointer": "/servers/production"
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn breaking_changes_are_rejected() {
+        let previous = parse_spec(SPEC);
+        let spec = parse_spec(&SPEC.replace("action: send", "action: receive"));
+
+        let rejected = gate().evaluate(&spec, Some(&previous));
+        let allowed = gate()
+            .allow_breaking_changes()
+            .evaluate(&spec, Some(&previous));
+
+        assert_eq!(
+            rejected.findings,
+            vec![Finding {
+                check: String::from("breaking-change"),
+                message: String::from("the `action` of `/operations/turnOn` changes"),
+                pointer: Some(JsonPointer::from_iter(["operations", "turnOn"])),
+            }]
+        );
+        assert!(allowed.passed);
+    }
+
+    #[cfg(feature = "validation")]
+    #[test]
+    fn invalid_documents_are_rejected() {
+        let spec = parse_spec(&SPEC.replace("'#/channels/lights'", "'#/channels/missing'"));
+
+        let rejected = gate().evaluate(&spec, None);
+        let skipped = Gate::new().skip_validation().evaluate(&spec, None);
+
+        assert!(!rejected.passed);
+        assert!(rejected
+            .findings
+            .iter()
+            .all(|finding| finding.check == "validation"));
+        assert!(skipped.passed);
+    }
+
+    #[test]
+    fn failing_custom_check() {
+        let spec = parse_spec(SPEC);
+        let gate = gate().check("contact", |spec| {
+            spec.info
+                .contact
+                .is_none()
+                .then(|| String::from("the document has no contact"))
+                .into_iter()
+                .collect()
+        });
+
+        let result = gate.evaluate(&spec, None);
+
+        assert!(!result.passed);
+        assert_eq!(
+            result.findings,
+            vec![Finding {
+                check: String::from("contact"),
+                message: String::from("the document has no contact"),
+                pointer: None,
+            }]
+        );
+    }
+}
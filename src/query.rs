@@ -0,0 +1,506 @@
+//! A small path language to look up values in a document.
+//!
+//! A query is a sequence of steps evaluated from the document root, each step turning the
+//! current set of values into a new one:
+//!
+//! - `name` or `"quoted.name"` selects the field with that name (or the array item with that
+//!   index), `*` selects every field or item. Selectors are separated by `.`.
+//! - `->` replaces the values which are [references](crate::spec::common::ReferenceObject) by
+//!   the referenced values. A selector may directly follow it: `channel->address`.
+//! - `[field]` keeps the values having the field, `[!field]` the values missing it,
+//!   `[field="text"]` the values whose field is the given string and `[!field="text"]` the
+//!   other ones.
+//!
+//! ```
+//! # use asyncapiv3::spec::AsyncApiV3Spec;
+//! # fn example(spec: &AsyncApiV3Spec) -> Result<(), asyncapiv3::error::Error> {
+//! // Messages using Avro, wherever they are defined.
+//! let avro = spec.query(r#"channels.*.messages.*->[contentType="application/avro"]"#)?;
+//! // Operations without description.
+//! let undocumented = spec.query("operations.*[!description]")?;
+//! // Addresses of the channels of every operation.
+//! let addresses = spec.query("operations.*.channel->address")?;
+//! # Ok(())
+//! # }
+//! ```
+use crate::error::Error;
+use crate::spec::common::JsonPointer;
+use crate::spec::AsyncApiV3Spec;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::fmt;
+use core::str::FromStr;
+use serde_json::Value;
+
+/// Maximum number of references followed by a single `->`.
+const MAX_REF_HOPS: usize = 16;
+
+/// Error returned when parsing a malformed query.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueryError {
+    /// The query being parsed.
+    pub expression: String,
+    /// Byte offset of the error in the query.
+    pub position: usize,
+    /// Description of the problem.
+    pub message: String,
+}
+
+impl fmt::Display for QueryError {
+    /// Displays the message followed by the query with a caret under the error position.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let column = self.expression[..self.position].chars().count();
+        write!(
+            f,
+            "{} at position {}\n{}\n{:column$}^",
+            self.message, self.position, self.expression, ""
+        )
+    }
+}
+
+impl core::error::Error for QueryError {}
+
+/// A value matched by a query.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QueryMatch<'a> {
+    /// Location of the value in the document.
+    pub pointer: JsonPointer,
+    /// The matched value.
+    pub value: &'a Value,
+}
+
+/// A parsed query, see the [module](self) documentation for the syntax.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Query {
+    steps: Vec<Step>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Step {
+    Field(String),
+    Wildcard,
+    Deref,
+    Filter {
+        negated: bool,
+        field: String,
+        value: Option<String>,
+    },
+}
+
+impl Query {
+    /// Evaluates the query against a JSON document.
+    pub fn evaluate<'a>(&self, document: &'a Value) -> Vec<QueryMatch<'a>> {
+        let mut matches = vec![QueryMatch {
+            pointer: JsonPointer::default(),
+            value: document,
+        }];
+        for step in &self.steps {
+            matches = match step {
+                Step::Field(name) => matches
+                    .into_iter()
+                    .filter_map(|current| {
+                        let value = match current.value {
+                            Value::Object(fields) => fields.get(name)?,
+                            Value::Array(items) => items.get(name.parse::<usize>().ok()?)?,
+                            _ => return None,
+                        };
+                        Some(QueryMatch {
+                            pointer: current.pointer.join(name.as_str()),
+                            value,
+                        })
+                    })
+                    .collect(),
+                Step::Wildcard => matches
+                    .into_iter()
+                    .flat_map(|current| children(current.pointer, current.value))
+                    .collect(),
+                Step::Deref => matches
+                    .into_iter()
+                    .filter_map(|current| deref(document, current))
+                    .collect(),
+                Step::Filter {
+                    negated,
+                    field,
+                    value,
+                } => matches
+                    .into_iter()
+                    .filter(|current| {
+                        let found = current.value.get(field).filter(|found| !found.is_null());
+                        let matching = match value {
+                            Some(expected) => found.and_then(Value::as_str) == Some(expected),
+                            None => found.is_some(),
+                        };
+                        matching != *negated
+                    })
+                    .collect(),
+            };
+        }
+        matches
+    }
+}
+
+fn children(pointer: JsonPointer, value: &Value) -> Vec<QueryMatch<'_>> {
+    match value {
+        Value::Object(fields) => fields
+            .iter()
+            .map(|(key, value)| QueryMatch {
+                pointer: pointer.join(key.as_str()),
+                value,
+            })
+            .collect(),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(index, value)| QueryMatch {
+                pointer: pointer.join(index.to_string()),
+                value,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Follows the references of a match, dropping it if they cannot be resolved.
+fn deref<'a>(document: &'a Value, mut current: QueryMatch<'a>) -> Option<QueryMatch<'a>> {
+    for _ in 0..MAX_REF_HOPS {
+        let Some(reference) = current.value.get("$ref").and_then(Value::as_str) else {
+            return Some(current);
+        };
+        let pointer = JsonPointer::parse(reference.strip_prefix('#')?).ok()?;
+        let value = pointer.evaluate(document)?;
+        current = QueryMatch { pointer, value };
+    }
+    None
+}
+
+impl FromStr for Query {
+    type Err = QueryError;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        Parser {
+            expression,
+            position: 0,
+        }
+        .parse()
+    }
+}
+
+struct Parser<'a> {
+    expression: &'a str,
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn parse(mut self) -> Result<Query, QueryError> {
+        let mut steps = vec![self.selector()?];
+        while let Some(next) = self.peek() {
+            if self.eat("->") {
+                steps.push(Step::Deref);
+                if matches!(self.peek(), Some(c) if c == '*' || c == '"' || is_name_char(c)) {
+                    steps.push(self.selector()?);
+                }
+            } else if self.eat(".") {
+                steps.push(self.selector()?);
+            } else if self.eat("[") {
+                steps.push(self.filter()?);
+            } else {
+                return Err(self.error(format!("unexpected '{next}', expected '.', '->' or '['")));
+            }
+        }
+        Ok(Query { steps })
+    }
+
+    fn selector(&mut self) -> Result<Step, QueryError> {
+        if self.eat("*") {
+            Ok(Step::Wildcard)
+        } else {
+            Ok(Step::Field(self.name()?))
+        }
+    }
+
+    fn filter(&mut self) -> Result<Step, QueryError> {
+        let negated = self.eat("!");
+        let field = self.name()?;
+        let value = if self.eat("=") {
+            Some(self.string()?)
+        } else {
+            None
+        };
+        if !self.eat("]") {
+            return Err(self.error(String::from("expected ']'")));
+        }
+        Ok(Step::Filter {
+            negated,
+            field,
+            value,
+        })
+    }
+
+    fn name(&mut self) -> Result<String, QueryError> {
+        if self.peek() == Some('"') {
+            return self.string();
+        }
+        let rest = &self.expression[self.position..];
+        let length = rest
+            .char_indices()
+            .find(|&(offset, c)| !is_name_char(c) || rest[offset..].starts_with("->"))
+            .map_or(rest.len(), |(offset, _)| offset);
+        if length == 0 {
+            return Err(self.error(String::from("expected a field name")));
+        }
+        self.position += length;
+        Ok(String::from(&rest[..length]))
+    }
+
+    fn string(&mut self) -> Result<String, QueryError> {
+        let start = self.position;
+        if !self.eat("\"") {
+            return Err(self.error(String::from("expected a quoted string")));
+        }
+        let mut string = String::new();
+        let mut chars = self.expression[self.position..].char_indices();
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.position += offset + 1;
+                    return Ok(string);
+                }
+                '\\' => match chars.next() {
+                    Some((_, escaped)) => string.push(escaped),
+                    None => break,
+                },
+                c => string.push(c),
+            }
+        }
+        self.position = start;
+        Err(self.error(String::from("unterminated string")))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.expression[self.position..].chars().next()
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        let matched = self.expression[self.position..].starts_with(token);
+        if matched {
+            self.position += token.len();
+        }
+        matched
+    }
+
+    fn error(&self, message: String) -> QueryError {
+        QueryError {
+            expression: String::from(self.expression),
+            position: self.position,
+            message,
+        }
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '$')
+}
+
+/// The values matched by [AsyncApiV3Spec::query].
+///
+/// It owns the JSON representation of the queried document, which the matches borrow from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QueryResults {
+    document: Value,
+    pointers: Vec<JsonPointer>,
+}
+
+impl QueryResults {
+    /// Iterates over the matched values, in document order.
+    pub fn iter(&self) -> impl Iterator<Item = QueryMatch<'_>> {
+        self.pointers.iter().filter_map(|pointer| {
+            Some(QueryMatch {
+                pointer: pointer.clone(),
+                value: pointer.evaluate(&self.document)?,
+            })
+        })
+    }
+
+    /// Returns the locations of the matched values.
+    pub fn pointers(&self) -> &[JsonPointer] {
+        &self.pointers
+    }
+
+    /// Returns the number of matched values.
+    pub fn len(&self) -> usize {
+        self.pointers.len()
+    }
+
+    /// Returns `true` if nothing matched.
+    pub fn is_empty(&self) -> bool {
+        self.pointers.is_empty()
+    }
+}
+
+impl AsyncApiV3Spec {
+    /// Looks up values of the document with a query, see the [query](crate::query) module
+    /// for the syntax.
+    ///
+    /// Fails with [Error::InvalidQuery] for a malformed query, or [Error::InvalidDocument] if
+    /// the document cannot be converted to JSON.
+    pub fn query(&self, expression: &str) -> Result<QueryResults, Error> {
+        let query = expression
+            .parse::<Query>()
+            .map_err(|error| Error::InvalidQuery { error })?;
+        let document = serde_json::to_value(self).map_err(|err| Error::InvalidDocument {
+            reason: err.to_string(),
+            hints: Vec::new(),
+        })?;
+        let pointers = query
+            .evaluate(&document)
+            .into_iter()
+            .map(|found| found.pointer)
+            .collect();
+        Ok(QueryResults { document, pointers })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::parse_spec;
+
+    const SPEC: &str = r#"
+asyncapi: 3.0.0
+info:
+  title: Queries
+  version: 1.0.0
+channels:
+  user.signedup:
+    address: user/signedup
+    messages:
+      userSignedUp:
+        $ref: '#/components/messages/userSignedUp'
+      userDeleted:
+        contentType: application/json
+  lights:
+    address: lights
+    messages:
+      lightMeasured:
+        $ref: '#/components/messages/lightMeasured'
+operations:
+  onUserSignedUp:
+    action: receive
+    description: Handles new users.
+    channel:
+      $ref: '#/channels/user.signedup'
+  turnOn:
+    action: send
+    channel:
+      $ref: '#/channels/lights'
+components:
+  messages:
+    userSignedUp:
+      contentType: application/avro
+    lightMeasured:
+      contentType: application/json
+"#;
+
+    fn pointers(spec: &AsyncApiV3Spec, expression: &str) -> Vec<String> {
+        spec.query(expression)
+            .unwrap()
+            .pointers()
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn select_fields_and_wildcards() {
+        let spec = parse_spec(SPEC);
+
+        assert_eq!(pointers(&spec, "info.title"), vec!["/info/title"]);
+        assert_eq!(
+            pointers(&spec, "channels.*.address"),
+            vec![
+                "/channels/lights/address",
+                "/channels/user.signedup/address"
+            ]
+        );
+        assert_eq!(
+            pointers(&spec, r#"channels."user.signedup".messages.*"#),
+            vec![
+                "/channels/user.signedup/messages/userDeleted",
+                "/channels/user.signedup/messages/userSignedUp"
+            ]
+        );
+        assert!(pointers(&spec, "channels.missing").is_empty());
+    }
+
+    #[test]
+    fn filter_on_fields() {
+        let spec = parse_spec(SPEC);
+
+        assert_eq!(
+            pointers(&spec, "operations.*[!description]"),
+            vec!["/operations/turnOn"]
+        );
+        assert_eq!(
+            pointers(&spec, "operations.*[description]"),
+            vec!["/operations/onUserSignedUp"]
+        );
+        assert_eq!(
+            pointers(&spec, r#"operations.*[action="send"][!action="receive"]"#),
+            vec!["/operations/turnOn"]
+        );
+    }
+
+    #[test]
+    fn follow_references() {
+        let spec = parse_spec(SPEC);
+
+        assert_eq!(
+            pointers(
+                &spec,
+                r#"channels.*.messages.*->[contentType="application/avro"]"#
+            ),
+            vec!["/components/messages/userSignedUp"]
+        );
+        let results = spec.query("operations.turnOn.channel->address").unwrap();
+        let matches = results.iter().collect::<Vec<_>>();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].pointer.to_string(), "/channels/lights/address");
+        assert_eq!(matches[0].value, "lights");
+    }
+
+    #[test]
+    fn evaluate_borrows_from_document() {
+        let document = serde_json::json!({ "servers": { "a": { "host": "a.example.com" } } });
+
+        let query = "servers.*.host".parse::<Query>().unwrap();
+        let matches = query.evaluate(&document);
+
+        assert!(core::ptr::eq(
+            matches[0].value,
+            &document["servers"]["a"]["host"]
+        ));
+    }
+
+    #[test]
+    fn report_parse_errors_with_caret() {
+        let err = "channels.*[!description".parse::<Query>().unwrap_err();
+
+        assert_eq!(err.position, 23);
+        assert_eq!(
+            err.to_string(),
+            "expected ']' at position 23\nchannels.*[!description\n                       ^"
+        );
+
+        let err = "channels..address".parse::<Query>().unwrap_err();
+        assert_eq!(err.position, 9);
+        assert_eq!(err.message, "expected a field name");
+
+        let err = r#"operations.*[action="send]"#.parse::<Query>().unwrap_err();
+        assert_eq!(err.message, "unterminated string");
+        assert_eq!(err.position, 20);
+
+        let err = parse_spec(SPEC).query("channels..address").unwrap_err();
+        assert!(matches!(err, Error::InvalidQuery { error } if error.position == 9));
+    }
+}
@@ -0,0 +1,210 @@
+//! Serving a document over HTTP, as JSON or YAML depending on the `Accept` header.
+//!
+//! [SpecDocument] negotiates the representation, caches both serializations and answers
+//! conditional requests, independently of any framework. The `axum` and `actix` features add
+//! ready-made handlers built on it.
+use crate::spec::uid::fnv1a_hex;
+use crate::spec::AsyncApiSpec;
+use std::sync::{Arc, OnceLock};
+
+#[cfg(feature = "actix")]
+#[cfg_attr(docsrs, doc(cfg(feature = "actix")))]
+pub mod actix;
+#[cfg(feature = "axum")]
+#[cfg_attr(docsrs, doc(cfg(feature = "axum")))]
+pub mod axum;
+
+/// A serialization of a document.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Representation {
+    /// The document as JSON, served as `application/json`.
+    Json,
+    /// The document as YAML, served as `application/yaml`, `text/yaml` or `application/x-yaml`.
+    Yaml,
+}
+
+/// Media types served, with their representation, in order of preference.
+const MEDIA_TYPES: [(&str, Representation); 4] = [
+    ("application/json", Representation::Json),
+    ("application/yaml", Representation::Yaml),
+    ("text/yaml", Representation::Yaml),
+    ("application/x-yaml", Representation::Yaml),
+];
+
+/// A serialized document with its entity tag.
+#[derive(Debug)]
+struct Rendered {
+    body: String,
+    etag: String,
+}
+
+/// A document served over HTTP, see [SpecDocument::respond].
+#[derive(Debug)]
+pub struct SpecDocument {
+    spec: Arc<AsyncApiSpec>,
+    json: OnceLock<Option<Rendered>>,
+    yaml: OnceLock<Option<Rendered>>,
+}
+
+/// The response to a request for a document, to be translated into the response type of a
+/// framework.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpecResponse<'a> {
+    /// `200`, `304` when the client holds the current representation, `406` when no served
+    /// media type is acceptable or `500` when the document cannot be serialized.
+    pub status: u16,
+    /// The media type of the body, set for `200` responses.
+    pub content_type: Option<&'static str>,
+    /// The quoted entity tag of the representation, set for `200` and `304` responses.
+    pub etag: Option<&'a str>,
+    /// The serialized document, empty unless the status is `200`.
+    pub body: &'a str,
+}
+
+impl SpecDocument {
+    /// Creates a document to serve, serialized on the first request for each representation.
+    pub fn new(spec: Arc<AsyncApiSpec>) -> Self {
+        Self {
+            spec,
+            json: OnceLock::new(),
+            yaml: OnceLock::new(),
+        }
+    }
+
+    /// Answers a request given its `Accept` and `If-None-Match` headers.
+    ///
+    /// The media type with the highest quality in the `Accept` header is served, among
+    /// `application/json`, `application/yaml`, `text/yaml` and `application/x-yaml`; wildcards
+    /// select JSON, or YAML for `text/*`. JSON is served when the header is missing. Each
+    /// representation is serialized on its first request only, and its entity tag is the hash
+    /// of its content.
+    pub fn respond(&self, accept: Option<&str>, if_none_match: Option<&str>) -> SpecResponse<'_> {
+        let empty = |status| SpecResponse {
+            status,
+            content_type: None,
+            etag: None,
+            body: "",
+        };
+        let Some((content_type, representation)) = negotiate(accept) else {
+            return empty(406);
+        };
+        let Some(rendered) = self.rendered(representation) else {
+            return empty(500);
+        };
+        if if_none_match.is_some_and(|tags| matches_etag(tags, &rendered.etag)) {
+            return SpecResponse {
+                etag: Some(&rendered.etag),
+                ..empty(304)
+            };
+        }
+        SpecResponse {
+            status: 200,
+            content_type: Some(content_type),
+            etag: Some(&rendered.etag),
+            body: &rendered.body,
+        }
+    }
+
+    fn rendered(&self, representation: Representation) -> Option<&Rendered> {
+        let (cache, serialize): (_, fn(&AsyncApiSpec) -> Option<String>) = match representation {
+            Representation::Json => (&self.json, |spec| serde_json::to_string(spec).ok()),
+            Representation::Yaml => (&self.yaml, |spec| serde_yaml::to_string(spec).ok()),
+        };
+        #[cfg(feature = "tracing")]
+        if cache.get().is_some() {
+            tracing::debug!(?representation, "serialization cache hit");
+        }
+        cache
+            .get_or_init(|| {
+                #[cfg(feature = "tracing")]
+                let _span = tracing::debug_span!("serialize", ?representation).entered();
+                let body = serialize(&self.spec)?;
+                let etag = format!("\"{}\"", fnv1a_hex(body.as_bytes()));
+                Some(Rendered { body, etag })
+            })
+            .as_ref()
+    }
+}
+
+/// Returns the served media type preferred by an `Accept` header, with its representation.
+///
+/// Each served media type gets the quality of the most specific range matching it, so that
+/// `application/json;q=0, */*` excludes JSON. Ties go to the first of [MEDIA_TYPES].
+fn negotiate(accept: Option<&str>) -> Option<(&'static str, Representation)> {
+    let accept = accept.map(str::trim).filter(|accept| !accept.is_empty());
+    let Some(accept) = accept else {
+        return Some(MEDIA_TYPES[0]);
+    };
+    let ranges: Vec<(String, f32)> = accept
+        .split(',')
+        .map(|range| {
+            let mut parameters = range.split(';');
+            let media_range = parameters.next().unwrap_or_default().trim();
+            let quality = parameters
+                .filter_map(|parameter| parameter.trim().strip_prefix("q="))
+                .find_map(|quality| quality.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (media_range.to_ascii_lowercase(), quality)
+        })
+        .collect();
+    let mut preferred = None::<(f32, (&'static str, Representation))>;
+    for served in MEDIA_TYPES {
+        let (media_type, _) = served;
+        let kind = media_type.split('/').next().unwrap_or_default();
+        let quality = ranges
+            .iter()
+            .filter_map(|(media_range, quality)| {
+                let specificity = match media_range.as_str() {
+                    "*/*" => 0,
+                    range if range == media_type => 2,
+                    range if range.strip_suffix("/*") == Some(kind) => 1,
+                    _ => return None,
+                };
+                Some((specificity, *quality))
+            })
+            .max_by_key(|(specificity, _)| *specificity)
+            .map(|(_, quality)| quality);
+        if let Some(quality) = quality {
+            if quality > 0.0 && preferred.is_none_or(|(best, _)| quality > best) {
+                preferred = Some((quality, served));
+            }
+        }
+    }
+    preferred.map(|(_, served)| served)
+}
+
+/// Returns `true` if an `If-None-Match` header lists an entity tag, weakly compared.
+fn matches_etag(if_none_match: &str, etag: &str) -> bool {
+    if_none_match.split(',').map(str::trim).any(|candidate| {
+        candidate == "*" || candidate.strip_prefix("W/").unwrap_or(candidate) == etag
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn negotiate_by_quality() {
+        assert_eq!(negotiate(None), Some(MEDIA_TYPES[0]));
+        assert_eq!(
+            negotiate(Some(
+                "text/html, application/yaml;q=0.9, application/json;q=0.5"
+            )),
+            Some(("application/yaml", Representation::Yaml))
+        );
+        assert_eq!(
+            negotiate(Some("text/*")),
+            Some(("text/yaml", Representation::Yaml))
+        );
+        assert_eq!(negotiate(Some("application/json;q=0, text/html")), None);
+        assert_eq!(
+            negotiate(Some("application/json;q=0, */*")),
+            Some(("application/yaml", Representation::Yaml))
+        );
+        assert_eq!(
+            negotiate(Some("application/*;q=0.2, */*;q=0.5")),
+            Some(("text/yaml", Representation::Yaml))
+        );
+    }
+}
@@ -0,0 +1,47 @@
+//! Serving a document with [actix-web](https://docs.rs/actix-web).
+use super::SpecDocument;
+use crate::spec::AsyncApiSpec;
+use actix_web::http::header::{self, HeaderName};
+use actix_web::http::StatusCode;
+use actix_web::{HttpRequest, HttpResponse};
+use core::future::{ready, Ready};
+use std::sync::Arc;
+
+/// Returns a handler serving the document as JSON or YAML, see [SpecDocument::respond].
+///
+/// ```no_run
+/// # use asyncapiv3::spec::AsyncApiSpec;
+/// # use std::sync::Arc;
+/// # fn configure(config: &mut actix_web::web::ServiceConfig, spec: Arc<AsyncApiSpec>) {
+/// config.route(
+///     "/asyncapi",
+///     actix_web::web::get().to(asyncapiv3::serve::actix::spec_handler(spec)),
+/// );
+/// # }
+/// ```
+pub fn spec_handler(
+    spec: Arc<AsyncApiSpec>,
+) -> impl Fn(HttpRequest) -> Ready<HttpResponse> + Clone + 'static {
+    let document = Arc::new(SpecDocument::new(spec));
+    move |request: HttpRequest| {
+        let header = |name: HeaderName| {
+            request
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+        };
+        let response = document.respond(header(header::ACCEPT), header(header::IF_NONE_MATCH));
+
+        let status =
+            StatusCode::from_u16(response.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let mut builder = HttpResponse::build(status);
+        builder.insert_header((header::VARY, "accept"));
+        if let Some(content_type) = response.content_type {
+            builder.insert_header((header::CONTENT_TYPE, content_type));
+        }
+        if let Some(etag) = response.etag {
+            builder.insert_header((header::ETAG, etag));
+        }
+        ready(builder.body(String::from(response.body)))
+    }
+}
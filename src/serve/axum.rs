@@ -0,0 +1,44 @@
+//! Serving a document with [axum](https://docs.rs/axum).
+use super::SpecDocument;
+use crate::spec::AsyncApiSpec;
+use ::axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use ::axum::response::{IntoResponse, Response};
+use core::future::{ready, Ready};
+use std::sync::Arc;
+
+/// Returns a handler serving the document as JSON or YAML, see [SpecDocument::respond].
+///
+/// ```no_run
+/// # use asyncapiv3::spec::AsyncApiSpec;
+/// # use std::sync::Arc;
+/// # fn router(spec: Arc<AsyncApiSpec>) -> axum::Router {
+/// axum::Router::new().route(
+///     "/asyncapi",
+///     axum::routing::get(asyncapiv3::serve::axum::spec_handler(spec)),
+/// )
+/// # }
+/// ```
+pub fn spec_handler(
+    spec: Arc<AsyncApiSpec>,
+) -> impl Fn(HeaderMap) -> Ready<Response> + Clone + Send + Sync + 'static {
+    let document = Arc::new(SpecDocument::new(spec));
+    move |headers: HeaderMap| {
+        let header = |name| headers.get(name).and_then(|value| value.to_str().ok());
+        let response = document.respond(header(header::ACCEPT), header(header::IF_NONE_MATCH));
+
+        let mut response_headers = HeaderMap::new();
+        response_headers.insert(header::VARY, HeaderValue::from_static("accept"));
+        if let Some(content_type) = response.content_type {
+            response_headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+        }
+        if let Some(etag) = response
+            .etag
+            .and_then(|etag| HeaderValue::from_str(etag).ok())
+        {
+            response_headers.insert(header::ETAG, etag);
+        }
+        let status =
+            StatusCode::from_u16(response.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        ready((status, response_headers, String::from(response.body)).into_response())
+    }
+}
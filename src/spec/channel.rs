@@ -1,9 +1,16 @@
 //! Contains types related to the [channels field](https://www.asyncapi.com/docs/concepts/asyncapi-document/structure#channels-field).
-use crate::spec::common::{ExternalDocumentation, RefOr, ReferenceObject, Tag};
+use crate::spec::common::{
+    is_none_or_empty, non_null_bindings, to_generic_bindings, BindingPatch, Extensions,
+    ExternalDocumentation, GenericBindings, Maybe, RefOr, ReferenceObject, Tag,
+};
 use crate::spec::message::Messages;
-use std::collections::HashMap;
+use crate::spec::AsyncApiV3Spec;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde_json::{Map, Value};
 
-pub type Channels = HashMap<String, RefOr<Channel>>;
+pub type Channels = BTreeMap<String, RefOr<Channel>>;
 
 /// A channel represents the communication pathways through which messages are exchanged. You can
 /// specify their purpose, address, and the expected message formats for communication.
@@ -11,8 +18,8 @@ pub type Channels = HashMap<String, RefOr<Channel>>;
 #[serde(rename_all = "camelCase")]
 pub struct Channel {
     /// An optional string representation of this channel's address. The address is typically the "topic name", "routing key", "event type", or "path". When null or absent, it MUST be interpreted as unknown. This is useful when the address is generated dynamically at runtime or can't be known upfront. It MAY contain Channel Address Expressions. Query parameters and fragments SHALL NOT be used, instead use bindings to define them.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub address: Option<String>,
+    #[serde(default, skip_serializing_if = "Maybe::is_absent")]
+    pub address: Maybe<String>,
     /// A map of the messages that will be sent to this channel by any application at any time. Every message sent to this channel MUST be valid against one, and only one, of the message objects defined in this map.
     pub messages: Messages,
     /// A human-friendly title for the channel.
@@ -39,16 +46,188 @@ pub struct Channel {
     /// A map where the keys describe the name of the protocol and the values describe protocol-specific definitions for the channel.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bindings: Option<RefOr<ChannelBindings>>,
+    /// Specification extensions of the channel.
+    #[serde(flatten)]
+    pub extensions: Extensions,
+}
+
+impl Channel {
+    /// Returns `true` if the channel is available on the root server with the given name,
+    /// which is the case for every server when the channel does not list any.
+    pub fn is_available_on(&self, server_name: &str) -> bool {
+        self.servers.is_empty()
+            || self.servers.iter().any(|server| {
+                server.local_pointer().is_some_and(|pointer| {
+                    matches!(pointer.segments(), [servers, name] if servers == "servers" && name == server_name)
+                })
+            })
+    }
+}
+
+impl AsyncApiV3Spec {
+    /// Returns the channels available on a server, sorted by name. Channels defined through a
+    /// reference which cannot be resolved are skipped.
+    pub fn channels_for_server(&self, server_name: &str) -> Vec<(&str, &Channel)> {
+        let mut channels = self
+            .channels
+            .iter()
+            .filter_map(|(name, channel)| Some((name.as_str(), channel.resolve(self).ok()?)))
+            .filter(|(_, channel)| channel.is_available_on(server_name))
+            .collect::<Vec<_>>();
+        channels.sort_unstable_by_key(|(name, _)| *name);
+        channels
+    }
+
+    /// Returns the names of the channels available on each server, keyed by server name.
+    /// Servers without any channel are listed with an empty list.
+    pub fn server_channel_matrix(&self) -> BTreeMap<&str, Vec<&str>> {
+        self.servers
+            .keys()
+            .map(|server| {
+                let channels = self
+                    .channels_for_server(server)
+                    .into_iter()
+                    .map(|(name, _)| name)
+                    .collect();
+                (server.as_str(), channels)
+            })
+            .collect()
+    }
+
+    /// Returns the root channel whose address matches a concrete address, e.g. the topic a
+    /// message was received on, along with the values of the address parameters.
+    ///
+    /// When several channels match, the one with the fewest parameters wins, then the first
+    /// one by name. Channels with an absent or `null` address, which is unknown, never match.
+    pub fn match_channel(&self, address: &str) -> Option<ChannelMatch<'_>> {
+        self.channels
+            .iter()
+            .filter_map(|(name, channel)| {
+                let channel = channel.resolve(self).ok()?;
+                let pattern = AddressPattern::parse(channel.address.as_deref()?);
+                Some((pattern.parameter_count(), name, channel, pattern))
+            })
+            .filter_map(|(count, name, channel, pattern)| {
+                let parameters = pattern.matches(address)?;
+                Some((
+                    count,
+                    ChannelMatch {
+                        name,
+                        channel,
+                        parameters,
+                    },
+                ))
+            })
+            .min_by_key(|(count, _)| *count)
+            .map(|(_, found)| found)
+    }
 }
 
-pub type Parameters = HashMap<String, RefOr<Parameter>>;
+/// A channel matching a concrete address.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChannelMatch<'a> {
+    /// Name of the channel in the root channels.
+    pub name: &'a str,
+    /// The matching channel, resolved if the root channel is a reference.
+    pub channel: &'a Channel,
+    /// Values of the address parameters, keyed by parameter name.
+    pub parameters: BTreeMap<String, String>,
+}
+
+/// A channel address split into literal parts and parameters, e.g.
+/// `lights.{streetlightId}.measured`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AddressPattern {
+    parts: Vec<AddressPart>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum AddressPart {
+    Literal(String),
+    Parameter(String),
+}
+
+impl AddressPattern {
+    /// Parses an address. An unclosed `{` is part of the literal text.
+    pub fn parse(address: &str) -> Self {
+        let mut parts = Vec::new();
+        let mut rest = address;
+        while let Some(start) = rest.find('{') {
+            let Some(length) = rest[start..].find('}') else {
+                break;
+            };
+            if start > 0 {
+                parts.push(AddressPart::Literal(String::from(&rest[..start])));
+            }
+            let name = &rest[start + 1..start + length];
+            parts.push(AddressPart::Parameter(String::from(name)));
+            rest = &rest[start + length + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(AddressPart::Literal(String::from(rest)));
+        }
+        Self { parts }
+    }
+
+    /// Returns the number of parameters of the address.
+    pub fn parameter_count(&self) -> usize {
+        self.parts
+            .iter()
+            .filter(|part| matches!(part, AddressPart::Parameter(_)))
+            .count()
+    }
+
+    /// Matches a concrete address, returning the values of the parameters. Values cannot be
+    /// empty nor contain the first character of the literal part following them, e.g. a `.`
+    /// for `{streetlightId}.measured`.
+    pub fn matches(&self, address: &str) -> Option<BTreeMap<String, String>> {
+        let mut parameters = BTreeMap::new();
+        match_parts(&self.parts, address, &mut parameters).then_some(parameters)
+    }
+}
+
+fn match_parts(
+    parts: &[AddressPart],
+    address: &str,
+    parameters: &mut BTreeMap<String, String>,
+) -> bool {
+    match parts {
+        [] => address.is_empty(),
+        [AddressPart::Literal(literal), rest @ ..] => address
+            .strip_prefix(literal.as_str())
+            .is_some_and(|address| match_parts(rest, address, parameters)),
+        [AddressPart::Parameter(name), rest @ ..] => {
+            // A value cannot contain the character separating it from the next literal part.
+            let separator = match rest.first() {
+                Some(AddressPart::Literal(literal)) => literal.chars().next(),
+                _ => None,
+            };
+            let value_len = separator
+                .and_then(|separator| address.find(separator))
+                .unwrap_or(address.len());
+            let ends = address[..value_len]
+                .char_indices()
+                .skip(1)
+                .map(|(end, _)| end);
+            for end in ends.chain([value_len]).filter(|&end| end > 0) {
+                if match_parts(rest, &address[end..], parameters) {
+                    parameters.insert(name.clone(), String::from(&address[..end]));
+                    return true;
+                }
+            }
+            false
+        }
+    }
+}
+
+pub type Parameters = BTreeMap<String, RefOr<Parameter>>;
 
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Parameter {
     /// An enumeration of string values to be used if the substitution options are from a limited set.
     #[serde(rename = "enum")]
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "is_none_or_empty")]
     pub enum_values: Option<Vec<String>>,
     /// The default value to use for substitution, and to send, if an alternate value is not supplied.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -64,7 +243,7 @@ pub struct Parameter {
     pub location: Option<String>,
 }
 
-#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ChannelBindings {
     // TODO: implement based on https://www.asyncapi.com/docs/reference/specification/v3.0.0#channelBindingsObject
@@ -77,9 +256,24 @@ pub struct ChannelBindings {
     /// Protocol-specific information for an HTTP channel.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub http: Option<HttpChannelBinding>,
+    /// Bindings of protocols without a typed representation, keyed by protocol name.
+    #[serde(flatten, deserialize_with = "non_null_bindings")]
+    pub other: BTreeMap<String, serde_json::Value>,
+}
+
+impl BindingPatch for ChannelBindings {}
+
+impl ChannelBindings {
+    /// Returns an untyped view of all the bindings, including the typed ones, keyed by
+    /// protocol name. Useful to read bindings of protocols this crate does not model yet.
+    pub fn as_generic(&self) -> GenericBindings {
+        to_generic_bindings(self)
+    }
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum WebSocketHttpMethod {
     Get,
@@ -92,7 +286,7 @@ pub enum WebSocketHttpMethod {
 /// or, put it another way, there's only one channel
 /// and its characteristics are strongly related to the protocol used for the handshake, i.e., HTTP.
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
 pub struct WebSocketChannelBinding {
     /// The HTTP method to use when establishing the connection.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -108,6 +302,9 @@ pub struct WebSocketChannelBinding {
     /// The version of this binding. If omitted, "latest" is assumed.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub binding_version: Option<String>,
+    /// Fields of newer binding versions this crate does not model yet.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 impl WebSocketChannelBinding {
@@ -116,10 +313,309 @@ impl WebSocketChannelBinding {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
-pub struct NatsChannelBinding;
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NatsChannelBinding {
+    /// Fields of newer binding versions this crate does not model yet.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpChannelBinding {
+    /// Fields of newer binding versions this crate does not model yet.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::error::Error;
+    use crate::spec::common::Either;
+    use crate::spec::AsyncApiSpec;
+    use crate::test_util::parse_spec;
+
+    const SPEC: &str = r#"
+asyncapi: 3.0.0
+info:
+  title: Brokers
+  version: 1.0.0
+servers:
+  production:
+    host: broker.example.com
+    protocol: kafka
+  staging:
+    host: staging.example.com
+    protocol: kafka
+  unused:
+    host: unused.example.com
+    protocol: mqtt
+channels:
+  orders:
+    address: orders
+    messages: {}
+    servers:
+      - $ref: '#/servers/production'
+  heartbeat:
+    $ref: '#/components/channels/heartbeat'
+  audit:
+    address: audit
+    messages: {}
+    servers:
+      - $ref: '#/servers/production'
+      - $ref: '#/servers/staging'
+components:
+  channels:
+    heartbeat:
+      address: heartbeat
+      messages: {}
+"#;
+
+    #[test]
+    fn channels_for_server_respect_server_lists() {
+        let mut spec = parse_spec(SPEC);
+        spec.channels.remove("audit");
+
+        let names = |server: &str| {
+            spec.channels_for_server(server)
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(names("production"), vec!["heartbeat", "orders"]);
+        assert_eq!(names("staging"), vec!["heartbeat"]);
+        assert_eq!(
+            spec.channels_for_server("production")[0]
+                .1
+                .address
+                .as_deref(),
+            Some("heartbeat")
+        );
+    }
+
+    #[test]
+    fn server_channel_matrix_lists_every_server() {
+        let mut spec = parse_spec(SPEC);
+        let RefOr::Right(heartbeat) = spec.components.channels.get_mut("heartbeat").unwrap() else {
+            panic!("heartbeat should be inline");
+        };
+        heartbeat.servers = vec![ReferenceObject::new("#/servers/staging")];
+
+        let matrix = spec.server_channel_matrix();
+
+        assert_eq!(
+            matrix,
+            BTreeMap::from([
+                ("production", vec!["audit", "orders"]),
+                ("staging", vec!["audit", "heartbeat"]),
+                ("unused", vec![]),
+            ])
+        );
+    }
+
+    #[test]
+    fn match_channel_with_parameters() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Lights
+  version: 1.0.0
+channels:
+  measured:
+    address: lights.{streetlightId}.measured
+    messages: {}
+  zoneMeasured:
+    address: lights.{zone}.{streetlightId}.measured
+    messages: {}
+  dashboard:
+    address: lights.dashboard.measured
+    messages: {}
+"#,
+        );
+
+        let found = spec.match_channel("lights.north.42.measured").unwrap();
+        let exact = spec.match_channel("lights.dashboard.measured").unwrap();
+
+        assert_eq!(found.name, "zoneMeasured");
+        assert_eq!(
+            found.parameters,
+            BTreeMap::from([
+                (String::from("streetlightId"), String::from("42")),
+                (String::from("zone"), String::from("north")),
+            ])
+        );
+        assert_eq!(exact.name, "dashboard");
+        assert!(exact.parameters.is_empty());
+        assert_eq!(spec.match_channel("lights..measured"), None);
+    }
 
-#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
-pub struct HttpChannelBinding;
+    #[test]
+    fn address_round_trips_absent_null_and_value() {
+        for (yaml, address, json) in [
+            ("messages: {}", Maybe::Absent, None),
+            (
+                "{address: null, messages: {}}",
+                Maybe::Null,
+                Some(serde_json::Value::Null),
+            ),
+            (
+                "{address: lights, messages: {}}",
+                Maybe::Value(String::from("lights")),
+                Some(serde_json::json!("lights")),
+            ),
+        ] {
+            let channel: Channel = serde_yaml::from_str(yaml).unwrap();
+            assert_eq!(channel.address, address);
+
+            let value = serde_json::to_value(&channel).unwrap();
+            assert_eq!(value.get("address"), json.as_ref());
+            assert_eq!(serde_json::from_value::<Channel>(value).unwrap(), channel);
+        }
+    }
+
+    #[test]
+    fn null_address_is_unknown() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Lights
+  version: 1.0.0
+channels:
+  dynamic:
+    address: null
+    messages: {}
+  dashboard:
+    address: lights.dashboard.measured
+    messages: {}
+"#,
+        );
+
+        let dynamic = spec.channels["dynamic"].resolve(&spec).unwrap();
+        assert!(dynamic.address.is_null());
+        assert_eq!(dynamic.address.as_option(), None);
+        assert_eq!(spec.match_channel("null"), None);
+        assert_eq!(spec.match_channel("").map(|found| found.name), None);
+        assert_eq!(
+            spec.match_channel("lights.dashboard.measured")
+                .map(|found| found.name),
+            Some("dashboard")
+        );
+    }
+
+    #[test]
+    fn patch_fields_of_newer_binding_versions() {
+        let mut bindings = ChannelBindings::default();
+
+        bindings
+            .patch("ws", "/method", serde_json::json!("POST"))
+            .unwrap();
+        bindings
+            .patch("ws", "/compression/enabled", serde_json::json!(true))
+            .unwrap();
+        bindings
+            .patch("kafka", "/topic", serde_json::json!("orders"))
+            .unwrap();
+        let error = bindings
+            .patch("ws", "/method", serde_json::json!("CONNECT"))
+            .unwrap_err();
+
+        assert!(matches!(error, Error::InvalidBindingPatch { protocol, .. } if protocol == "ws"));
+        let round_trip =
+            serde_json::from_value::<ChannelBindings>(serde_json::to_value(&bindings).unwrap())
+                .unwrap();
+        assert_eq!(round_trip, bindings);
+        let ws = bindings.ws.unwrap();
+        assert_eq!(ws.method, Some(WebSocketHttpMethod::Post));
+        assert_eq!(
+            ws.extra["compression"],
+            serde_json::json!({ "enabled": true })
+        );
+        assert_eq!(
+            bindings.other["kafka"],
+            serde_json::json!({ "topic": "orders" })
+        );
+    }
+
+    #[test]
+    fn patched_fields_are_read_typed_after_an_upgrade() {
+        /// The Kafka channel binding as a later version of the crate would model it.
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct KafkaChannelBinding {
+            topic: String,
+            topic_configuration: TopicConfiguration,
+        }
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct TopicConfiguration {
+            #[serde(rename = "retention.ms")]
+            retention_ms: u64,
+        }
+        let mut spec = parse_spec(SPEC);
+        let mut bindings = ChannelBindings::default();
+        bindings
+            .patch("kafka", "/topic", serde_json::json!("orders"))
+            .unwrap();
+        bindings
+            .patch(
+                "kafka",
+                "/topicConfiguration/retention.ms",
+                serde_json::json!(604800000),
+            )
+            .unwrap();
+        let Some(Either::Right(orders)) = spec.channels.get_mut("orders") else {
+            panic!("orders is an inline channel");
+        };
+        orders.bindings = Some(Either::Right(bindings));
+
+        let yaml = serde_yaml::to_string(&AsyncApiSpec::V3_0_0(spec)).unwrap();
+        let upgraded = parse_spec(&yaml);
+
+        let Some(Either::Right(orders)) = upgraded.channels.get("orders") else {
+            panic!("orders is an inline channel");
+        };
+        let Some(Either::Right(bindings)) = &orders.bindings else {
+            panic!("the bindings of orders are inline");
+        };
+        let kafka = serde_json::from_value::<KafkaChannelBinding>(
+            bindings.as_generic().remove("kafka").unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            kafka,
+            KafkaChannelBinding {
+                topic: String::from("orders"),
+                topic_configuration: TopicConfiguration {
+                    retention_ms: 604800000
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn parameter_enum_round_trips_under_its_name() {
+        let parameter = serde_yaml::from_str::<Parameter>("enum: [eu, us]").unwrap();
+        let empty = serde_yaml::from_str::<Parameter>("enum: []").unwrap();
+        let absent = serde_yaml::from_str::<Parameter>("default: eu").unwrap();
+
+        assert_eq!(
+            parameter.enum_values,
+            Some(vec![String::from("eu"), String::from("us")])
+        );
+        assert_eq!(
+            serde_json::to_value(&parameter).unwrap()["enum"],
+            serde_json::json!(["eu", "us"])
+        );
+        assert_eq!(empty.enum_values, Some(Vec::new()));
+        assert_eq!(absent.enum_values, None);
+        for skipped in [empty, absent] {
+            let value = serde_json::to_value(&skipped).unwrap();
+            assert!(value.get("enum").is_none(), "{value}");
+            assert!(value.get("enumValues").is_none(), "{value}");
+        }
+    }
+}
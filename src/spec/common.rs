@@ -1,4 +1,13 @@
 //! Module for common types or utilities used throughout the specification.
+use crate::error::Error;
+use crate::spec::component::{is_valid_key, ComponentSection};
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::fmt;
+use serde_json::{Map, Value};
 
 /// Either type used to store either one type or another.
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
@@ -23,41 +32,551 @@ where
     }
 }
 
+/// A field which can be absent, explicitly `null` or set, for the fields where the
+/// specification gives `null` a meaning of its own, e.g. a channel address which is unknown.
+///
+/// Use it with `#[serde(default, skip_serializing_if = "Maybe::is_absent")]` so an absent field
+/// stays omitted and `null` is written back as `null`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Maybe<T> {
+    /// The field is not present in the document.
+    #[default]
+    Absent,
+    /// The field is present with a `null` value.
+    Null,
+    /// The field is present with a value.
+    Value(T),
+}
+
+impl<T> Maybe<T> {
+    /// Returns `true` if the field is not present in the document.
+    pub fn is_absent(&self) -> bool {
+        matches!(self, Self::Absent)
+    }
+
+    /// Returns `true` if the field is present with a `null` value.
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    /// Returns the value, if any, treating an absent and a `null` field the same.
+    pub fn as_option(&self) -> Option<&T> {
+        match self {
+            Self::Value(value) => Some(value),
+            Self::Absent | Self::Null => None,
+        }
+    }
+
+    /// Returns the dereferenced value, if any, e.g. a `&str` for a `Maybe<String>`.
+    pub fn as_deref(&self) -> Option<&T::Target>
+    where
+        T: core::ops::Deref,
+    {
+        self.as_option().map(|value| &**value)
+    }
+
+    /// Converts into an [Option], treating an absent and a `null` field the same.
+    pub fn into_option(self) -> Option<T> {
+        match self {
+            Self::Value(value) => Some(value),
+            Self::Absent | Self::Null => None,
+        }
+    }
+}
+
+impl<T> From<T> for Maybe<T> {
+    fn from(value: T) -> Self {
+        Self::Value(value)
+    }
+}
+
+impl<T: serde::Serialize> serde::Serialize for Maybe<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Value(value) => value.serialize(serializer),
+            Self::Absent | Self::Null => serializer.serialize_none(),
+        }
+    }
+}
+
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for Maybe<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // An absent field never reaches this point, it is filled in by `#[serde(default)]`.
+        Ok(match Option::<T>::deserialize(deserializer)? {
+            Some(value) => Self::Value(value),
+            None => Self::Null,
+        })
+    }
+}
+
 /// To prevent excessive repetitions of shared elements AsyncAPI allows you to refer to already
 /// defined objects via references. This type is used for serializing and deserializing said
 /// references.
-#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+///
+/// References are ordered by their `$ref` string first, then by summary and description. Two
+/// references to the same target with different descriptions differ, [ReferenceObject::key]
+/// identifies their target only.
+#[derive(
+    Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
 pub struct ReferenceObject {
     /// The reference for the referred to object
     #[serde(rename = "$ref")]
     pub reference: String,
+    /// A short summary which by default SHOULD override that of the referenced component.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    /// A description which by default SHOULD override that of the referenced component.
+    /// CommonMark syntax can be used for rich text representation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
 }
 
 impl ReferenceObject {
-    /// Creates a reference to a channel.
-    pub fn new_channel(channel_name: &str) -> Self {
+    /// Creates a reference without summary nor description.
+    pub fn new(reference: impl Into<String>) -> Self {
         Self {
-            reference: format!("#/channels/{channel_name}"),
+            reference: reference.into(),
+            summary: None,
+            description: None,
         }
     }
 
+    /// Creates a reference to a channel.
+    pub fn new_channel(channel_name: &str) -> Self {
+        Self::new(format!("#/channels/{channel_name}"))
+    }
+
     /// Creates a reference to a message.
     pub fn new_message(message_name: &str) -> Self {
-        Self {
-            reference: format!("#/components/messages/{message_name}"),
-        }
+        Self::new(format!("#/components/messages/{message_name}"))
     }
 
     /// Creates a reference to a channel message.
     pub fn new_channel_message(channel_name: &str, message_name: &str) -> Self {
+        Self::new(format!("#/channels/{channel_name}/messages/{message_name}"))
+    }
+
+    /// Returns the key identifying the target of the reference.
+    pub fn key(&self) -> ReferenceKey {
+        ReferenceKey::from(self)
+    }
+
+    /// Returns the JSON pointer of a reference to the current document, or `None` if the
+    /// reference points to an external resource.
+    pub fn local_pointer(&self) -> Option<JsonPointer> {
+        local_pointer(&self.reference)
+    }
+}
+
+/// Returns the JSON pointer of a `$ref` to the current document, like
+/// [ReferenceObject::local_pointer].
+pub(crate) fn local_pointer(reference: &str) -> Option<JsonPointer> {
+    JsonPointer::parse(reference.strip_prefix('#')?).ok()
+}
+
+/// Returns the `$ref`s found anywhere inside a value in document order, along with the location
+/// of the objects holding them, `pointer` being the location of `value`.
+pub(crate) fn collect_references<'v>(
+    value: &'v Value,
+    pointer: &JsonPointer,
+) -> Vec<(JsonPointer, &'v str)> {
+    fn walk<'v>(
+        value: &'v Value,
+        pointer: &JsonPointer,
+        references: &mut Vec<(JsonPointer, &'v str)>,
+    ) {
+        match value {
+            Value::Object(fields) => {
+                if let Some(Value::String(reference)) = fields.get("$ref") {
+                    references.push((pointer.clone(), reference.as_str()));
+                }
+                for (key, value) in fields {
+                    walk(value, &pointer.join(key.as_str()), references);
+                }
+            }
+            Value::Array(items) => {
+                for (index, item) in items.iter().enumerate() {
+                    walk(item, &pointer.join(index.to_string()), references);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut references = Vec::new();
+    walk(value, pointer, &mut references);
+    references
+}
+
+/// The `$ref` of a [ReferenceObject], identifying its target whatever the summary and the
+/// description of the reference, e.g. as a map key. Keys can be looked up by `&str`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ReferenceKey(String);
+
+impl ReferenceKey {
+    /// Returns the `$ref` string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for ReferenceKey {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&ReferenceObject> for ReferenceKey {
+    fn from(reference: &ReferenceObject) -> Self {
+        Self(reference.reference.clone())
+    }
+}
+
+/// A [JSON pointer](https://www.rfc-editor.org/rfc/rfc6901) stored as its unescaped segments.
+///
+/// Pointers are ordered segment-wise, so a pointer sorts right before its descendants:
+/// `/channels` < `/channels/a` < `/channels/a/messages` < `/channels/b`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct JsonPointer {
+    segments: Vec<String>,
+}
+
+impl JsonPointer {
+    /// Parses a pointer such as `/components/messages/userSignedUp`. Segments are unescaped
+    /// (`~1` to `/` and `~0` to `~`) and percent-decoded.
+    pub fn parse(pointer: &str) -> Result<Self, InvalidPointer> {
+        if pointer.is_empty() {
+            return Ok(Self::default());
+        }
+        let Some(rest) = pointer.strip_prefix('/') else {
+            return Err(InvalidPointer {
+                pointer: String::from(pointer),
+            });
+        };
+        let segments = rest
+            .split('/')
+            .map(|segment| percent_decode(&segment.replace("~1", "/").replace("~0", "~")))
+            .collect();
+        Ok(Self { segments })
+    }
+
+    /// Returns the unescaped segments of the pointer.
+    pub fn segments(&self) -> &[String] {
+        &self.segments
+    }
+
+    /// Returns the value addressed by the pointer inside the given JSON value.
+    pub fn evaluate<'a>(&self, value: &'a Value) -> Option<&'a Value> {
+        self.segments
+            .iter()
+            .try_fold(value, |current, segment| match current {
+                Value::Object(fields) => fields.get(segment),
+                Value::Array(items) => items.get(segment.parse::<usize>().ok()?),
+                _ => None,
+            })
+    }
+
+    /// Returns the mutable value addressed by the pointer inside the given JSON value.
+    pub fn evaluate_mut<'a>(&self, value: &'a mut Value) -> Option<&'a mut Value> {
+        self.segments
+            .iter()
+            .try_fold(value, |current, segment| match current {
+                Value::Object(fields) => fields.get_mut(segment),
+                Value::Array(items) => items.get_mut(segment.parse::<usize>().ok()?),
+                _ => None,
+            })
+    }
+
+    /// Returns the pointer without its last segment, or `None` for the root pointer.
+    pub fn parent(&self) -> Option<Self> {
+        let (_, segments) = self.segments.split_last()?;
+        Some(Self {
+            segments: segments.to_vec(),
+        })
+    }
+
+    /// Returns `true` if the pointer is `other` or one of its descendants.
+    pub fn starts_with(&self, other: &Self) -> bool {
+        self.segments.starts_with(&other.segments)
+    }
+
+    /// Returns a new pointer with the given segment appended.
+    pub fn join(&self, segment: impl Into<String>) -> Self {
+        let mut segments = self.segments.clone();
+        segments.push(segment.into());
+        Self { segments }
+    }
+}
+
+impl fmt::Display for JsonPointer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for segment in &self.segments {
+            write!(f, "/{}", segment.replace('~', "~0").replace('/', "~1"))?;
+        }
+        Ok(())
+    }
+}
+
+impl serde::Serialize for JsonPointer {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for JsonPointer {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pointer = <String as serde::Deserialize>::deserialize(deserializer)?;
+        Self::parse(&pointer).map_err(serde::de::Error::custom)
+    }
+}
+
+impl<S: Into<String>> FromIterator<S> for JsonPointer {
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
         Self {
-            reference: format!("#/channels/{channel_name}/messages/{message_name}"),
+            segments: iter.into_iter().map(Into::into).collect(),
         }
     }
 }
 
+/// Error returned when parsing a malformed [JsonPointer].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidPointer {
+    /// The pointer that could not be parsed.
+    pub pointer: String,
+}
+
+impl fmt::Display for InvalidPointer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid JSON pointer '{}'", self.pointer)
+    }
+}
+
+impl core::error::Error for InvalidPointer {}
+
+fn percent_decode(segment: &str) -> String {
+    if !segment.contains('%') {
+        return String::from(segment);
+    }
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| core::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).unwrap_or_else(|_| String::from(segment))
+}
+
+/// Skips optional lists which are absent or empty, e.g. an `enum` without values.
+pub(crate) fn is_none_or_empty<T>(list: &Option<Vec<T>>) -> bool {
+    list.as_ref().is_none_or(Vec::is_empty)
+}
+
+/// Untyped view of a bindings object, mapping each protocol name to its binding definition.
+///
+/// A plain map rather than a newtype: it is a snapshot with no invariant to uphold, read by
+/// indexing it with a protocol name or deserializing a binding out of it, e.g.
+/// `bindings.as_generic()["kafka"]`.
+pub type GenericBindings = BTreeMap<String, Value>;
+
+/// Deserializes the untyped bindings of a bindings object, dropping the protocols declared as
+/// `null`, which declare no binding.
+pub(crate) fn non_null_bindings<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<GenericBindings, D::Error> {
+    let mut bindings = <GenericBindings as serde::Deserialize>::deserialize(deserializer)?;
+    bindings.retain(|_, binding| !binding.is_null());
+    Ok(bindings)
+}
+
+/// Serializes a bindings object into its [GenericBindings] view.
+pub(crate) fn to_generic_bindings<T: serde::Serialize>(bindings: &T) -> GenericBindings {
+    match serde_json::to_value(bindings) {
+        Ok(Value::Object(fields)) => fields.into_iter().collect(),
+        _ => GenericBindings::new(),
+    }
+}
+
+/// Writes fields into a bindings object, including fields of binding versions this crate does
+/// not model yet. Implemented by the bindings objects of servers, channels, operations and
+/// messages.
+pub trait BindingPatch: serde::Serialize + serde::de::DeserializeOwned {
+    /// Sets the value at a JSON pointer inside the binding of a protocol, e.g.
+    /// `patch("kafka", "/schemaRegistryUrl", json!("https://registry.example.com"))`, creating
+    /// the binding and the intermediate objects as needed.
+    ///
+    /// Fields modeled by the typed bindings are type checked: a value they cannot hold fails
+    /// with [Error::InvalidBindingPatch] and leaves the bindings untouched. The other fields go
+    /// to the `extra` map of the typed bindings, or to the untyped `other` bindings, so they
+    /// survive round trips and are read typed once the crate models them. Validation ignores
+    /// them.
+    fn patch(&mut self, protocol: &str, pointer: &str, value: Value) -> Result<(), Error> {
+        let invalid = |reason: String| Error::InvalidBindingPatch {
+            protocol: String::from(protocol),
+            reason,
+        };
+        let pointer = JsonPointer::parse(pointer).map_err(|err| invalid(err.to_string()))?;
+        let mut bindings = to_generic_bindings(self);
+        let mut target = bindings
+            .entry(String::from(protocol))
+            .or_insert_with(|| Value::Object(Map::new()));
+        for segment in pointer.segments() {
+            target = match target {
+                Value::Object(fields) => fields
+                    .entry(segment.clone())
+                    .or_insert_with(|| Value::Object(Map::new())),
+                _ => {
+                    return Err(invalid(format!(
+                        "`{pointer}` does not point inside objects"
+                    )))
+                }
+            };
+        }
+        *target = value;
+        let bindings = Value::Object(bindings.into_iter().collect());
+        *self = serde_json::from_value(bindings).map_err(|err| invalid(err.to_string()))?;
+        Ok(())
+    }
+}
+
+/// [Specification extensions](https://www.asyncapi.com/docs/reference/specification/v3.0.0#specificationExtensions)
+/// attached to an object. Only fields whose name starts with `x-` are kept.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize)]
+pub struct Extensions(BTreeMap<String, Value>);
+
+impl Extensions {
+    /// Returns the value of the given extension.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.0.get(key)
+    }
+
+    /// Returns the value of the given extension if it is an unsigned integer.
+    pub fn get_u64(&self, key: &str) -> Option<u64> {
+        self.get(key).and_then(Value::as_u64)
+    }
+
+    /// Sets the value of an extension, returning the previous one.
+    ///
+    /// Fails with [Error::InvalidExtensionKey] if the key does not start with `x-`.
+    pub fn insert(&mut self, key: impl Into<String>, value: Value) -> Result<Option<Value>, Error> {
+        let key = key.into();
+        if !key.starts_with("x-") {
+            return Err(Error::InvalidExtensionKey { key });
+        }
+        Ok(self.0.insert(key, value))
+    }
+
+    /// Removes an extension, returning its value.
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        self.0.remove(key)
+    }
+
+    /// Iterates over the extensions sorted by key.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Value)> {
+        self.0.iter()
+    }
+
+    /// Returns `true` if there is no extension.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Extensions {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let fields = BTreeMap::<String, Value>::deserialize(deserializer)?;
+        Ok(Self(
+            fields
+                .into_iter()
+                .filter(|(key, _)| key.starts_with("x-"))
+                .collect(),
+        ))
+    }
+}
+
 pub type RefOr<T> = Either<ReferenceObject, T>;
 
+impl<T> RefOr<T> {
+    /// Wraps an object defined in place.
+    pub fn item(item: T) -> Self {
+        Self::Right(item)
+    }
+
+    /// Creates a reference, e.g. to `#/channels/orders`.
+    pub fn reference(reference: impl Into<String>) -> Self {
+        Self::Left(ReferenceObject::new(reference))
+    }
+
+    /// Creates a reference to the entry `key` of a section of the components, e.g.
+    /// `#/components/messages/orderPlaced`.
+    ///
+    /// Fails with [Error::InvalidKey] if the key is not a [valid component key](is_valid_key).
+    pub fn component_ref(section: ComponentSection, key: &str) -> Result<Self, Error> {
+        if !is_valid_key(key) {
+            return Err(Error::InvalidKey {
+                key: String::from(key),
+            });
+        }
+        Ok(Self::reference(format!("#/components/{section}/{key}")))
+    }
+
+    /// Returns the object defined in place, `None` for a reference.
+    pub fn as_item(&self) -> Option<&T> {
+        match self {
+            Self::Left(_) => None,
+            Self::Right(item) => Some(item),
+        }
+    }
+
+    /// Returns the reference, `None` for an object defined in place.
+    pub fn as_reference(&self) -> Option<&ReferenceObject> {
+        match self {
+            Self::Left(reference) => Some(reference),
+            Self::Right(_) => None,
+        }
+    }
+
+    /// Maps the object defined in place, leaving references untouched.
+    pub fn map_item<U>(self, f: impl FnOnce(T) -> U) -> RefOr<U> {
+        match self {
+            Self::Left(reference) => RefOr::Left(reference),
+            Self::Right(item) => RefOr::Right(f(item)),
+        }
+    }
+}
+
+impl<T> From<T> for RefOr<T> {
+    fn from(item: T) -> Self {
+        Self::Right(item)
+    }
+}
+
+/// Builds a map of objects or references, e.g. [Servers](crate::spec::server::Servers) or
+/// [Channels](crate::spec::channel::Channels), from pairs of keys and objects or references.
+///
+/// The maps are [BTreeMap]s, which also collect iterators of `(String, RefOr<T>)` pairs.
+pub fn from_pairs<T, K: Into<String>, V: Into<RefOr<T>>>(
+    pairs: impl IntoIterator<Item = (K, V)>,
+) -> BTreeMap<String, RefOr<T>> {
+    pairs
+        .into_iter()
+        .map(|(key, value)| (key.into(), value.into()))
+        .collect()
+}
+
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Tag {
@@ -80,3 +599,170 @@ pub struct ExternalDocumentation {
     /// The URL for the target documentation. This MUST be in the form of an absolute URL.
     pub url: String,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn json_pointer_segment_wise_order() {
+        let pointers: BTreeMap<JsonPointer, ()> = [
+            "/channels/b",
+            "/channels/a/messages",
+            "/channels",
+            "/channels/a",
+            "/channels/a~1b",
+        ]
+        .into_iter()
+        .map(|pointer| (JsonPointer::parse(pointer).unwrap(), ()))
+        .collect();
+
+        let ordered = pointers.keys().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            ordered,
+            vec![
+                "/channels",
+                "/channels/a",
+                "/channels/a/messages",
+                "/channels/a~1b",
+                "/channels/b",
+            ]
+        );
+    }
+
+    #[test]
+    fn reference_object_as_map_key() {
+        let described = |description: &str| ReferenceObject {
+            description: Some(String::from(description)),
+            ..ReferenceObject::new_message("a")
+        };
+        let references: BTreeMap<ReferenceObject, u32> = [
+            (ReferenceObject::new_message("b"), 2),
+            (ReferenceObject::new_channel("a"), 1),
+            (described("Second"), 4),
+            (described("First"), 3),
+        ]
+        .into_iter()
+        .collect();
+
+        let ordered = references.values().copied().collect::<Vec<_>>();
+        assert_eq!(ordered, vec![1, 3, 4, 2]);
+        assert_ne!(described("First"), described("Second"));
+    }
+
+    #[test]
+    fn reference_key_ignores_documentation() {
+        let described = ReferenceObject {
+            description: Some(String::from("The lights")),
+            ..ReferenceObject::new_channel("a")
+        };
+        let keys: BTreeMap<ReferenceKey, u32> = [
+            (ReferenceObject::new_message("b").key(), 2),
+            (described.key(), 1),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(described.key(), ReferenceObject::new_channel("a").key());
+        assert_eq!(keys.get("#/channels/a"), Some(&1));
+        assert_eq!(keys.get("#/components/messages/b"), Some(&2));
+    }
+
+    #[test]
+    fn reference_with_description_round_trips() {
+        let written = serde_json::json!({
+            "$ref": "#/components/tags/lights",
+            "description": "The tag of the lighting services.",
+        });
+
+        let reference: RefOr<Tag> = serde_json::from_value(written.clone()).unwrap();
+
+        let RefOr::Left(reference) = &reference else {
+            panic!("expected a reference");
+        };
+        assert_eq!(
+            reference.description.as_deref(),
+            Some("The tag of the lighting services.")
+        );
+        assert_eq!(reference.summary, None);
+        assert_eq!(serde_json::to_value(reference).unwrap(), written);
+    }
+
+    #[test]
+    fn component_references() {
+        let pointers = ComponentSection::ALL
+            .into_iter()
+            .map(|section| {
+                let reference = RefOr::<Tag>::component_ref(section, "lights.v1").unwrap();
+                reference.as_reference().unwrap().reference.clone()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            pointers,
+            [
+                "#/components/schemas/lights.v1",
+                "#/components/servers/lights.v1",
+                "#/components/channels/lights.v1",
+                "#/components/operations/lights.v1",
+                "#/components/messages/lights.v1",
+                "#/components/securitySchemes/lights.v1",
+                "#/components/serverVariables/lights.v1",
+                "#/components/parameters/lights.v1",
+                "#/components/correlationIds/lights.v1",
+                "#/components/replies/lights.v1",
+                "#/components/replyAddresses/lights.v1",
+                "#/components/externalDocs/lights.v1",
+                "#/components/tags/lights.v1",
+                "#/components/operationTraits/lights.v1",
+                "#/components/messageTraits/lights.v1",
+                "#/components/serverBindings/lights.v1",
+                "#/components/channelBindings/lights.v1",
+                "#/components/operationBindings/lights.v1",
+                "#/components/messageBindings/lights.v1",
+            ]
+        );
+        assert!(matches!(
+            RefOr::<Tag>::component_ref(ComponentSection::Tags, "lights/v1"),
+            Err(Error::InvalidKey { key }) if key == "lights/v1"
+        ));
+    }
+
+    #[test]
+    fn extension_keys_start_with_x() {
+        let mut extensions = Extensions::default();
+
+        assert_eq!(
+            extensions.insert("x-team", Value::from("lights")).unwrap(),
+            None
+        );
+        assert!(matches!(
+            extensions.insert("team", Value::from("lights")),
+            Err(Error::InvalidExtensionKey { key }) if key == "team"
+        ));
+        assert_eq!(extensions.iter().count(), 1);
+    }
+
+    #[test]
+    fn ref_or_accessors() {
+        let tag = Tag {
+            name: String::from("lights"),
+            description: None,
+            external_docs: None,
+        };
+        let item = RefOr::from(tag.clone());
+        let reference = RefOr::<Tag>::reference("#/components/tags/lights");
+
+        assert_eq!(item.as_item(), Some(&tag));
+        assert_eq!(item.as_reference(), None);
+        assert_eq!(reference.as_item(), None);
+        assert_eq!(
+            reference.clone().map_item(|tag| tag.name),
+            RefOr::reference("#/components/tags/lights")
+        );
+        assert_eq!(
+            item.map_item(|tag| tag.name),
+            RefOr::item(String::from("lights"))
+        );
+    }
+}
@@ -6,71 +6,186 @@ use crate::spec::message::{
     CorrelationId, Message, MessageBindings, MessageTrait, MultiFormatSchema,
 };
 use crate::spec::operation::{
-    Operation, OperationBindings, OperationReply, OperationReplyAddress, OperationTrait,
+    keyed_operations, Operation, OperationBindings, OperationReply, OperationReplyAddress,
+    OperationTrait,
 };
 use crate::spec::security::SecurityScheme;
 use crate::spec::server::{Server, ServerBindings, Variable};
-use std::collections::HashMap;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use core::fmt;
 
 #[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 /// Holds a set of reusable objects for different aspects of the AsyncAPI specification. All objects defined within the components object will have no effect on the API unless they are explicitly referenced from properties outside the components object.
 pub struct Components {
     /// An object to hold reusable Schema Object. If this is a Schema Object, then the schemaFormat will be assumed to be "application/vnd.aai.asyncapi+json;version=asyncapi" where the version is equal to the AsyncAPI Version String.
-    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
-    pub schemas: HashMap<String, RefOr<Either<schemars::Schema, MultiFormatSchema>>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub schemas: BTreeMap<String, RefOr<Either<schemars::Schema, MultiFormatSchema>>>,
     /// An object to hold reusable [Server Objects](Server).
-    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
-    pub servers: HashMap<String, RefOr<Server>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub servers: BTreeMap<String, RefOr<Server>>,
     /// An object to hold reusable [Channel Objects](Channel).
-    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
-    pub channels: HashMap<String, RefOr<Channel>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub channels: BTreeMap<String, RefOr<Channel>>,
     /// An object to hold reusable [Operation Objects](Operation).
-    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
-    pub operations: HashMap<String, RefOr<Operation>>,
+    #[serde(
+        default,
+        deserialize_with = "keyed_operations",
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    pub operations: BTreeMap<String, RefOr<Operation>>,
     /// An object to hold reusable [Message Objects](Message).
-    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
-    pub messages: HashMap<String, RefOr<Message>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub messages: BTreeMap<String, RefOr<Message>>,
     /// An object to hold reusable [Security Scheme Objects](SecurityScheme).
-    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
-    pub security_schemes: HashMap<String, RefOr<SecurityScheme>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub security_schemes: BTreeMap<String, RefOr<SecurityScheme>>,
     /// An object to hold reusable [Server Variable Objects](Variable).
-    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
-    pub server_variables: HashMap<String, RefOr<Variable>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub server_variables: BTreeMap<String, RefOr<Variable>>,
     /// An object to hold reusable [Parameter Objects](Parameter).
-    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
-    pub parameters: HashMap<String, RefOr<Parameter>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub parameters: BTreeMap<String, RefOr<Parameter>>,
     /// An object to hold reusable Correlation [ID Objects](CorrelationId).
-    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
-    pub correlation_ids: HashMap<String, RefOr<CorrelationId>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub correlation_ids: BTreeMap<String, RefOr<CorrelationId>>,
     /// An object to hold reusable [Operation Reply Objects](OperationReply).
-    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
-    pub replies: HashMap<String, RefOr<OperationReply>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub replies: BTreeMap<String, RefOr<OperationReply>>,
     /// An object to hold reusable Operation [Reply Address Objects](OperationReplyAddress).
-    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
-    pub reply_addresses: HashMap<String, RefOr<OperationReplyAddress>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub reply_addresses: BTreeMap<String, RefOr<OperationReplyAddress>>,
     /// An object to hold reusable [External Documentation Objects](ExternalDocumentation).
-    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
-    pub external_docs: HashMap<String, RefOr<ExternalDocumentation>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub external_docs: BTreeMap<String, RefOr<ExternalDocumentation>>,
     /// An object to hold reusable [Tag Objects](Tag).
-    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
-    pub tags: HashMap<String, RefOr<Tag>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, RefOr<Tag>>,
     /// An object to hold reusable [Operation Trait Objects](OperationTrait).
-    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
-    pub operation_traits: HashMap<String, RefOr<OperationTrait>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub operation_traits: BTreeMap<String, RefOr<OperationTrait>>,
     /// An object to hold reusable [Message Trait Objects](MessageTrait).
-    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
-    pub message_traits: HashMap<String, RefOr<MessageTrait>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub message_traits: BTreeMap<String, RefOr<MessageTrait>>,
     /// An object to hold reusable [Server Bindings Objects](ServerBindings).
-    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
-    pub server_bindings: HashMap<String, RefOr<ServerBindings>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub server_bindings: BTreeMap<String, RefOr<ServerBindings>>,
     /// An object to hold reusable [Channel Bindings Objects](ChannelBindings).
-    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
-    pub channel_bindings: HashMap<String, RefOr<ChannelBindings>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub channel_bindings: BTreeMap<String, RefOr<ChannelBindings>>,
     /// An object to hold reusable [Operation Bindings Objects](OperationBindings).
-    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
-    pub operation_bindings: HashMap<String, RefOr<OperationBindings>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub operation_bindings: BTreeMap<String, RefOr<OperationBindings>>,
     /// An object to hold reusable [Message Bindings Objects](MessageBindings).
-    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
-    pub message_bindings: HashMap<String, RefOr<MessageBindings>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub message_bindings: BTreeMap<String, RefOr<MessageBindings>>,
+}
+
+/// The sections of the [Components] object.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ComponentSection {
+    /// `components/schemas`.
+    Schemas,
+    /// `components/servers`.
+    Servers,
+    /// `components/channels`.
+    Channels,
+    /// `components/operations`.
+    Operations,
+    /// `components/messages`.
+    Messages,
+    /// `components/securitySchemes`.
+    SecuritySchemes,
+    /// `components/serverVariables`.
+    ServerVariables,
+    /// `components/parameters`.
+    Parameters,
+    /// `components/correlationIds`.
+    CorrelationIds,
+    /// `components/replies`.
+    Replies,
+    /// `components/replyAddresses`.
+    ReplyAddresses,
+    /// `components/externalDocs`.
+    ExternalDocs,
+    /// `components/tags`.
+    Tags,
+    /// `components/operationTraits`.
+    OperationTraits,
+    /// `components/messageTraits`.
+    MessageTraits,
+    /// `components/serverBindings`.
+    ServerBindings,
+    /// `components/channelBindings`.
+    ChannelBindings,
+    /// `components/operationBindings`.
+    OperationBindings,
+    /// `components/messageBindings`.
+    MessageBindings,
+}
+
+impl ComponentSection {
+    /// Every section, in the order of the fields of [Components].
+    pub const ALL: [ComponentSection; 19] = [
+        Self::Schemas,
+        Self::Servers,
+        Self::Channels,
+        Self::Operations,
+        Self::Messages,
+        Self::SecuritySchemes,
+        Self::ServerVariables,
+        Self::Parameters,
+        Self::CorrelationIds,
+        Self::Replies,
+        Self::ReplyAddresses,
+        Self::ExternalDocs,
+        Self::Tags,
+        Self::OperationTraits,
+        Self::MessageTraits,
+        Self::ServerBindings,
+        Self::ChannelBindings,
+        Self::OperationBindings,
+        Self::MessageBindings,
+    ];
+
+    /// Returns the name of the section in documents, e.g. `securitySchemes`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Schemas => "schemas",
+            Self::Servers => "servers",
+            Self::Channels => "channels",
+            Self::Operations => "operations",
+            Self::Messages => "messages",
+            Self::SecuritySchemes => "securitySchemes",
+            Self::ServerVariables => "serverVariables",
+            Self::Parameters => "parameters",
+            Self::CorrelationIds => "correlationIds",
+            Self::Replies => "replies",
+            Self::ReplyAddresses => "replyAddresses",
+            Self::ExternalDocs => "externalDocs",
+            Self::Tags => "tags",
+            Self::OperationTraits => "operationTraits",
+            Self::MessageTraits => "messageTraits",
+            Self::ServerBindings => "serverBindings",
+            Self::ChannelBindings => "channelBindings",
+            Self::OperationBindings => "operationBindings",
+            Self::MessageBindings => "messageBindings",
+        }
+    }
+}
+
+impl fmt::Display for ComponentSection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Returns `true` if a key is a valid key of the components, i.e. matches
+/// `^[a-zA-Z0-9\.\-_]+$`.
+pub fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
 }
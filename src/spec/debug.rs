@@ -0,0 +1,173 @@
+//! Compact [Debug](fmt::Debug) output for the large objects of a document.
+//!
+//! The derived `Debug` of a document prints every field and easily spans thousands of lines,
+//! which buries the interesting part of a failed assertion. [CompactDebug] prints the
+//! serialized form of an object instead, where:
+//!
+//! - absent and empty fields are omitted,
+//! - objects with more than [MAX_ENTRIES] entries are summarized by all of their keys,
+//!   without the values, e.g. `{9 entries: a, b, c, d, e, f, g, h, i}`,
+//! - strings longer than [MAX_STRING_LEN] bytes are cut, with a note of their length.
+//!
+//! The derived `Debug` remains the complete output.
+use crate::spec::channel::Channel;
+use crate::spec::component::Components;
+use crate::spec::message::Message;
+use crate::spec::AsyncApiV3Spec;
+use core::fmt;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Number of entries above which an object is summarized by its keys.
+pub const MAX_ENTRIES: usize = 8;
+
+/// Number of bytes above which a string is cut.
+pub const MAX_STRING_LEN: usize = 64;
+
+/// Compact debug view of an object, see the [module documentation](self).
+pub struct CompactDebug<'a, T> {
+    name: &'static str,
+    value: &'a T,
+}
+
+impl<T: Serialize> fmt::Debug for CompactDebug<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match serde_json::to_value(self.value) {
+            Ok(Value::Object(fields)) => {
+                let mut debug = f.debug_struct(self.name);
+                for (key, value) in fields.iter().filter(|(_, value)| !is_empty(value)) {
+                    debug.field(key, &Compact(value));
+                }
+                debug.finish()
+            }
+            Ok(value) => f.debug_tuple(self.name).field(&Compact(&value)).finish(),
+            Err(_) => write!(f, "{} {{ .. }}", self.name),
+        }
+    }
+}
+
+macro_rules! compact_debug {
+    ($($ty:ident,)*) => {
+        $(
+            impl $ty {
+                /// Returns a compact [Debug](fmt::Debug) view of the object, omitting empty
+                /// fields and summarizing large maps and long strings. The derived `Debug`
+                /// prints everything.
+                pub fn debug_compact(&self) -> CompactDebug<'_, Self> {
+                    CompactDebug {
+                        name: stringify!($ty),
+                        value: self,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+compact_debug! {
+    AsyncApiV3Spec,
+    Components,
+    Channel,
+    Message,
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Array(items) => items.is_empty(),
+        Value::Object(fields) => fields.is_empty(),
+        _ => false,
+    }
+}
+
+struct Compact<'a>(&'a Value);
+
+impl fmt::Debug for Compact<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Value::Object(fields) if fields.len() > MAX_ENTRIES => {
+                write!(f, "{{{} entries: ", fields.len())?;
+                for (index, key) in fields.keys().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    f.write_str(key)?;
+                }
+                f.write_str("}")
+            }
+            Value::Object(fields) => f
+                .debug_map()
+                .entries(
+                    fields
+                        .iter()
+                        .filter(|(_, value)| !is_empty(value))
+                        .map(|(key, value)| (Key(key), Compact(value))),
+                )
+                .finish(),
+            Value::Array(items) => f.debug_list().entries(items.iter().map(Compact)).finish(),
+            Value::String(text) if text.len() > MAX_STRING_LEN => {
+                let cut = (0..=MAX_STRING_LEN)
+                    .rev()
+                    .find(|&index| text.is_char_boundary(index))
+                    .unwrap_or(0);
+                write!(f, "{:?}… ({} bytes)", &text[..cut], text.len())
+            }
+            Value::String(text) => fmt::Debug::fmt(text, f),
+            value => fmt::Display::fmt(value, f),
+        }
+    }
+}
+
+/// A map key, printed without quotes.
+struct Key<'a>(&'a str);
+
+impl fmt::Debug for Key<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::streetlights;
+    use alloc::format;
+
+    #[test]
+    fn compact_document_snapshot() {
+        let spec = streetlights();
+
+        let output = format!("{:#?}\n", spec.debug_compact());
+
+        let snapshot =
+            std::fs::read_to_string("test-res/snapshots/streetlights-kafka-compact-debug.txt")
+                .unwrap();
+        assert_eq!(output, snapshot);
+    }
+
+    #[test]
+    fn summarize_large_maps() {
+        let mut spec = streetlights();
+        let channel = spec.channels["lightsDim"].clone();
+        for index in 0..5 {
+            spec.channels
+                .insert(format!("extra{index}"), channel.clone());
+        }
+        let channel = spec.channels["lightingMeasured"]
+            .resolve(&spec)
+            .unwrap()
+            .clone();
+
+        let output = format!("{:?}", spec.debug_compact());
+
+        assert!(output.contains(
+            "channels: {9 entries: extra0, extra1, extra2, extra3, extra4, lightTurnOff, lightTurnOn, lightingMeasured, lightsDim}"
+        ));
+        assert_eq!(
+            format!("{:?}", channel.debug_compact()),
+            "Channel { address: \"smartylighting.streetlights.1.0.event.{streetlightId}.lighting.m\"… (71 bytes), \
+             description: \"The topic on which measured values may be produced and consumed.\", \
+             messages: {lightMeasured: {$ref: \"#/components/messages/lightMeasured\"}}, \
+             parameters: {streetlightId: {$ref: \"#/components/parameters/streetlightId\"}} }"
+        );
+    }
+}
@@ -0,0 +1,382 @@
+//! Normalized view of the delivery semantics expressed by protocol bindings.
+//!
+//! Protocols describe persistence, expiration and quality of service in different binding
+//! fields. [DeliveryProfile] gathers them into one representation:
+//!
+//! | Protocol | Persistence       | TTL                         | QoS   | Retained | Priority      |
+//! |----------|-------------------|-----------------------------|-------|----------|---------------|
+//! | `amqp`   | `deliveryMode`    | `expiration` (ms)           |       |          | `priority`    |
+//! | `mqtt`   |                   | `messageExpiryInterval` (s) | `qos` | `retain` |               |
+//! | `jms`    | `JMSDeliveryMode` |                             |       |          | `JMSPriority` |
+//!
+//! The JMS binding describes its headers with a schema: the value of a header is the `const`,
+//! the `default` or the single `enum` value of its property in the inline `headers` schema.
+use crate::spec::common::GenericBindings;
+use crate::spec::message::Message;
+use crate::spec::operation::Operation;
+use crate::spec::AsyncApiV3Spec;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::time::Duration;
+use serde_json::Value;
+
+/// Whether messages survive a broker restart.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Persistence {
+    /// Messages are stored until consumed, e.g. AMQP `deliveryMode: 2` or JMS `PERSISTENT`.
+    Persistent,
+    /// Messages may be lost when the broker restarts, e.g. AMQP `deliveryMode: 1` or JMS
+    /// `NON_PERSISTENT`.
+    Transient,
+    /// No binding declares the persistence.
+    #[default]
+    Unknown,
+}
+
+/// The values of a [DeliveryProfile].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum DeliveryField {
+    /// [DeliveryProfile::persistence].
+    Persistence,
+    /// [DeliveryProfile::ttl].
+    Ttl,
+    /// [DeliveryProfile::qos].
+    Qos,
+    /// [DeliveryProfile::retained].
+    Retained,
+    /// [DeliveryProfile::priority].
+    Priority,
+}
+
+/// Whether a binding is declared on an operation or on a message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum BindingLevel {
+    Operation,
+    Message,
+}
+
+/// The binding a value of a [DeliveryProfile] comes from.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DeliverySource {
+    /// Whether the binding belongs to the operation or to the message.
+    pub level: BindingLevel,
+    /// Name of the protocol of the binding.
+    pub protocol: String,
+}
+
+/// Two bindings declaring different values for the same field.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DeliveryConflict {
+    pub field: DeliveryField,
+    /// The binding whose value is kept in the profile.
+    pub kept: DeliverySource,
+    /// The binding whose value is ignored.
+    pub ignored: DeliverySource,
+}
+
+/// Delivery semantics of an operation or a message, normalized across protocols.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DeliveryProfile {
+    /// Whether messages survive a broker restart.
+    pub persistence: Persistence,
+    /// How long a message is kept before expiring.
+    pub ttl: Option<Duration>,
+    /// Quality of service level, e.g. 2 for MQTT exactly once delivery.
+    pub qos: Option<u8>,
+    /// Whether the broker keeps the last message for new subscribers.
+    pub retained: Option<bool>,
+    /// Priority of the messages, from 0 (lowest) to 9 for AMQP and JMS.
+    pub priority: Option<u8>,
+    /// The binding each value of the profile comes from.
+    pub provenance: BTreeMap<DeliveryField, DeliverySource>,
+    /// Values declared differently by several bindings. Operation bindings take precedence over
+    /// message bindings, then bindings are considered in protocol name order.
+    pub conflicts: Vec<DeliveryConflict>,
+}
+
+impl DeliveryProfile {
+    fn collect(&mut self, bindings: &GenericBindings, level: BindingLevel) {
+        for (protocol, binding) in bindings {
+            let source = DeliverySource {
+                level,
+                protocol: protocol.clone(),
+            };
+            let field = |name: &str| binding.get(name).and_then(Value::as_u64);
+            match protocol.as_str() {
+                "amqp" => {
+                    let persistence = field("deliveryMode").and_then(|mode| match mode {
+                        1 => Some(Persistence::Transient),
+                        2 => Some(Persistence::Persistent),
+                        _ => None,
+                    });
+                    if let Some(persistence) = persistence {
+                        self.set_persistence(persistence, &source);
+                    }
+                    if let Some(expiration) = field("expiration") {
+                        let ttl = Duration::from_millis(expiration);
+                        self.set(DeliveryField::Ttl, ttl, &source, |p| &mut p.ttl);
+                    }
+                    if let Some(priority) = field("priority").and_then(|p| u8::try_from(p).ok()) {
+                        self.set(DeliveryField::Priority, priority, &source, |p| {
+                            &mut p.priority
+                        });
+                    }
+                }
+                "mqtt" => {
+                    if let Some(qos) = field("qos").and_then(|qos| u8::try_from(qos).ok()) {
+                        self.set(DeliveryField::Qos, qos, &source, |p| &mut p.qos);
+                    }
+                    if let Some(retain) = binding.get("retain").and_then(Value::as_bool) {
+                        self.set(DeliveryField::Retained, retain, &source, |p| {
+                            &mut p.retained
+                        });
+                    }
+                    if let Some(interval) = field("messageExpiryInterval") {
+                        let ttl = Duration::from_secs(interval);
+                        self.set(DeliveryField::Ttl, ttl, &source, |p| &mut p.ttl);
+                    }
+                }
+                "jms" => {
+                    let persistence = jms_header(binding, "JMSDeliveryMode").and_then(|mode| {
+                        match mode.as_str()? {
+                            "PERSISTENT" => Some(Persistence::Persistent),
+                            "NON_PERSISTENT" => Some(Persistence::Transient),
+                            _ => None,
+                        }
+                    });
+                    if let Some(persistence) = persistence {
+                        self.set_persistence(persistence, &source);
+                    }
+                    let priority = jms_header(binding, "JMSPriority")
+                        .and_then(Value::as_u64)
+                        .and_then(|priority| u8::try_from(priority).ok());
+                    if let Some(priority) = priority {
+                        self.set(DeliveryField::Priority, priority, &source, |p| {
+                            &mut p.priority
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn set<T: PartialEq>(
+        &mut self,
+        field: DeliveryField,
+        value: T,
+        source: &DeliverySource,
+        slot: fn(&mut Self) -> &mut Option<T>,
+    ) {
+        let differs = slot(self).as_ref().is_some_and(|current| *current != value);
+        if self.record(field, source, differs) {
+            *slot(self) = Some(value);
+        }
+    }
+
+    fn set_persistence(&mut self, persistence: Persistence, source: &DeliverySource) {
+        let differs = self.persistence != Persistence::Unknown && self.persistence != persistence;
+        if self.record(DeliveryField::Persistence, source, differs) {
+            self.persistence = persistence;
+        }
+    }
+
+    /// Records the source of a value, returning `true` if the value is the first one declared.
+    fn record(&mut self, field: DeliveryField, source: &DeliverySource, differs: bool) -> bool {
+        match self.provenance.get(&field) {
+            None => {
+                self.provenance.insert(field, source.clone());
+                true
+            }
+            Some(kept) => {
+                if differs {
+                    self.conflicts.push(DeliveryConflict {
+                        field,
+                        kept: kept.clone(),
+                        ignored: source.clone(),
+                    });
+                }
+                false
+            }
+        }
+    }
+}
+
+/// Returns the value of a header of a JMS binding, fixed by its schema in `headers`.
+fn jms_header<'a>(binding: &'a Value, name: &str) -> Option<&'a Value> {
+    let schema = binding.get("headers")?.get("properties")?.get(name)?;
+    schema
+        .get("const")
+        .or_else(|| schema.get("default"))
+        .or_else(|| match schema.get("enum")?.as_array()?.as_slice() {
+            [value] => Some(value),
+            _ => None,
+        })
+}
+
+impl Message {
+    /// Returns the delivery semantics declared by the bindings of the message.
+    pub fn delivery_profile(&self, spec: &AsyncApiV3Spec) -> DeliveryProfile {
+        let mut profile = DeliveryProfile::default();
+        collect_message(&mut profile, self, spec);
+        profile
+    }
+}
+
+impl Operation {
+    /// Returns the delivery semantics declared by the bindings of the operation and of its
+    /// messages.
+    pub fn delivery_profile(&self, spec: &AsyncApiV3Spec) -> DeliveryProfile {
+        let mut profile = DeliveryProfile::default();
+        if let Some(bindings) = self
+            .bindings
+            .as_ref()
+            .and_then(|bindings| bindings.resolve(spec).ok())
+        {
+            profile.collect(&bindings.as_generic(), BindingLevel::Operation);
+        }
+        for message in self.resolved_messages(spec) {
+            collect_message(&mut profile, message, spec);
+        }
+        profile
+    }
+}
+
+fn collect_message(profile: &mut DeliveryProfile, message: &Message, spec: &AsyncApiV3Spec) {
+    if let Some(bindings) = message
+        .bindings
+        .as_ref()
+        .and_then(|bindings| bindings.resolve(spec).ok())
+    {
+        profile.collect(&bindings.as_generic(), BindingLevel::Message);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::spec::common::RefOr;
+    use crate::test_util::parse_spec;
+
+    const SPEC: &str = r#"
+asyncapi: 3.0.0
+info:
+  title: Orders
+  version: 1.0.0
+channels:
+  orders:
+    address: orders
+    messages:
+      orderPlaced:
+        bindings:
+          amqp:
+            deliveryMode: 2
+            expiration: 60000
+      lightMeasured:
+        bindings:
+          mqtt:
+            qos: 2
+            retain: true
+      orderShipped:
+        bindings:
+          jms:
+            headers:
+              type: object
+              properties:
+                JMSDeliveryMode:
+                  type: string
+                  enum: [PERSISTENT, NON_PERSISTENT]
+                  default: PERSISTENT
+                JMSPriority:
+                  type: integer
+                  const: 7
+operations:
+  placeOrder:
+    action: send
+    channel:
+      $ref: '#/channels/orders'
+    messages:
+      - $ref: '#/channels/orders/messages/orderPlaced'
+    bindings:
+      amqp:
+        deliveryMode: 1
+        priority: 5
+"#;
+
+    fn message<'a>(spec: &'a AsyncApiV3Spec, name: &str) -> &'a Message {
+        let channel = spec.channels["orders"].resolve(spec).unwrap();
+        channel.messages[name].resolve(spec).unwrap()
+    }
+
+    #[test]
+    fn amqp_persistent_message() {
+        let spec = parse_spec(SPEC);
+
+        let profile = message(&spec, "orderPlaced").delivery_profile(&spec);
+
+        assert_eq!(profile.persistence, Persistence::Persistent);
+        assert_eq!(profile.ttl, Some(Duration::from_secs(60)));
+        assert_eq!(profile.qos, None);
+        assert_eq!(
+            profile.provenance[&DeliveryField::Persistence],
+            DeliverySource {
+                level: BindingLevel::Message,
+                protocol: String::from("amqp")
+            }
+        );
+        assert!(profile.conflicts.is_empty());
+    }
+
+    #[test]
+    fn mqtt_retained_message() {
+        let spec = parse_spec(SPEC);
+
+        let profile = message(&spec, "lightMeasured").delivery_profile(&spec);
+
+        assert_eq!(profile.persistence, Persistence::Unknown);
+        assert_eq!(profile.qos, Some(2));
+        assert_eq!(profile.retained, Some(true));
+        assert_eq!(profile.ttl, None);
+    }
+
+    #[test]
+    fn jms_headers() {
+        let spec = parse_spec(SPEC);
+
+        let profile = message(&spec, "orderShipped").delivery_profile(&spec);
+
+        assert_eq!(profile.persistence, Persistence::Persistent);
+        assert_eq!(profile.priority, Some(7));
+        assert_eq!(profile.provenance[&DeliveryField::Priority].protocol, "jms");
+        assert_eq!(profile.ttl, None);
+    }
+
+    #[test]
+    fn conflict_between_operation_and_message() {
+        let spec = parse_spec(SPEC);
+        let RefOr::Right(operation) = &spec.operations["placeOrder"] else {
+            panic!("inline operation expected");
+        };
+
+        let profile = operation.delivery_profile(&spec);
+
+        assert_eq!(profile.persistence, Persistence::Transient);
+        assert_eq!(profile.priority, Some(5));
+        assert_eq!(profile.ttl, Some(Duration::from_secs(60)));
+        assert_eq!(
+            profile.conflicts,
+            vec![DeliveryConflict {
+                field: DeliveryField::Persistence,
+                kept: DeliverySource {
+                    level: BindingLevel::Operation,
+                    protocol: String::from("amqp")
+                },
+                ignored: DeliverySource {
+                    level: BindingLevel::Message,
+                    protocol: String::from("amqp")
+                },
+            }]
+        );
+    }
+}
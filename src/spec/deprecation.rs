@@ -0,0 +1,234 @@
+//! Deprecation of the elements of a document.
+//!
+//! Schemas declare their deprecation with the `deprecated` keyword of JSON schema. Channels,
+//! messages and operations have no such field in the specification and declare it with the
+//! [DEPRECATED_EXTENSION] instead. Both can announce the date after which the element is
+//! removed with the [SUNSET_EXTENSION].
+use crate::spec::channel::Channel;
+use crate::spec::common::{Either, Extensions, JsonPointer, RefOr};
+use crate::spec::message::Message;
+use crate::spec::operation::Operation;
+use crate::spec::AsyncApiV3Spec;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde_json::Value;
+
+/// Extension marking a channel, a message or an operation as deprecated when set to `true`.
+pub const DEPRECATED_EXTENSION: &str = "x-deprecated";
+
+/// Extension holding the date, e.g. `2025-12-31`, after which a deprecated element is removed.
+pub const SUNSET_EXTENSION: &str = "x-sunset";
+
+/// How the deprecation of an element is declared.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum DeprecationSource {
+    /// The `deprecated` keyword of a schema.
+    Keyword,
+    /// The [DEPRECATED_EXTENSION].
+    Extension,
+}
+
+/// A deprecated element of a document, see [AsyncApiV3Spec::deprecations].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Deprecation {
+    /// Location of the element.
+    pub pointer: JsonPointer,
+    pub source: DeprecationSource,
+    /// The [sunset date](SUNSET_EXTENSION) of the element.
+    pub sunset: Option<String>,
+}
+
+impl Extensions {
+    /// Returns `true` if the [DEPRECATED_EXTENSION] is set to `true`.
+    pub fn deprecated(&self) -> bool {
+        self.get(DEPRECATED_EXTENSION)
+            .and_then(Value::as_bool)
+            .unwrap_or(false)
+    }
+
+    /// Returns the [SUNSET_EXTENSION], if it is a string.
+    pub fn sunset(&self) -> Option<&str> {
+        self.get(SUNSET_EXTENSION).and_then(Value::as_str)
+    }
+}
+
+impl Channel {
+    /// Returns `true` if the channel is marked with the [DEPRECATED_EXTENSION].
+    pub fn is_deprecated(&self) -> bool {
+        self.extensions.deprecated()
+    }
+}
+
+impl Message {
+    /// Returns `true` if the message is marked with the [DEPRECATED_EXTENSION].
+    pub fn is_deprecated(&self) -> bool {
+        self.extensions.deprecated()
+    }
+}
+
+impl Operation {
+    /// Returns `true` if the operation is marked with the [DEPRECATED_EXTENSION].
+    pub fn is_deprecated(&self) -> bool {
+        self.extensions.deprecated()
+    }
+}
+
+impl AsyncApiV3Spec {
+    /// Lists the deprecated channels, messages, operations and component schemas, in document
+    /// order. Elements are reported where they are defined, not where they are referenced.
+    pub fn deprecations(&self) -> Vec<Deprecation> {
+        let mut deprecations = Vec::new();
+        let root = JsonPointer::default();
+        let components = root.join("components");
+        for (section, channels) in [
+            (&root, &self.channels),
+            (&components, &self.components.channels),
+        ] {
+            let section = section.join("channels");
+            for (name, channel) in inline(channels) {
+                let pointer = section.join(name.as_str());
+                push_extension(&channel.extensions, &pointer, &mut deprecations);
+                let messages = pointer.join("messages");
+                for (name, message) in inline(&channel.messages) {
+                    let pointer = messages.join(name.as_str());
+                    push_extension(&message.extensions, &pointer, &mut deprecations);
+                }
+            }
+        }
+        let messages = components.join("messages");
+        for (name, message) in inline(&self.components.messages) {
+            let pointer = messages.join(name.as_str());
+            push_extension(&message.extensions, &pointer, &mut deprecations);
+        }
+        for (section, operations) in [
+            (&root, &self.operations),
+            (&components, &self.components.operations),
+        ] {
+            let section = section.join("operations");
+            for (name, operation) in inline(operations) {
+                let pointer = section.join(name.as_str());
+                push_extension(&operation.extensions, &pointer, &mut deprecations);
+            }
+        }
+        let schemas = components.join("schemas");
+        for (name, schema) in inline(&self.components.schemas) {
+            let Either::Left(schema) = schema else {
+                continue;
+            };
+            if schema.get("deprecated").and_then(Value::as_bool) == Some(true) {
+                deprecations.push(Deprecation {
+                    pointer: schemas.join(name.as_str()),
+                    source: DeprecationSource::Keyword,
+                    sunset: schema
+                        .get(SUNSET_EXTENSION)
+                        .and_then(Value::as_str)
+                        .map(String::from),
+                });
+            }
+        }
+        deprecations
+    }
+}
+
+/// Returns the entries of a section defined in place, leaving out references.
+fn inline<T>(entries: &BTreeMap<String, RefOr<T>>) -> impl Iterator<Item = (&String, &T)> {
+    entries.iter().filter_map(|(name, entry)| match entry {
+        Either::Right(entry) => Some((name, entry)),
+        Either::Left(_) => None,
+    })
+}
+
+fn push_extension(
+    extensions: &Extensions,
+    pointer: &JsonPointer,
+    deprecations: &mut Vec<Deprecation>,
+) {
+    if extensions.deprecated() {
+        deprecations.push(Deprecation {
+            pointer: pointer.clone(),
+            source: DeprecationSource::Extension,
+            sunset: extensions.sunset().map(String::from),
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::parse_spec;
+    use alloc::string::ToString;
+
+    #[test]
+    fn list_keyword_and_extension_deprecations() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Orders
+  version: 1.0.0
+channels:
+  legacyOrders:
+    address: orders.v1
+    x-deprecated: true
+    x-sunset: '2025-12-31'
+    messages:
+      orderPlaced:
+        $ref: '#/components/messages/orderPlaced'
+operations:
+  placeOrder:
+    action: send
+    x-deprecated: false
+    channel:
+      $ref: '#/channels/legacyOrders'
+components:
+  messages:
+    orderPlaced:
+      x-deprecated: true
+      payload:
+        $ref: '#/components/schemas/order'
+  schemas:
+    order:
+      type: object
+      deprecated: true
+"#,
+        );
+
+        let deprecations = spec
+            .deprecations()
+            .into_iter()
+            .map(|deprecation| {
+                (
+                    deprecation.pointer.to_string(),
+                    deprecation.source,
+                    deprecation.sunset,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            deprecations,
+            [
+                (
+                    String::from("/channels/legacyOrders"),
+                    DeprecationSource::Extension,
+                    Some(String::from("2025-12-31"))
+                ),
+                (
+                    String::from("/components/messages/orderPlaced"),
+                    DeprecationSource::Extension,
+                    None
+                ),
+                (
+                    String::from("/components/schemas/order"),
+                    DeprecationSource::Keyword,
+                    None
+                ),
+            ]
+        );
+        assert!(!spec.operations["placeOrder"]
+            .resolve(&spec)
+            .unwrap()
+            .is_deprecated());
+    }
+}
@@ -0,0 +1,378 @@
+//! The effective document, where the values implied by the specification are written out:
+//! traits applied, content types inherited, defaulted channel servers and operation messages
+//! listed and unknown channel addresses written as `null`.
+use crate::error::Error;
+use crate::spec::channel::Channel;
+use crate::spec::common::{JsonPointer, RefOr, ReferenceObject};
+use crate::spec::resolve::Referenceable;
+use crate::spec::AsyncApiV3Spec;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use serde::Serialize;
+use serde_json::Value;
+
+/// How a value of the effective document was obtained.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Mechanism {
+    /// Inherited from a field of an enclosing object, e.g. the `defaultContentType`.
+    Inherited,
+    /// Supplied by a trait.
+    Trait,
+    /// Written out from the default the specification gives to an absent or empty field.
+    Defaulted,
+}
+
+/// Where a value of the effective document comes from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Provenance {
+    /// Location of the value in the original document the value was obtained from.
+    pub source: JsonPointer,
+    pub mechanism: Mechanism,
+}
+
+/// The provenance of the values of an effective document which are not written in the
+/// original document, by their location in the effective document.
+pub type ProvenanceMap = BTreeMap<JsonPointer, Provenance>;
+
+impl AsyncApiV3Spec {
+    /// Returns the effective document, see [AsyncApiV3Spec::effective_with_provenance].
+    pub fn effective(&self) -> Result<AsyncApiV3Spec, Error> {
+        self.effective_with_provenance()
+            .map(|(effective, _)| effective)
+    }
+
+    /// Returns the effective document, along with the provenance of the values it adds:
+    ///
+    /// - the traits of the messages and operations defined in place are
+    ///   [applied](crate::spec::message::Message::apply_traits),
+    /// - messages without content type inherit the `defaultContentType`,
+    /// - root channels without servers list every root server,
+    /// - operations without messages list every message of their channel,
+    /// - channels without address get a `null` address: both mean the address is unknown, e.g.
+    ///   generated at runtime.
+    ///
+    /// References are kept as written. Fails with the error of a trait which cannot be applied.
+    pub fn effective_with_provenance(&self) -> Result<(AsyncApiV3Spec, ProvenanceMap), Error> {
+        let mut document = to_value(self)?;
+        let mut provenance = ProvenanceMap::new();
+
+        for (pointer, message) in self.defined_messages() {
+            let mut effective = message.apply_traits(self)?;
+            record_traits(self, message, &message.traits, &pointer, &mut provenance)?;
+            if effective.content_type.is_none() {
+                if let Some(content_type) = &self.default_content_type {
+                    effective.content_type = Some(content_type.clone());
+                    provenance.insert(
+                        pointer.join("contentType"),
+                        Provenance {
+                            source: JsonPointer::from_iter(["defaultContentType"]),
+                            mechanism: Mechanism::Inherited,
+                        },
+                    );
+                }
+            }
+            replace(&mut document, &pointer, to_value(&effective)?);
+        }
+
+        let root = JsonPointer::default();
+        let components = root.join("components");
+        for (section, operations) in [
+            (&root, &self.operations),
+            (&components, &self.components.operations),
+        ] {
+            for (name, operation) in operations {
+                let RefOr::Right(operation) = operation else {
+                    continue;
+                };
+                let pointer = section.join("operations").join(name.as_str());
+                let mut effective = operation.apply_traits(self)?;
+                record_traits(
+                    self,
+                    operation,
+                    &operation.traits,
+                    &pointer,
+                    &mut provenance,
+                )?;
+                let lists_messages = effective
+                    .messages
+                    .as_ref()
+                    .is_some_and(|messages| !messages.is_empty());
+                let channel = operation.channel.local_pointer();
+                if let (false, Some(channel)) = (lists_messages, channel) {
+                    if let Ok(resolved) = operation.channel.resolve::<Channel>(self) {
+                        let messages = channel.join("messages");
+                        effective.messages = Some(
+                            resolved
+                                .messages
+                                .keys()
+                                .map(|key| {
+                                    ReferenceObject::new(format!(
+                                        "#{}",
+                                        messages.join(key.as_str())
+                                    ))
+                                })
+                                .collect(),
+                        );
+                        provenance.insert(
+                            pointer.join("messages"),
+                            Provenance {
+                                source: messages,
+                                mechanism: Mechanism::Defaulted,
+                            },
+                        );
+                    }
+                }
+                replace(&mut document, &pointer, to_value(&effective)?);
+            }
+        }
+
+        if !self.servers.is_empty() {
+            let servers = self
+                .servers
+                .keys()
+                .map(|name| {
+                    ReferenceObject::new(format!("#{}", root.join("servers").join(name.as_str())))
+                })
+                .collect::<Vec<_>>();
+            for (name, channel) in &self.channels {
+                let RefOr::Right(channel) = channel else {
+                    continue;
+                };
+                if !channel.servers.is_empty() {
+                    continue;
+                }
+                let pointer = root.join("channels").join(name.as_str()).join("servers");
+                replace(&mut document, &pointer, to_value(&servers)?);
+                provenance.insert(
+                    pointer,
+                    Provenance {
+                        source: root.join("servers"),
+                        mechanism: Mechanism::Defaulted,
+                    },
+                );
+            }
+        }
+
+        for (section, channels) in [
+            (&root, &self.channels),
+            (&components, &self.components.channels),
+        ] {
+            for (name, channel) in channels {
+                let RefOr::Right(channel) = channel else {
+                    continue;
+                };
+                if !channel.address.is_absent() {
+                    continue;
+                }
+                let pointer = section.join("channels").join(name.as_str());
+                if let Some(Value::Object(fields)) = pointer.evaluate_mut(&mut document) {
+                    fields.insert(String::from("address"), Value::Null);
+                    provenance.insert(
+                        pointer.join("address"),
+                        Provenance {
+                            source: pointer,
+                            mechanism: Mechanism::Defaulted,
+                        },
+                    );
+                }
+            }
+        }
+
+        let effective = serde_json::from_value(document).map_err(invalid_document)?;
+        Ok((effective, provenance))
+    }
+}
+
+fn invalid_document(err: serde_json::Error) -> Error {
+    Error::InvalidDocument {
+        reason: err.to_string(),
+        hints: Vec::new(),
+    }
+}
+
+fn to_value<T: Serialize>(value: &T) -> Result<Value, Error> {
+    serde_json::to_value(value).map_err(invalid_document)
+}
+
+/// Replaces the value at a pointer, if any.
+fn replace(document: &mut Value, pointer: &JsonPointer, value: Value) {
+    if let Some(target) = pointer.evaluate_mut(document) {
+        *target = value;
+    }
+}
+
+/// Records the fields an object at `pointer` takes from its traits, the last trait supplying a
+/// field winning, as when traits are applied.
+fn record_traits<O: Serialize, T: Serialize + Referenceable>(
+    spec: &AsyncApiV3Spec,
+    object: &O,
+    traits: &[RefOr<T>],
+    pointer: &JsonPointer,
+    provenance: &mut ProvenanceMap,
+) -> Result<(), Error> {
+    let own = to_value(object)?;
+    let is_set = |value: &Value| match value {
+        Value::Null => false,
+        Value::Array(items) => !items.is_empty(),
+        _ => true,
+    };
+    for (index, object_trait) in traits.iter().enumerate() {
+        let inline = pointer.join("traits").join(format!("{index}"));
+        let source = match object_trait {
+            RefOr::Left(reference) => reference.local_pointer().unwrap_or(inline),
+            RefOr::Right(_) => inline,
+        };
+        let Value::Object(fields) = to_value(object_trait.resolve(spec)?)? else {
+            continue;
+        };
+        for (field, value) in fields {
+            if is_set(&value) && !own.get(&field).is_some_and(is_set) {
+                provenance.insert(
+                    pointer.join(field.as_str()),
+                    Provenance {
+                        source: source.join(field),
+                        mechanism: Mechanism::Trait,
+                    },
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::parse_spec;
+
+    #[test]
+    fn provenance_of_added_values() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Orders
+  version: 1.0.0
+defaultContentType: application/json
+servers:
+  production:
+    host: broker.example.com
+    protocol: kafka
+channels:
+  orders:
+    address: orders
+    messages:
+      orderPlaced:
+        traits:
+          - $ref: '#/components/messageTraits/audited'
+operations:
+  placeOrder:
+    action: send
+    channel:
+      $ref: '#/channels/orders'
+components:
+  messageTraits:
+    audited:
+      summary: An audited event.
+"#,
+        );
+        let pointer = |pointer: &str| JsonPointer::parse(pointer).unwrap();
+        let provenance = |source: &str, mechanism| Provenance {
+            source: pointer(source),
+            mechanism,
+        };
+
+        let (effective, provenance_map) = spec.effective_with_provenance().unwrap();
+
+        assert_eq!(
+            provenance_map,
+            ProvenanceMap::from([
+                (
+                    pointer("/channels/orders/messages/orderPlaced/contentType"),
+                    provenance("/defaultContentType", Mechanism::Inherited),
+                ),
+                (
+                    pointer("/channels/orders/messages/orderPlaced/summary"),
+                    provenance(
+                        "/components/messageTraits/audited/summary",
+                        Mechanism::Trait
+                    ),
+                ),
+                (
+                    pointer("/channels/orders/servers"),
+                    provenance("/servers", Mechanism::Defaulted),
+                ),
+                (
+                    pointer("/operations/placeOrder/messages"),
+                    provenance("/channels/orders/messages", Mechanism::Defaulted),
+                ),
+            ])
+        );
+        let document = serde_json::to_value(&effective).unwrap();
+        for location in provenance_map.keys() {
+            assert!(location.evaluate(&document).is_some(), "{location}");
+        }
+        assert_eq!(
+            document["channels"]["orders"]["messages"]["orderPlaced"]["summary"],
+            "An audited event."
+        );
+        assert_eq!(
+            document["channels"]["orders"]["servers"][0]["$ref"],
+            "#/servers/production"
+        );
+    }
+
+    #[test]
+    fn absent_and_null_addresses_are_unknown() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Lights
+  version: 1.0.0
+channels:
+  dynamic:
+    address: null
+    messages: {}
+  implicit:
+    messages: {}
+components:
+  channels:
+    shared:
+      messages: {}
+"#,
+        );
+
+        let (effective, provenance) = spec.effective_with_provenance().unwrap();
+
+        for section in [&effective.channels, &effective.components.channels] {
+            for channel in section.values() {
+                assert!(channel.resolve(&effective).unwrap().address.is_null());
+            }
+        }
+        assert_eq!(
+            provenance
+                .keys()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            [
+                "/channels/implicit/address",
+                "/components/channels/shared/address"
+            ]
+        );
+        assert_eq!(
+            provenance[&JsonPointer::parse("/channels/implicit/address").unwrap()],
+            Provenance {
+                source: JsonPointer::parse("/channels/implicit").unwrap(),
+                mechanism: Mechanism::Defaulted,
+            }
+        );
+        let document = serde_json::to_value(&effective).unwrap();
+        assert_eq!(
+            document["channels"]["dynamic"].get("address"),
+            Some(&Value::Null)
+        );
+    }
+}
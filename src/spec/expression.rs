@@ -0,0 +1,72 @@
+//! Parsing and evaluation of [runtime
+//! expressions](https://www.asyncapi.com/docs/reference/specification/v3.0.0#runtimeExpression)
+//! such as `$message.header#/replyTo`.
+use crate::error::Error;
+use crate::spec::common::JsonPointer;
+use alloc::string::String;
+use core::fmt;
+use core::str::FromStr;
+use serde_json::Value;
+
+/// The part of a message a runtime expression is evaluated against.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum MessageSource {
+    /// The message headers, `$message.header`.
+    Header,
+    /// The message payload, `$message.payload`.
+    Payload,
+}
+
+/// A parsed runtime expression pointing at a value inside a message.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RuntimeExpression {
+    /// The part of the message the pointer is applied to.
+    pub source: MessageSource,
+    /// The location of the value inside the source. Empty when the whole source is addressed.
+    pub pointer: JsonPointer,
+}
+
+impl RuntimeExpression {
+    /// Evaluates the expression against a concrete message, represented as a JSON object with
+    /// optional `headers` and `payload` fields (the same shape used by message examples).
+    pub fn evaluate<'a>(&self, message: &'a Value) -> Option<&'a Value> {
+        let source = match self.source {
+            MessageSource::Header => message.get("headers"),
+            MessageSource::Payload => message.get("payload"),
+        }?;
+        self.pointer.evaluate(source)
+    }
+}
+
+impl FromStr for RuntimeExpression {
+    type Err = Error;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::InvalidRuntimeExpression {
+            expression: String::from(expression),
+        };
+        let rest = expression.strip_prefix("$message.").ok_or_else(invalid)?;
+        let (source, fragment) = rest.split_once('#').unwrap_or((rest, ""));
+        let source = match source {
+            "header" => MessageSource::Header,
+            "payload" => MessageSource::Payload,
+            _ => return Err(invalid()),
+        };
+        let pointer = JsonPointer::parse(fragment).map_err(|_| invalid())?;
+        Ok(Self { source, pointer })
+    }
+}
+
+impl fmt::Display for RuntimeExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let source = match self.source {
+            MessageSource::Header => "header",
+            MessageSource::Payload => "payload",
+        };
+        write!(f, "$message.{source}")?;
+        if !self.pointer.segments().is_empty() {
+            write!(f, "#{}", self.pointer)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,512 @@
+//! Extraction of the part of a document carrying a given tag.
+//!
+//! The extraction works on the JSON representation of the document: the tagged operations,
+//! channels and channel messages are kept together with everything they reference, directly or
+//! through other references, and the rest of the document is dropped.
+use crate::error::Error;
+use crate::spec::common::{collect_references, local_pointer, JsonPointer};
+use crate::spec::AsyncApiV3Spec;
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// Maximum number of references followed when looking up the tags of an object.
+const MAX_REF_HOPS: usize = 16;
+
+impl AsyncApiV3Spec {
+    /// Returns a document restricted to the elements tagged with `tag`.
+    ///
+    /// The operations, channels and channel messages carrying the tag are kept, along with the
+    /// channels of the kept operations and messages, every message of the kept channels, the
+    /// servers of the kept channels (all of them for channels available everywhere) and the
+    /// components they transitively reference.
+    /// Everything else is dropped, and the info description mentions the extraction.
+    ///
+    /// Fails if a kept element references a local object which does not exist.
+    pub fn extract_by_tag(&self, tag: &str) -> Result<AsyncApiV3Spec, Error> {
+        self.restrict_to_tag(tag, true)
+    }
+
+    /// Returns a document restricted to the elements tagged with `tag`, e.g. to document the
+    /// part of an API meant for one audience.
+    ///
+    /// Elements are kept as by [extract_by_tag](Self::extract_by_tag), but references which
+    /// cannot be resolved are left as they are and the document info is unchanged. Fails if the
+    /// document cannot be converted to JSON and back.
+    pub fn filter_by_tag(&self, tag: &str) -> Result<AsyncApiV3Spec, Error> {
+        self.restrict_to_tag(tag, false)
+    }
+
+    fn restrict_to_tag(&self, tag: &str, strict: bool) -> Result<AsyncApiV3Spec, Error> {
+        let source = to_value(self)?;
+        let mut extraction = Extraction::new(&source, strict);
+        extraction.seed(tag)?;
+        extraction.run()?;
+
+        let output = extraction.output(strict.then_some(tag));
+        AsyncApiV3Spec::deserialize(&output).map_err(|err| Error::InvalidDocument {
+            reason: err.to_string(),
+            hints: Vec::new(),
+        })
+    }
+}
+
+fn to_value(spec: &AsyncApiV3Spec) -> Result<Value, Error> {
+    serde_json::to_value(spec).map_err(|err| Error::InvalidDocument {
+        reason: err.to_string(),
+        hints: Vec::new(),
+    })
+}
+
+struct Extraction<'a> {
+    source: &'a Value,
+    /// Whether references which cannot be resolved are errors.
+    strict: bool,
+    /// Pointers of the kept entities, see [entity_of].
+    kept: BTreeSet<JsonPointer>,
+    pending: Vec<JsonPointer>,
+}
+
+impl<'a> Extraction<'a> {
+    fn new(source: &'a Value, strict: bool) -> Self {
+        Self {
+            source,
+            strict,
+            kept: BTreeSet::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    fn seed(&mut self, tag: &str) -> Result<(), Error> {
+        let root = JsonPointer::default();
+        for (key, operation) in entries(self.source, "operations") {
+            if self.carries_tag(operation, tag) {
+                self.keep(root.join("operations").join(key));
+            }
+        }
+        for (key, channel) in entries(self.source, "channels") {
+            let channel_pointer = root.join("channels").join(key);
+            if self.carries_tag(channel, tag) {
+                self.keep(channel_pointer.clone());
+            }
+            for (name, message) in entries(channel, "messages") {
+                if self.carries_tag(message, tag) {
+                    self.keep(channel_pointer.join("messages").join(name));
+                }
+            }
+        }
+        if let Some(info) = self.source.get("info") {
+            self.keep_references(info)?;
+        }
+        Ok(())
+    }
+
+    fn run(&mut self) -> Result<(), Error> {
+        while let Some(pointer) = self.pending.pop() {
+            let Some(value) = pointer.evaluate(self.source) else {
+                continue;
+            };
+            match pointer.segments() {
+                [channels, key, messages, _]
+                    if channels == "channels" && messages == "messages" =>
+                {
+                    self.keep(JsonPointer::from_iter(["channels", key.as_str()]));
+                    self.keep_references(value)?;
+                }
+                [channels, _] if channels == "channels" => {
+                    for (name, _) in entries(value, "messages") {
+                        self.keep(pointer.join("messages").join(name));
+                    }
+                    let servers = value.get("servers").and_then(Value::as_array);
+                    if servers.is_none_or(Vec::is_empty) {
+                        for (key, _) in entries(self.source, "servers") {
+                            self.keep(JsonPointer::from_iter(["servers", key]));
+                        }
+                    }
+                    if let Value::Object(fields) = value {
+                        for (field, value) in fields {
+                            if field != "messages" {
+                                self.keep_references(value)?;
+                            }
+                        }
+                    }
+                }
+                _ => self.keep_references(value)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Keeps the entities referenced anywhere inside a value.
+    fn keep_references(&mut self, value: &Value) -> Result<(), Error> {
+        for (_, reference) in collect_references(value, &JsonPointer::default()) {
+            let Some(pointer) = local_pointer(reference) else {
+                continue;
+            };
+            if self.strict && pointer.evaluate(self.source).is_none() {
+                return Err(Error::UnresolvedReference {
+                    reference: String::from(reference),
+                });
+            }
+            if let Some(entity) = entity_of(&pointer) {
+                self.keep(entity);
+            }
+        }
+        Ok(())
+    }
+
+    fn keep(&mut self, pointer: JsonPointer) {
+        if self.kept.insert(pointer.clone()) {
+            self.pending.push(pointer);
+        }
+    }
+
+    fn carries_tag(&self, value: &Value, tag: &str) -> bool {
+        let has_name = |tag_value: &Value| {
+            self.follow(tag_value)
+                .and_then(|tag_value| tag_value.get("name"))
+                .and_then(Value::as_str)
+                == Some(tag)
+        };
+        self.follow(value)
+            .and_then(|value| value.get("tags"))
+            .and_then(Value::as_array)
+            .is_some_and(|tags| tags.iter().any(has_name))
+    }
+
+    /// Follows the references of a value until reaching an object which is not a reference.
+    fn follow(&self, mut value: &'a Value) -> Option<&'a Value> {
+        for _ in 0..MAX_REF_HOPS {
+            match value.get("$ref").and_then(Value::as_str) {
+                Some(reference) => value = local_pointer(reference)?.evaluate(self.source)?,
+                None => return Some(value),
+            }
+        }
+        None
+    }
+
+    /// Returns the kept part of the document, noting the extracted tag in the info description
+    /// if given.
+    fn output(&self, tag: Option<&str>) -> Value {
+        let mut output = Map::new();
+        let Value::Object(fields) = self.source else {
+            return Value::Object(output);
+        };
+        for (key, value) in fields {
+            let value = match key.as_str() {
+                "info" => {
+                    let mut info = value.clone();
+                    if let Some(tag) = tag {
+                        let note = format!("Restricted to the elements tagged `{tag}`.");
+                        let description = match info.get("description").and_then(Value::as_str) {
+                            Some(description) => format!("{description}\n\n{note}"),
+                            None => note,
+                        };
+                        info["description"] = Value::String(description);
+                    }
+                    info
+                }
+                "servers" | "operations" => {
+                    self.kept_entries(value, &JsonPointer::from_iter([key]))
+                }
+                "channels" => {
+                    let mut channels = self.kept_entries(value, &JsonPointer::from_iter([key]));
+                    if let Value::Object(channels) = &mut channels {
+                        for (name, channel) in channels.iter_mut() {
+                            if let Some(messages) = channel.get_mut("messages") {
+                                let pointer =
+                                    JsonPointer::from_iter(["channels", name, "messages"]);
+                                *messages = self.kept_entries(messages, &pointer);
+                            }
+                        }
+                    }
+                    channels
+                }
+                "components" => {
+                    let mut components = Map::new();
+                    for (section, entries) in value.as_object().into_iter().flatten() {
+                        let pointer = JsonPointer::from_iter(["components", section]);
+                        let entries = self.kept_entries(entries, &pointer);
+                        if entries
+                            .as_object()
+                            .is_some_and(|entries| !entries.is_empty())
+                        {
+                            components.insert(section.clone(), entries);
+                        }
+                    }
+                    Value::Object(components)
+                }
+                _ => value.clone(),
+            };
+            output.insert(key.clone(), value);
+        }
+        Value::Object(output)
+    }
+
+    /// Returns the entries of a map whose pointer, under `parent`, is kept.
+    fn kept_entries(&self, map: &Value, parent: &JsonPointer) -> Value {
+        let entries = map
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter(|(key, _)| self.kept.contains(&parent.join(key.as_str())))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        Value::Object(entries)
+    }
+}
+
+/// Returns the pointer of the entity containing the addressed value: a root server, channel or
+/// operation, a message of a root channel or a component. Values outside of those (e.g. the
+/// info object) are always kept.
+fn entity_of(pointer: &JsonPointer) -> Option<JsonPointer> {
+    let segments = pointer.segments();
+    let length = match segments {
+        [channels, _, messages, _, ..] if channels == "channels" && messages == "messages" => 4,
+        [root, _, ..] if matches!(root.as_str(), "servers" | "channels" | "operations") => 2,
+        [components, _, _, ..] if components == "components" => 3,
+        _ => return None,
+    };
+    Some(segments[..length].iter().cloned().collect())
+}
+
+fn entries<'v>(value: &'v Value, field: &str) -> impl Iterator<Item = (&'v str, &'v Value)> {
+    value
+        .get(field)
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .map(|(key, value)| (key.as_str(), value))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::parse_spec;
+    use alloc::collections::BTreeMap;
+
+    const MIXED_SPEC: &str = r#"
+asyncapi: 3.0.0
+info:
+  title: Accounts
+  version: 1.0.0
+  description: Account events.
+servers:
+  public:
+    host: broker.example.com
+    protocol: kafka-secure
+    security:
+      - $ref: '#/components/securitySchemes/scram'
+  internal:
+    host: broker.internal
+    protocol: kafka
+channels:
+  userSignedUp:
+    address: user.signedup
+    servers:
+      - $ref: '#/servers/public'
+    messages:
+      userSignedUp:
+        $ref: '#/components/messages/userSignedUp'
+      userSignedUpDebug:
+        $ref: '#/components/messages/debug'
+  audit:
+    address: audit
+    servers:
+      - $ref: '#/servers/internal'
+    messages:
+      auditEntry:
+        $ref: '#/components/messages/auditEntry'
+  broadcast:
+    address: broadcast
+    tags:
+      - name: public
+    messages: {}
+operations:
+  onUserSignedUp:
+    action: receive
+    channel:
+      $ref: '#/channels/userSignedUp'
+    messages:
+      - $ref: '#/channels/userSignedUp/messages/userSignedUp'
+    tags:
+      - name: public
+  onAudit:
+    action: receive
+    channel:
+      $ref: '#/channels/audit'
+    tags:
+      - name: internal
+components:
+  messages:
+    userSignedUp:
+      payload:
+        $ref: '#/components/schemas/user'
+    debug:
+      payload:
+        $ref: '#/components/schemas/debug'
+    auditEntry:
+      payload:
+        $ref: '#/components/schemas/auditEntry'
+  schemas:
+    user:
+      type: object
+      properties:
+        address:
+          $ref: '#/components/schemas/address'
+    address:
+      type: string
+    debug:
+      type: object
+    auditEntry:
+      type: object
+  securitySchemes:
+    scram:
+      type: scramSha256
+    internalKey:
+      type: apiKey
+      in: user
+"#;
+
+    fn keys<V>(map: &BTreeMap<String, V>) -> Vec<&str> {
+        map.keys().map(String::as_str).collect()
+    }
+
+    fn dangling_refs(root: &Value, value: &Value, dangling: &mut Vec<String>) {
+        match value {
+            Value::Object(fields) => {
+                if let Some(Value::String(reference)) = fields.get("$ref") {
+                    if local_pointer(reference)
+                        .and_then(|pointer| pointer.evaluate(root))
+                        .is_none()
+                    {
+                        dangling.push(reference.clone());
+                    }
+                }
+                fields
+                    .values()
+                    .for_each(|value| dangling_refs(root, value, dangling));
+            }
+            Value::Array(items) => items
+                .iter()
+                .for_each(|item| dangling_refs(root, item, dangling)),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn extract_public_elements() {
+        let spec = parse_spec(MIXED_SPEC);
+
+        let public = spec.extract_by_tag("public").unwrap();
+
+        assert_eq!(keys(&public.operations), vec!["onUserSignedUp"]);
+        assert_eq!(keys(&public.channels), vec!["broadcast", "userSignedUp"]);
+        // Kept channels keep their untagged messages.
+        let channel = public.channels["userSignedUp"].resolve(&public).unwrap();
+        assert_eq!(
+            keys(&channel.messages),
+            vec!["userSignedUp", "userSignedUpDebug"]
+        );
+        // The broadcast channel is available on every server.
+        assert_eq!(keys(&public.servers), vec!["internal", "public"]);
+        assert_eq!(
+            keys(&public.components.messages),
+            vec!["debug", "userSignedUp"]
+        );
+        assert_eq!(
+            keys(&public.components.schemas),
+            vec!["address", "debug", "user"]
+        );
+        assert_eq!(keys(&public.components.security_schemes), vec!["scram"]);
+        assert_eq!(
+            public.info.description.as_deref(),
+            Some("Account events.\n\nRestricted to the elements tagged `public`.")
+        );
+    }
+
+    #[test]
+    fn extract_only_servers_of_kept_channels() {
+        let spec = parse_spec(MIXED_SPEC);
+
+        let internal = spec.extract_by_tag("internal").unwrap();
+
+        assert_eq!(keys(&internal.operations), vec!["onAudit"]);
+        assert_eq!(keys(&internal.servers), vec!["internal"]);
+        assert_eq!(keys(&internal.components.messages), vec!["auditEntry"]);
+        assert_eq!(keys(&internal.components.schemas), vec!["auditEntry"]);
+        assert!(internal.components.security_schemes.is_empty());
+    }
+
+    #[cfg(feature = "validation")]
+    #[test]
+    fn extracted_documents_pass_validation() {
+        use crate::validation::{validate, Severity};
+        let spec = parse_spec(MIXED_SPEC);
+
+        for tag in ["public", "internal", "unknown"] {
+            let extracted = spec.extract_by_tag(tag).unwrap();
+
+            let errors = validate(&extracted)
+                .into_iter()
+                .filter(|issue| issue.severity == Severity::Error)
+                .collect::<Vec<_>>();
+            assert!(errors.is_empty(), "{tag}: {errors:?}");
+        }
+    }
+
+    #[test]
+    fn extracted_documents_have_no_dangling_refs() {
+        let spec = parse_spec(MIXED_SPEC);
+
+        for tag in ["public", "internal", "unknown"] {
+            let extracted = spec.extract_by_tag(tag).unwrap();
+
+            let value = serde_json::to_value(&extracted).unwrap();
+            let mut dangling = Vec::new();
+            dangling_refs(&value, &value, &mut dangling);
+            assert!(dangling.is_empty(), "{tag}: {dangling:?}");
+            for operation in extracted.operations.values() {
+                let operation = operation.resolve(&extracted).unwrap();
+                operation
+                    .channel
+                    .resolve::<crate::spec::channel::Channel>(&extracted)
+                    .unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn extract_fails_on_dangling_source_refs() {
+        let mut spec = parse_spec(MIXED_SPEC);
+        spec.components.schemas.remove("address");
+
+        assert!(matches!(
+            spec.extract_by_tag("public"),
+            Err(Error::UnresolvedReference { reference }) if reference == "#/components/schemas/address"
+        ));
+    }
+
+    #[test]
+    fn filter_down_to_one_operation() {
+        let mut spec = parse_spec(MIXED_SPEC);
+        spec.channels.remove("broadcast");
+        spec.components.schemas.remove("address");
+
+        let filtered = spec.filter_by_tag("internal").unwrap();
+
+        assert_eq!(keys(&filtered.operations), vec!["onAudit"]);
+        assert_eq!(keys(&filtered.channels), vec!["audit"]);
+        assert_eq!(keys(&filtered.servers), vec!["internal"]);
+        assert_eq!(keys(&filtered.components.messages), vec!["auditEntry"]);
+        assert_eq!(keys(&filtered.components.schemas), vec!["auditEntry"]);
+        assert_eq!(filtered.info, spec.info);
+        assert_eq!(
+            keys(&spec.filter_by_tag("public").unwrap().components.schemas),
+            vec!["debug", "user"]
+        );
+    }
+}
@@ -0,0 +1,166 @@
+//! Hoisting of inline schemas into the components of a document.
+use crate::error::Error;
+use crate::spec::common::JsonPointer;
+use crate::spec::message::is_json_schema_format;
+use crate::spec::schema::{subschemas, SchemaRole};
+use crate::spec::AsyncApiV3Spec;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use serde_json::{Map, Value};
+
+impl AsyncApiV3Spec {
+    /// Moves the inline schemas carrying a `title` into `components/schemas`, under their
+    /// title, and replaces them with references. Returns the number of replaced schemas.
+    ///
+    /// The schemas of message payloads and headers, of bindings and the subschemas of any
+    /// schema, including the `components/schemas` entries, are visited. Nested schemas are
+    /// hoisted before the schemas containing them, which then reference them. Schemas written
+    /// in a format other than JSON schema are left untouched.
+    ///
+    /// Fails with [Error::DuplicateDefinition] if a title is already the key of a different
+    /// component schema, or the title of two different schemas, in which case the document is
+    /// left untouched.
+    pub fn name_inline_schemas(&mut self) -> Result<usize, Error> {
+        let parse_error = |err: serde_json::Error| Error::Parse {
+            reason: err.to_string(),
+        };
+        let mut document = serde_json::to_value(&*self).map_err(parse_error)?;
+        let mut titled = Vec::new();
+        for site in self.schemas() {
+            let Some(schema) = site.pointer.evaluate(&document) else {
+                continue;
+            };
+            let (schema, pointer) = match (schema.get("schemaFormat"), schema.get("schema")) {
+                (Some(Value::String(format)), Some(schema)) if is_json_schema_format(format) => {
+                    (schema, site.pointer.join("schema"))
+                }
+                (Some(_), Some(_)) => continue,
+                _ => (schema, site.pointer),
+            };
+            let is_component = site.role == SchemaRole::Component;
+            collect_titled(schema, &pointer, !is_component, &mut titled);
+        }
+        if titled.is_empty() {
+            return Ok(0);
+        }
+
+        let components = JsonPointer::from_iter(["components", "schemas"]);
+        for (pointer, title) in &titled {
+            let Some(schema) = pointer.evaluate_mut(&mut document) else {
+                continue;
+            };
+            let reference = format!("#{}", components.join(title.as_str()));
+            let schema = core::mem::replace(
+                schema,
+                Value::Object(Map::from_iter([(
+                    String::from("$ref"),
+                    Value::String(reference),
+                )])),
+            );
+            let schemas = document
+                .as_object_mut()
+                .expect("documents serialize to objects")
+                .entry("components")
+                .or_insert_with(|| Value::Object(Map::new()))
+                .as_object_mut()
+                .expect("the components serialize to an object")
+                .entry("schemas")
+                .or_insert_with(|| Value::Object(Map::new()))
+                .as_object_mut()
+                .expect("the component schemas serialize to an object");
+            match schemas.get(title) {
+                Some(existing) if *existing != schema => {
+                    return Err(Error::DuplicateDefinition { key: title.clone() });
+                }
+                Some(_) => {}
+                None => {
+                    schemas.insert(title.clone(), schema);
+                }
+            }
+        }
+        *self = serde_json::from_value(document).map_err(parse_error)?;
+        Ok(titled.len())
+    }
+}
+
+/// Collects the location and the title of the titled schemas, nested schemas first.
+fn collect_titled(
+    schema: &Value,
+    pointer: &JsonPointer,
+    include_root: bool,
+    titled: &mut Vec<(JsonPointer, String)>,
+) {
+    for (subschema, pointer) in subschemas(schema, pointer) {
+        collect_titled(subschema, &pointer, true, titled);
+    }
+    if !include_root || schema.get("$ref").is_some() {
+        return;
+    }
+    if let Some(Value::String(title)) = schema.get("title") {
+        titled.push((pointer.clone(), title.clone()));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::spec::common::Either;
+    use crate::test_util::parse_spec;
+
+    const SPEC: &str = r#"
+asyncapi: 3.0.0
+info:
+  title: Orders
+  version: 1.0.0
+components:
+  messages:
+    orderPlaced:
+      payload:
+        title: Order
+        type: object
+        properties:
+          address:
+            title: Address
+            type: object
+            properties:
+              city:
+                type: string
+"#;
+
+    #[test]
+    fn hoist_titled_inline_schemas() {
+        let mut spec = parse_spec(SPEC);
+
+        let hoisted = spec.name_inline_schemas().unwrap();
+
+        assert_eq!(hoisted, 2);
+        let value = serde_json::to_value(&spec).unwrap();
+        assert_eq!(
+            value["components"]["messages"]["orderPlaced"]["payload"],
+            serde_json::json!({ "$ref": "#/components/schemas/Order" })
+        );
+        assert_eq!(
+            value["components"]["schemas"]["Order"]["properties"]["address"],
+            serde_json::json!({ "$ref": "#/components/schemas/Address" })
+        );
+        let Either::Right(Either::Left(address)) = &spec.components.schemas["Address"] else {
+            panic!("the schema is not an inline schema");
+        };
+        assert_eq!(address.get("title"), Some(&Value::from("Address")));
+        assert_eq!(spec.name_inline_schemas().unwrap(), 0);
+    }
+
+    #[test]
+    fn title_collision() {
+        let mut spec = parse_spec(&format!(
+            "{SPEC}  schemas:\n    Address:\n      type: string\n"
+        ));
+        let original = spec.clone();
+
+        let result = spec.name_inline_schemas();
+
+        assert!(matches!(result, Err(Error::DuplicateDefinition { key }) if key == "Address"));
+        assert_eq!(spec, original);
+    }
+}
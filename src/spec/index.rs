@@ -0,0 +1,295 @@
+//! Precomputed lookups for services resolving the same document over and over.
+use crate::spec::channel::{AddressPattern, Channel, ChannelMatch};
+use crate::spec::common::{JsonPointer, RefOr};
+use crate::spec::message::{Message, Messages};
+use crate::spec::operation::Operation;
+use crate::spec::resolve::Referenceable;
+use crate::spec::schema::{json_schema, SchemaValidator, SchemaViolation};
+use crate::spec::AsyncApiV3Spec;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use serde_json::Value;
+
+#[cfg(feature = "std")]
+type Map<T> = std::collections::HashMap<String, T>;
+#[cfg(not(feature = "std"))]
+type Map<T> = BTreeMap<String, T>;
+
+/// Lookup tables over a document, built once and queried without parsing pointers or
+/// following references.
+///
+/// Every channel, operation and message of the root sections and of the components is keyed
+/// by its JSON pointer, e.g. `/channels/lights/messages/lightMeasured`, with references
+/// already resolved. The lookups are hashed with the `std` feature. The index borrows the
+/// document: after a mutation, build a new index.
+pub struct DocumentIndex<'a> {
+    spec: &'a AsyncApiV3Spec,
+    channels: Map<&'a Channel>,
+    operations: Map<&'a Operation>,
+    messages: Map<&'a Message>,
+    payloads: Map<&'a Value>,
+    validator: SchemaValidator<'a>,
+    addresses: Vec<(usize, &'a str, &'a Channel, AddressPattern)>,
+}
+
+impl<'a> DocumentIndex<'a> {
+    /// Indexes the channels, operations, messages and payload schemas of a document, skipping
+    /// the references which cannot be resolved. The references of the payload schemas are
+    /// resolved as well.
+    pub fn build(spec: &'a AsyncApiV3Spec) -> Self {
+        let mut index = Self {
+            spec,
+            channels: Map::default(),
+            operations: Map::default(),
+            messages: Map::default(),
+            payloads: Map::default(),
+            validator: SchemaValidator::new(spec),
+            addresses: Vec::new(),
+        };
+        let root = JsonPointer::default();
+        let components = root.join("components");
+        for (section, channels) in [
+            (&root, &spec.channels),
+            (&components, &spec.components.channels),
+        ] {
+            let section = section.join("channels");
+            index.insert_section(&section, channels, |index| &mut index.channels);
+            for (name, channel) in channels {
+                if let Ok(channel) = channel.resolve(spec) {
+                    let messages = section.join(name.as_str()).join("messages");
+                    index.insert_messages(&messages, &channel.messages);
+                }
+            }
+        }
+        index.insert_messages(&components.join("messages"), &spec.components.messages);
+        for (section, operations) in [
+            (&root, &spec.operations),
+            (&components, &spec.components.operations),
+        ] {
+            let section = section.join("operations");
+            index.insert_section(&section, operations, |index| &mut index.operations);
+        }
+
+        index.addresses = spec
+            .channels
+            .iter()
+            .filter_map(|(name, channel)| {
+                let channel = channel.resolve(spec).ok()?;
+                let pattern = AddressPattern::parse(channel.address.as_deref()?);
+                Some((pattern.parameter_count(), name.as_str(), channel, pattern))
+            })
+            .collect();
+        // Matching in this order keeps the first match, as `AsyncApiV3Spec::match_channel`.
+        index.addresses.sort_by_key(|(count, _, _, _)| *count);
+        index
+    }
+
+    fn insert_section<T: Referenceable>(
+        &mut self,
+        section: &JsonPointer,
+        entries: &'a BTreeMap<String, RefOr<T>>,
+        map: fn(&mut Self) -> &mut Map<&'a T>,
+    ) {
+        for (name, entry) in entries {
+            if let Ok(entry) = entry.resolve(self.spec) {
+                map(self).insert(section.join(name.as_str()).to_string(), entry);
+            }
+        }
+    }
+
+    fn insert_messages(&mut self, section: &JsonPointer, messages: &'a Messages) {
+        self.insert_section(section, messages, |index| &mut index.messages);
+        for (name, message) in messages {
+            let payload = message
+                .resolve(self.spec)
+                .ok()
+                .and_then(|message| message.payload.as_ref())
+                .and_then(|payload| json_schema(payload, self.spec));
+            if let Some(payload) = payload {
+                self.validator.prepare(payload);
+                let pointer = section.join(name.as_str()).to_string();
+                self.payloads.insert(pointer, payload);
+            }
+        }
+    }
+
+    /// Returns the document the index was built from.
+    pub fn spec(&self) -> &'a AsyncApiV3Spec {
+        self.spec
+    }
+
+    /// Returns the channel at a JSON pointer, e.g. `/channels/lights` or `#/channels/lights`.
+    pub fn channel(&self, pointer: &str) -> Option<&'a Channel> {
+        self.channels.get(strip_fragment(pointer)).copied()
+    }
+
+    /// Returns the operation at a JSON pointer.
+    pub fn operation(&self, pointer: &str) -> Option<&'a Operation> {
+        self.operations.get(strip_fragment(pointer)).copied()
+    }
+
+    /// Returns the message at a JSON pointer.
+    pub fn message(&self, pointer: &str) -> Option<&'a Message> {
+        self.messages.get(strip_fragment(pointer)).copied()
+    }
+
+    /// Returns the validator of the payload of the message at a JSON pointer, or `None` if the
+    /// payload is absent or written in another schema format.
+    pub fn compiled_payload(&self, message_pointer: &str) -> Option<CompiledPayload<'_, 'a>> {
+        let schema = self.payloads.get(strip_fragment(message_pointer))?;
+        Some(CompiledPayload {
+            schema,
+            validator: &self.validator,
+        })
+    }
+
+    /// Returns the root channel matching a concrete address, as
+    /// [AsyncApiV3Spec::match_channel].
+    pub fn match_channel(&self, address: &str) -> Option<ChannelMatch<'a>> {
+        self.addresses
+            .iter()
+            .find_map(|(_, name, channel, pattern)| {
+                Some(ChannelMatch {
+                    name,
+                    channel,
+                    parameters: pattern.matches(address)?,
+                })
+            })
+    }
+}
+
+impl fmt::Debug for DocumentIndex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DocumentIndex")
+            .field("channels", &sorted_keys(&self.channels))
+            .field("operations", &sorted_keys(&self.operations))
+            .field("messages", &sorted_keys(&self.messages))
+            .finish_non_exhaustive()
+    }
+}
+
+fn sorted_keys<T>(map: &Map<T>) -> Vec<&str> {
+    let mut keys: Vec<&str> = map.keys().map(String::as_str).collect();
+    keys.sort_unstable();
+    keys
+}
+
+/// The payload schema of a message, validating values with the references already resolved by
+/// its [DocumentIndex].
+#[derive(Clone, Copy)]
+pub struct CompiledPayload<'i, 'a> {
+    schema: &'a Value,
+    validator: &'i SchemaValidator<'a>,
+}
+
+impl<'a> CompiledPayload<'_, 'a> {
+    /// Returns the JSON schema of the payload.
+    pub fn schema(&self) -> &'a Value {
+        self.schema
+    }
+
+    /// Validates a payload against the schema, returning every violation found.
+    pub fn validate(&self, payload: &Value) -> Vec<SchemaViolation> {
+        self.validator.validate(self.schema, payload)
+    }
+}
+
+impl fmt::Debug for CompiledPayload<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("CompiledPayload").field(self.schema).finish()
+    }
+}
+
+fn strip_fragment(pointer: &str) -> &str {
+    pointer.strip_prefix('#').unwrap_or(pointer)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::spec::common::ReferenceObject;
+    use crate::test_util::streetlights;
+    use alloc::format;
+    use serde_json::json;
+
+    #[test]
+    fn index_agrees_with_resolution() {
+        let spec = streetlights();
+        let index = DocumentIndex::build(&spec);
+
+        assert!(!index.messages.is_empty());
+        for (pointer, message) in &index.messages {
+            let reference = ReferenceObject::new(format!("#{pointer}"));
+            assert_eq!(reference.resolve::<Message>(&spec).ok(), Some(*message));
+            assert_eq!(
+                index
+                    .compiled_payload(pointer)
+                    .map(|payload| payload.schema()),
+                message
+                    .payload
+                    .as_ref()
+                    .and_then(|payload| json_schema(payload, &spec))
+            );
+        }
+        for (pointer, operation) in &index.operations {
+            let reference = ReferenceObject::new(format!("#{pointer}"));
+            assert_eq!(reference.resolve::<Operation>(&spec).ok(), Some(*operation));
+        }
+        assert_eq!(
+            index.channel("#/channels/lightsDim"),
+            spec.channels["lightsDim"].resolve(&spec).ok()
+        );
+        assert_eq!(index.message("#/components/messages/missing"), None);
+    }
+
+    #[test]
+    fn compiled_payloads_validate_messages() {
+        let spec = streetlights();
+        let index = DocumentIndex::build(&spec);
+        let payload = index
+            .compiled_payload("#/components/messages/lightMeasured")
+            .unwrap();
+
+        let reasons = |value| {
+            payload
+                .validate(&value)
+                .into_iter()
+                .map(|violation| violation.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert!(reasons(json!({ "lumens": 3, "sentAt": "2024-01-01T00:00:00Z" })).is_empty());
+        assert_eq!(
+            reasons(json!({ "lumens": -1, "sentAt": 42 })),
+            [
+                "/lumens: -1 is lower than the minimum 0",
+                "/sentAt: expected string, found number"
+            ]
+        );
+        assert!(index
+            .compiled_payload("#/components/messages/missing")
+            .is_none());
+    }
+
+    #[test]
+    fn index_matches_channels_as_the_document() {
+        let spec = streetlights();
+        let index = DocumentIndex::build(&spec);
+
+        for address in [
+            "smartylighting.streetlights.1.0.event.42.lighting.measured",
+            "smartylighting.streetlights.1.0.action.7.turn.on",
+            "smartylighting.streetlights.1.0.action.7.turn",
+        ] {
+            assert_eq!(index.match_channel(address), spec.match_channel(address));
+        }
+        assert_eq!(
+            index
+                .match_channel("smartylighting.streetlights.1.0.action.7.dim")
+                .unwrap()
+                .parameters["streetlightId"],
+            "7"
+        );
+    }
+}
@@ -1,6 +1,9 @@
 //! Contains types related to the [info
 //! field](https://www.asyncapi.com/docs/concepts/asyncapi-document/structure#info-field)
 use crate::spec::common::{ExternalDocumentation, RefOr, Tag};
+use crate::spec::AsyncApiV3Spec;
+use alloc::string::String;
+use alloc::vec::Vec;
 
 /// The info field in an API document offers crucial metadata, including the API's title,
 /// version, description, contact details, and license. This field provides a
@@ -29,12 +32,23 @@ pub struct Info {
     pub license: Option<License>,
     /// A list of tags for application API documentation control. Tags can be used for logical grouping of applications.
     #[serde(default)]
-    pub tags: Vec<Tag>,
+    pub tags: Vec<RefOr<Tag>>,
     /// Additional external documentation of the exposed API.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub external_docs: Option<RefOr<ExternalDocumentation>>,
 }
 
+impl Info {
+    /// Returns the tags of the application, following the references to the `components/tags`
+    /// of the document and leaving out those which do not resolve.
+    pub fn resolved_tags<'a>(&'a self, spec: &'a AsyncApiV3Spec) -> Vec<&'a Tag> {
+        self.tags
+            .iter()
+            .filter_map(|tag| tag.resolve(spec).ok())
+            .collect()
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Contact {
@@ -58,3 +72,61 @@ pub struct License {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub url: Option<String>,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::parse_spec;
+
+    #[test]
+    fn referenced_tags_and_external_docs_round_trip() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Orders
+  version: 1.0.0
+  tags:
+    - $ref: '#/components/tags/orders'
+    - name: shop
+  externalDocs:
+    $ref: '#/components/externalDocs/handbook'
+components:
+  tags:
+    orders:
+      name: orders
+      description: Order management.
+  externalDocs:
+    handbook:
+      url: https://example.com/handbook
+"#,
+        );
+
+        let names = spec
+            .info
+            .resolved_tags(&spec)
+            .into_iter()
+            .map(|tag| tag.name.as_str())
+            .collect::<Vec<_>>();
+        let external_docs = spec.info.external_docs.as_ref().unwrap();
+
+        assert_eq!(names, ["orders", "shop"]);
+        assert_eq!(
+            external_docs.resolve(&spec).unwrap().url,
+            "https://example.com/handbook"
+        );
+        let value = serde_json::to_value(&spec).unwrap();
+        assert_eq!(
+            value["info"]["tags"],
+            serde_json::json!([{ "$ref": "#/components/tags/orders" }, { "name": "shop" }])
+        );
+        assert_eq!(
+            value["info"]["externalDocs"],
+            serde_json::json!({ "$ref": "#/components/externalDocs/handbook" })
+        );
+        assert_eq!(
+            serde_json::from_value::<AsyncApiV3Spec>(value).unwrap(),
+            spec
+        );
+    }
+}
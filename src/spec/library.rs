@@ -0,0 +1,199 @@
+//! Extraction of components into a library document shared by several documents.
+use crate::error::Error;
+use crate::spec::common::{collect_references, local_pointer, JsonPointer};
+use crate::spec::component::ComponentSection;
+use crate::spec::AsyncApiV3Spec;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// The components moved by [AsyncApiV3Spec::extract_components]: whole sections or single
+/// entries.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ComponentSelection {
+    /// The selected keys of each section, `None` selecting the whole section.
+    sections: BTreeMap<ComponentSection, Option<BTreeSet<String>>>,
+}
+
+impl ComponentSelection {
+    /// Creates an empty selection, extracting nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Selects every entry of a section.
+    pub fn section(mut self, section: ComponentSection) -> Self {
+        self.sections.insert(section, None);
+        self
+    }
+
+    /// Selects the entry `key` of a section.
+    pub fn entry(mut self, section: ComponentSection, key: &str) -> Self {
+        if let Some(keys) = self
+            .sections
+            .entry(section)
+            .or_insert(Some(BTreeSet::new()))
+        {
+            keys.insert(String::from(key));
+        }
+        self
+    }
+
+    /// Returns `true` if the entry `key` of the section named `section` is selected.
+    pub fn contains(&self, section: &str, key: &str) -> bool {
+        self.sections.iter().any(|(selected, keys)| {
+            selected.as_str() == section && keys.as_ref().is_none_or(|keys| keys.contains(key))
+        })
+    }
+}
+
+impl AsyncApiV3Spec {
+    /// Moves the selected components into a library document, returning the library and this
+    /// document referencing them from the library, at `base`, e.g. `common.yaml` turns
+    /// `#/components/schemas/order` into `common.yaml#/components/schemas/order`.
+    ///
+    /// The library holds the moved components only, and this document's info titled after it,
+    /// e.g. `Orders components`. Bundling the rewritten document with the library, named after
+    /// `base`, gives back this document, without the moved components it does not reference.
+    ///
+    /// Fails with [Error::IncompleteSelection] listing the objects the moved components
+    /// reference which are not moved, since the library would not hold them.
+    pub fn extract_components(
+        &self,
+        selection: &ComponentSelection,
+        base: &str,
+    ) -> Result<(AsyncApiV3Spec, AsyncApiV3Spec), Error> {
+        let invalid = |err: serde_json::Error| Error::InvalidDocument {
+            reason: err.to_string(),
+            hints: Vec::new(),
+        };
+        let mut document = serde_json::to_value(self).map_err(invalid)?;
+
+        let mut moved = Map::new();
+        if let Some(Value::Object(components)) = document.get_mut("components") {
+            for (section, entries) in components.iter_mut() {
+                let Value::Object(entries) = entries else {
+                    continue;
+                };
+                let keys = entries
+                    .keys()
+                    .filter(|key| selection.contains(section, key))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                let mut moved_entries = Map::new();
+                for key in keys {
+                    if let Some(entry) = entries.remove(&key) {
+                        moved_entries.insert(key, entry);
+                    }
+                }
+                if !moved_entries.is_empty() {
+                    moved.insert(section.clone(), Value::Object(moved_entries));
+                }
+            }
+            components.retain(|_, entries| {
+                entries
+                    .as_object()
+                    .is_none_or(|entries| !entries.is_empty())
+            });
+        }
+
+        let is_moved = |pointer: &JsonPointer| match pointer.segments() {
+            [components, section, key, ..] if components == "components" => moved
+                .get(section)
+                .and_then(Value::as_object)
+                .is_some_and(|entries| entries.contains_key(key)),
+            _ => false,
+        };
+        let moved_value = Value::Object(moved.clone());
+        let references = collect_references(&moved_value, &JsonPointer::default())
+            .into_iter()
+            .filter_map(|(_, reference)| local_pointer(reference))
+            .collect::<Vec<_>>();
+        let required = references
+            .iter()
+            .filter(|target| !is_moved(target))
+            .map(|target| {
+                let entry_depth = match target.segments().first().map(String::as_str) {
+                    Some("components") => 3,
+                    _ => 2,
+                };
+                let entry = target
+                    .segments()
+                    .iter()
+                    .take(entry_depth)
+                    .cloned()
+                    .collect::<JsonPointer>();
+                format!("#{entry}")
+            })
+            .collect::<BTreeSet<_>>();
+        if !required.is_empty() {
+            return Err(Error::IncompleteSelection {
+                required: required.into_iter().collect(),
+            });
+        }
+
+        rewrite_references(&mut document, &|reference: &str| {
+            let target = JsonPointer::parse(reference.strip_prefix('#')?).ok()?;
+            is_moved(&target).then(|| format!("{base}{reference}"))
+        });
+
+        let info = self.info.clone();
+        let library = serde_json::json!({
+            "info": {
+                "title": format!("{} components", info.title),
+                "version": info.version,
+            },
+            "components": moved,
+        });
+        Ok((
+            AsyncApiV3Spec::deserialize(&library).map_err(invalid)?,
+            AsyncApiV3Spec::deserialize(&document).map_err(invalid)?,
+        ))
+    }
+}
+
+/// Replaces the references for which `rewrite` returns a new reference.
+fn rewrite_references(value: &mut Value, rewrite: &dyn Fn(&str) -> Option<String>) {
+    match value {
+        Value::Object(fields) => {
+            if let Some(Value::String(reference)) = fields.get_mut("$ref") {
+                if let Some(rewritten) = rewrite(reference) {
+                    *reference = rewritten;
+                }
+            }
+            fields
+                .values_mut()
+                .for_each(|value| rewrite_references(value, rewrite));
+        }
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|item| rewrite_references(item, rewrite)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::parse_spec;
+
+    const ORDERS: &str = include_str!("../../test-res/library/orders.yaml");
+
+    #[test]
+    fn dependencies_must_move_too() {
+        let spec = parse_spec(ORDERS);
+        let selection = ComponentSelection::new().entry(ComponentSection::Messages, "orderPlaced");
+
+        let err = spec
+            .extract_components(&selection, "common.yaml")
+            .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "The selected components also require #/components/schemas/order"
+        );
+    }
+}
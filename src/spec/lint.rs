@@ -0,0 +1,116 @@
+//! Checks of documentation completeness.
+use crate::spec::common::{JsonPointer, RefOr};
+use crate::spec::resolve::Referenceable;
+use crate::spec::AsyncApiV3Spec;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+impl AsyncApiV3Spec {
+    /// Returns the JSON pointers of the servers, channels, operations and channel messages
+    /// lacking a description, in document order.
+    ///
+    /// Entries defined through a reference are checked on the referenced object and reported
+    /// at the referencing location. Entries whose reference cannot be resolved are skipped.
+    pub fn undocumented(&self) -> Vec<String> {
+        let mut undocumented = Vec::new();
+        self.collect_undocumented(
+            &JsonPointer::from_iter(["servers"]),
+            &self.servers,
+            |server| server.description.as_deref(),
+            &mut undocumented,
+        );
+        self.collect_undocumented(
+            &JsonPointer::from_iter(["channels"]),
+            &self.channels,
+            |channel| channel.description.as_deref(),
+            &mut undocumented,
+        );
+        for (name, channel) in &self.channels {
+            if let Ok(channel) = channel.resolve(self) {
+                self.collect_undocumented(
+                    &JsonPointer::from_iter(["channels", name.as_str(), "messages"]),
+                    &channel.messages,
+                    |message| message.description.as_deref(),
+                    &mut undocumented,
+                );
+            }
+        }
+        self.collect_undocumented(
+            &JsonPointer::from_iter(["operations"]),
+            &self.operations,
+            |operation| operation.description.as_deref(),
+            &mut undocumented,
+        );
+        undocumented
+    }
+
+    fn collect_undocumented<T: Referenceable>(
+        &self,
+        section: &JsonPointer,
+        entries: &BTreeMap<String, RefOr<T>>,
+        description: fn(&T) -> Option<&str>,
+        undocumented: &mut Vec<String>,
+    ) {
+        for (name, entry) in entries {
+            let Ok(entry) = entry.resolve(self) else {
+                continue;
+            };
+            if description(entry).is_none_or(|description| description.trim().is_empty()) {
+                undocumented.push(section.join(name.as_str()).to_string());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::spec::AsyncApiSpec;
+
+    #[test]
+    fn flag_undescribed_entries() {
+        let AsyncApiSpec::V3_0_0(spec) = serde_yaml::from_str(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Lights
+  version: 1.0.0
+servers:
+  production:
+    host: broker.example.com
+    protocol: mqtt
+    description: Production broker.
+channels:
+  lights:
+    address: lights
+    description: Light measurements.
+    messages:
+      lightMeasured:
+        $ref: '#/components/messages/lightMeasured'
+operations:
+  turnOn:
+    action: send
+    channel:
+      $ref: '#/channels/lights'
+  turnOff:
+    action: send
+    description: Turns the lights off.
+    channel:
+      $ref: '#/channels/lights'
+components:
+  messages:
+    lightMeasured:
+      description: '  '
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            spec.undocumented(),
+            vec![
+                "/channels/lights/messages/lightMeasured",
+                "/operations/turnOn"
+            ]
+        );
+    }
+}
@@ -0,0 +1,259 @@
+//! Merging of documents into one another.
+use crate::error::Error;
+use crate::spec::common::JsonPointer;
+use crate::spec::AsyncApiV3Spec;
+use alloc::format;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// Root sections whose entries are keyed by name.
+const ROOT_SECTIONS: [&str; 3] = ["servers", "channels", "operations"];
+
+impl AsyncApiV3Spec {
+    /// Imports the servers, channels, operations and components of another document, with
+    /// `prefix` prepended to all their keys so they cannot collide with the ones of this
+    /// document. The references of the imported document are rewritten to the prefixed keys.
+    ///
+    /// Entries which still collide after prefixing are accepted when identical; otherwise
+    /// [Error::DuplicateOperation] or [Error::DuplicateDefinition] is returned and this document
+    /// is left untouched.
+    pub fn merge_with_prefix(&mut self, other: &AsyncApiV3Spec, prefix: &str) -> Result<(), Error> {
+        let mut merged = to_value(self)?;
+        let mut imported = to_value(other)?;
+        prefix_references(&mut imported, prefix);
+
+        for section in ROOT_SECTIONS {
+            merge_entries(&mut merged, &imported, &[section], prefix)?;
+        }
+        let component_sections = imported
+            .get("components")
+            .and_then(Value::as_object)
+            .map(|components| components.keys().cloned().collect::<Vec<_>>())
+            .unwrap_or_default();
+        for section in component_sections {
+            merge_entries(&mut merged, &imported, &["components", &section], prefix)?;
+        }
+
+        *self = AsyncApiV3Spec::deserialize(&merged).map_err(|err| Error::InvalidDocument {
+            reason: err.to_string(),
+            hints: Vec::new(),
+        })?;
+        Ok(())
+    }
+}
+
+fn to_value(spec: &AsyncApiV3Spec) -> Result<Value, Error> {
+    serde_json::to_value(spec).map_err(|err| Error::InvalidDocument {
+        reason: err.to_string(),
+        hints: Vec::new(),
+    })
+}
+
+/// Copies the entries of the map at `path` from `imported` into `merged`, prefixing their keys.
+fn merge_entries(
+    merged: &mut Value,
+    imported: &Value,
+    path: &[&str],
+    prefix: &str,
+) -> Result<(), Error> {
+    let Some(entries) = JsonPointer::from_iter(path.iter().copied())
+        .evaluate(imported)
+        .and_then(Value::as_object)
+    else {
+        return Ok(());
+    };
+    let mut target = merged;
+    for segment in path {
+        target = target
+            .as_object_mut()
+            .expect("documents serialize to objects")
+            .entry(*segment)
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+    let target = target
+        .as_object_mut()
+        .expect("document sections serialize to objects");
+
+    for (key, value) in entries {
+        let key = format!("{prefix}{key}");
+        match target.get(&key) {
+            Some(existing) if existing != value => {
+                return Err(if path == ["operations"] {
+                    Error::DuplicateOperation { name: key }
+                } else {
+                    Error::DuplicateDefinition { key }
+                });
+            }
+            Some(_) => {}
+            None => {
+                target.insert(key, value.clone());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Rewrites the local references of a document to point at the prefixed keys.
+fn prefix_references(value: &mut Value, prefix: &str) {
+    match value {
+        Value::Object(fields) => {
+            if let Some(Value::String(reference)) = fields.get_mut("$ref") {
+                if let Some(pointer) = reference
+                    .strip_prefix('#')
+                    .and_then(|pointer| JsonPointer::parse(pointer).ok())
+                {
+                    *reference = format!("#{}", prefix_pointer(&pointer, prefix));
+                }
+            }
+            for value in fields.values_mut() {
+                prefix_references(value, prefix);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                prefix_references(item, prefix);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn prefix_pointer(pointer: &JsonPointer, prefix: &str) -> JsonPointer {
+    let mut segments = pointer.segments().to_vec();
+    let index = match segments.as_slice() {
+        [root, _, ..] if ROOT_SECTIONS.contains(&root.as_str()) => 1,
+        [components, _, _, ..] if components == "components" => 2,
+        _ => return pointer.clone(),
+    };
+    segments[index] = format!("{prefix}{}", segments[index]);
+    segments.into_iter().collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::spec::channel::Channel;
+    use crate::spec::message::Message;
+    use crate::test_util::parse_spec;
+
+    const ACCOUNTS: &str = r#"
+asyncapi: 3.0.0
+info:
+  title: Accounts
+  version: 1.0.0
+servers:
+  production:
+    host: accounts.example.com
+    protocol: kafka
+channels:
+  userSignedUp:
+    address: user.signedup
+    messages:
+      userSignedUp:
+        $ref: '#/components/messages/userSignedUp'
+operations:
+  onUserSignedUp:
+    action: receive
+    channel:
+      $ref: '#/channels/userSignedUp'
+components:
+  messages:
+    userSignedUp:
+      payload:
+        $ref: '#/components/schemas/user'
+  schemas:
+    user:
+      type: object
+"#;
+
+    const BILLING: &str = r#"
+asyncapi: 3.0.0
+info:
+  title: Billing
+  version: 2.0.0
+servers:
+  production:
+    host: billing.example.com
+    protocol: kafka
+channels:
+  userSignedUp:
+    address: billing.user.signedup
+    servers:
+      - $ref: '#/servers/production'
+    messages:
+      userSignedUp:
+        $ref: '#/components/messages/userSignedUp'
+operations:
+  onUserSignedUp:
+    action: send
+    channel:
+      $ref: '#/channels/userSignedUp'
+    messages:
+      - $ref: '#/channels/userSignedUp/messages/userSignedUp'
+components:
+  messages:
+    userSignedUp:
+      payload:
+        $ref: '#/components/schemas/user'
+  schemas:
+    user:
+      type: object
+      properties:
+        invoice:
+          $ref: '#/components/schemas/invoice'
+    invoice:
+      type: string
+"#;
+
+    #[test]
+    fn merge_under_prefix() {
+        let mut spec = parse_spec(ACCOUNTS);
+
+        spec.merge_with_prefix(&parse_spec(BILLING), "billing.")
+            .unwrap();
+
+        assert_eq!(spec.info.title, "Accounts");
+        assert_eq!(spec.operations.len(), 2);
+        assert_eq!(spec.channels.len(), 2);
+        assert_eq!(spec.servers.len(), 2);
+        let mut schemas = spec.components.schemas.keys().collect::<Vec<_>>();
+        schemas.sort_unstable();
+        assert_eq!(schemas, vec!["billing.invoice", "billing.user", "user"]);
+
+        let operation = spec.operations["billing.onUserSignedUp"]
+            .resolve(&spec)
+            .unwrap();
+        assert_eq!(
+            operation.channel.reference,
+            "#/channels/billing.userSignedUp"
+        );
+        let channel = operation.channel.resolve::<Channel>(&spec).unwrap();
+        assert_eq!(channel.address.as_deref(), Some("billing.user.signedup"));
+        assert_eq!(channel.servers[0].reference, "#/servers/billing.production");
+        let message = operation.messages.as_ref().unwrap()[0]
+            .resolve::<Message>(&spec)
+            .unwrap();
+        let schema =
+            crate::spec::schema::json_schema(message.payload.as_ref().unwrap(), &spec).unwrap();
+        assert_eq!(
+            schema["properties"]["invoice"]["$ref"],
+            "#/components/schemas/billing.invoice"
+        );
+
+        let accounts = spec.operations["onUserSignedUp"].resolve(&spec).unwrap();
+        assert_eq!(accounts.channel.reference, "#/channels/userSignedUp");
+    }
+
+    #[test]
+    fn merge_rejects_conflicting_entries() {
+        let mut spec = parse_spec(ACCOUNTS);
+        let original = spec.clone();
+
+        let result = spec.merge_with_prefix(&parse_spec(BILLING), "");
+
+        assert!(matches!(result, Err(Error::DuplicateDefinition { key }) if key == "production"));
+        assert_eq!(spec, original);
+    }
+}
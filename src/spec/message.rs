@@ -1,11 +1,24 @@
 //! Contains the [message
 //! object](https://www.asyncapi.com/docs/reference/specification/v3.0.0#messageObject) and related
 //! types.
-use crate::spec::common::{Either, ExternalDocumentation, RefOr, Tag};
+use crate::error::Error;
+use crate::spec::common::{
+    non_null_bindings, to_generic_bindings, BindingPatch, Either, Extensions,
+    ExternalDocumentation, GenericBindings, JsonPointer, RefOr, ReferenceObject, Tag,
+};
+use crate::spec::resolve::{Referenceable, ResolveOptions};
+use crate::spec::schema::{json_schema, schema_format, SchemaValidator};
+use crate::spec::serialization::{content_type_format, PayloadFormat};
+use crate::spec::AsyncApiV3Spec;
+use alloc::borrow::Cow;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
 use core::num::NonZeroU16;
-use std::collections::HashMap;
+use serde_json::{Map, Value};
 
-pub type Messages = HashMap<String, RefOr<Message>>;
+pub type Messages = BTreeMap<String, RefOr<Message>>;
 
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -50,8 +63,233 @@ pub struct Message {
     /// A list of traits to apply to the message object. Traits MUST be merged using traits merge mechanism. The resulting object MUST be a valid Message Object.
     #[serde(default)]
     pub traits: Vec<RefOr<MessageTrait>>,
+    /// Specification extensions of the message.
+    #[serde(flatten)]
+    pub extensions: Extensions,
 }
 
+impl Message {
+    /// Returns the content type of the message, falling back to the default content type of
+    /// the document.
+    pub fn effective_content_type<'a>(&'a self, spec: &'a AsyncApiV3Spec) -> Option<&'a str> {
+        self.content_type
+            .as_deref()
+            .or(spec.default_content_type.as_deref())
+    }
+
+    /// Returns the content type of the message, falling back to the default content type of
+    /// the document, then to the default content type of the options.
+    pub fn effective_content_type_with<'a>(
+        &'a self,
+        spec: &'a AsyncApiV3Spec,
+        options: &'a ResolveOptions,
+    ) -> &'a str {
+        self.effective_content_type(spec)
+            .unwrap_or(&options.default_content_type)
+    }
+
+    /// Checks that a concrete message conforms to this definition: the headers and the payload
+    /// must satisfy their JSON schemas (schemas in other formats are not checked) and the
+    /// payload must be representable with the effective content type, i.e. a string for
+    /// textual content types such as `text/plain` or `application/xml`. Every violation found is
+    /// returned.
+    pub fn validate_instance(
+        &self,
+        headers: &Value,
+        payload: &Value,
+        spec: &AsyncApiV3Spec,
+    ) -> Result<(), Vec<Error>> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("validate_instance").entered();
+        let validator = SchemaValidator::new(spec);
+        let mut errors = Vec::new();
+        for (location, _rule, schema, instance) in [
+            ("headers", "headers-schema", &self.headers, headers),
+            ("payload", "payload-schema", &self.payload, payload),
+        ] {
+            let Some(schema) = schema.as_ref().and_then(|schema| json_schema(schema, spec)) else {
+                continue;
+            };
+            #[cfg(feature = "tracing")]
+            let _span = tracing::debug_span!("rule", code = _rule).entered();
+            errors.extend(
+                validator
+                    .validate(schema, instance)
+                    .into_iter()
+                    .map(|violation| Error::InvalidInstance {
+                        location: format!("{location}#{}", violation.pointer),
+                        reason: violation.reason,
+                    }),
+            );
+        }
+
+        if let Some(content_type) = self.effective_content_type(spec) {
+            #[cfg(feature = "tracing")]
+            let _span = tracing::debug_span!("rule", code = "content-type").entered();
+            let is_text = content_type_format(content_type) == Some(PayloadFormat::Text);
+            if is_text && !payload.is_string() {
+                errors.push(Error::ContentTypeMismatch {
+                    content_type: String::from(content_type),
+                });
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl AsyncApiV3Spec {
+    /// Returns the location of the messages defined in place in the channels and the
+    /// components of the document, leaving out references.
+    pub(crate) fn defined_messages(&self) -> Vec<(JsonPointer, &Message)> {
+        let mut messages = Vec::new();
+        let root = JsonPointer::default();
+        let components = root.join("components");
+        for (section, channels) in [
+            (&root, &self.channels),
+            (&components, &self.components.channels),
+        ] {
+            for (name, channel) in channels {
+                let Either::Right(channel) = channel else {
+                    continue;
+                };
+                let channel_pointer = section.join("channels").join(name.as_str());
+                for (key, message) in &channel.messages {
+                    if let Either::Right(message) = message {
+                        let pointer = channel_pointer.join("messages").join(key.as_str());
+                        messages.push((pointer, message));
+                    }
+                }
+            }
+        }
+        for (name, message) in &self.components.messages {
+            if let Either::Right(message) = message {
+                messages.push((components.join("messages").join(name.as_str()), message));
+            }
+        }
+        messages
+    }
+
+    /// Returns the names of the component messages which are not reachable from any channel
+    /// or operation, i.e. defined in the components but attached nowhere. References are
+    /// followed through channel messages and through references to other component messages.
+    pub fn unreachable_messages(&self) -> Vec<String> {
+        let channels = self
+            .channels
+            .values()
+            .chain(self.components.channels.values())
+            .filter_map(|channel| channel.resolve(self).ok())
+            .flat_map(|channel| channel.messages.values())
+            .filter_map(|message| match message {
+                Either::Left(reference) => Some(reference),
+                Either::Right(_) => None,
+            });
+        let operations = self
+            .operations
+            .values()
+            .chain(self.components.operations.values())
+            .filter_map(|operation| operation.resolve(self).ok());
+        let replies = operations
+            .clone()
+            .filter_map(|operation| operation.reply.as_ref())
+            .chain(self.components.replies.values())
+            .filter_map(|reply| reply.resolve(self).ok())
+            .flat_map(|reply| &reply.messages);
+        let operations = operations
+            .filter_map(|operation| operation.messages.as_ref())
+            .flatten();
+
+        let mut reachable = BTreeSet::new();
+        for reference in channels.chain(operations).chain(replies) {
+            self.reach_messages(reference, &mut reachable);
+        }
+        self.components
+            .messages
+            .keys()
+            .filter(|name| !reachable.contains(name.as_str()))
+            .cloned()
+            .collect()
+    }
+
+    /// Groups the keys of the messages by the [schema format](schema_format) of their payload,
+    /// for the component messages then the messages defined in channels. Messages without a
+    /// payload, or whose payload reference does not resolve, are left out.
+    pub fn messages_by_schema_format(&self) -> BTreeMap<String, Vec<&str>> {
+        let channel_messages = self
+            .channels
+            .values()
+            .chain(self.components.channels.values())
+            .filter_map(|channel| match channel {
+                Either::Right(channel) => Some(&channel.messages),
+                Either::Left(_) => None,
+            })
+            .flatten();
+        let mut formats = BTreeMap::<String, Vec<&str>>::new();
+        for (key, message) in self.components.messages.iter().chain(channel_messages) {
+            let Either::Right(message) = message else {
+                continue;
+            };
+            let Some(Ok(payload)) = message
+                .payload
+                .as_ref()
+                .map(|payload| payload.resolve(self))
+            else {
+                continue;
+            };
+            let format = schema_format(payload);
+            formats.entry(String::from(format)).or_default().push(key);
+        }
+        formats
+    }
+
+    /// Returns the distinct [schema formats](schema_format) of the payloads and headers of the
+    /// messages, i.e. the schema parsers needed to read the document. Schemas without an
+    /// explicit format count as the [DEFAULT_SCHEMA_FORMAT]; references which do not resolve
+    /// are left out.
+    pub fn schema_formats(&self) -> BTreeSet<String> {
+        self.defined_messages()
+            .into_iter()
+            .flat_map(|(_, message)| [&message.headers, &message.payload])
+            .filter_map(|schema| schema.as_ref()?.resolve(self).ok())
+            .map(|schema| String::from(schema_format(schema)))
+            .collect()
+    }
+
+    fn reach_messages<'a>(
+        &'a self,
+        reference: &'a ReferenceObject,
+        reachable: &mut BTreeSet<&'a str>,
+    ) {
+        let mut visited = Vec::new();
+        let mut current = reference;
+        while !visited.contains(&current) {
+            visited.push(current);
+            let Some(pointer) = current.local_pointer() else {
+                return;
+            };
+            if let [components, messages, name] = pointer.segments() {
+                if components == "components" && messages == "messages" {
+                    if let Some((name, _)) = self.components.messages.get_key_value(name.as_str()) {
+                        reachable.insert(name.as_str());
+                    }
+                }
+            }
+            match Message::lookup(self, &pointer) {
+                Some(Either::Left(next)) => current = next,
+                _ => return,
+            }
+        }
+    }
+}
+
+/// The schema format of the schemas which are not [Multi Format Schema
+/// Objects](MultiFormatSchema).
+pub const DEFAULT_SCHEMA_FORMAT: &str = "application/vnd.aai.asyncapi+json;version=3.0.0";
+
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 /// The Multi Format Schema Object represents a schema definition. It differs from the Schema Object in that it supports multiple schema formats or languages (e.g., JSON Schema, Avro, etc.).
@@ -64,6 +302,32 @@ pub struct MultiFormatSchema {
     pub schema: serde_json::Value,
 }
 
+impl MultiFormatSchema {
+    /// Returns `true` when the schema format declares a Protobuf schema, e.g.
+    /// `application/vnd.google.protobuf;version=3`.
+    pub fn is_protobuf(&self) -> bool {
+        self.schema_format
+            .trim()
+            .to_ascii_lowercase()
+            .starts_with("application/vnd.google.protobuf")
+    }
+
+    /// Returns `true` when the schema format declares a JSON schema, either the AsyncAPI
+    /// schema format or a plain JSON schema draft.
+    pub fn is_json_schema(&self) -> bool {
+        is_json_schema_format(&self.schema_format)
+    }
+}
+
+/// Returns `true` when a schema format declares a JSON schema, see
+/// [MultiFormatSchema::is_json_schema].
+pub(crate) fn is_json_schema_format(schema_format: &str) -> bool {
+    let schema_format = schema_format.trim().to_ascii_lowercase();
+    schema_format.starts_with("application/vnd.aai.asyncapi")
+        || schema_format.starts_with("application/schema+json")
+        || schema_format.starts_with("application/schema+yaml")
+}
+
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CorrelationId {
@@ -84,15 +348,36 @@ pub struct MessageBindings {
     pub nats: Option<NatsMessageBinding>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub http: Option<HttpMessageBinding>,
+    /// Bindings of protocols without a typed representation, keyed by protocol name.
+    #[serde(flatten, deserialize_with = "non_null_bindings")]
+    pub other: BTreeMap<String, serde_json::Value>,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
-pub struct WebSocketMessageBinding;
+impl BindingPatch for MessageBindings {}
 
-#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
-pub struct NatsMessageBinding;
+impl MessageBindings {
+    /// Returns an untyped view of all the bindings, including the typed ones, keyed by
+    /// protocol name. Useful to read bindings of protocols this crate does not model yet.
+    pub fn as_generic(&self) -> GenericBindings {
+        to_generic_bindings(self)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebSocketMessageBinding {
+    /// Fields of newer binding versions this crate does not model yet.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NatsMessageBinding {
+    /// Fields of newer binding versions this crate does not model yet.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
 
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -110,12 +395,43 @@ pub struct HttpMessageBinding {
     /// The version of this binding. If omitted, "latest" MUST be assumed.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub binding_version: Option<String>,
+    /// Fields of newer binding versions this crate does not model yet.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// The class of an HTTP response status code, given by its first digit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StatusClass {
+    /// `1xx`
+    Informational,
+    /// `2xx`
+    Success,
+    /// `3xx`
+    Redirect,
+    /// `4xx`
+    ClientError,
+    /// `5xx`
+    ServerError,
 }
 
 impl HttpMessageBinding {
     pub fn binding_version(&self) -> &str {
         self.binding_version.as_deref().unwrap_or("latest")
     }
+
+    /// Returns the class of the status code, `None` without a status code or for codes
+    /// outside `100..=599`.
+    pub fn status_class(&self) -> Option<StatusClass> {
+        match self.status_code?.get() {
+            100..=199 => Some(StatusClass::Informational),
+            200..=299 => Some(StatusClass::Success),
+            300..=399 => Some(StatusClass::Redirect),
+            400..=499 => Some(StatusClass::ClientError),
+            500..=599 => Some(StatusClass::ServerError),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -123,11 +439,13 @@ impl HttpMessageBinding {
 /// Message Example Object represents an example of a Message Object and MUST contain either headers and/or payload fields.
 pub struct MessageExample {
     /// The value of this field MUST validate against the Message Object's headers field.
-    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
-    pub headers: HashMap<String, serde_json::Value>,
-    /// The value of this field MUST validate against the Message Object's payload field.
-    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
-    pub payload: HashMap<String, serde_json::Value>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, serde_json::Value>,
+    /// The value of this field MUST validate against the Message Object's payload field. It can
+    /// be a reference to a value stored elsewhere in the document or in an external file, see
+    /// [MessageExample::resolve_payload].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<RefOr<Value>>,
     /// A machine-friendly name.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
@@ -136,6 +454,51 @@ pub struct MessageExample {
     pub summary: Option<String>,
 }
 
+impl MessageExample {
+    /// Returns the payload of the example, following a reference to another location of the
+    /// document, e.g. `#/components/schemas/order/examples/0`. External references are
+    /// unresolved, see [MessageExample::resolve_payload_with].
+    pub fn resolve_payload(&self, spec: &AsyncApiV3Spec) -> Result<Option<Cow<'_, Value>>, Error> {
+        self.resolve_payload_with(spec, |uri| {
+            Err(Error::UnresolvedReference {
+                reference: String::from(uri),
+            })
+        })
+    }
+
+    /// Returns the payload of the example, following references through the document or, for
+    /// external references, through the value returned by `load` for the reference without its
+    /// fragment, e.g. `examples/order.json` for `examples/order.json#/placed`.
+    pub fn resolve_payload_with(
+        &self,
+        spec: &AsyncApiV3Spec,
+        load: impl FnOnce(&str) -> Result<Value, Error>,
+    ) -> Result<Option<Cow<'_, Value>>, Error> {
+        let reference = match &self.payload {
+            None => return Ok(None),
+            Some(Either::Right(payload)) => return Ok(Some(Cow::Borrowed(payload))),
+            Some(Either::Left(reference)) => &reference.reference,
+        };
+        let (uri, fragment) = reference
+            .split_once('#')
+            .unwrap_or((reference.as_str(), ""));
+        let document = if uri.is_empty() {
+            serde_json::to_value(spec).map_err(|err| Error::Parse {
+                reason: err.to_string(),
+            })?
+        } else {
+            load(uri)?
+        };
+        JsonPointer::parse(fragment)
+            .ok()
+            .and_then(|pointer| pointer.evaluate(&document))
+            .map(|payload| Some(Cow::Owned(payload.clone())))
+            .ok_or_else(|| Error::UnresolvedReference {
+                reference: reference.clone(),
+            })
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 /// Describes a trait that MAY be applied to a Message Object. This object MAY contain any property from the Message Object, except payload and traits.
@@ -175,3 +538,427 @@ pub struct MessageTrait {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub examples: Vec<MessageExample>,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::parse_spec;
+    use serde_json::json;
+
+    const USER_SPEC: &str = r#"
+asyncapi: 3.0.0
+info:
+  title: Users
+  version: 1.0.0
+defaultContentType: application/json
+components:
+  messages:
+    userSignedUp:
+      headers:
+        type: object
+        required: [correlationId]
+        properties:
+          correlationId:
+            type: string
+      payload:
+        $ref: '#/components/schemas/user'
+    userNote:
+      contentType: text/plain
+      payload:
+        type: string
+  schemas:
+    user:
+      type: object
+      required: [id, email]
+      properties:
+        id:
+          type: integer
+          minimum: 1
+        email:
+          type: string
+        address:
+          type: object
+          properties:
+            city:
+              type: string
+"#;
+
+    #[test]
+    fn http_status_classes() {
+        let class = |status_code: u16| {
+            serde_json::from_value::<HttpMessageBinding>(serde_json::json!({
+                "statusCode": status_code
+            }))
+            .unwrap()
+            .status_class()
+        };
+
+        assert_eq!(class(200), Some(StatusClass::Success));
+        assert_eq!(class(404), Some(StatusClass::ClientError));
+        assert_eq!(class(503), Some(StatusClass::ServerError));
+        assert_eq!(class(600), None);
+    }
+
+    #[test]
+    fn content_type_defaults() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Lights
+  version: 1.0.0
+components:
+  messages:
+    lightMeasured:
+      payload:
+        type: object
+    lightLogged:
+      contentType: text/plain
+"#,
+        );
+        let message = |name: &str| spec.components.messages[name].resolve(&spec).unwrap();
+        let custom = ResolveOptions {
+            default_content_type: String::from("application/cbor"),
+        };
+
+        assert_eq!(message("lightMeasured").effective_content_type(&spec), None);
+        assert_eq!(
+            message("lightMeasured").effective_content_type_with(&spec, &ResolveOptions::default()),
+            "application/json"
+        );
+        assert_eq!(
+            message("lightMeasured").effective_content_type_with(&spec, &custom),
+            "application/cbor"
+        );
+        assert_eq!(
+            message("lightLogged").effective_content_type_with(&spec, &custom),
+            "text/plain"
+        );
+    }
+
+    #[test]
+    fn validate_conforming_instance() {
+        let spec = parse_spec(USER_SPEC);
+        let message = spec.components.messages["userSignedUp"]
+            .resolve(&spec)
+            .unwrap();
+
+        let result = message.validate_instance(
+            &json!({ "correlationId": "abc" }),
+            &json!({ "id": 1, "email": "jane@example.com", "address": { "city": "Milan" } }),
+            &spec,
+        );
+
+        assert!(result.is_ok(), "{result:?}");
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn validate_instance_traces_rules() {
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id};
+        use tracing_subscriber::layer::{Context, SubscriberExt};
+        use tracing_subscriber::Layer;
+
+        #[derive(Default)]
+        struct RuleRecorder(Arc<Mutex<Vec<String>>>);
+
+        impl Visit for RuleRecorder {
+            fn record_str(&mut self, field: &Field, value: &str) {
+                if field.name() == "code" {
+                    self.0.lock().unwrap().push(String::from(value));
+                }
+            }
+
+            fn record_debug(&mut self, _: &Field, _: &dyn core::fmt::Debug) {}
+        }
+
+        impl<S: tracing::Subscriber> Layer<S> for RuleRecorder {
+            fn on_new_span(&self, attrs: &Attributes<'_>, _: &Id, _: Context<'_, S>) {
+                if attrs.metadata().name() == "rule" {
+                    attrs.record(&mut RuleRecorder(self.0.clone()));
+                }
+            }
+        }
+
+        let spec = parse_spec(USER_SPEC);
+        let message = spec.components.messages["userSignedUp"]
+            .resolve(&spec)
+            .unwrap();
+        let rules = Arc::new(Mutex::new(Vec::new()));
+        let subscriber = tracing_subscriber::registry().with(RuleRecorder(rules.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            message
+                .validate_instance(
+                    &json!({ "correlationId": "abc" }),
+                    &json!({ "id": 1, "email": "jane@example.com" }),
+                    &spec,
+                )
+                .unwrap();
+        });
+
+        assert_eq!(
+            *rules.lock().unwrap(),
+            vec!["headers-schema", "payload-schema", "content-type"]
+        );
+    }
+
+    #[test]
+    fn validate_instance_with_invalid_payload() {
+        let spec = parse_spec(USER_SPEC);
+        let message = spec.components.messages["userSignedUp"]
+            .resolve(&spec)
+            .unwrap();
+
+        let errors = message
+            .validate_instance(
+                &json!({ "correlationId": "abc" }),
+                &json!({ "id": 0, "address": { "city": 42 } }),
+                &spec,
+            )
+            .unwrap_err();
+
+        let locations = errors
+            .iter()
+            .map(|error| match error {
+                Error::InvalidInstance { location, .. } => location.as_str(),
+                other => panic!("unexpected error {other}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            locations,
+            vec!["payload#", "payload#/address/city", "payload#/id"]
+        );
+    }
+
+    #[test]
+    fn validate_instance_content_type() {
+        let spec = parse_spec(USER_SPEC);
+        let message = spec.components.messages["userNote"].resolve(&spec).unwrap();
+
+        let errors = message
+            .validate_instance(&Value::Null, &json!({ "note": "hi" }), &spec)
+            .unwrap_err();
+
+        assert!(errors.iter().any(|error| matches!(
+            error,
+            Error::ContentTypeMismatch { content_type } if content_type == "text/plain"
+        )));
+
+        for (content_type, textual) in [
+            ("application/xml", true),
+            ("application/soap+xml; charset=utf-8", true),
+            ("text/csv", true),
+            ("text/json", false),
+            ("application/cloudevents+json", false),
+        ] {
+            let mut message = message.clone();
+            message.content_type = Some(String::from(content_type));
+            let mismatch = message
+                .validate_instance(&Value::Null, &json!({ "note": "hi" }), &spec)
+                .is_err_and(|errors| {
+                    errors
+                        .iter()
+                        .any(|error| matches!(error, Error::ContentTypeMismatch { .. }))
+                });
+            assert_eq!(mismatch, textual, "{content_type}");
+        }
+    }
+
+    #[test]
+    fn detached_message_is_unreachable() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Orders
+  version: 1.0.0
+channels:
+  orders:
+    address: orders
+    messages:
+      orderPlaced:
+        $ref: '#/components/messages/orderPlaced'
+operations:
+  placeOrder:
+    action: send
+    channel:
+      $ref: '#/channels/orders'
+    reply:
+      messages:
+        - $ref: '#/components/messages/orderAccepted'
+components:
+  messages:
+    orderPlaced:
+      $ref: '#/components/messages/orderEvent'
+    orderEvent:
+      payload:
+        type: object
+    orderAccepted:
+      payload:
+        type: object
+    legacyOrder:
+      payload:
+        type: object
+"#,
+        );
+
+        assert_eq!(spec.unreachable_messages(), vec!["legacyOrder"]);
+    }
+
+    #[test]
+    fn resolve_referenced_example_payload() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Orders
+  version: 1.0.0
+components:
+  messages:
+    orderPlaced:
+      payload:
+        $ref: '#/components/schemas/order'
+      examples:
+        - name: stored
+          payload:
+            $ref: '#/components/schemas/order/examples/0'
+        - name: external
+          payload:
+            $ref: 'examples/orders.json#/placed'
+        - name: inline
+          payload:
+            id: 7
+        - name: headersOnly
+          headers:
+            correlationId: abc
+  schemas:
+    order:
+      type: object
+      examples:
+        - id: 42
+"#,
+        );
+        let Either::Right(message) = &spec.components.messages["orderPlaced"] else {
+            panic!("inline message");
+        };
+        let [stored, external, inline, headers_only] = &message.examples[..] else {
+            panic!("four examples");
+        };
+
+        assert_eq!(
+            stored.resolve_payload(&spec).unwrap().as_deref(),
+            Some(&json!({ "id": 42 }))
+        );
+        assert!(matches!(
+            external.resolve_payload(&spec),
+            Err(Error::UnresolvedReference { reference }) if reference == "examples/orders.json"
+        ));
+        assert_eq!(
+            external
+                .resolve_payload_with(&spec, |uri| {
+                    assert_eq!(uri, "examples/orders.json");
+                    Ok(json!({ "placed": { "id": 1 } }))
+                })
+                .unwrap()
+                .as_deref(),
+            Some(&json!({ "id": 1 }))
+        );
+        assert_eq!(
+            inline.resolve_payload(&spec).unwrap().as_deref(),
+            Some(&json!({ "id": 7 }))
+        );
+        assert_eq!(headers_only.resolve_payload(&spec).unwrap(), None);
+    }
+
+    #[test]
+    fn group_messages_by_schema_format() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Orders
+  version: 1.0.0
+channels:
+  orders:
+    address: orders
+    messages:
+      orderPlaced:
+        payload:
+          schemaFormat: application/vnd.apache.avro;version=1.9.0
+          schema:
+            type: record
+            name: OrderPlaced
+            fields: []
+      orderShipped:
+        $ref: '#/components/messages/orderShipped'
+components:
+  messages:
+    orderShipped:
+      payload:
+        type: object
+"#,
+        );
+
+        let formats = spec.messages_by_schema_format();
+
+        assert_eq!(
+            formats,
+            BTreeMap::from([
+                (
+                    String::from("application/vnd.aai.asyncapi+json;version=3.0.0"),
+                    vec!["orderShipped"]
+                ),
+                (
+                    String::from("application/vnd.apache.avro;version=1.9.0"),
+                    vec!["orderPlaced"]
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn distinct_schema_formats() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Orders
+  version: 1.0.0
+channels:
+  orders:
+    address: orders
+    messages:
+      orderPlaced:
+        headers:
+          type: object
+        payload:
+          schemaFormat: application/vnd.apache.avro;version=1.9.0
+          schema:
+            type: record
+            name: OrderPlaced
+            fields: []
+      orderShipped:
+        $ref: '#/components/messages/orderShipped'
+components:
+  messages:
+    orderShipped:
+      payload:
+        $ref: '#/components/schemas/shipment'
+  schemas:
+    shipment:
+      type: object
+"#,
+        );
+
+        assert_eq!(
+            spec.schema_formats(),
+            BTreeSet::from([
+                String::from(DEFAULT_SCHEMA_FORMAT),
+                String::from("application/vnd.apache.avro;version=1.9.0"),
+            ])
+        );
+    }
+}
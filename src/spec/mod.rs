@@ -1,18 +1,44 @@
 //! Contains the types present in a specification.
+use crate::compat::detect_v2_constructs;
+use crate::error::Error;
 use crate::spec::channel::Channels;
 use crate::spec::component::Components;
 use crate::spec::info::Info;
-use crate::spec::operation::Operations;
+use crate::spec::operation::{keyed_operations, Operations};
 use crate::spec::server::Servers;
+use alloc::string::{String, ToString};
 
 pub mod channel;
 pub mod common;
 pub mod component;
+pub mod debug;
+pub mod delivery;
+pub mod deprecation;
+pub mod effective;
+pub mod expression;
+pub mod extract;
+pub mod hoist;
+pub mod index;
 pub mod info;
+pub mod library;
+pub mod lint;
+pub mod merge;
 pub mod message;
 pub mod operation;
+pub mod partial;
+pub mod resolve;
+pub mod schema;
 pub mod security;
+pub mod serialization;
 pub mod server;
+#[cfg(target_has_atomic = "ptr")]
+pub mod shared;
+pub mod summary;
+pub mod traits;
+pub mod uid;
+
+use core::str::FromStr;
+use serde::Deserialize;
 
 /// Enum to store a versioned instance of the specification.
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -23,6 +49,46 @@ pub enum AsyncApiSpec {
     V3_0_0(AsyncApiV3Spec),
 }
 
+impl FromStr for AsyncApiSpec {
+    type Err = Error;
+
+    /// Parses a JSON document. When the document cannot be deserialized, the error lists the
+    /// AsyncAPI 2 constructs found in it, if any.
+    fn from_str(document: &str) -> Result<Self, Self::Err> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("parse", bytes = document.len()).entered();
+        let value =
+            serde_json::from_str::<serde_json::Value>(document).map_err(|err| Error::Parse {
+                reason: err.to_string(),
+            })?;
+        Self::from_value(&value)
+    }
+}
+
+impl AsyncApiSpec {
+    /// Parses a YAML document, like [FromStr] does for JSON. Aliases are replaced by the
+    /// anchored values before mapping the document, so the expanded values are kept when
+    /// serializing it back.
+    #[cfg(feature = "yaml")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "yaml")))]
+    pub fn from_yaml(document: &str) -> Result<Self, Error> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("parse", bytes = document.len(), yaml = true).entered();
+        let parse_error = |reason: String| Error::Parse { reason };
+        let value = serde_yaml::from_str::<serde_yaml::Value>(document)
+            .map_err(|err| parse_error(err.to_string()))?;
+        let value = serde_json::to_value(value).map_err(|err| parse_error(err.to_string()))?;
+        Self::from_value(&value)
+    }
+
+    fn from_value(value: &serde_json::Value) -> Result<Self, Error> {
+        Self::deserialize(value).map_err(|err| Error::InvalidDocument {
+            reason: err.to_string(),
+            hints: detect_v2_constructs(value),
+        })
+    }
+}
+
 /// Root type of an AsyncAPI 3 specification.
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -42,9 +108,115 @@ pub struct AsyncApiV3Spec {
     #[serde(default)]
     pub channels: Channels,
     /// The operations this application MUST implement.
-    #[serde(default)]
+    #[serde(default, deserialize_with = "keyed_operations")]
     pub operations: Operations,
     /// An element to hold various reusable objects for the specification. Everything that is defined inside this object represents a resource that MAY or MAY NOT be used in the rest of the document and MAY or MAY NOT be used by the implemented Application.
     #[serde(default)]
     pub components: Components,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::compat::V2ConstructKind;
+
+    #[test]
+    fn from_str_reports_v2_constructs() {
+        let document = r#"{
+            "asyncapi": "3.0.0",
+            "info": { "title": "Legacy", "version": "1.0.0" },
+            "servers": {
+                "production": { "url": "mqtt://broker.example.com", "protocol": "mqtt" }
+            },
+            "channels": {
+                "lights": { "publish": { "message": { "payload": { "type": "string" } } } }
+            }
+        }"#;
+
+        let err = document.parse::<AsyncApiSpec>().unwrap_err();
+
+        let Error::InvalidDocument { hints, .. } = &err else {
+            panic!("unexpected error {err}");
+        };
+        let kinds = hints.iter().map(|hint| hint.kind).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![V2ConstructKind::ChannelPublish, V2ConstructKind::ServerUrl]
+        );
+        assert!(err
+            .to_string()
+            .contains("/servers/production/url: AsyncAPI 2 `url`, split the url"));
+    }
+
+    #[test]
+    fn from_str_parses_v3_document() {
+        let document = r#"{
+            "asyncapi": "3.0.0",
+            "info": { "title": "Lights", "version": "1.0.0" },
+            "channels": { "lights": { "address": "lights", "messages": {} } }
+        }"#;
+
+        let AsyncApiSpec::V3_0_0(spec) = document.parse::<AsyncApiSpec>().unwrap();
+
+        assert_eq!(spec.info.title, "Lights");
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn from_yaml_keeps_quoted_merge_keys() {
+        let document = r#"
+asyncapi: 3.0.0
+info:
+  title: Shifts
+  version: 1.0.0
+components:
+  schemas:
+    operators:
+      type: object
+      properties:
+        "<<":
+          type: string
+"#;
+
+        let AsyncApiSpec::V3_0_0(spec) = AsyncApiSpec::from_yaml(document).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&spec.components.schemas["operators"]).unwrap()["properties"],
+            serde_json::json!({ "<<": { "type": "string" } })
+        );
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn from_yaml_expands_aliases() {
+        let document = r#"
+asyncapi: 3.0.0
+info:
+  title: Lights
+  version: 1.0.0
+servers:
+  production:
+    host: '{region}.example.com'
+    protocol: mqtt
+    variables: &regions
+      region:
+        enum: [eu, us]
+        default: eu
+  staging:
+    host: '{region}.staging.example.com'
+    protocol: mqtt
+    variables: *regions
+"#;
+
+        let AsyncApiSpec::V3_0_0(spec) = AsyncApiSpec::from_yaml(document).unwrap();
+
+        let production = spec.servers["production"].resolve(&spec).unwrap();
+        let staging = spec.servers["staging"].resolve(&spec).unwrap();
+        assert_eq!(staging.variables, production.variables);
+        let serialized = serde_json::to_value(&spec).unwrap();
+        assert_eq!(
+            serialized["servers"]["staging"]["variables"]["region"]["default"],
+            "eu"
+        );
+    }
+}
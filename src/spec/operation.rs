@@ -1,10 +1,49 @@
 //! Contains types related to the [operations
 //! field](https://www.asyncapi.com/docs/concepts/asyncapi-document/structure#operations-field)
-use crate::spec::common::{ExternalDocumentation, RefOr, ReferenceObject, Tag};
-use crate::spec::security::SecurityScheme;
-use std::collections::HashMap;
+use crate::error::Error;
+use crate::spec::channel::Channel;
+use crate::spec::common::{
+    non_null_bindings, to_generic_bindings, BindingPatch, Extensions, ExternalDocumentation,
+    GenericBindings, RefOr, ReferenceObject, Tag,
+};
+use crate::spec::expression::RuntimeExpression;
+use crate::spec::message::{Message, MessageBindings};
+use crate::spec::security::{SecurityRequirements, SecurityScheme};
+use crate::spec::AsyncApiV3Spec;
+use alloc::borrow::Cow;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::time::Duration;
+use serde::de::{Deserialize, Error as _};
+use serde_json::{Map, Value};
 
-pub type Operations = HashMap<String, RefOr<Operation>>;
+/// Extension declaring, in milliseconds, how long a requester should wait for the reply of an
+/// operation. It can be set either on the [Operation] or on its [OperationReply].
+pub const REPLY_TIMEOUT_EXTENSION: &str = "x-reply-timeout-ms";
+
+pub type Operations = BTreeMap<String, RefOr<Operation>>;
+
+/// Deserializes a map of operations, naming the operation in the error of an invalid one.
+pub(crate) fn keyed_operations<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Operations, D::Error> {
+    let entries = <BTreeMap<String, Value> as serde::Deserialize>::deserialize(deserializer)?;
+    entries
+        .into_iter()
+        .map(|(key, entry)| {
+            let invalid =
+                |err: serde_json::Error| D::Error::custom(format!("operation `{key}`: {err}"));
+            // The untagged reference-or-operation would hide the error of the action.
+            if let Some(action) = entry.get("action") {
+                OperationAction::deserialize(action).map_err(invalid)?;
+            }
+            let operation = serde_json::from_value(entry).map_err(invalid)?;
+            Ok((key, operation))
+        })
+        .collect()
+}
 
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -44,15 +83,177 @@ pub struct Operation {
     /// The definition of the reply in a request-reply operation.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub reply: Option<RefOr<OperationReply>>,
+    /// Specification extensions of the operation.
+    #[serde(flatten)]
+    pub extensions: Extensions,
+}
+
+impl Operation {
+    /// Returns the reply timeout declared through the [REPLY_TIMEOUT_EXTENSION] on the operation
+    /// or, if absent, on its reply.
+    pub fn reply_timeout(&self, spec: &AsyncApiV3Spec) -> Option<Duration> {
+        self.extensions
+            .get_u64(REPLY_TIMEOUT_EXTENSION)
+            .or_else(|| {
+                let reply = self.reply.as_ref()?.resolve(spec).ok()?;
+                reply.extensions.get_u64(REPLY_TIMEOUT_EXTENSION)
+            })
+            .map(Duration::from_millis)
+    }
+
+    /// Returns the resolved messages of the operation. When no message is listed, all the
+    /// messages of the operation channel are returned. References which cannot be resolved are
+    /// skipped.
+    pub fn resolved_messages<'a>(&'a self, spec: &'a AsyncApiV3Spec) -> Vec<&'a Message> {
+        match &self.messages {
+            Some(messages) if !messages.is_empty() => messages
+                .iter()
+                .filter_map(|message| message.resolve::<Message>(spec).ok())
+                .collect(),
+            _ => self
+                .channel
+                .resolve::<Channel>(spec)
+                .map(|channel| {
+                    channel
+                        .messages
+                        .values()
+                        .filter_map(|message| message.resolve(spec).ok())
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Returns the names of the root servers a client must connect to in order to perform the
+    /// operation, i.e. the servers its channel is available on, sorted by name. Empty if the
+    /// channel cannot be resolved.
+    pub fn required_servers<'a>(&'a self, spec: &'a AsyncApiV3Spec) -> Vec<&'a str> {
+        let Ok(channel) = self.channel.resolve::<Channel>(spec) else {
+            return Vec::new();
+        };
+        spec.servers
+            .keys()
+            .map(String::as_str)
+            .filter(|server| channel.is_available_on(server))
+            .collect()
+    }
+
+    /// Returns the security schemes a client must satisfy to perform the operation: those of
+    /// its [required servers](Operation::required_servers) and its own. References which cannot
+    /// be resolved are skipped.
+    pub fn security_requirements<'a>(
+        &'a self,
+        spec: &'a AsyncApiV3Spec,
+    ) -> SecurityRequirements<'a> {
+        SecurityRequirements {
+            servers: server_security(self.channel.resolve::<Channel>(spec).ok(), spec),
+            operation: self
+                .security
+                .iter()
+                .filter_map(|scheme| scheme.resolve(spec).ok())
+                .collect(),
+        }
+    }
+
+    /// Returns the distinct [effective content
+    /// types](Message::effective_content_type) of the operation messages, i.e. the formats a
+    /// client performing the operation must be able to serialize.
+    pub fn content_types(&self, spec: &AsyncApiV3Spec) -> BTreeSet<String> {
+        self.resolved_messages(spec)
+            .into_iter()
+            .filter_map(|message| message.effective_content_type(spec))
+            .map(String::from)
+            .collect()
+    }
+}
+
+impl AsyncApiV3Spec {
+    /// Returns the reply loops of the root operations, as the keys of the operations of each
+    /// loop. An operation leads to another when its reply is sent on the channel of the other,
+    /// e.g. `A` replying on the channel of `B`, which replies on the channel of `A`, gives
+    /// `["A", "B"]`.
+    ///
+    /// Every loop is returned once, starting with its smallest key, and loops are sorted.
+    /// Operations replying on their own channel, the usual request-reply, are not loops.
+    pub fn reply_cycles(&self) -> Vec<Vec<String>> {
+        let operations = self
+            .operations
+            .iter()
+            .filter_map(|(key, operation)| Some((key.as_str(), operation.resolve(self).ok()?)))
+            .collect::<Vec<_>>();
+        let channel = |reference: &ReferenceObject| reference.resolve::<Channel>(self).ok();
+        let next = operations
+            .iter()
+            .map(|(key, operation)| {
+                let reply_channel = operation
+                    .reply
+                    .as_ref()
+                    .and_then(|reply| reply.resolve(self).ok())
+                    .and_then(|reply| reply.channel.as_ref())
+                    .and_then(channel);
+                let targets = operations
+                    .iter()
+                    .filter(|(target, other)| {
+                        target != key
+                            && reply_channel.is_some_and(|reply_channel| {
+                                channel(&other.channel)
+                                    .is_some_and(|other| core::ptr::eq(reply_channel, other))
+                            })
+                    })
+                    .map(|(target, _)| *target)
+                    .collect::<Vec<_>>();
+                (*key, targets)
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        // Each loop is found from its smallest key, only visiting greater keys.
+        fn walk<'a>(
+            next: &BTreeMap<&'a str, Vec<&'a str>>,
+            start: &'a str,
+            path: &mut Vec<&'a str>,
+            cycles: &mut Vec<Vec<String>>,
+        ) {
+            let current = path[path.len() - 1];
+            for &target in next.get(current).into_iter().flatten() {
+                if target == start {
+                    cycles.push(path.iter().map(|key| String::from(*key)).collect());
+                } else if target > start && !path.contains(&target) {
+                    path.push(target);
+                    walk(next, start, path, cycles);
+                    path.pop();
+                }
+            }
+        }
+        let mut cycles = Vec::new();
+        for start in next.keys() {
+            walk(&next, start, &mut Vec::from([*start]), &mut cycles);
+        }
+        cycles.sort();
+        cycles
+    }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+/// The kind of an operation. Ordered by declaration: `Send` before `Receive`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize)]
 #[serde(rename_all = "camelCase")]
 pub enum OperationAction {
     Send,
     Receive,
 }
 
+impl<'de> serde::Deserialize<'de> for OperationAction {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let action = <Cow<'de, str> as serde::Deserialize>::deserialize(deserializer)?;
+        match &*action {
+            "send" => Ok(Self::Send),
+            "receive" => Ok(Self::Receive),
+            other => Err(D::Error::custom(format!(
+                "invalid action `{other}`, expected `send` or `receive`"
+            ))),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct OperationBindings {
@@ -63,9 +264,24 @@ pub struct OperationBindings {
     pub nats: Option<NatsOperationBinding>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub http: Option<HttpOperationBinding>,
+    /// Bindings of protocols without a typed representation, keyed by protocol name.
+    #[serde(flatten, deserialize_with = "non_null_bindings")]
+    pub other: BTreeMap<String, serde_json::Value>,
+}
+
+impl BindingPatch for OperationBindings {}
+
+impl OperationBindings {
+    /// Returns an untyped view of all the bindings, including the typed ones, keyed by
+    /// protocol name. Useful to read bindings of protocols this crate does not model yet.
+    pub fn as_generic(&self) -> GenericBindings {
+        to_generic_bindings(self)
+    }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum HttpOperationMethod {
     Get,
@@ -80,7 +296,7 @@ pub enum HttpOperationMethod {
 }
 
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
 pub struct HttpOperationBinding {
     /// The HTTP method for the request.
     pub method: HttpOperationMethod,
@@ -91,14 +307,21 @@ pub struct HttpOperationBinding {
     /// The version of this binding. If omitted, "latest" MUST be assumed.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub binding_version: Option<String>,
+    /// Fields of newer binding versions this crate does not model yet.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
-pub struct WebSocketOperationBinding;
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebSocketOperationBinding {
+    /// Fields of newer binding versions this crate does not model yet.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
 
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
 pub struct NatsOperationBinding {
     /// Defines the name of the queue to use.
     /// It MUST NOT exceed 255 characters.
@@ -106,6 +329,9 @@ pub struct NatsOperationBinding {
     /// The version of this binding. If omitted, "latest" MUST be assumed.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub binding_version: Option<String>,
+    /// Fields of newer binding versions this crate does not model yet.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 impl NatsOperationBinding {
@@ -155,6 +381,95 @@ pub struct OperationReply {
     /// A list of $ref pointers pointing to the supported Message Objects that can be processed by this operation as reply. It MUST contain a subset of the messages defined in the channel referenced in this operation reply, and MUST NOT point to a subset of message definitions located in the Components Object or anywhere else. Every message processed by this operation MUST be valid against one, and only one, of the message objects referenced in this list. Please note the messages property value MUST be a list of Reference Objects and, therefore, MUST NOT contain Message Objects. However, it is RECOMMENDED that parsers (or other software) dereference this property for a better development experience.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub messages: Vec<ReferenceObject>,
+    /// Specification extensions of the reply.
+    #[serde(flatten)]
+    pub extensions: Extensions,
+}
+
+impl OperationReply {
+    /// Returns the resolved reply messages. When no message is listed, all the messages of the
+    /// reply channel are returned. References which cannot be resolved are skipped.
+    pub fn resolved_messages<'a>(&'a self, spec: &'a AsyncApiV3Spec) -> Vec<&'a Message> {
+        if !self.messages.is_empty() {
+            return self
+                .messages
+                .iter()
+                .filter_map(|message| message.resolve::<Message>(spec).ok())
+                .collect();
+        }
+        self.channel
+            .as_ref()
+            .and_then(|channel| channel.resolve::<Channel>(spec).ok())
+            .map(|channel| {
+                channel
+                    .messages
+                    .values()
+                    .filter_map(|message| message.resolve(spec).ok())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns the security schemes of the root servers the reply channel is available on, which
+    /// a client receiving the reply must satisfy on top of the [requirements of the
+    /// operation](Operation::security_requirements). Empty if there is no reply channel or it
+    /// cannot be resolved.
+    pub fn security_requirements<'a>(
+        &'a self,
+        spec: &'a AsyncApiV3Spec,
+    ) -> SecurityRequirements<'a> {
+        let channel = self
+            .channel
+            .as_ref()
+            .and_then(|channel| channel.resolve::<Channel>(spec).ok());
+        SecurityRequirements {
+            servers: server_security(channel, spec),
+            operation: Vec::new(),
+        }
+    }
+
+    /// Returns the distinct HTTP status codes declared by the HTTP bindings of the reply
+    /// messages, in declaration order. Empty if the reply messages have no HTTP binding.
+    pub fn expected_status_codes(&self, spec: &AsyncApiV3Spec) -> Vec<u16> {
+        let mut status_codes = Vec::new();
+        for message in self.resolved_messages(spec) {
+            let status_code = message
+                .bindings
+                .as_ref()
+                .and_then(|bindings| bindings.resolve(spec).ok())
+                .and_then(|bindings: &MessageBindings| bindings.http.as_ref())
+                .and_then(|http| http.status_code);
+            if let Some(status_code) = status_code {
+                if !status_codes.contains(&status_code.get()) {
+                    status_codes.push(status_code.get());
+                }
+            }
+        }
+        status_codes
+    }
+}
+
+/// Returns the schemes of the root servers a channel is available on, by server name.
+fn server_security<'a>(
+    channel: Option<&Channel>,
+    spec: &'a AsyncApiV3Spec,
+) -> BTreeMap<&'a str, Vec<&'a SecurityScheme>> {
+    let Some(channel) = channel else {
+        return BTreeMap::new();
+    };
+    spec.servers
+        .iter()
+        .filter(|(name, _)| channel.is_available_on(name))
+        .filter_map(|(name, server)| Some((name.as_str(), server.resolve(spec).ok()?)))
+        .map(|(name, server)| {
+            let schemes = server
+                .security
+                .iter()
+                .filter_map(|scheme| scheme.resolve(spec).ok())
+                .collect();
+            (name, schemes)
+        })
+        .collect()
 }
 
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -168,3 +483,335 @@ pub struct OperationReplyAddress {
     /// A runtime expression that specifies the location of the reply address.
     pub location: String,
 }
+
+impl OperationReplyAddress {
+    /// Applies the location expression to a concrete message, represented as a JSON object with
+    /// `headers` and `payload` fields, returning the reply address it designates.
+    pub fn evaluate(&self, message: &Value) -> Result<Value, Error> {
+        let expression = self.location.parse::<RuntimeExpression>()?;
+        expression
+            .evaluate(message)
+            .cloned()
+            .ok_or_else(|| Error::UnmatchedRuntimeExpression {
+                expression: String::from(&self.location),
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::spec::AsyncApiSpec;
+    use crate::test_util::parse_spec;
+
+    const REPLY_SPEC: &str = r#"
+asyncapi: 3.0.0
+info:
+  title: Orders
+  version: 1.0.0
+channels:
+  createOrder:
+    address: /orders
+    messages:
+      createOrder:
+        payload:
+          type: object
+  orderAccepted:
+    address: /orders/accepted
+    messages:
+      orderAccepted:
+        $ref: '#/components/messages/orderAccepted'
+operations:
+  createOrder:
+    action: send
+    channel:
+      $ref: '#/channels/createOrder'
+    x-reply-timeout-ms: 1500
+    reply:
+      $ref: '#/components/replies/orderAccepted'
+  placeOrder:
+    action: send
+    channel:
+      $ref: '#/channels/createOrder'
+components:
+  replies:
+    orderAccepted:
+      channel:
+        $ref: '#/channels/orderAccepted'
+      messages:
+        - $ref: '#/channels/orderAccepted/messages/orderAccepted'
+  messages:
+    orderAccepted:
+      payload:
+        type: object
+      bindings:
+        http:
+          statusCode: 202
+"#;
+
+    #[test]
+    fn operation_action_order() {
+        let actions: BTreeMap<OperationAction, &str> = [
+            (OperationAction::Receive, "receive"),
+            (OperationAction::Send, "send"),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            actions.into_values().collect::<Vec<_>>(),
+            vec!["send", "receive"]
+        );
+    }
+
+    #[test]
+    fn reply_expected_status_codes() {
+        let spec = parse_spec(REPLY_SPEC);
+        let RefOr::Right(operation) = &spec.operations["createOrder"] else {
+            panic!("inline operation expected");
+        };
+        let reply = operation.reply.as_ref().unwrap().resolve(&spec).unwrap();
+
+        assert_eq!(reply.expected_status_codes(&spec), vec![202]);
+    }
+
+    #[test]
+    fn operation_content_types() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Lights
+  version: 1.0.0
+defaultContentType: application/json
+channels:
+  lights:
+    address: lights
+    messages:
+      lightMeasured:
+        payload:
+          type: object
+      lightMeasuredAvro:
+        contentType: application/avro
+        payload:
+          type: object
+operations:
+  onLightMeasured:
+    action: receive
+    channel:
+      $ref: '#/channels/lights'
+  onLightMeasuredAvro:
+    action: receive
+    channel:
+      $ref: '#/channels/lights'
+    messages:
+      - $ref: '#/channels/lights/messages/lightMeasuredAvro'
+"#,
+        );
+        let RefOr::Right(all) = &spec.operations["onLightMeasured"] else {
+            panic!("inline operation expected");
+        };
+        let RefOr::Right(avro) = &spec.operations["onLightMeasuredAvro"] else {
+            panic!("inline operation expected");
+        };
+
+        assert_eq!(
+            all.content_types(&spec).into_iter().collect::<Vec<_>>(),
+            vec!["application/avro", "application/json"]
+        );
+        assert_eq!(
+            avro.content_types(&spec).into_iter().collect::<Vec<_>>(),
+            vec!["application/avro"]
+        );
+    }
+
+    #[test]
+    fn operation_required_servers() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Lights
+  version: 1.0.0
+servers:
+  production:
+    host: kafka.example.com
+    protocol: kafka
+  staging:
+    host: staging.example.com
+    protocol: kafka
+  test:
+    host: test.example.com
+    protocol: kafka
+channels:
+  lights:
+    address: lights
+    servers:
+      - $ref: '#/servers/staging'
+      - $ref: '#/servers/production'
+    messages: {}
+operations:
+  turnOn:
+    action: send
+    channel:
+      $ref: '#/channels/lights'
+"#,
+        );
+        let RefOr::Right(operation) = &spec.operations["turnOn"] else {
+            panic!("inline operation expected");
+        };
+
+        assert_eq!(
+            operation.required_servers(&spec),
+            vec!["production", "staging"]
+        );
+    }
+
+    #[test]
+    fn reply_timeout_extension() {
+        let spec = parse_spec(REPLY_SPEC);
+        let RefOr::Right(with_timeout) = &spec.operations["createOrder"] else {
+            panic!("inline operation expected");
+        };
+        let RefOr::Right(without_timeout) = &spec.operations["placeOrder"] else {
+            panic!("inline operation expected");
+        };
+
+        assert_eq!(
+            with_timeout.reply_timeout(&spec),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(without_timeout.reply_timeout(&spec), None);
+    }
+
+    #[test]
+    fn evaluate_reply_address_from_header() {
+        let address = OperationReplyAddress {
+            description: None,
+            location: String::from("$message.header#/replyTo"),
+        };
+        let message = serde_json::json!({
+            "headers": { "replyTo": "orders.replies.42" },
+            "payload": { "id": 42 }
+        });
+
+        assert_eq!(address.evaluate(&message).unwrap(), "orders.replies.42");
+    }
+
+    #[test]
+    fn evaluate_reply_address_from_payload_pointer() {
+        let address = OperationReplyAddress {
+            description: None,
+            location: String::from("$message.payload#/routing/destinations/1"),
+        };
+        let message = serde_json::json!({
+            "payload": { "routing": { "destinations": ["primary", "fallback"] } }
+        });
+
+        assert_eq!(address.evaluate(&message).unwrap(), "fallback");
+    }
+
+    #[test]
+    fn evaluate_reply_address_errors() {
+        let message = serde_json::json!({ "headers": {} });
+        let missing = OperationReplyAddress {
+            description: None,
+            location: String::from("$message.header#/replyTo"),
+        };
+        let invalid = OperationReplyAddress {
+            description: None,
+            location: String::from("$request.header#/replyTo"),
+        };
+
+        assert!(matches!(
+            missing.evaluate(&message),
+            Err(Error::UnmatchedRuntimeExpression { .. })
+        ));
+        assert!(matches!(
+            invalid.evaluate(&message),
+            Err(Error::InvalidRuntimeExpression { .. })
+        ));
+    }
+
+    #[test]
+    fn reply_extension_round_trip() {
+        let spec = parse_spec(REPLY_SPEC);
+        let RefOr::Right(operation) = &spec.operations["createOrder"] else {
+            panic!("inline operation expected");
+        };
+        let serialized = serde_json::to_value(operation).unwrap();
+
+        assert_eq!(serialized["x-reply-timeout-ms"], 1500);
+    }
+
+    #[test]
+    fn invalid_action_names_the_operation() {
+        let document = serde_json::json!({
+            "asyncapi": "3.0.0",
+            "info": { "title": "Orders", "version": "1.0.0" },
+            "channels": { "orders": { "address": "orders", "messages": {} } },
+            "operations": {
+                "placeOrder": {
+                    "action": "sned",
+                    "channel": { "$ref": "#/channels/orders" }
+                }
+            }
+        });
+
+        let error = document.to_string().parse::<AsyncApiSpec>().unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Invalid document: operation `placeOrder`: invalid action `sned`, expected `send` or `receive`"
+        );
+    }
+
+    #[test]
+    fn two_operations_replying_to_each_other() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Ping pong
+  version: 1.0.0
+channels:
+  ping:
+    address: ping
+    messages: {}
+  pong:
+    address: pong
+    messages: {}
+  status:
+    address: status
+    messages: {}
+operations:
+  sendPing:
+    action: send
+    channel:
+      $ref: '#/channels/ping'
+    reply:
+      channel:
+        $ref: '#/channels/pong'
+  sendPong:
+    action: send
+    channel:
+      $ref: '#/channels/pong'
+    reply:
+      channel:
+        $ref: '#/channels/ping'
+  requestStatus:
+    action: send
+    channel:
+      $ref: '#/channels/status'
+    reply:
+      channel:
+        $ref: '#/channels/status'
+"#,
+        );
+
+        assert_eq!(
+            spec.reply_cycles(),
+            vec![vec![String::from("sendPing"), String::from("sendPong")]]
+        );
+    }
+}
@@ -0,0 +1,446 @@
+//! Error tolerant parsing, keeping whatever can be read from a broken document.
+//!
+//! The top-level sections, each server, channel and operation and each components entry are
+//! deserialized independently. Nodes which fail are replaced by a [Placeholder] holding their
+//! raw value, and a [ParseIssue] locates the failure.
+use crate::spec::channel::Channel;
+use crate::spec::common::{JsonPointer, RefOr, ReferenceObject};
+use crate::spec::component::Components;
+use crate::spec::info::Info;
+use crate::spec::operation::Operation;
+use crate::spec::server::Server;
+use crate::spec::AsyncApiV3Spec;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// Options of [AsyncApiV3Spec::parse_partial].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Reports an issue when the `asyncapi` field is missing or is not `3.0.0`.
+    pub check_version: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            check_version: true,
+        }
+    }
+}
+
+/// A problem found while parsing a document partially.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseIssue {
+    /// Location of the offending node in the document.
+    pub pointer: JsonPointer,
+    /// Description of the problem.
+    pub message: String,
+}
+
+impl fmt::Display for ParseIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.pointer, self.message)
+    }
+}
+
+/// Raw content of a node which could not be deserialized.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Placeholder {
+    /// The value found in the document, `null` when the node is missing.
+    pub raw: Value,
+    /// Why the value could not be deserialized.
+    pub error: String,
+}
+
+/// A node of a [PartialDocument], either deserialized or replaced by a placeholder.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Node<T> {
+    /// The node was deserialized successfully.
+    Parsed(T),
+    /// The node could not be deserialized.
+    Placeholder(Placeholder),
+}
+
+impl<T> Node<T> {
+    /// Returns the deserialized node, if any.
+    pub fn parsed(&self) -> Option<&T> {
+        match self {
+            Node::Parsed(node) => Some(node),
+            Node::Placeholder(_) => None,
+        }
+    }
+
+    /// Returns `true` if the node could not be deserialized.
+    pub fn is_placeholder(&self) -> bool {
+        matches!(self, Node::Placeholder(_))
+    }
+
+    fn map<U>(self, f: impl FnOnce(T) -> U) -> Node<U> {
+        match self {
+            Node::Parsed(node) => Node::Parsed(f(node)),
+            Node::Placeholder(placeholder) => Node::Placeholder(placeholder),
+        }
+    }
+}
+
+/// A document parsed by [AsyncApiV3Spec::parse_partial].
+///
+/// It converts into an [AsyncApiV3Spec] through [TryFrom] once no placeholders remain.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PartialDocument {
+    /// Identifier of the application the AsyncAPI document is defining.
+    pub id: Option<String>,
+    /// Metadata about the API.
+    pub info: Node<Info>,
+    /// Connection details of servers.
+    pub servers: BTreeMap<String, Node<RefOr<Server>>>,
+    /// Default content type to use when encoding/decoding a message's payload.
+    pub default_content_type: Option<String>,
+    /// The channels used by this application.
+    pub channels: BTreeMap<String, Node<RefOr<Channel>>>,
+    /// The operations this application MUST implement.
+    pub operations: BTreeMap<String, Node<RefOr<Operation>>>,
+    /// The components entries which could be deserialized.
+    pub components: Components,
+    /// The components entries which could not be deserialized, keyed by their location.
+    pub invalid_components: BTreeMap<JsonPointer, Placeholder>,
+    /// The nodes left out of a filtered parse, whose content is unknown. A skipped `info` is a
+    /// placeholder, skipped sections and entries are missing from the document.
+    pub skipped: Vec<JsonPointer>,
+}
+
+impl PartialDocument {
+    /// Returns the number of nodes replaced by placeholders.
+    pub fn placeholders(&self) -> usize {
+        let count =
+            |nodes: &mut dyn Iterator<Item = bool>| nodes.filter(|invalid| *invalid).count();
+        usize::from(self.info.is_placeholder())
+            + count(&mut self.servers.values().map(Node::is_placeholder))
+            + count(&mut self.channels.values().map(Node::is_placeholder))
+            + count(&mut self.operations.values().map(Node::is_placeholder))
+            + self.invalid_components.len()
+    }
+}
+
+impl TryFrom<PartialDocument> for AsyncApiV3Spec {
+    type Error = PartialDocument;
+
+    /// Converts a partial document without placeholders nor skipped nodes, returning it
+    /// unchanged otherwise.
+    fn try_from(document: PartialDocument) -> Result<Self, Self::Error> {
+        if document.placeholders() > 0 || !document.skipped.is_empty() {
+            return Err(document);
+        }
+        let Node::Parsed(info) = document.info else {
+            unreachable!("no placeholders remain");
+        };
+        Ok(AsyncApiV3Spec {
+            id: document.id,
+            info,
+            servers: parsed(document.servers),
+            default_content_type: document.default_content_type,
+            channels: parsed(document.channels),
+            operations: parsed(document.operations),
+            components: document.components,
+        })
+    }
+}
+
+fn parsed<T>(nodes: BTreeMap<String, Node<T>>) -> BTreeMap<String, T> {
+    nodes
+        .into_iter()
+        .filter_map(|(key, node)| match node {
+            Node::Parsed(node) => Some((key, node)),
+            Node::Placeholder(_) => None,
+        })
+        .collect()
+}
+
+impl AsyncApiV3Spec {
+    /// Parses a JSON document, keeping the parts which can be deserialized when others cannot.
+    ///
+    /// Every node which fails is reported as a [ParseIssue] and replaced by a [Placeholder] in
+    /// the returned document.
+    pub fn parse_partial(
+        input: &str,
+        options: &ParseOptions,
+    ) -> (PartialDocument, Vec<ParseIssue>) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("parse", bytes = input.len(), partial = true).entered();
+        let mut parser = PartialParser::default();
+        let root = match serde_json::from_str::<Value>(input) {
+            Ok(Value::Object(root)) => root,
+            Ok(_) => {
+                parser.issue(JsonPointer::default(), "expected an object");
+                Map::new()
+            }
+            Err(err) => {
+                parser.issue(JsonPointer::default(), err);
+                Map::new()
+            }
+        };
+        let document = parser.document(&root, options, Vec::new());
+        (document, parser.issues)
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct PartialParser {
+    pub(crate) issues: Vec<ParseIssue>,
+}
+
+impl PartialParser {
+    /// Deserializes the sections of a root object, `skipped` listing the nodes left out of it.
+    pub(crate) fn document(
+        &mut self,
+        root: &Map<String, Value>,
+        options: &ParseOptions,
+        skipped: Vec<JsonPointer>,
+    ) -> PartialDocument {
+        let root_pointer = JsonPointer::default();
+        if options.check_version {
+            match root.get("asyncapi") {
+                Some(Value::String(version)) if version == "3.0.0" => {}
+                Some(version) => self.issue(
+                    root_pointer.join("asyncapi"),
+                    format!("unsupported AsyncAPI version {version}"),
+                ),
+                None => self.issue(root_pointer.join("asyncapi"), "missing AsyncAPI version"),
+            }
+        }
+
+        let info_pointer = root_pointer.join("info");
+        let document = PartialDocument {
+            id: self.optional(root, "id"),
+            info: match root.get("info") {
+                Some(info) => self.node(info, info_pointer),
+                None if skipped.contains(&info_pointer) => Node::Placeholder(Placeholder {
+                    raw: Value::Null,
+                    error: String::from("skipped"),
+                }),
+                None => self.placeholder(
+                    Value::Null,
+                    info_pointer,
+                    String::from("missing field `info`"),
+                ),
+            },
+            servers: self.ref_or_map(root, "servers"),
+            default_content_type: self.optional(root, "defaultContentType"),
+            channels: self.ref_or_map(root, "channels"),
+            operations: self.ref_or_map(root, "operations"),
+            components: Components::default(),
+            invalid_components: BTreeMap::new(),
+            skipped,
+        };
+        self.components(root, document)
+    }
+
+    fn issue(&mut self, pointer: JsonPointer, message: impl ToString) {
+        self.issues.push(ParseIssue {
+            pointer,
+            message: message.to_string(),
+        });
+    }
+
+    fn placeholder<T>(&mut self, raw: Value, pointer: JsonPointer, error: String) -> Node<T> {
+        self.issue(pointer, &error);
+        Node::Placeholder(Placeholder { raw, error })
+    }
+
+    fn node<T: DeserializeOwned>(&mut self, value: &Value, pointer: JsonPointer) -> Node<T> {
+        match T::deserialize(value) {
+            Ok(node) => Node::Parsed(node),
+            Err(err) => self.placeholder(value.clone(), pointer, err.to_string()),
+        }
+    }
+
+    /// Deserializes either a reference or an object, reporting the errors of the object instead
+    /// of a generic mismatch of both alternatives.
+    fn ref_or_node<T: DeserializeOwned>(
+        &mut self,
+        value: &Value,
+        pointer: JsonPointer,
+    ) -> Node<RefOr<T>> {
+        if value.get("$ref").is_some() {
+            self.node::<ReferenceObject>(value, pointer)
+                .map(RefOr::Left)
+        } else {
+            self.node::<T>(value, pointer).map(RefOr::Right)
+        }
+    }
+
+    fn ref_or_map<T: DeserializeOwned>(
+        &mut self,
+        root: &Map<String, Value>,
+        section: &str,
+    ) -> BTreeMap<String, Node<RefOr<T>>> {
+        let pointer = JsonPointer::default().join(section);
+        match root.get(section) {
+            None => BTreeMap::new(),
+            Some(Value::Object(entries)) => entries
+                .iter()
+                .map(|(key, value)| {
+                    let node = self.ref_or_node(value, pointer.join(key.as_str()));
+                    (key.clone(), node)
+                })
+                .collect(),
+            Some(_) => {
+                self.issue(pointer, "expected a map");
+                BTreeMap::new()
+            }
+        }
+    }
+
+    fn optional<T: DeserializeOwned>(
+        &mut self,
+        root: &Map<String, Value>,
+        field: &str,
+    ) -> Option<T> {
+        let value = root.get(field)?;
+        match T::deserialize(value) {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.issue(JsonPointer::default().join(field), err);
+                None
+            }
+        }
+    }
+
+    /// Deserializes each components entry on its own, keeping the valid ones.
+    fn components(
+        &mut self,
+        root: &Map<String, Value>,
+        mut document: PartialDocument,
+    ) -> PartialDocument {
+        let pointer = JsonPointer::default().join("components");
+        let sections = match root.get("components") {
+            None => return document,
+            Some(Value::Object(sections)) => sections,
+            Some(_) => {
+                self.issue(pointer, "expected an object");
+                return document;
+            }
+        };
+
+        let mut valid = Map::new();
+        for (section, entries) in sections {
+            let section_pointer = pointer.join(section.as_str());
+            let Value::Object(entries) = entries else {
+                self.issue(section_pointer, "expected a map");
+                continue;
+            };
+            let mut valid_entries = Map::new();
+            for (key, value) in entries {
+                let single = Value::Object(Map::from_iter([(
+                    section.clone(),
+                    Value::Object(Map::from_iter([(key.clone(), value.clone())])),
+                )]));
+                match Components::deserialize(&single) {
+                    Ok(_) => {
+                        valid_entries.insert(key.clone(), value.clone());
+                    }
+                    Err(err) => {
+                        let entry_pointer = section_pointer.join(key.as_str());
+                        self.issue(entry_pointer.clone(), &err);
+                        document.invalid_components.insert(
+                            entry_pointer,
+                            Placeholder {
+                                raw: value.clone(),
+                                error: err.to_string(),
+                            },
+                        );
+                    }
+                }
+            }
+            valid.insert(section.clone(), Value::Object(valid_entries));
+        }
+        match Components::deserialize(&Value::Object(valid)) {
+            Ok(components) => document.components = components,
+            Err(err) => self.issue(pointer, err),
+        }
+        document
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DOCUMENT: &str = r##"{
+        "asyncapi": "3.0.0",
+        "info": { "title": "Lights", "version": "1.0.0" },
+        "channels": {
+            "lightsOn": { "address": "lights.on", "messages": {} },
+            "lightsOff": { "address": 42, "messages": {} },
+            "lightsDim": { "$ref": "#/components/channels/dim" }
+        },
+        "operations": {
+            "turnOn": { "action": "send", "channel": { "$ref": "#/channels/lightsOn" } }
+        },
+        "components": {
+            "channels": { "dim": { "address": "lights.dim", "messages": {} } },
+            "schemas": { "level": { "type": "integer" } },
+            "securitySchemes": { "broken": { "type": "http" } }
+        }
+    }"##;
+
+    #[test]
+    fn parse_partial_keeps_valid_nodes() {
+        let (document, issues) = AsyncApiV3Spec::parse_partial(DOCUMENT, &ParseOptions::default());
+
+        assert!(document.channels["lightsOn"].parsed().is_some());
+        assert!(document.channels["lightsDim"].parsed().is_some());
+        assert!(document.operations["turnOn"].parsed().is_some());
+        assert!(document.components.channels.contains_key("dim"));
+        assert!(document.components.schemas.contains_key("level"));
+
+        let Node::Placeholder(placeholder) = &document.channels["lightsOff"] else {
+            panic!("lightsOff should be a placeholder");
+        };
+        assert_eq!(placeholder.raw["address"], 42);
+        let pointers = issues
+            .iter()
+            .map(|issue| issue.pointer.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            pointers,
+            vec!["/channels/lightsOff", "/components/securitySchemes/broken"]
+        );
+        assert!(issues[0].message.contains("invalid type: integer `42`"));
+        assert_eq!(document.placeholders(), 2);
+    }
+
+    #[test]
+    fn convert_once_placeholders_are_removed() {
+        let (document, _) = AsyncApiV3Spec::parse_partial(DOCUMENT, &ParseOptions::default());
+
+        let mut document = AsyncApiV3Spec::try_from(document).unwrap_err();
+        document.channels.remove("lightsOff");
+        document.invalid_components.clear();
+
+        let spec = AsyncApiV3Spec::try_from(document).unwrap();
+        assert_eq!(spec.info.title, "Lights");
+        assert_eq!(spec.channels.len(), 2);
+    }
+
+    #[test]
+    fn parse_partial_reports_missing_info_and_version() {
+        let (document, issues) = AsyncApiV3Spec::parse_partial(
+            r#"{ "asyncapi": "2.6.0", "channels": {} }"#,
+            &ParseOptions::default(),
+        );
+
+        assert!(document.info.is_placeholder());
+        let pointers = issues
+            .iter()
+            .map(|issue| issue.pointer.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(pointers, vec!["/asyncapi", "/info"]);
+    }
+}
@@ -0,0 +1,374 @@
+//! Resolution of [Reference Objects](ReferenceObject) pointing inside the current document.
+use crate::error::Error;
+use crate::spec::channel::{Channel, ChannelBindings, Parameter};
+use crate::spec::common::{
+    collect_references, Either, ExternalDocumentation, JsonPointer, RefOr, ReferenceObject, Tag,
+};
+use crate::spec::message::{
+    CorrelationId, Message, MessageBindings, MessageTrait, MultiFormatSchema,
+};
+use crate::spec::operation::{
+    Operation, OperationBindings, OperationReply, OperationReplyAddress, OperationTrait,
+};
+use crate::spec::security::SecurityScheme;
+use crate::spec::server::{Server, ServerBindings, Variable};
+use crate::spec::AsyncApiV3Spec;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Defaults applied when the document leaves a value unspecified, e.g. by
+/// [Message::effective_content_type_with].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResolveOptions {
+    /// Content type of the messages when neither the message nor the document declares one,
+    /// `application/json` by default.
+    pub default_content_type: String,
+}
+
+impl Default for ResolveOptions {
+    fn default() -> Self {
+        Self {
+            default_content_type: String::from("application/json"),
+        }
+    }
+}
+
+/// Types which can be the target of a [ReferenceObject].
+pub trait Referenceable: Sized {
+    /// Name of the components section holding objects of this type, e.g. `messages`.
+    const CATEGORY: &'static str;
+
+    /// Looks up the entry addressed by a pointer relative to the document root.
+    fn lookup<'a>(spec: &'a AsyncApiV3Spec, pointer: &JsonPointer) -> Option<&'a RefOr<Self>>;
+}
+
+impl<T: Referenceable> RefOr<T> {
+    /// Returns the contained object, following references (and references to references)
+    /// through the given document.
+    pub fn resolve<'a>(&'a self, spec: &'a AsyncApiV3Spec) -> Result<&'a T, Error> {
+        let mut current = self;
+        let mut visited: Vec<&str> = Vec::new();
+        loop {
+            match current {
+                RefOr::Right(item) => return Ok(item),
+                RefOr::Left(reference) => {
+                    if visited.contains(&reference.reference.as_str()) {
+                        return Err(Error::CyclicReference {
+                            reference: String::from(&reference.reference),
+                        });
+                    }
+                    visited.push(&reference.reference);
+                    current = lookup(spec, reference)?;
+                }
+            }
+        }
+    }
+}
+
+impl ReferenceObject {
+    /// Resolves the referenced object in the given document.
+    pub fn resolve<'a, T: Referenceable>(&self, spec: &'a AsyncApiV3Spec) -> Result<&'a T, Error> {
+        lookup(spec, self)?.resolve(spec)
+    }
+}
+
+impl AsyncApiV3Spec {
+    /// Returns the files and URLs referenced by the document, the part of the `$ref`s before
+    /// the `#`, e.g. `common.yaml` for `common.yaml#/components/schemas/id`. Relative paths are
+    /// returned as written.
+    pub fn external_dependencies(&self) -> BTreeSet<String> {
+        let Ok(document) = serde_json::to_value(self) else {
+            return BTreeSet::new();
+        };
+        collect_references(&document, &JsonPointer::default())
+            .into_iter()
+            .filter_map(|(_, reference)| {
+                let location = reference
+                    .split_once('#')
+                    .map_or(reference, |(location, _)| location);
+                (!location.is_empty()).then(|| String::from(location))
+            })
+            .collect()
+    }
+}
+
+fn lookup<'a, T: Referenceable>(
+    spec: &'a AsyncApiV3Spec,
+    reference: &ReferenceObject,
+) -> Result<&'a RefOr<T>, Error> {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::trace_span!("dereference", reference = %reference.reference).entered();
+    reference
+        .local_pointer()
+        .and_then(|pointer| T::lookup(spec, &pointer))
+        .ok_or_else(
+            || match reference.local_pointer().as_ref().and_then(category) {
+                Some(found) if found != T::CATEGORY => Error::WrongCategory {
+                    reference: String::from(&reference.reference),
+                    expected: String::from(T::CATEGORY),
+                    found: String::from(found),
+                },
+                _ => Error::UnresolvedReference {
+                    reference: String::from(&reference.reference),
+                },
+            },
+        )
+}
+
+/// Returns the category of the objects a pointer addresses, named after the components section
+/// holding them.
+fn category(pointer: &JsonPointer) -> Option<&str> {
+    let segments = match pointer.segments() {
+        [components, rest @ ..] if components == "components" => rest,
+        segments => segments,
+    };
+    match segments {
+        [section, _] => Some(section.as_str()),
+        [channels, _, child, _] if channels == "channels" => Some(child.as_str()),
+        [operations, _, reply] if operations == "operations" && reply == "reply" => Some("replies"),
+        _ => None,
+    }
+}
+
+/// Looks up `/components/{section}/{key}`.
+fn component<'a, T>(
+    map: &'a BTreeMap<String, RefOr<T>>,
+    section: &str,
+    pointer: &JsonPointer,
+) -> Option<&'a RefOr<T>> {
+    match pointer.segments() {
+        [components, s, key] if components == "components" && s == section => map.get(key),
+        _ => None,
+    }
+}
+
+/// Splits a pointer to a field of a channel into the channel and the rest of the pointer.
+fn channel_child<'a, 'p>(
+    spec: &'a AsyncApiV3Spec,
+    pointer: &'p JsonPointer,
+) -> Option<(&'a Channel, &'p [String])> {
+    let (channel, rest) = match pointer.segments() {
+        [channels, key, rest @ ..] if channels == "channels" => (spec.channels.get(key)?, rest),
+        [components, channels, key, rest @ ..]
+            if components == "components" && channels == "channels" =>
+        {
+            (spec.components.channels.get(key)?, rest)
+        }
+        _ => return None,
+    };
+    Some((channel.resolve(spec).ok()?, rest))
+}
+
+/// Splits a pointer to a field of an operation into the operation and the rest of the pointer.
+fn operation_child<'a, 'p>(
+    spec: &'a AsyncApiV3Spec,
+    pointer: &'p JsonPointer,
+) -> Option<(&'a Operation, &'p [String])> {
+    let (operation, rest) = match pointer.segments() {
+        [operations, key, rest @ ..] if operations == "operations" => {
+            (spec.operations.get(key)?, rest)
+        }
+        [components, operations, key, rest @ ..]
+            if components == "components" && operations == "operations" =>
+        {
+            (spec.components.operations.get(key)?, rest)
+        }
+        _ => return None,
+    };
+    Some((operation.resolve(spec).ok()?, rest))
+}
+
+impl Referenceable for Server {
+    const CATEGORY: &'static str = "servers";
+
+    fn lookup<'a>(spec: &'a AsyncApiV3Spec, pointer: &JsonPointer) -> Option<&'a RefOr<Self>> {
+        match pointer.segments() {
+            [servers, key] if servers == "servers" => spec.servers.get(key),
+            _ => component(&spec.components.servers, "servers", pointer),
+        }
+    }
+}
+
+impl Referenceable for Channel {
+    const CATEGORY: &'static str = "channels";
+
+    fn lookup<'a>(spec: &'a AsyncApiV3Spec, pointer: &JsonPointer) -> Option<&'a RefOr<Self>> {
+        match pointer.segments() {
+            [channels, key] if channels == "channels" => spec.channels.get(key),
+            _ => component(&spec.components.channels, "channels", pointer),
+        }
+    }
+}
+
+impl Referenceable for Operation {
+    const CATEGORY: &'static str = "operations";
+
+    fn lookup<'a>(spec: &'a AsyncApiV3Spec, pointer: &JsonPointer) -> Option<&'a RefOr<Self>> {
+        match pointer.segments() {
+            [operations, key] if operations == "operations" => spec.operations.get(key),
+            _ => component(&spec.components.operations, "operations", pointer),
+        }
+    }
+}
+
+impl Referenceable for Message {
+    const CATEGORY: &'static str = "messages";
+
+    fn lookup<'a>(spec: &'a AsyncApiV3Spec, pointer: &JsonPointer) -> Option<&'a RefOr<Self>> {
+        if let Some((channel, [messages, key])) = channel_child(spec, pointer) {
+            return (messages == "messages")
+                .then(|| channel.messages.get(key))
+                .flatten();
+        }
+        component(&spec.components.messages, "messages", pointer)
+    }
+}
+
+impl Referenceable for Parameter {
+    const CATEGORY: &'static str = "parameters";
+
+    fn lookup<'a>(spec: &'a AsyncApiV3Spec, pointer: &JsonPointer) -> Option<&'a RefOr<Self>> {
+        if let Some((channel, [parameters, key])) = channel_child(spec, pointer) {
+            return (parameters == "parameters")
+                .then(|| channel.parameters.get(key))
+                .flatten();
+        }
+        component(&spec.components.parameters, "parameters", pointer)
+    }
+}
+
+impl Referenceable for OperationReply {
+    const CATEGORY: &'static str = "replies";
+
+    fn lookup<'a>(spec: &'a AsyncApiV3Spec, pointer: &JsonPointer) -> Option<&'a RefOr<Self>> {
+        if let Some((operation, [reply])) = operation_child(spec, pointer) {
+            return (reply == "reply")
+                .then_some(operation.reply.as_ref())
+                .flatten();
+        }
+        component(&spec.components.replies, "replies", pointer)
+    }
+}
+
+macro_rules! component_referenceable {
+    ($($ty:ty => $field:ident, $section:literal;)*) => {
+        $(
+            impl Referenceable for $ty {
+                const CATEGORY: &'static str = $section;
+
+                fn lookup<'a>(
+                    spec: &'a AsyncApiV3Spec,
+                    pointer: &JsonPointer,
+                ) -> Option<&'a RefOr<Self>> {
+                    component(&spec.components.$field, $section, pointer)
+                }
+            }
+        )*
+    };
+}
+
+component_referenceable! {
+    Either<schemars::Schema, MultiFormatSchema> => schemas, "schemas";
+    SecurityScheme => security_schemes, "securitySchemes";
+    Variable => server_variables, "serverVariables";
+    CorrelationId => correlation_ids, "correlationIds";
+    OperationReplyAddress => reply_addresses, "replyAddresses";
+    ExternalDocumentation => external_docs, "externalDocs";
+    Tag => tags, "tags";
+    OperationTrait => operation_traits, "operationTraits";
+    MessageTrait => message_traits, "messageTraits";
+    ServerBindings => server_bindings, "serverBindings";
+    ChannelBindings => channel_bindings, "channelBindings";
+    OperationBindings => operation_bindings, "operationBindings";
+    MessageBindings => message_bindings, "messageBindings";
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::parse_spec;
+
+    #[test]
+    fn reference_to_wrong_category() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Lights
+  version: 1.0.0
+channels:
+  lights:
+    address: lights
+    messages:
+      lightMeasured:
+        $ref: '#/components/schemas/lightMeasured'
+      lightMissing:
+        $ref: '#/components/messages/lightMissing'
+components:
+  schemas:
+    lightMeasured:
+      type: object
+"#,
+        );
+        let channel = spec.channels["lights"].resolve(&spec).unwrap();
+
+        let err = channel.messages["lightMeasured"]
+            .resolve(&spec)
+            .unwrap_err();
+        assert!(matches!(
+            &err,
+            Error::WrongCategory { reference, expected, found }
+                if reference == "#/components/schemas/lightMeasured"
+                    && expected == "messages"
+                    && found == "schemas"
+        ));
+        assert_eq!(
+            err.to_string(),
+            "Reference '#/components/schemas/lightMeasured' points at schemas instead of messages"
+        );
+        assert!(matches!(
+            channel.messages["lightMissing"].resolve(&spec),
+            Err(Error::UnresolvedReference { .. })
+        ));
+    }
+
+    #[test]
+    fn list_external_dependencies() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Lights
+  version: 1.0.0
+channels:
+  lights:
+    address: lights
+    messages:
+      lightMeasured:
+        $ref: 'messages.yaml#/components/messages/lightMeasured'
+      lightDimmed:
+        payload:
+          type: object
+          properties:
+            id:
+              $ref: 'https://schemas.example.com/common.json#/definitions/id'
+            level:
+              $ref: '#/components/schemas/level'
+      lightOff:
+        $ref: 'messages.yaml#/components/messages/lightOff'
+components:
+  schemas:
+    level:
+      type: integer
+"#,
+        );
+
+        let dependencies = spec.external_dependencies();
+
+        assert_eq!(
+            dependencies.into_iter().collect::<Vec<_>>(),
+            ["https://schemas.example.com/common.json", "messages.yaml"]
+        );
+    }
+}
@@ -0,0 +1,1392 @@
+//! Helpers working on the JSON schemas embedded in a document.
+//!
+//! Schemas are stored as [schemars::Schema], a thin wrapper around a JSON value, so the helpers
+//! operate on [Value]s and resolve `$ref`s against the `components/schemas` of the document.
+use crate::error::Error;
+use crate::spec::channel::ChannelBindings;
+use crate::spec::common::{Either, JsonPointer, RefOr};
+use crate::spec::message::{Message, MessageBindings, MultiFormatSchema, DEFAULT_SCHEMA_FORMAT};
+use crate::spec::AsyncApiV3Spec;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::fmt;
+use serde_json::Value;
+
+/// Maximum number of nested schemas followed while validating, guarding against recursive
+/// schemas.
+const MAX_DEPTH: usize = 64;
+
+/// Returns the JSON schema described by a schema field of the document, or `None` when it is
+/// written in a format other than JSON schema (e.g. Avro) or cannot be resolved.
+pub fn json_schema<'a>(
+    schema: &'a RefOr<Either<schemars::Schema, MultiFormatSchema>>,
+    spec: &'a AsyncApiV3Spec,
+) -> Option<&'a Value> {
+    match schema.resolve(spec).ok()? {
+        Either::Left(schema) => Some(schema.as_value()),
+        Either::Right(multi_format) if multi_format.is_json_schema() => Some(&multi_format.schema),
+        Either::Right(_) => None,
+    }
+}
+
+/// Returns the schema format of a schema field, the [DEFAULT_SCHEMA_FORMAT] for Schema Objects.
+///
+/// Multi Format Schema Objects usually deserialize as [schemars::Schema]s, since any object is
+/// a valid JSON schema, so a `schemaFormat` next to a `schema` is read from those as well.
+pub fn schema_format(schema: &Either<schemars::Schema, MultiFormatSchema>) -> &str {
+    match schema {
+        Either::Left(schema) => match (schema.get("schemaFormat"), schema.get("schema")) {
+            (Some(Value::String(format)), Some(_)) => format,
+            _ => DEFAULT_SCHEMA_FORMAT,
+        },
+        Either::Right(multi_format) => &multi_format.schema_format,
+    }
+}
+
+/// Keywords holding a single subschema, or an array of them for `items` in older drafts.
+const SCHEMA_KEYWORDS: [&str; 11] = [
+    "additionalItems",
+    "additionalProperties",
+    "contains",
+    "else",
+    "if",
+    "items",
+    "not",
+    "propertyNames",
+    "then",
+    "unevaluatedItems",
+    "unevaluatedProperties",
+];
+
+/// Keywords holding an array of subschemas.
+const SCHEMA_ARRAY_KEYWORDS: [&str; 4] = ["allOf", "anyOf", "oneOf", "prefixItems"];
+
+/// Keywords holding a map of subschemas.
+const SCHEMA_MAP_KEYWORDS: [&str; 5] = [
+    "$defs",
+    "definitions",
+    "dependentSchemas",
+    "patternProperties",
+    "properties",
+];
+
+/// Returns the subschemas of a schema with their location, leaving out those of nested
+/// subschemas.
+pub(crate) fn subschemas<'a>(
+    schema: &'a Value,
+    pointer: &JsonPointer,
+) -> Vec<(&'a Value, JsonPointer)> {
+    let mut subschemas = Vec::new();
+    let Value::Object(keywords) = schema else {
+        return subschemas;
+    };
+    for (keyword, value) in keywords {
+        let keyword = keyword.as_str();
+        let keyword_pointer = pointer.join(keyword);
+        match value {
+            Value::Array(items)
+                if SCHEMA_KEYWORDS.contains(&keyword)
+                    || SCHEMA_ARRAY_KEYWORDS.contains(&keyword) =>
+            {
+                subschemas.extend(
+                    items
+                        .iter()
+                        .enumerate()
+                        .map(|(index, item)| (item, keyword_pointer.join(index.to_string()))),
+                );
+            }
+            Value::Object(entries) if SCHEMA_MAP_KEYWORDS.contains(&keyword) => {
+                subschemas.extend(
+                    entries
+                        .iter()
+                        .map(|(name, entry)| (entry, keyword_pointer.join(name.as_str()))),
+                );
+            }
+            _ if SCHEMA_KEYWORDS.contains(&keyword) => subschemas.push((value, keyword_pointer)),
+            _ => {}
+        }
+    }
+    subschemas
+}
+
+/// A value which does not satisfy a schema.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchemaViolation {
+    /// Location of the offending value inside the validated instance.
+    pub pointer: JsonPointer,
+    /// Description of the failed constraint.
+    pub reason: String,
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.pointer, self.reason)
+    }
+}
+
+/// Resolves `$ref`s to the schemas of the document components, remembering the schema each
+/// reference resolves to.
+///
+/// Meant to be kept around while validating many values against the same document: every
+/// reference is resolved once, on its first lookup. With the `std` feature the resolver can be
+/// shared between threads.
+pub struct SchemaResolver<'a> {
+    spec: &'a AsyncApiV3Spec,
+    memo: Lock<Memo<'a>>,
+}
+
+#[cfg(feature = "std")]
+type Lock<T> = std::sync::Mutex<T>;
+#[cfg(not(feature = "std"))]
+type Lock<T> = core::cell::RefCell<T>;
+
+#[cfg(feature = "std")]
+type Cache<'a> = std::collections::HashMap<String, &'a Value>;
+#[cfg(not(feature = "std"))]
+type Cache<'a> = alloc::collections::BTreeMap<String, &'a Value>;
+
+/// The resolved references of a [SchemaResolver] and the number of lookups they answered.
+#[derive(Default)]
+struct Memo<'a> {
+    cache: Cache<'a>,
+    hits: usize,
+}
+
+impl<'a> SchemaResolver<'a> {
+    /// Creates a resolver of the references to the schemas of the components, e.g.
+    /// `#/components/schemas/user`.
+    pub fn new(spec: &'a AsyncApiV3Spec) -> Self {
+        Self {
+            spec,
+            memo: Lock::default(),
+        }
+    }
+
+    /// Resolves a reference such as `#/components/schemas/user`, following references to
+    /// references until a schema which is not a reference.
+    ///
+    /// Fails with [Error::UnresolvedReference] if a reference of the chain cannot be resolved
+    /// and with [Error::CyclicReference] if the chain loops. Successful resolutions are
+    /// remembered, including those of references to the inside of a component schema, e.g.
+    /// `#/components/schemas/user/properties/id`.
+    pub fn resolve_ref_chain(&self, reference: &str) -> Result<&'a Value, Error> {
+        {
+            let mut memo = self.memo();
+            if let Some(schema) = memo.cache.get(reference).copied() {
+                memo.hits += 1;
+                #[cfg(feature = "tracing")]
+                tracing::debug!(reference, "schema cache hit");
+                return Ok(schema);
+            }
+        }
+        let schema = self.follow_ref_chain(reference)?;
+        self.memo().cache.insert(String::from(reference), schema);
+        Ok(schema)
+    }
+
+    fn follow_ref_chain(&self, reference: &str) -> Result<&'a Value, Error> {
+        let mut visited: Vec<&str> = Vec::new();
+        let mut current = reference;
+        loop {
+            if visited.contains(&current) {
+                return Err(Error::CyclicReference {
+                    reference: String::from(reference),
+                });
+            }
+            visited.push(current);
+            let schema = self
+                .resolve_ref(current)
+                .ok_or_else(|| Error::UnresolvedReference {
+                    reference: String::from(current),
+                })?;
+            match schema.get("$ref").and_then(Value::as_str) {
+                Some(next) => current = next,
+                None => return Ok(schema),
+            }
+        }
+    }
+
+    /// Returns the number of references whose resolution is remembered.
+    pub fn cached(&self) -> usize {
+        self.memo().cache.len()
+    }
+
+    /// Returns the number of resolutions answered from the cache.
+    pub fn cache_hits(&self) -> usize {
+        self.memo().hits
+    }
+
+    #[cfg(feature = "std")]
+    fn memo(&self) -> std::sync::MutexGuard<'_, Memo<'a>> {
+        self.memo
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn memo(&self) -> core::cell::RefMut<'_, Memo<'a>> {
+        self.memo.borrow_mut()
+    }
+
+    fn resolve_ref(&self, reference: &str) -> Option<&'a Value> {
+        let pointer = JsonPointer::parse(reference.strip_prefix('#')?).ok()?;
+        match pointer.segments() {
+            [components, schemas, key, rest @ ..]
+                if components == "components" && schemas == "schemas" =>
+            {
+                let schema = json_schema(self.spec.components.schemas.get(key)?, self.spec)?;
+                rest.iter()
+                    .cloned()
+                    .collect::<JsonPointer>()
+                    .evaluate(schema)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Validates JSON values against JSON schemas of a document.
+///
+/// Supports the commonly used subset of JSON schema: `type`, `enum`, `const`, the numeric,
+/// string length, array and property count constraints, `properties`, `required`,
+/// `additionalProperties`, `propertyNames`, `items`, the `allOf`/`anyOf`/`oneOf`/`not`
+/// combinators and `$ref`s to the document components, as well as `pattern` with the `regex`
+/// feature. Other keywords, such as `format`, are ignored. References are resolved through a
+/// [SchemaResolver], shared by every validation of the validator.
+pub struct SchemaValidator<'a> {
+    resolver: SchemaResolver<'a>,
+}
+
+impl<'a> SchemaValidator<'a> {
+    /// Creates a validator of values against the schemas of a document.
+    pub fn new(spec: &'a AsyncApiV3Spec) -> Self {
+        Self {
+            resolver: SchemaResolver::new(spec),
+        }
+    }
+
+    /// Resolves the references of a schema of the document, and of the schemas they lead to,
+    /// ahead of the validations against it.
+    pub(crate) fn prepare(&self, schema: &'a Value) {
+        let mut pending = vec![schema];
+        while let Some(schema) = pending.pop() {
+            if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+                let cached = self.resolver.cached();
+                match self.resolver.resolve_ref_chain(reference) {
+                    Ok(target) if self.resolver.cached() > cached => pending.push(target),
+                    _ => {}
+                }
+            }
+            let subschemas = subschemas(schema, &JsonPointer::default());
+            pending.extend(subschemas.into_iter().map(|(subschema, _)| subschema));
+        }
+    }
+
+    /// Validates an instance against a schema, returning every violation found.
+    pub fn validate(&self, schema: &Value, instance: &Value) -> Vec<SchemaViolation> {
+        let mut violations = Vec::new();
+        self.check(
+            schema,
+            instance,
+            &JsonPointer::default(),
+            &mut violations,
+            0,
+        );
+        violations
+    }
+
+    fn check(
+        &self,
+        schema: &Value,
+        instance: &Value,
+        pointer: &JsonPointer,
+        violations: &mut Vec<SchemaViolation>,
+        depth: usize,
+    ) {
+        let push = |violations: &mut Vec<SchemaViolation>, reason: String| {
+            violations.push(SchemaViolation {
+                pointer: pointer.clone(),
+                reason,
+            })
+        };
+        let schema = match schema {
+            Value::Bool(true) => return,
+            Value::Bool(false) => return push(violations, String::from("no value is allowed")),
+            Value::Object(schema) => schema,
+            _ => return,
+        };
+        if depth > MAX_DEPTH {
+            return push(violations, String::from("schema nesting too deep"));
+        }
+
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            match self.resolver.resolve_ref_chain(reference) {
+                Ok(target) => self.check(target, instance, pointer, violations, depth + 1),
+                Err(Error::CyclicReference { .. }) => {
+                    push(violations, format!("cyclic schema reference '{reference}'"))
+                }
+                Err(_) => push(
+                    violations,
+                    format!("unresolved schema reference '{reference}'"),
+                ),
+            }
+            return;
+        }
+
+        if let Some(expected) = schema.get("type") {
+            let allowed = match expected {
+                Value::String(ty) => vec![ty.as_str()],
+                Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+                _ => vec![],
+            };
+            if !allowed.is_empty() && !allowed.iter().any(|ty| has_type(instance, ty)) {
+                push(
+                    violations,
+                    format!(
+                        "expected {}, found {}",
+                        allowed.join(" or "),
+                        type_name(instance)
+                    ),
+                );
+                return;
+            }
+        }
+        if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+            if !values.contains(instance) {
+                push(
+                    violations,
+                    format!("{instance} is not one of the allowed values"),
+                );
+            }
+        }
+        if let Some(expected) = schema.get("const") {
+            if expected != instance {
+                push(violations, format!("expected {expected}, found {instance}"));
+            }
+        }
+
+        match instance {
+            Value::Number(number) => {
+                let number = number.as_f64().unwrap_or_default();
+                let bound = |keyword: &str| schema.get(keyword).and_then(Value::as_f64);
+                if let Some(minimum) = bound("minimum").filter(|min| number < *min) {
+                    push(
+                        violations,
+                        format!("{number} is lower than the minimum {minimum}"),
+                    );
+                }
+                if let Some(maximum) = bound("maximum").filter(|max| number > *max) {
+                    push(
+                        violations,
+                        format!("{number} is greater than the maximum {maximum}"),
+                    );
+                }
+                if let Some(minimum) = bound("exclusiveMinimum").filter(|min| number <= *min) {
+                    push(
+                        violations,
+                        format!("{number} is not greater than {minimum}"),
+                    );
+                }
+                if let Some(maximum) = bound("exclusiveMaximum").filter(|max| number >= *max) {
+                    push(violations, format!("{number} is not lower than {maximum}"));
+                }
+                if let Some(divisor) = bound("multipleOf").filter(|divisor| *divisor > 0.0) {
+                    if !is_multiple(number, divisor) {
+                        push(
+                            violations,
+                            format!("{number} is not a multiple of {divisor}"),
+                        );
+                    }
+                }
+            }
+            Value::String(string) => {
+                let length = string.chars().count() as u64;
+                let bound = |keyword: &str| schema.get(keyword).and_then(Value::as_u64);
+                if let Some(min) = bound("minLength").filter(|min| length < *min) {
+                    push(violations, format!("string shorter than {min} characters"));
+                }
+                if let Some(max) = bound("maxLength").filter(|max| length > *max) {
+                    push(violations, format!("string longer than {max} characters"));
+                }
+                #[cfg(feature = "regex")]
+                if let Some(pattern) = schema.get("pattern").and_then(Value::as_str) {
+                    match regex::Regex::new(pattern) {
+                        Ok(regex) if !regex.is_match(string) => push(
+                            violations,
+                            format!("string does not match the pattern '{pattern}'"),
+                        ),
+                        Ok(_) => {}
+                        Err(_) => push(violations, format!("invalid pattern '{pattern}'")),
+                    }
+                }
+            }
+            Value::Array(items) => {
+                let length = items.len() as u64;
+                let bound = |keyword: &str| schema.get(keyword).and_then(Value::as_u64);
+                if let Some(min) = bound("minItems").filter(|min| length < *min) {
+                    push(violations, format!("array has fewer than {min} items"));
+                }
+                if let Some(max) = bound("maxItems").filter(|max| length > *max) {
+                    push(violations, format!("array has more than {max} items"));
+                }
+                if schema.get("uniqueItems") == Some(&Value::Bool(true)) {
+                    let duplicated = items
+                        .iter()
+                        .enumerate()
+                        .any(|(i, item)| items[..i].contains(item));
+                    if duplicated {
+                        push(violations, String::from("array items are not unique"));
+                    }
+                }
+                match schema.get("items") {
+                    Some(Value::Array(tuple)) => {
+                        for (index, (item, item_schema)) in items.iter().zip(tuple).enumerate() {
+                            let item_pointer = pointer.join(index.to_string());
+                            self.check(item_schema, item, &item_pointer, violations, depth + 1);
+                        }
+                    }
+                    Some(item_schema) => {
+                        for (index, item) in items.iter().enumerate() {
+                            let item_pointer = pointer.join(index.to_string());
+                            self.check(item_schema, item, &item_pointer, violations, depth + 1);
+                        }
+                    }
+                    None => {}
+                }
+            }
+            Value::Object(fields) => {
+                let count = fields.len() as u64;
+                let bound = |keyword: &str| schema.get(keyword).and_then(Value::as_u64);
+                if let Some(min) = bound("minProperties").filter(|min| count < *min) {
+                    push(
+                        violations,
+                        format!("object has fewer than {min} properties"),
+                    );
+                }
+                if let Some(max) = bound("maxProperties").filter(|max| count > *max) {
+                    push(violations, format!("object has more than {max} properties"));
+                }
+                if let Some(names) = schema.get("propertyNames") {
+                    for name in fields.keys() {
+                        let key = Value::String(name.clone());
+                        if !self.matches(names, &key, depth) {
+                            push(
+                                violations,
+                                format!("property name '{name}' does not match the propertyNames schema"),
+                            );
+                        }
+                    }
+                }
+                if let Some(required) = schema.get("required").and_then(Value::as_array) {
+                    for field in required.iter().filter_map(Value::as_str) {
+                        if !fields.contains_key(field) {
+                            push(violations, format!("missing required property '{field}'"));
+                        }
+                    }
+                }
+                let properties = schema.get("properties").and_then(Value::as_object);
+                let additional = schema.get("additionalProperties");
+                for (name, value) in fields {
+                    let field_pointer = pointer.join(name.as_str());
+                    match (properties.and_then(|p| p.get(name)), additional) {
+                        (Some(property), _) | (None, Some(property)) => {
+                            self.check(property, value, &field_pointer, violations, depth + 1)
+                        }
+                        (None, None) => {}
+                    }
+                }
+            }
+            Value::Null | Value::Bool(_) => {}
+        }
+
+        if let Some(all_of) = schema.get("allOf").and_then(Value::as_array) {
+            for sub_schema in all_of {
+                self.check(sub_schema, instance, pointer, violations, depth + 1);
+            }
+        }
+        if let Some(any_of) = schema.get("anyOf").and_then(Value::as_array) {
+            if !any_of.iter().any(|s| self.matches(s, instance, depth)) {
+                push(
+                    violations,
+                    String::from("value does not match any of the anyOf schemas"),
+                );
+            }
+        }
+        if let Some(one_of) = schema.get("oneOf").and_then(Value::as_array) {
+            let matching = one_of
+                .iter()
+                .filter(|s| self.matches(s, instance, depth))
+                .count();
+            if matching != 1 {
+                push(
+                    violations,
+                    format!("value matches {matching} of the oneOf schemas instead of 1"),
+                );
+            }
+        }
+        if let Some(not) = schema.get("not") {
+            if self.matches(not, instance, depth) {
+                push(
+                    violations,
+                    String::from("value matches the schema it must not match"),
+                );
+            }
+        }
+    }
+
+    fn matches(&self, schema: &Value, instance: &Value, depth: usize) -> bool {
+        let mut violations = Vec::new();
+        let pointer = JsonPointer::default();
+        self.check(schema, instance, &pointer, &mut violations, depth + 1);
+        violations.is_empty()
+    }
+}
+
+/// Suggests Rust types for schemas, for code generation tooling.
+pub trait SuggestRustType {
+    /// Returns the Rust type best representing the values of the schema: a primitive type for
+    /// the primitive JSON types, `Vec<..>` for arrays, `HashMap<String, ..>` for maps, the
+    /// PascalCase name of the `title` or of the referenced component for objects and enums,
+    /// and `serde_json::Value` when nothing more specific fits. Nullable schemas, using either
+    /// `nullable: true` or a `null` type, are wrapped in an `Option<..>`.
+    fn suggest_rust_type(&self) -> String;
+}
+
+impl SuggestRustType for schemars::Schema {
+    fn suggest_rust_type(&self) -> String {
+        rust_type(self.as_value())
+    }
+}
+
+fn rust_type(schema: &Value) -> String {
+    const ANY: &str = "serde_json::Value";
+    let Value::Object(schema) = schema else {
+        return String::from(ANY);
+    };
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        let name = reference.rsplit('/').next().unwrap_or(reference);
+        return pascal_case(name);
+    }
+
+    let mut types = match schema.get("type") {
+        Some(Value::String(ty)) => vec![ty.as_str()],
+        Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).collect(),
+        _ => vec![],
+    };
+    let nullable = schema.get("nullable") == Some(&Value::Bool(true)) || types.contains(&"null");
+    types.retain(|ty| *ty != "null");
+
+    let title = schema.get("title").and_then(Value::as_str);
+    let ty = match (types.as_slice(), title) {
+        (["object"] | [], Some(title))
+            if schema.contains_key("properties") || !types.is_empty() =>
+        {
+            pascal_case(title)
+        }
+        (_, Some(title)) if schema.contains_key("enum") => pascal_case(title),
+        (["string"], _) => String::from("String"),
+        (["integer"], _) => String::from("i64"),
+        (["number"], _) => String::from("f64"),
+        (["boolean"], _) => String::from("bool"),
+        (["array"], _) => {
+            let items = schema
+                .get("items")
+                .map_or_else(|| String::from(ANY), rust_type);
+            format!("Vec<{items}>")
+        }
+        (["object"], _) => match schema.get("additionalProperties") {
+            Some(values @ Value::Object(_)) if !schema.contains_key("properties") => {
+                format!("HashMap<String, {}>", rust_type(values))
+            }
+            _ => String::from(ANY),
+        },
+        ([], None) if nullable => String::from("()"),
+        _ => String::from(ANY),
+    };
+    if nullable && ty != "()" {
+        format!("Option<{ty}>")
+    } else {
+        ty
+    }
+}
+
+fn pascal_case(name: &str) -> String {
+    name.split(|c: char| !c.is_alphanumeric())
+        .flat_map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars))
+                .into_iter()
+                .flatten()
+        })
+        .collect()
+}
+
+/// Visits the properties of schemas, for field documentation tooling.
+pub trait WalkProperties {
+    /// Calls `f` for every property of the schema and, recursively, of the property schemas,
+    /// with its dotted path, e.g. `address.city`. Array `items` are walked as the array
+    /// property itself, so the properties of `tags` items are `tags.name`. References to the
+    /// `$defs` (or `definitions`) of the schema are followed and `f` receives the referenced
+    /// schema; other references are not.
+    fn walk_properties(&self, f: &mut dyn FnMut(&str, &schemars::Schema));
+}
+
+impl WalkProperties for schemars::Schema {
+    fn walk_properties(&self, f: &mut dyn FnMut(&str, &schemars::Schema)) {
+        let root = self.as_value();
+        walk_properties(root, root, "", &mut Vec::new(), f);
+    }
+}
+
+fn walk_properties<'a>(
+    root: &'a Value,
+    schema: &'a Value,
+    path: &str,
+    expanding: &mut Vec<&'a str>,
+    f: &mut dyn FnMut(&str, &schemars::Schema),
+) {
+    let (schema, reference) = match local_definition(root, schema) {
+        Some((_, reference)) if expanding.contains(&reference) => return,
+        Some((target, reference)) => (target, Some(reference)),
+        None => (schema, None),
+    };
+    expanding.extend(reference);
+
+    if let Some(Value::Object(properties)) = schema.get("properties") {
+        for (name, property) in properties {
+            let path = if path.is_empty() {
+                name.clone()
+            } else {
+                format!("{path}.{name}")
+            };
+            let resolved = local_definition(root, property).map_or(property, |(target, _)| target);
+            if let Ok(resolved) = <&schemars::Schema>::try_from(resolved) {
+                f(&path, resolved);
+            }
+            walk_properties(root, property, &path, expanding, f);
+        }
+    }
+    if let Some(items) = schema.get("items") {
+        walk_properties(root, items, path, expanding, f);
+    }
+
+    if reference.is_some() {
+        expanding.pop();
+    }
+}
+
+/// Returns the schema referenced from the `$defs` or `definitions` of the root schema, with the
+/// reference.
+fn local_definition<'a>(root: &'a Value, schema: &'a Value) -> Option<(&'a Value, &'a str)> {
+    let reference = schema.get("$ref")?.as_str()?;
+    let pointer = reference.strip_prefix('#')?;
+    if !pointer.starts_with("/$defs/") && !pointer.starts_with("/definitions/") {
+        return None;
+    }
+    let target = JsonPointer::parse(pointer).ok()?.evaluate(root)?;
+    Some((target, reference))
+}
+
+/// What a schema describes, see [AsyncApiV3Spec::schemas].
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SchemaRole {
+    /// The payload of a message.
+    Payload,
+    /// The application headers of a message or a message trait.
+    Headers,
+    /// A field of the bindings of a channel or a message, e.g. the `query` of the `ws` channel
+    /// binding or the `key` of the `kafka` message binding.
+    Binding { protocol: String, field: String },
+    /// An entry of the `components/schemas` section.
+    Component,
+}
+
+/// A schema as written at a site of the document.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SiteSchema<'a> {
+    /// A schema field accepting multi-format schemas.
+    Document(&'a RefOr<Either<schemars::Schema, MultiFormatSchema>>),
+    /// A schema field of the typed bindings.
+    Binding(&'a RefOr<schemars::Schema>),
+    /// A schema field of the untyped bindings.
+    Untyped(&'a Value),
+}
+
+impl SiteSchema<'_> {
+    /// Returns the `$ref` of the schema, if it is a reference.
+    pub fn reference(&self) -> Option<&str> {
+        match self {
+            Self::Document(RefOr::Left(reference)) | Self::Binding(RefOr::Left(reference)) => {
+                Some(&reference.reference)
+            }
+            Self::Untyped(value) => value.get("$ref")?.as_str(),
+            _ => None,
+        }
+    }
+}
+
+/// A schema of a document with its role, see [AsyncApiV3Spec::schemas].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SchemaSite<'a> {
+    /// What the schema describes.
+    pub role: SchemaRole,
+    /// Location of the object owning the schema, e.g. the message of a payload.
+    pub owner: JsonPointer,
+    /// Location of the schema itself.
+    pub pointer: JsonPointer,
+    /// The schema as written at the site, possibly a reference.
+    pub schema: SiteSchema<'a>,
+}
+
+/// Iterator over the schemas of a document, returned by [AsyncApiV3Spec::schemas].
+#[derive(Clone, Debug)]
+pub struct SchemaSites<'a> {
+    sites: vec::IntoIter<SchemaSite<'a>>,
+}
+
+impl<'a> SchemaSites<'a> {
+    /// Keeps a single site per schema: a reference is dropped when its target is listed by an
+    /// earlier site, or is the target of an earlier reference.
+    pub fn deduplicated(self) -> impl Iterator<Item = SchemaSite<'a>> {
+        let mut seen = Vec::<String>::new();
+        self.sites.filter(move |site| {
+            let key = match site.schema.reference() {
+                Some(reference) => String::from(reference),
+                None => format!("#{}", site.pointer),
+            };
+            if seen.contains(&key) {
+                return false;
+            }
+            seen.push(key);
+            true
+        })
+    }
+}
+
+impl<'a> Iterator for SchemaSites<'a> {
+    type Item = SchemaSite<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.sites.next()
+    }
+}
+
+impl AsyncApiV3Spec {
+    /// Lists the schemas of the document with their role: message payloads and headers, the
+    /// schemas of channel and message bindings, and the `components/schemas` entries.
+    ///
+    /// Nothing is resolved: objects defined by a reference are visited where they are defined,
+    /// and a schema referenced from several places is listed at each of them, see
+    /// [SchemaSites::deduplicated].
+    pub fn schemas(&self) -> SchemaSites<'_> {
+        let mut sites = Vec::new();
+        let root = JsonPointer::default();
+        let components = root.join("components");
+        for (section, channels) in [
+            (&root, &self.channels),
+            (&components, &self.components.channels),
+        ] {
+            for (name, channel) in channels {
+                let RefOr::Right(channel) = channel else {
+                    continue;
+                };
+                let owner = section.join("channels").join(name.as_str());
+                if let Some(RefOr::Right(bindings)) = &channel.bindings {
+                    channel_binding_sites(bindings, &owner, &owner.join("bindings"), &mut sites);
+                }
+                for (key, message) in &channel.messages {
+                    if let RefOr::Right(message) = message {
+                        let owner = owner.join("messages").join(key.as_str());
+                        message_sites(message, &owner, &mut sites);
+                    }
+                }
+            }
+        }
+        for (name, message) in &self.components.messages {
+            if let RefOr::Right(message) = message {
+                let owner = components.join("messages").join(name.as_str());
+                message_sites(message, &owner, &mut sites);
+            }
+        }
+        for (name, message_trait) in &self.components.message_traits {
+            if let RefOr::Right(message_trait) = message_trait {
+                let owner = components.join("messageTraits").join(name.as_str());
+                push_headers(&message_trait.headers, &owner, &mut sites);
+            }
+        }
+        for (name, bindings) in &self.components.channel_bindings {
+            if let RefOr::Right(bindings) = bindings {
+                let owner = components.join("channelBindings").join(name.as_str());
+                channel_binding_sites(bindings, &owner, &owner, &mut sites);
+            }
+        }
+        for (name, bindings) in &self.components.message_bindings {
+            if let RefOr::Right(bindings) = bindings {
+                let owner = components.join("messageBindings").join(name.as_str());
+                message_binding_sites(bindings, &owner, &owner, &mut sites);
+            }
+        }
+        for (name, schema) in &self.components.schemas {
+            let pointer = components.join("schemas").join(name.as_str());
+            sites.push(SchemaSite {
+                role: SchemaRole::Component,
+                owner: pointer.clone(),
+                pointer,
+                schema: SiteSchema::Document(schema),
+            });
+        }
+        SchemaSites {
+            sites: sites.into_iter(),
+        }
+    }
+}
+
+fn message_sites<'a>(message: &'a Message, owner: &JsonPointer, sites: &mut Vec<SchemaSite<'a>>) {
+    if let Some(payload) = &message.payload {
+        sites.push(SchemaSite {
+            role: SchemaRole::Payload,
+            owner: owner.clone(),
+            pointer: owner.join("payload"),
+            schema: SiteSchema::Document(payload),
+        });
+    }
+    push_headers(&message.headers, owner, sites);
+    for (index, message_trait) in message.traits.iter().enumerate() {
+        if let RefOr::Right(message_trait) = message_trait {
+            let owner = owner.join("traits").join(format!("{index}"));
+            push_headers(&message_trait.headers, &owner, sites);
+        }
+    }
+    if let Some(RefOr::Right(bindings)) = &message.bindings {
+        message_binding_sites(bindings, owner, &owner.join("bindings"), sites);
+    }
+}
+
+fn push_headers<'a>(
+    headers: &'a Option<RefOr<Either<schemars::Schema, MultiFormatSchema>>>,
+    owner: &JsonPointer,
+    sites: &mut Vec<SchemaSite<'a>>,
+) {
+    if let Some(headers) = headers {
+        sites.push(SchemaSite {
+            role: SchemaRole::Headers,
+            owner: owner.clone(),
+            pointer: owner.join("headers"),
+            schema: SiteSchema::Document(headers),
+        });
+    }
+}
+
+fn binding_site<'a>(
+    protocol: &str,
+    field: &str,
+    schema: SiteSchema<'a>,
+    owner: &JsonPointer,
+    bindings: &JsonPointer,
+) -> SchemaSite<'a> {
+    SchemaSite {
+        role: SchemaRole::Binding {
+            protocol: String::from(protocol),
+            field: String::from(field),
+        },
+        owner: owner.clone(),
+        pointer: bindings.join(protocol).join(field),
+        schema,
+    }
+}
+
+fn channel_binding_sites<'a>(
+    bindings: &'a ChannelBindings,
+    owner: &JsonPointer,
+    pointer: &JsonPointer,
+    sites: &mut Vec<SchemaSite<'a>>,
+) {
+    if let Some(ws) = &bindings.ws {
+        for (field, schema) in [("query", &ws.query), ("headers", &ws.headers)] {
+            if let Some(schema) = schema {
+                let schema = SiteSchema::Binding(schema);
+                sites.push(binding_site("ws", field, schema, owner, pointer));
+            }
+        }
+    }
+}
+
+fn message_binding_sites<'a>(
+    bindings: &'a MessageBindings,
+    owner: &JsonPointer,
+    pointer: &JsonPointer,
+    sites: &mut Vec<SchemaSite<'a>>,
+) {
+    if let Some(headers) = bindings
+        .http
+        .as_ref()
+        .and_then(|http| http.headers.as_ref())
+    {
+        let schema = SiteSchema::Binding(headers);
+        sites.push(binding_site("http", "headers", schema, owner, pointer));
+    }
+    if let Some(key) = bindings
+        .other
+        .get("kafka")
+        .and_then(|kafka| kafka.get("key"))
+    {
+        sites.push(binding_site(
+            "kafka",
+            "key",
+            SiteSchema::Untyped(key),
+            owner,
+            pointer,
+        ));
+    }
+}
+
+fn has_type(instance: &Value, ty: &str) -> bool {
+    match ty {
+        "null" => instance.is_null(),
+        "boolean" => instance.is_boolean(),
+        "object" => instance.is_object(),
+        "array" => instance.is_array(),
+        "string" => instance.is_string(),
+        "number" => instance.is_number(),
+        "integer" => {
+            instance.is_i64()
+                || instance.is_u64()
+                || instance.as_f64().is_some_and(|n| n % 1.0 == 0.0)
+        }
+        _ => true,
+    }
+}
+
+/// Returns `true` if `number` is a multiple of `divisor`, ignoring the rounding errors of binary
+/// floating point numbers, e.g. `0.3` is a multiple of `0.1` although `0.3 / 0.1` is
+/// `2.9999999999999996`.
+fn is_multiple(number: f64, divisor: f64) -> bool {
+    let quotient = (number / divisor).abs();
+    let fraction = quotient % 1.0;
+    fraction.min(1.0 - fraction) <= 4.0 * f64::EPSILON * quotient.max(1.0)
+}
+
+fn type_name(instance: &Value) -> &'static str {
+    match instance {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::parse_spec;
+    use serde_json::json;
+
+    fn suggest(schema: Value) -> String {
+        schemars::Schema::try_from(schema)
+            .unwrap()
+            .suggest_rust_type()
+    }
+
+    #[test]
+    fn suggest_primitive_types() {
+        assert_eq!(suggest(json!({ "type": "string" })), "String");
+        assert_eq!(suggest(json!({ "type": "integer" })), "i64");
+        assert_eq!(suggest(json!({ "type": "number" })), "f64");
+        assert_eq!(suggest(json!({ "type": "boolean" })), "bool");
+        assert_eq!(suggest(json!({ "type": "null" })), "()");
+        assert_eq!(suggest(json!({})), "serde_json::Value");
+    }
+
+    #[test]
+    fn suggest_array_types() {
+        assert_eq!(
+            suggest(json!({ "type": "array", "items": { "type": "integer" } })),
+            "Vec<i64>"
+        );
+        assert_eq!(
+            suggest(json!({
+                "type": "array",
+                "items": { "type": "array", "items": { "$ref": "#/components/schemas/light-level" } }
+            })),
+            "Vec<Vec<LightLevel>>"
+        );
+        assert_eq!(
+            suggest(json!({ "type": "array" })),
+            "Vec<serde_json::Value>"
+        );
+    }
+
+    #[test]
+    fn suggest_nullable_types() {
+        assert_eq!(
+            suggest(json!({ "type": "string", "nullable": true })),
+            "Option<String>"
+        );
+        assert_eq!(
+            suggest(json!({ "type": ["integer", "null"] })),
+            "Option<i64>"
+        );
+        assert_eq!(
+            suggest(json!({
+                "type": ["array", "null"],
+                "items": { "type": ["string", "null"] }
+            })),
+            "Option<Vec<Option<String>>>"
+        );
+    }
+
+    #[test]
+    fn suggest_named_types() {
+        assert_eq!(
+            suggest(json!({
+                "type": "object",
+                "title": "user signed up",
+                "properties": { "id": { "type": "string" } }
+            })),
+            "UserSignedUp"
+        );
+        assert_eq!(
+            suggest(json!({ "type": "string", "title": "color", "enum": ["red", "green"] })),
+            "Color"
+        );
+        assert_eq!(
+            suggest(json!({ "type": "object", "additionalProperties": { "type": "number" } })),
+            "HashMap<String, f64>"
+        );
+    }
+
+    #[test]
+    fn property_count_bounds() {
+        let spec = parse_spec("asyncapi: 3.0.0\ninfo:\n  title: Tags\n  version: 1.0.0\n");
+        let validator = SchemaValidator::new(&spec);
+        let schema = json!({ "type": "object", "minProperties": 1, "maxProperties": 2 });
+        let reasons = |instance: Value| {
+            validator
+                .validate(&schema, &instance)
+                .into_iter()
+                .map(|violation| violation.reason)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(reasons(json!({})), ["object has fewer than 1 properties"]);
+        assert!(reasons(json!({ "a": 1, "b": 2 })).is_empty());
+        assert_eq!(
+            reasons(json!({ "a": 1, "b": 2, "c": 3 })),
+            ["object has more than 2 properties"]
+        );
+    }
+
+    #[test]
+    fn decimal_multiples() {
+        let spec = parse_spec("asyncapi: 3.0.0\ninfo:\n  title: Prices\n  version: 1.0.0\n");
+        let validator = SchemaValidator::new(&spec);
+        let schema = json!({ "type": "number", "multipleOf": 0.1 });
+        let reasons = |instance: Value| {
+            validator
+                .validate(&schema, &instance)
+                .into_iter()
+                .map(|violation| violation.reason)
+                .collect::<Vec<_>>()
+        };
+
+        assert!(reasons(json!(0.3)).is_empty());
+        assert!(reasons(json!(-1.7)).is_empty());
+        assert!(reasons(json!(12345.6)).is_empty());
+        assert_eq!(reasons(json!(0.35)), ["0.35 is not a multiple of 0.1"]);
+    }
+
+    #[test]
+    fn property_names_schema() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Tags
+  version: 1.0.0
+components:
+  schemas:
+    labels:
+      type: object
+      minProperties: 1
+      maxProperties: 8
+      propertyNames:
+        pattern: '^[a-z]+$'
+        maxLength: 5
+      additionalProperties:
+        type: string
+"#,
+        );
+        let schema = json_schema(&spec.components.schemas["labels"], &spec).unwrap();
+        let validator = SchemaValidator::new(&spec);
+
+        let written = serde_json::to_value(&spec.components.schemas["labels"]).unwrap();
+        assert_eq!(written["minProperties"], 1);
+        assert_eq!(written["maxProperties"], 8);
+        assert_eq!(written["propertyNames"]["pattern"], "^[a-z]+$");
+        assert!(subschemas(schema, &JsonPointer::default())
+            .iter()
+            .any(|(_, pointer)| pointer.to_string() == "/propertyNames"));
+        assert!(validator
+            .validate(schema, &json!({ "team": "core" }))
+            .is_empty());
+        assert_eq!(
+            validator.validate(schema, &json!({ "team": "core", "release": "1.0" })),
+            [SchemaViolation {
+                pointer: JsonPointer::default(),
+                reason: String::from(
+                    "property name 'release' does not match the propertyNames schema"
+                ),
+            }]
+        );
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn string_patterns() {
+        let spec = parse_spec("asyncapi: 3.0.0\ninfo:\n  title: Tags\n  version: 1.0.0\n");
+        let validator = SchemaValidator::new(&spec);
+        let schema = json!({
+            "type": "object",
+            "propertyNames": { "pattern": "^[a-z]+$" },
+            "properties": { "sku": { "type": "string", "pattern": "[0-9]{4}" } }
+        });
+        let reasons = |instance: Value| {
+            validator
+                .validate(&schema, &instance)
+                .into_iter()
+                .map(|violation| violation.reason)
+                .collect::<Vec<_>>()
+        };
+
+        assert!(reasons(json!({ "sku": "AB-1234" })).is_empty());
+        assert_eq!(
+            reasons(json!({ "sku": "AB-12" })),
+            ["string does not match the pattern '[0-9]{4}'"]
+        );
+        assert_eq!(
+            reasons(json!({ "Team": "core" })),
+            ["property name 'Team' does not match the propertyNames schema"]
+        );
+        assert_eq!(
+            validator.validate(&json!({ "pattern": "(" }), &json!("core"))[0].reason,
+            "invalid pattern '('"
+        );
+    }
+
+    #[test]
+    fn resolve_ref_chain_is_cached() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Users
+  version: 1.0.0
+components:
+  schemas:
+    customer:
+      $ref: '#/components/schemas/user'
+    user:
+      type: object
+      required: [id]
+      properties:
+        id:
+          type: integer
+    loop:
+      properties:
+        next:
+          $ref: '#/components/schemas/loop/properties/next'
+"#,
+        );
+        let resolver = SchemaResolver::new(&spec);
+        assert_eq!(resolver.cached(), 0);
+
+        let first = resolver
+            .resolve_ref_chain("#/components/schemas/customer")
+            .unwrap();
+        let second = resolver
+            .resolve_ref_chain("#/components/schemas/customer")
+            .unwrap();
+
+        assert_eq!(first["required"], json!(["id"]));
+        assert!(core::ptr::eq(first, second));
+        assert_eq!((resolver.cached(), resolver.cache_hits()), (1, 1));
+
+        let id = resolver
+            .resolve_ref_chain("#/components/schemas/user/properties/id")
+            .unwrap();
+        let again = resolver
+            .resolve_ref_chain("#/components/schemas/user/properties/id")
+            .unwrap();
+
+        assert_eq!(id, &json!({ "type": "integer" }));
+        assert!(core::ptr::eq(id, again));
+        assert_eq!((resolver.cached(), resolver.cache_hits()), (2, 2));
+        assert!(matches!(
+            resolver.resolve_ref_chain("#/components/schemas/loop/properties/next"),
+            Err(Error::CyclicReference { .. })
+        ));
+        assert!(matches!(
+            resolver.resolve_ref_chain("#/components/schemas/missing"),
+            Err(Error::UnresolvedReference { .. })
+        ));
+        assert_eq!((resolver.cached(), resolver.cache_hits()), (2, 2));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn validators_are_shared_between_threads() {
+        fn shared<T: Send + Sync>() {}
+
+        shared::<SchemaValidator>();
+    }
+
+    #[test]
+    fn walk_nested_property_paths() {
+        let schema = schemars::Schema::try_from(json!({
+            "type": "object",
+            "properties": {
+                "id": { "type": "integer" },
+                "address": {
+                    "type": "object",
+                    "properties": {
+                        "city": { "type": "string" },
+                        "geo": { "$ref": "#/$defs/point" }
+                    }
+                },
+                "tags": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": { "name": { "type": "string" } }
+                    }
+                },
+                "parent": { "$ref": "#/$defs/node" }
+            },
+            "$defs": {
+                "point": {
+                    "description": "A position.",
+                    "properties": { "lat": { "type": "number" }, "lon": { "type": "number" } }
+                },
+                "node": {
+                    "properties": { "parent": { "$ref": "#/$defs/node" } }
+                }
+            }
+        }))
+        .unwrap();
+
+        let mut visited = Vec::new();
+        schema.walk_properties(&mut |path, schema| {
+            let description = schema.get("description").and_then(Value::as_str);
+            visited.push((path.to_string(), description.map(String::from)));
+        });
+
+        let paths = visited
+            .iter()
+            .map(|(path, _)| path.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec![
+                "address",
+                "address.city",
+                "address.geo",
+                "address.geo.lat",
+                "address.geo.lon",
+                "id",
+                "parent",
+                "parent.parent",
+                "tags",
+                "tags.name",
+            ]
+        );
+        assert_eq!(visited[2].1.as_deref(), Some("A position."));
+    }
+
+    #[test]
+    fn list_schemas_with_their_role() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Orders
+  version: 1.0.0
+channels:
+  orders:
+    address: orders
+    bindings:
+      ws:
+        query:
+          type: object
+    messages:
+      orderPlaced:
+        payload:
+          $ref: '#/components/schemas/order'
+        headers:
+          type: object
+        bindings:
+          kafka:
+            key:
+              type: string
+      orderShipped:
+        payload:
+          $ref: '#/components/schemas/order'
+components:
+  schemas:
+    order:
+      type: object
+"#,
+        );
+
+        let sites = spec
+            .schemas()
+            .map(|site| (site.role, site.pointer.to_string()))
+            .collect::<Vec<_>>();
+        let binding = |protocol: &str, field: &str| SchemaRole::Binding {
+            protocol: String::from(protocol),
+            field: String::from(field),
+        };
+        assert_eq!(
+            sites,
+            vec![
+                (
+                    binding("ws", "query"),
+                    String::from("/channels/orders/bindings/ws/query")
+                ),
+                (
+                    SchemaRole::Payload,
+                    String::from("/channels/orders/messages/orderPlaced/payload")
+                ),
+                (
+                    SchemaRole::Headers,
+                    String::from("/channels/orders/messages/orderPlaced/headers")
+                ),
+                (
+                    binding("kafka", "key"),
+                    String::from("/channels/orders/messages/orderPlaced/bindings/kafka/key")
+                ),
+                (
+                    SchemaRole::Payload,
+                    String::from("/channels/orders/messages/orderShipped/payload")
+                ),
+                (
+                    SchemaRole::Component,
+                    String::from("/components/schemas/order")
+                ),
+            ]
+        );
+        let order = "#/components/schemas/order";
+        let references = |sites: Vec<SchemaSite>| {
+            sites
+                .iter()
+                .filter(|site| site.schema.reference() == Some(order))
+                .count()
+        };
+        assert_eq!(references(spec.schemas().collect()), 2);
+        assert_eq!(references(spec.schemas().deduplicated().collect()), 1);
+    }
+}
@@ -1,41 +1,527 @@
 //! Represents the AsyncAPI security property as well as the various security schemes supported in
 //! the specification.
-use std::collections::HashMap;
+use crate::spec::common::{Extensions, RefOr};
+use crate::spec::operation::OperationTrait;
+use crate::spec::AsyncApiV3Spec;
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
 
 /// You can describe how your server is secured with the security property where you define
 /// which security schemes can be used with the server in context. Each server in the
 /// AsyncAPI document can have one or more security schemes declared. A security scheme
 /// defines a security requirement that must be satisfied to authorize an operation, such as an
 /// API key or a username and password.
-#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+///
+/// Schemes of a type this crate does not model are kept as [SecurityScheme::Other].
+#[derive(Clone, Debug, PartialEq)]
+pub enum SecurityScheme {
+    UserPassword(UserPasswordSecurityScheme),
+    ApiKey(ApiKeySecurityScheme),
+    X509(X509SecurityScheme),
+    SymmetricEncryption(SymmetricEncryptionSecurityScheme),
+    AsymmetricEncryption(AsymmetricEncryptionSecurityScheme),
+    HttpApiKey(HttpApiKeySecurityScheme),
+    Http(HttpSecurityScheme),
+    Oauth2(Box<Oauth2SecurityScheme>),
+    OpenIdConnect(OpenIdConnectSecurityScheme),
+    Plain(PlainSecurityScheme),
+    ScramSha256(ScramSha256SecurityScheme),
+    ScramSha512(ScramSha512SecurityScheme),
+    Gssapi(GssapiSecurityScheme),
+    /// A scheme of another type, e.g. added by a later version of the specification.
+    Other {
+        /// The value of the `type` field.
+        type_: String,
+        /// The other fields of the scheme, as written.
+        raw: Map<String, Value>,
+    },
+}
+
+/// A scheme along with its `type` field, which comes first.
+#[derive(Serialize)]
+struct Tagged<'a, T> {
+    #[serde(rename = "type")]
+    type_: &'a str,
+    #[serde(flatten)]
+    scheme: &'a T,
+}
+
+impl Serialize for SecurityScheme {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        fn tagged<S: Serializer, T: Serialize>(
+            serializer: S,
+            type_: &str,
+            scheme: &T,
+        ) -> Result<S::Ok, S::Error> {
+            Tagged { type_, scheme }.serialize(serializer)
+        }
+        let type_ = self.kind().as_str();
+        match self {
+            SecurityScheme::UserPassword(scheme) => tagged(serializer, type_, scheme),
+            SecurityScheme::ApiKey(scheme) => tagged(serializer, type_, scheme),
+            SecurityScheme::X509(scheme) => tagged(serializer, type_, scheme),
+            SecurityScheme::SymmetricEncryption(scheme) => tagged(serializer, type_, scheme),
+            SecurityScheme::AsymmetricEncryption(scheme) => tagged(serializer, type_, scheme),
+            SecurityScheme::HttpApiKey(scheme) => tagged(serializer, type_, scheme),
+            SecurityScheme::Http(scheme) => tagged(serializer, type_, scheme),
+            SecurityScheme::Oauth2(scheme) => tagged(serializer, type_, scheme),
+            SecurityScheme::OpenIdConnect(scheme) => tagged(serializer, type_, scheme),
+            SecurityScheme::Plain(scheme) => tagged(serializer, type_, scheme),
+            SecurityScheme::ScramSha256(scheme) => tagged(serializer, type_, scheme),
+            SecurityScheme::ScramSha512(scheme) => tagged(serializer, type_, scheme),
+            SecurityScheme::Gssapi(scheme) => tagged(serializer, type_, scheme),
+            SecurityScheme::Other { type_, raw } => tagged(serializer, type_, raw),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for SecurityScheme {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        fn scheme<'de, T: Deserialize<'de>, E: serde::de::Error>(
+            fields: &'de Map<String, Value>,
+        ) -> Result<T, E> {
+            T::deserialize(fields).map_err(E::custom)
+        }
+
+        let mut fields = Map::<String, Value>::deserialize(deserializer)?;
+        let type_ = match fields.remove("type") {
+            Some(Value::String(type_)) => type_,
+            Some(_) => {
+                return Err(D::Error::custom(
+                    "the `type` of a security scheme is a string",
+                ))
+            }
+            None => return Err(D::Error::missing_field("type")),
+        };
+        let kind = SecuritySchemeKind::deserialize(Value::String(type_.clone()))
+            .map_err(D::Error::custom)?;
+        Ok(match kind {
+            SecuritySchemeKind::UserPassword => SecurityScheme::UserPassword(scheme(&fields)?),
+            SecuritySchemeKind::ApiKey => SecurityScheme::ApiKey(scheme(&fields)?),
+            SecuritySchemeKind::X509 => SecurityScheme::X509(scheme(&fields)?),
+            SecuritySchemeKind::SymmetricEncryption => {
+                SecurityScheme::SymmetricEncryption(scheme(&fields)?)
+            }
+            SecuritySchemeKind::AsymmetricEncryption => {
+                SecurityScheme::AsymmetricEncryption(scheme(&fields)?)
+            }
+            SecuritySchemeKind::HttpApiKey => SecurityScheme::HttpApiKey(scheme(&fields)?),
+            SecuritySchemeKind::Http => SecurityScheme::Http(scheme(&fields)?),
+            SecuritySchemeKind::Oauth2 => SecurityScheme::Oauth2(scheme(&fields)?),
+            SecuritySchemeKind::OpenIdConnect => SecurityScheme::OpenIdConnect(scheme(&fields)?),
+            SecuritySchemeKind::Plain => SecurityScheme::Plain(scheme(&fields)?),
+            SecuritySchemeKind::ScramSha256 => SecurityScheme::ScramSha256(scheme(&fields)?),
+            SecuritySchemeKind::ScramSha512 => SecurityScheme::ScramSha512(scheme(&fields)?),
+            SecuritySchemeKind::Gssapi => SecurityScheme::Gssapi(scheme(&fields)?),
+            SecuritySchemeKind::Other => SecurityScheme::Other { type_, raw: fields },
+        })
+    }
+}
+
+impl SecurityScheme {
+    /// Returns the type of the scheme.
+    pub fn kind(&self) -> SecuritySchemeKind {
+        match self {
+            SecurityScheme::UserPassword(_) => SecuritySchemeKind::UserPassword,
+            SecurityScheme::ApiKey(_) => SecuritySchemeKind::ApiKey,
+            SecurityScheme::X509(_) => SecuritySchemeKind::X509,
+            SecurityScheme::SymmetricEncryption(_) => SecuritySchemeKind::SymmetricEncryption,
+            SecurityScheme::AsymmetricEncryption(_) => SecuritySchemeKind::AsymmetricEncryption,
+            SecurityScheme::HttpApiKey(_) => SecuritySchemeKind::HttpApiKey,
+            SecurityScheme::Http(_) => SecuritySchemeKind::Http,
+            SecurityScheme::Oauth2(_) => SecuritySchemeKind::Oauth2,
+            SecurityScheme::OpenIdConnect(_) => SecuritySchemeKind::OpenIdConnect,
+            SecurityScheme::Plain(_) => SecuritySchemeKind::Plain,
+            SecurityScheme::ScramSha256(_) => SecuritySchemeKind::ScramSha256,
+            SecurityScheme::ScramSha512(_) => SecuritySchemeKind::ScramSha512,
+            SecurityScheme::Gssapi(_) => SecuritySchemeKind::Gssapi,
+            SecurityScheme::Other { .. } => SecuritySchemeKind::Other,
+        }
+    }
+
+    /// Returns the Kafka SASL mechanism (the `sasl.mechanism` client setting) implemented by the
+    /// scheme, if any.
+    pub fn sasl_mechanism(&self) -> Option<SaslMechanism> {
+        match self {
+            SecurityScheme::Plain(_) => Some(SaslMechanism::Plain),
+            SecurityScheme::ScramSha256(_) => Some(SaslMechanism::ScramSha256),
+            SecurityScheme::ScramSha512(_) => Some(SaslMechanism::ScramSha512),
+            SecurityScheme::Gssapi(_) => Some(SaslMechanism::Gssapi),
+            _ => None,
+        }
+    }
+
+    /// Returns an illustrative snippet showing how a client authenticates with the scheme, for
+    /// documentation, e.g. `Authorization: Bearer <token>` for an http bearer scheme.
+    pub fn example_usage(&self) -> UsageExample {
+        let placeholder = |name: &str, description: String| Placeholder {
+            name: String::from(name),
+            description,
+        };
+        let sasl = |mechanism: SaslMechanism, credentials: [(&str, &str, &str); 2]| {
+            let mut template = format!("sasl.mechanism={mechanism}");
+            let mut placeholders = Vec::new();
+            for (setting, name, description) in credentials {
+                template.push_str(&format!("\nsasl.{setting}=<{name}>"));
+                placeholders.push(placeholder(name, String::from(description)));
+            }
+            UsageExample {
+                kind: UsageKind::SaslConfig,
+                template,
+                placeholders,
+            }
+        };
+        let sasl_password = [
+            ("username", "username", "the SASL username"),
+            ("password", "password", "the SASL password"),
+        ];
+        let bearer = |name: &str, description: String| UsageExample {
+            kind: UsageKind::Header,
+            template: format!("Authorization: Bearer <{name}>"),
+            placeholders: Vec::from([placeholder(name, description)]),
+        };
+
+        match self {
+            SecurityScheme::UserPassword(_) => UsageExample {
+                kind: UsageKind::Credentials,
+                template: String::from("username=<username>\npassword=<password>"),
+                placeholders: Vec::from([
+                    placeholder("username", String::from("the user name")),
+                    placeholder("password", String::from("the password of the user")),
+                ]),
+            },
+            SecurityScheme::ApiKey(scheme) => {
+                let field = match scheme.location {
+                    ApiKeyLocation::User => "username",
+                    ApiKeyLocation::Password => "password",
+                };
+                UsageExample {
+                    kind: UsageKind::Credentials,
+                    template: format!("{field}=<api-key>"),
+                    placeholders: Vec::from([placeholder(
+                        "api-key",
+                        format!("the API key, sent as the {field}"),
+                    )]),
+                }
+            }
+            SecurityScheme::X509(_) => UsageExample {
+                kind: UsageKind::Tls,
+                template: String::from(
+                    "certificate=<client-certificate.pem>\nprivate-key=<client-key.pem>",
+                ),
+                placeholders: Vec::from([
+                    placeholder(
+                        "client-certificate.pem",
+                        String::from("the PEM encoded client certificate"),
+                    ),
+                    placeholder(
+                        "client-key.pem",
+                        String::from("the PEM encoded private key of the certificate"),
+                    ),
+                ]),
+            },
+            SecurityScheme::SymmetricEncryption(_) => UsageExample {
+                kind: UsageKind::Encryption,
+                template: String::from("key=<shared-key>"),
+                placeholders: Vec::from([placeholder(
+                    "shared-key",
+                    String::from("the key shared with the server"),
+                )]),
+            },
+            SecurityScheme::AsymmetricEncryption(_) => UsageExample {
+                kind: UsageKind::Encryption,
+                template: String::from("public-key=<server-public-key>\nprivate-key=<private-key>"),
+                placeholders: Vec::from([
+                    placeholder(
+                        "server-public-key",
+                        String::from("the public key of the server"),
+                    ),
+                    placeholder("private-key", String::from("the private key of the client")),
+                ]),
+            },
+            SecurityScheme::HttpApiKey(scheme) => {
+                let name = &scheme.name;
+                let (kind, template) = match scheme.location {
+                    HttpApiKeyLocation::Query => (UsageKind::Query, format!("?{name}=<api-key>")),
+                    HttpApiKeyLocation::Header => (UsageKind::Header, format!("{name}: <api-key>")),
+                    HttpApiKeyLocation::Cookie => {
+                        (UsageKind::Cookie, format!("Cookie: {name}=<api-key>"))
+                    }
+                };
+                UsageExample {
+                    kind,
+                    template,
+                    placeholders: Vec::from([placeholder("api-key", String::from("the API key"))]),
+                }
+            }
+            SecurityScheme::Http(scheme) => match scheme.scheme.to_ascii_lowercase().as_str() {
+                "bearer" => bearer(
+                    "token",
+                    match &scheme.bearer_format {
+                        Some(format) => format!("the bearer token, formatted as {format}"),
+                        None => String::from("the bearer token"),
+                    },
+                ),
+                "basic" => UsageExample {
+                    kind: UsageKind::Header,
+                    template: String::from("Authorization: Basic <credentials>"),
+                    placeholders: Vec::from([placeholder(
+                        "credentials",
+                        String::from("the base64 encoding of `username:password`"),
+                    )]),
+                },
+                _ => UsageExample {
+                    kind: UsageKind::Header,
+                    template: format!("Authorization: {} <credentials>", scheme.scheme),
+                    placeholders: Vec::from([placeholder(
+                        "credentials",
+                        format!("the credentials of the {} scheme", scheme.scheme),
+                    )]),
+                },
+            },
+            SecurityScheme::Oauth2(scheme) => bearer(
+                "access-token",
+                match scheme.flows.iter().find_map(|(_, flow)| flow.token_url) {
+                    Some(url) => format!("an OAuth2 access token, issued by {url}"),
+                    None => String::from("an OAuth2 access token"),
+                },
+            ),
+            SecurityScheme::OpenIdConnect(scheme) => bearer(
+                "access-token",
+                format!(
+                    "an access token of the provider described at {}",
+                    scheme.open_id_connect_url
+                ),
+            ),
+            SecurityScheme::Plain(_) => sasl(SaslMechanism::Plain, sasl_password),
+            SecurityScheme::ScramSha256(_) => sasl(SaslMechanism::ScramSha256, sasl_password),
+            SecurityScheme::ScramSha512(_) => sasl(SaslMechanism::ScramSha512, sasl_password),
+            SecurityScheme::Gssapi(_) => sasl(
+                SaslMechanism::Gssapi,
+                [
+                    ("kerberos.principal", "principal", "the Kerberos principal"),
+                    (
+                        "kerberos.keytab",
+                        "keytab-path",
+                        "the path of the keytab of the principal",
+                    ),
+                ],
+            ),
+            SecurityScheme::Other { type_, .. } => UsageExample {
+                kind: UsageKind::Credentials,
+                template: String::from("<credentials>"),
+                placeholders: Vec::from([placeholder(
+                    "credentials",
+                    format!("the credentials of the `{type_}` scheme"),
+                )]),
+            },
+        }
+    }
+}
+
+/// An illustrative snippet of how a client authenticates with a security scheme, see
+/// [SecurityScheme::example_usage].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UsageExample {
+    pub kind: UsageKind,
+    /// The snippet, where each placeholder is written as `<name>`, e.g.
+    /// `Authorization: Bearer <token>`.
+    pub template: String,
+    /// The placeholders of the template, in order.
+    pub placeholders: Vec<Placeholder>,
+}
+
+/// Where the snippet of a [UsageExample] goes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum UsageKind {
+    /// An HTTP header line.
+    Header,
+    /// An HTTP query string.
+    Query,
+    /// An HTTP cookie header line.
+    Cookie,
+    /// Credentials given to the client, one `key=value` per line.
+    Credentials,
+    /// TLS client settings, one `key=value` per line.
+    Tls,
+    /// Encryption keys, one `key=value` per line.
+    Encryption,
+    /// Kafka client SASL settings, one `key=value` per line.
+    SaslConfig,
+}
+
+/// A value of a [UsageExample] to be replaced by an actual credential.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Placeholder {
+    /// The name of the placeholder, written as `<name>` in the template.
+    pub name: String,
+    pub description: String,
+}
+
+/// The type of a [SecurityScheme], as named by its `type` field.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
 #[serde(rename_all = "camelCase")]
-pub struct SecurityScheme {
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub user_password: Option<UserPasswordSecurityScheme>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub api_key: Option<ApiKeySecurityScheme>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub x509: Option<X509SecurityScheme>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub symmetric_encryption: Option<SymmetricEncryptionSecurityScheme>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub asymmetric_encryption: Option<AsymmetricEncryptionSecurityScheme>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub http_api_key: Option<HttpApiKeySecurityScheme>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub http: Option<HttpSecurityScheme>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub oauth2: Option<Oauth2SecurityScheme>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub open_id_connect: Option<OpenIdConnectSecurityScheme>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub plain: Option<PlainSecurityScheme>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub scram_sha256: Option<ScramSha256SecurityScheme>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub scram_sha512: Option<ScramSha512SecurityScheme>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub gssapi: Option<GssapiSecurityScheme>,
+pub enum SecuritySchemeKind {
+    UserPassword,
+    ApiKey,
+    #[serde(rename = "X509")]
+    X509,
+    SymmetricEncryption,
+    AsymmetricEncryption,
+    HttpApiKey,
+    Http,
+    Oauth2,
+    OpenIdConnect,
+    Plain,
+    ScramSha256,
+    ScramSha512,
+    Gssapi,
+    /// A type this crate does not model, see [SecurityScheme::Other].
+    #[serde(other)]
+    Other,
+}
+
+impl SecuritySchemeKind {
+    /// Returns the value of the `type` field, e.g. `httpApiKey`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SecuritySchemeKind::UserPassword => "userPassword",
+            SecuritySchemeKind::ApiKey => "apiKey",
+            SecuritySchemeKind::X509 => "X509",
+            SecuritySchemeKind::SymmetricEncryption => "symmetricEncryption",
+            SecuritySchemeKind::AsymmetricEncryption => "asymmetricEncryption",
+            SecuritySchemeKind::HttpApiKey => "httpApiKey",
+            SecuritySchemeKind::Http => "http",
+            SecuritySchemeKind::Oauth2 => "oauth2",
+            SecuritySchemeKind::OpenIdConnect => "openIdConnect",
+            SecuritySchemeKind::Plain => "plain",
+            SecuritySchemeKind::ScramSha256 => "scramSha256",
+            SecuritySchemeKind::ScramSha512 => "scramSha512",
+            SecuritySchemeKind::Gssapi => "gssapi",
+            SecuritySchemeKind::Other => "other",
+        }
+    }
+}
+
+impl fmt::Display for SecuritySchemeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl AsyncApiV3Spec {
+    /// Returns the types of the security schemes defined anywhere in the document: in the
+    /// components or inline in servers, operations and operation traits.
+    ///
+    /// Schemes defined through a reference which cannot be resolved are skipped.
+    pub fn security_scheme_kinds(&self) -> BTreeSet<SecuritySchemeKind> {
+        let servers = self
+            .servers
+            .values()
+            .chain(self.components.servers.values());
+        let operations = self
+            .operations
+            .values()
+            .chain(self.components.operations.values())
+            .filter_map(|operation| operation.resolve(self).ok());
+        let traits = operations
+            .clone()
+            .flat_map(|operation| &operation.traits)
+            .chain(self.components.operation_traits.values())
+            .filter_map(|operation_trait: &RefOr<OperationTrait>| {
+                operation_trait.resolve(self).ok()
+            });
+        servers
+            .filter_map(|server| server.resolve(self).ok())
+            .flat_map(|server| &server.security)
+            .chain(operations.flat_map(|operation| &operation.security))
+            .chain(traits.filter_map(|operation_trait| operation_trait.security.as_ref()))
+            .chain(self.components.security_schemes.values())
+            .filter_map(|scheme| scheme.resolve(self).ok())
+            .map(SecurityScheme::kind)
+            .collect()
+    }
+
+    /// Returns the scheme name, location and parameter name of every HTTP API key scheme of
+    /// the components, sorted by scheme name, e.g. to generate the code signing requests.
+    ///
+    /// Schemes defined inline in servers and operations have no name and are not listed.
+    /// References which cannot be resolved are skipped.
+    pub fn http_api_key_params(&self) -> Vec<(String, HttpApiKeyLocation, String)> {
+        self.components
+            .security_schemes
+            .iter()
+            .filter_map(|(name, scheme)| match scheme.resolve(self).ok()? {
+                SecurityScheme::HttpApiKey(scheme) => {
+                    Some((name.clone(), scheme.location, scheme.name.clone()))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// The security a client must satisfy to perform an operation: one of the schemes of the server
+/// it connects to, and one of the schemes of the operation, if it lists any.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SecurityRequirements<'a> {
+    /// The alternative schemes of each server, by name. Servers without security are listed
+    /// without schemes.
+    pub servers: BTreeMap<&'a str, Vec<&'a SecurityScheme>>,
+    /// The alternative schemes of the operation itself.
+    pub operation: Vec<&'a SecurityScheme>,
+}
+
+impl SecurityRequirements<'_> {
+    /// Returns the types of all the schemes, of the servers and of the operation.
+    pub fn kinds(&self) -> BTreeSet<SecuritySchemeKind> {
+        self.servers
+            .values()
+            .flatten()
+            .chain(&self.operation)
+            .map(|scheme| scheme.kind())
+            .collect()
+    }
+}
+
+/// A SASL mechanism, as named by the Kafka `sasl.mechanism` client setting.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SaslMechanism {
+    /// `PLAIN`, a username and password sent as is.
+    Plain,
+    /// `SCRAM-SHA-256`, a salted challenge-response with SHA-256.
+    ScramSha256,
+    /// `SCRAM-SHA-512`, a salted challenge-response with SHA-512.
+    ScramSha512,
+    /// `GSSAPI`, Kerberos authentication.
+    Gssapi,
+}
+
+impl SaslMechanism {
+    /// Returns the value of the `sasl.mechanism` setting, e.g. `SCRAM-SHA-256`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SaslMechanism::Plain => "PLAIN",
+            SaslMechanism::ScramSha256 => "SCRAM-SHA-256",
+            SaslMechanism::ScramSha512 => "SCRAM-SHA-512",
+            SaslMechanism::Gssapi => "GSSAPI",
+        }
+    }
+}
+
+impl fmt::Display for SaslMechanism {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -44,6 +530,9 @@ pub struct UserPasswordSecurityScheme {
     /// A short description for security scheme. CommonMark syntax MAY be used for rich text representation.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// Specification extensions of the security scheme.
+    #[serde(flatten)]
+    pub extensions: Extensions,
 }
 
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -55,6 +544,9 @@ pub struct ApiKeySecurityScheme {
     /// The location of the API key. Valid values are "user" and "password" for apiKey and "query", "header" or "cookie" for httpApiKey.
     #[serde(rename = "in")]
     pub location: ApiKeyLocation,
+    /// Specification extensions of the security scheme.
+    #[serde(flatten)]
+    pub extensions: Extensions,
 }
 
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -63,6 +555,9 @@ pub struct X509SecurityScheme {
     /// A short description for security scheme. CommonMark syntax MAY be used for rich text representation.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// Specification extensions of the security scheme.
+    #[serde(flatten)]
+    pub extensions: Extensions,
 }
 
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -71,6 +566,9 @@ pub struct SymmetricEncryptionSecurityScheme {
     /// A short description for security scheme. CommonMark syntax MAY be used for rich text representation.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// Specification extensions of the security scheme.
+    #[serde(flatten)]
+    pub extensions: Extensions,
 }
 
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -79,6 +577,9 @@ pub struct AsymmetricEncryptionSecurityScheme {
     /// A short description for security scheme. CommonMark syntax MAY be used for rich text representation.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// Specification extensions of the security scheme.
+    #[serde(flatten)]
+    pub extensions: Extensions,
 }
 
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -92,6 +593,9 @@ pub struct HttpApiKeySecurityScheme {
     /// The location of the API key. Valid values are "user" and "password" for apiKey and "query", "header" or "cookie" for httpApiKey.
     #[serde(rename = "in")]
     pub location: HttpApiKeyLocation,
+    /// Specification extensions of the security scheme.
+    #[serde(flatten)]
+    pub extensions: Extensions,
 }
 
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -105,6 +609,9 @@ pub struct HttpSecurityScheme {
     /// A hint to the client to identify how the bearer token is formatted. Bearer tokens are usually generated by an authorization server, so this information is primarily for documentation purposes.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub bearer_format: Option<String>,
+    /// Specification extensions of the security scheme.
+    #[serde(flatten)]
+    pub extensions: Extensions,
 }
 
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -118,6 +625,9 @@ pub struct Oauth2SecurityScheme {
     /// List of the needed scope names. An empty array means no scopes are needed.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub scopes: Vec<String>,
+    /// Specification extensions of the security scheme.
+    #[serde(flatten)]
+    pub extensions: Extensions,
 }
 
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -131,6 +641,9 @@ pub struct OpenIdConnectSecurityScheme {
     /// List of the needed scope names. An empty array means no scopes are needed.
     #[serde(default)]
     pub scopes: Vec<String>,
+    /// Specification extensions of the security scheme.
+    #[serde(flatten)]
+    pub extensions: Extensions,
 }
 
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -139,6 +652,9 @@ pub struct PlainSecurityScheme {
     /// A short description for security scheme. CommonMark syntax MAY be used for rich text representation.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// Specification extensions of the security scheme.
+    #[serde(flatten)]
+    pub extensions: Extensions,
 }
 
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -147,6 +663,9 @@ pub struct ScramSha256SecurityScheme {
     /// A short description for security scheme. CommonMark syntax MAY be used for rich text representation.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// Specification extensions of the security scheme.
+    #[serde(flatten)]
+    pub extensions: Extensions,
 }
 
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -155,6 +674,9 @@ pub struct ScramSha512SecurityScheme {
     /// A short description for security scheme. CommonMark syntax MAY be used for rich text representation.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// Specification extensions of the security scheme.
+    #[serde(flatten)]
+    pub extensions: Extensions,
 }
 
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -163,6 +685,9 @@ pub struct GssapiSecurityScheme {
     /// A short description for security scheme. CommonMark syntax MAY be used for rich text representation.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// Specification extensions of the security scheme.
+    #[serde(flatten)]
+    pub extensions: Extensions,
 }
 
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -182,6 +707,72 @@ pub struct OAuthFlows {
     pub authorization_code: Option<AuthorizationCodeOAuthFlow>,
 }
 
+impl OAuthFlows {
+    /// Returns the configured flows, in the order of the fields.
+    pub fn iter(&self) -> impl Iterator<Item = (OAuthFlowKind, OAuthFlowRef<'_>)> {
+        let implicit = self.implicit.as_ref().map(|flow| {
+            let flow_ref = OAuthFlowRef {
+                authorization_url: Some(&flow.authorization_url),
+                token_url: None,
+                refresh_url: flow.refresh_url.as_deref(),
+                available_scopes: &flow.available_scopes,
+            };
+            (OAuthFlowKind::Implicit, flow_ref)
+        });
+        let password = self.password.as_ref().map(|flow| {
+            let flow_ref = OAuthFlowRef {
+                authorization_url: None,
+                token_url: Some(&flow.token_url),
+                refresh_url: flow.refresh_url.as_deref(),
+                available_scopes: &flow.available_scopes,
+            };
+            (OAuthFlowKind::Password, flow_ref)
+        });
+        let client_credentials = self.client_credentials.as_ref().map(|flow| {
+            let flow_ref = OAuthFlowRef {
+                authorization_url: None,
+                token_url: Some(&flow.token_url),
+                refresh_url: flow.refresh_url.as_deref(),
+                available_scopes: &flow.available_scopes,
+            };
+            (OAuthFlowKind::ClientCredentials, flow_ref)
+        });
+        let authorization_code = self.authorization_code.as_ref().map(|flow| {
+            let flow_ref = OAuthFlowRef {
+                authorization_url: Some(&flow.authorization_url),
+                token_url: Some(&flow.token_url),
+                refresh_url: flow.refresh_url.as_deref(),
+                available_scopes: &flow.available_scopes,
+            };
+            (OAuthFlowKind::AuthorizationCode, flow_ref)
+        });
+        [implicit, password, client_credentials, authorization_code]
+            .into_iter()
+            .flatten()
+    }
+}
+
+/// The OAuth flows of [OAuthFlows].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum OAuthFlowKind {
+    Implicit,
+    Password,
+    ClientCredentials,
+    AuthorizationCode,
+}
+
+/// The configuration of an OAuth flow, whichever its kind.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OAuthFlowRef<'a> {
+    /// The authorization URL, for the implicit and authorization code flows.
+    pub authorization_url: Option<&'a str>,
+    /// The token URL, for the password, client credentials and authorization code flows.
+    pub token_url: Option<&'a str>,
+    pub refresh_url: Option<&'a str>,
+    /// The available scopes, mapping scope names to a short description.
+    pub available_scopes: &'a BTreeMap<String, String>,
+}
+
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ImplicitOAuthFlow {
@@ -190,7 +781,7 @@ pub struct ImplicitOAuthFlow {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     refresh_url: Option<String>,
     /// The available scopes for the OAuth2 security scheme. A map between the scope name and a short description for it.
-    available_scopes: HashMap<String, String>,
+    available_scopes: BTreeMap<String, String>,
 }
 
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -202,7 +793,7 @@ pub struct PasswordOAuthFlow {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     refresh_url: Option<String>,
     /// The available scopes for the OAuth2 security scheme. A map between the scope name and a short description for it.
-    available_scopes: HashMap<String, String>,
+    available_scopes: BTreeMap<String, String>,
 }
 
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -214,7 +805,7 @@ pub struct ClientCredentialsOAuthFlow {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     refresh_url: Option<String>,
     /// The available scopes for the OAuth2 security scheme. A map between the scope name and a short description for it.
-    available_scopes: HashMap<String, String>,
+    available_scopes: BTreeMap<String, String>,
 }
 
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -228,10 +819,12 @@ pub struct AuthorizationCodeOAuthFlow {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     refresh_url: Option<String>,
     /// The available scopes for the OAuth2 security scheme. A map between the scope name and a short description for it.
-    available_scopes: HashMap<String, String>,
+    available_scopes: BTreeMap<String, String>,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
 #[serde(rename_all = "camelCase")]
 pub enum ApiKeyLocation {
     User,
@@ -239,7 +832,9 @@ pub enum ApiKeyLocation {
 }
 
 /// Represents where the users API key is located.
-#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
 #[serde(rename_all = "camelCase")]
 pub enum HttpApiKeyLocation {
     /// Located in the HTTP query string e.g. `?api_key=<KEY>`
@@ -249,3 +844,322 @@ pub enum HttpApiKeyLocation {
     /// Located in a session cookie
     Cookie,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::spec::AsyncApiSpec;
+
+    #[test]
+    fn kinds_from_components_and_inline_schemes() {
+        let AsyncApiSpec::V3_0_0(spec) = serde_yaml::from_str(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Orders
+  version: 1.0.0
+servers:
+  production:
+    host: broker.example.com
+    protocol: amqps
+    security:
+      - $ref: '#/components/securitySchemes/oauth'
+      - $ref: '#/components/securitySchemes/missing'
+channels:
+  orders:
+    address: orders
+    messages: {}
+operations:
+  placeOrder:
+    action: send
+    channel:
+      $ref: '#/channels/orders'
+    security:
+      - type: apiKey
+        in: user
+components:
+  securitySchemes:
+    oauth:
+      type: oauth2
+      flows: {}
+"#,
+        )
+        .unwrap();
+
+        let kinds = spec.security_scheme_kinds();
+
+        assert_eq!(
+            kinds.into_iter().collect::<Vec<_>>(),
+            vec![SecuritySchemeKind::ApiKey, SecuritySchemeKind::Oauth2]
+        );
+    }
+
+    #[test]
+    fn header_and_query_api_keys() {
+        let AsyncApiSpec::V3_0_0(spec) = serde_yaml::from_str(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Orders
+  version: 1.0.0
+components:
+  securitySchemes:
+    signature:
+      type: httpApiKey
+      name: X-Signature
+      in: header
+    token:
+      type: httpApiKey
+      name: api_key
+      in: query
+    alias:
+      $ref: '#/components/securitySchemes/token'
+    basic:
+      type: http
+      scheme: basic
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            spec.http_api_key_params(),
+            [
+                (
+                    String::from("alias"),
+                    HttpApiKeyLocation::Query,
+                    String::from("api_key")
+                ),
+                (
+                    String::from("signature"),
+                    HttpApiKeyLocation::Header,
+                    String::from("X-Signature")
+                ),
+                (
+                    String::from("token"),
+                    HttpApiKeyLocation::Query,
+                    String::from("api_key")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn round_trip_unknown_scheme_type() {
+        let yaml = "type: passkey\ndescription: A passkey.\nrelyingParty: example.com\n";
+
+        let scheme = serde_yaml::from_str::<SecurityScheme>(yaml).unwrap();
+
+        let SecurityScheme::Other { type_, raw } = &scheme else {
+            panic!("passkey is not a modeled type");
+        };
+        assert_eq!(type_, "passkey");
+        assert_eq!(raw["relyingParty"], "example.com");
+        assert_eq!(scheme.kind(), SecuritySchemeKind::Other);
+        assert_eq!(serde_yaml::to_string(&scheme).unwrap(), yaml);
+        let known = serde_yaml::from_str::<SecurityScheme>("type: X509\n").unwrap();
+        assert_eq!(
+            known,
+            SecurityScheme::X509(X509SecurityScheme {
+                description: None,
+                extensions: Extensions::default(),
+            })
+        );
+        assert_eq!(serde_yaml::to_string(&known).unwrap(), "type: X509\n");
+        assert!(serde_yaml::from_str::<SecurityScheme>("type: http\n").is_err());
+    }
+
+    #[test]
+    fn round_trip_extensions() {
+        let yaml = "type: http\nscheme: bearer\nx-token-ttl: 3600\n";
+
+        let scheme = serde_yaml::from_str::<SecurityScheme>(yaml).unwrap();
+
+        let SecurityScheme::Http(http) = &scheme else {
+            panic!("http is a modeled type");
+        };
+        assert_eq!(http.extensions.get_u64("x-token-ttl"), Some(3600));
+        assert_eq!(serde_yaml::to_string(&scheme).unwrap(), yaml);
+    }
+
+    #[test]
+    fn example_usage_of_every_scheme_kind() {
+        let schemes = [
+            "type: userPassword",
+            "type: apiKey\nin: password",
+            "type: X509",
+            "type: symmetricEncryption",
+            "type: asymmetricEncryption",
+            "type: httpApiKey\nname: api_key\nin: query",
+            "type: http\nscheme: bearer\nbearerFormat: JWT",
+            "type: oauth2\nflows:\n  clientCredentials:\n    tokenUrl: https://auth.example.com/token\n    availableScopes: {}",
+            "type: openIdConnect\nopenIdConnectUrl: https://auth.example.com/.well-known/openid-configuration",
+            "type: plain",
+            "type: scramSha256",
+            "type: scramSha512",
+            "type: gssapi",
+        ];
+
+        let examples = schemes
+            .iter()
+            .map(|yaml| {
+                let scheme = serde_yaml::from_str::<SecurityScheme>(yaml).unwrap();
+                let example = scheme.example_usage();
+                for placeholder in &example.placeholders {
+                    let marked = format!("<{}>", placeholder.name);
+                    assert!(example.template.contains(&marked), "{example:?}");
+                }
+                let placeholders = example
+                    .placeholders
+                    .iter()
+                    .map(|placeholder| format!("{}: {}", placeholder.name, placeholder.description))
+                    .collect::<Vec<_>>();
+                (scheme.kind(), example.kind, example.template, placeholders)
+            })
+            .collect::<Vec<_>>();
+
+        let example = |kind, usage, template: &str, placeholders: &[&str]| {
+            (
+                kind,
+                usage,
+                String::from(template),
+                placeholders
+                    .iter()
+                    .map(|p| String::from(*p))
+                    .collect::<Vec<_>>(),
+            )
+        };
+        let sasl_password = ["username: the SASL username", "password: the SASL password"];
+        assert_eq!(
+            examples,
+            [
+                example(
+                    SecuritySchemeKind::UserPassword,
+                    UsageKind::Credentials,
+                    "username=<username>\npassword=<password>",
+                    &["username: the user name", "password: the password of the user"],
+                ),
+                example(
+                    SecuritySchemeKind::ApiKey,
+                    UsageKind::Credentials,
+                    "password=<api-key>",
+                    &["api-key: the API key, sent as the password"],
+                ),
+                example(
+                    SecuritySchemeKind::X509,
+                    UsageKind::Tls,
+                    "certificate=<client-certificate.pem>\nprivate-key=<client-key.pem>",
+                    &[
+                        "client-certificate.pem: the PEM encoded client certificate",
+                        "client-key.pem: the PEM encoded private key of the certificate",
+                    ],
+                ),
+                example(
+                    SecuritySchemeKind::SymmetricEncryption,
+                    UsageKind::Encryption,
+                    "key=<shared-key>",
+                    &["shared-key: the key shared with the server"],
+                ),
+                example(
+                    SecuritySchemeKind::AsymmetricEncryption,
+                    UsageKind::Encryption,
+                    "public-key=<server-public-key>\nprivate-key=<private-key>",
+                    &[
+                        "server-public-key: the public key of the server",
+                        "private-key: the private key of the client",
+                    ],
+                ),
+                example(
+                    SecuritySchemeKind::HttpApiKey,
+                    UsageKind::Query,
+                    "?api_key=<api-key>",
+                    &["api-key: the API key"],
+                ),
+                example(
+                    SecuritySchemeKind::Http,
+                    UsageKind::Header,
+                    "Authorization: Bearer <token>",
+                    &["token: the bearer token, formatted as JWT"],
+                ),
+                example(
+                    SecuritySchemeKind::Oauth2,
+                    UsageKind::Header,
+                    "Authorization: Bearer <access-token>",
+                    &["access-token: an OAuth2 access token, issued by https://auth.example.com/token"],
+                ),
+                example(
+                    SecuritySchemeKind::OpenIdConnect,
+                    UsageKind::Header,
+                    "Authorization: Bearer <access-token>",
+                    &["access-token: an access token of the provider described at https://auth.example.com/.well-known/openid-configuration"],
+                ),
+                example(
+                    SecuritySchemeKind::Plain,
+                    UsageKind::SaslConfig,
+                    "sasl.mechanism=PLAIN\nsasl.username=<username>\nsasl.password=<password>",
+                    &sasl_password,
+                ),
+                example(
+                    SecuritySchemeKind::ScramSha256,
+                    UsageKind::SaslConfig,
+                    "sasl.mechanism=SCRAM-SHA-256\nsasl.username=<username>\nsasl.password=<password>",
+                    &sasl_password,
+                ),
+                example(
+                    SecuritySchemeKind::ScramSha512,
+                    UsageKind::SaslConfig,
+                    "sasl.mechanism=SCRAM-SHA-512\nsasl.username=<username>\nsasl.password=<password>",
+                    &sasl_password,
+                ),
+                example(
+                    SecuritySchemeKind::Gssapi,
+                    UsageKind::SaslConfig,
+                    "sasl.mechanism=GSSAPI\nsasl.kerberos.principal=<principal>\nsasl.kerberos.keytab=<keytab-path>",
+                    &[
+                        "principal: the Kerberos principal",
+                        "keytab-path: the path of the keytab of the principal",
+                    ],
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn iterate_configured_oauth_flows() {
+        let flows = serde_yaml::from_str::<OAuthFlows>(
+            r#"
+implicit:
+  authorizationUrl: https://auth.example.com/authorize
+  availableScopes:
+    lights:read: Read the lights.
+password:
+  tokenUrl: https://auth.example.com/token
+  refreshUrl: https://auth.example.com/refresh
+  availableScopes: {}
+"#,
+        )
+        .unwrap();
+
+        let configured = flows.iter().collect::<Vec<_>>();
+
+        assert_eq!(
+            configured.iter().map(|(kind, _)| *kind).collect::<Vec<_>>(),
+            vec![OAuthFlowKind::Implicit, OAuthFlowKind::Password]
+        );
+        let (_, implicit) = configured[0];
+        assert_eq!(
+            implicit.authorization_url,
+            Some("https://auth.example.com/authorize")
+        );
+        assert_eq!(implicit.token_url, None);
+        assert_eq!(
+            implicit.available_scopes.keys().collect::<Vec<_>>(),
+            vec!["lights:read"]
+        );
+        let (_, password) = configured[1];
+        assert_eq!(password.token_url, Some("https://auth.example.com/token"));
+        assert_eq!(
+            password.refresh_url,
+            Some("https://auth.example.com/refresh")
+        );
+    }
+}
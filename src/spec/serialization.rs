@@ -0,0 +1,429 @@
+//! Serialization strategies of messages, for runtimes encoding and decoding payloads.
+//!
+//! The strategy of a message is told by its effective content type, the schema format of its
+//! payload and, for Avro, the `schemaIdLocation` of its Kafka binding.
+use crate::error::Error;
+use crate::spec::common::RefOr;
+use crate::spec::message::{Message, DEFAULT_SCHEMA_FORMAT};
+use crate::spec::schema::schema_format;
+use crate::spec::AsyncApiV3Spec;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde_json::Value;
+
+/// Where the identifier of the Avro schema of a Kafka message is written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SchemaIdLocation {
+    Header,
+    Payload,
+}
+
+/// How the payload of a message is serialized.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PayloadFormat {
+    Json,
+    Avro {
+        /// The `schemaIdLocation` of the Kafka binding of the message.
+        schema_id_location: Option<SchemaIdLocation>,
+    },
+    Protobuf,
+    /// Text, for the `text/*` content types other than JSON ones and for XML and form data.
+    Text,
+    /// Opaque bytes, for `application/octet-stream`.
+    Binary,
+    /// A content type or a schema format this crate does not recognize, as written.
+    Unknown {
+        content_type: Option<String>,
+        schema_format: String,
+    },
+}
+
+/// What told the [PayloadFormat] of a [SerializationHint].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HintProvenance {
+    /// Neither the content type nor the schema format, JSON being assumed.
+    Default,
+    /// The content type, the payload being described by a JSON schema.
+    ContentType,
+    /// The schema format, the content type being absent or unrecognized.
+    SchemaFormat,
+    /// The content type and the schema format, which agree.
+    Both,
+    /// The schema format, which wins over the content type telling another format.
+    Conflict {
+        content_type: String,
+        schema_format: String,
+    },
+}
+
+/// The serialization strategy of a message, see [Message::serialization_hint].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SerializationHint {
+    pub format: PayloadFormat,
+    pub provenance: HintProvenance,
+}
+
+/// What a schema format tells about the serialization of a payload.
+enum SchemaFormatKind {
+    /// A structural schema, e.g. JSON schema, compatible with several serializations.
+    Structural,
+    Encoding(PayloadFormat),
+    Unrecognized,
+}
+
+impl Message {
+    /// Returns the serialization strategy of the message, combining its [effective content
+    /// type](Message::effective_content_type), the schema format of its payload and the
+    /// `schemaIdLocation` of its Kafka binding.
+    ///
+    /// Structural schema formats, like JSON schema, leave the choice to the content type. When
+    /// the content type and the schema format tell different serializations, the schema format
+    /// wins and the [provenance](HintProvenance::Conflict) records the conflict, also reported
+    /// by the `serialization-conflict` validation rule.
+    pub fn serialization_hint(&self, spec: &AsyncApiV3Spec) -> SerializationHint {
+        let content_type = self.effective_content_type(spec);
+        let payload_schema_format = self
+            .payload
+            .as_ref()
+            .and_then(|payload| payload.resolve(spec).ok())
+            .map_or(DEFAULT_SCHEMA_FORMAT, schema_format);
+
+        let unknown = || PayloadFormat::Unknown {
+            content_type: content_type.map(String::from),
+            schema_format: String::from(payload_schema_format),
+        };
+        let from_content_type = content_type.map(content_type_format);
+        let (format, provenance) =
+            match (from_content_type, schema_format_kind(payload_schema_format)) {
+                (None, SchemaFormatKind::Structural) => {
+                    (PayloadFormat::Json, HintProvenance::Default)
+                }
+                (Some(Some(format)), SchemaFormatKind::Structural) => {
+                    (format, HintProvenance::ContentType)
+                }
+                (None | Some(None), SchemaFormatKind::Encoding(format)) => {
+                    (format, HintProvenance::SchemaFormat)
+                }
+                (Some(Some(told)), SchemaFormatKind::Encoding(format)) if told == format => {
+                    (format, HintProvenance::Both)
+                }
+                (Some(Some(_)), SchemaFormatKind::Encoding(format)) => (
+                    format,
+                    HintProvenance::Conflict {
+                        content_type: String::from(content_type.unwrap_or_default()),
+                        schema_format: String::from(payload_schema_format),
+                    },
+                ),
+                (Some(None), SchemaFormatKind::Structural) => {
+                    (unknown(), HintProvenance::ContentType)
+                }
+                (_, SchemaFormatKind::Unrecognized) => (unknown(), HintProvenance::SchemaFormat),
+            };
+        let format = match format {
+            PayloadFormat::Avro { .. } => PayloadFormat::Avro {
+                schema_id_location: self.kafka_schema_id_location(spec),
+            },
+            format => format,
+        };
+        SerializationHint { format, provenance }
+    }
+
+    fn kafka_schema_id_location(&self, spec: &AsyncApiV3Spec) -> Option<SchemaIdLocation> {
+        let bindings = match self.bindings.as_ref()? {
+            RefOr::Left(reference) => reference.resolve(spec).ok()?,
+            RefOr::Right(bindings) => bindings,
+        };
+        let location = bindings
+            .as_generic()
+            .get("kafka")?
+            .get("schemaIdLocation")
+            .and_then(Value::as_str)
+            .map(str::to_ascii_lowercase)?;
+        match location.as_str() {
+            "header" => Some(SchemaIdLocation::Header),
+            "payload" => Some(SchemaIdLocation::Payload),
+            _ => None,
+        }
+    }
+}
+
+/// A media type, e.g. `text/plain; charset=utf-8`, as written in a content type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MediaType<'a> {
+    /// The top-level type, e.g. `text`, as written.
+    pub type_: &'a str,
+    /// The subtype, e.g. `plain`, as written.
+    pub subtype: &'a str,
+    /// The parameters, by name and value, quoted values being kept as written.
+    pub parameters: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> MediaType<'a> {
+    /// Parses a media type following the syntax of RFC 9110: a `type/subtype` of tokens,
+    /// followed by `; name=value` parameters whose values are tokens or quoted strings, which
+    /// may contain `;`. Empty parameters, e.g. in `text/plain;`, are skipped.
+    pub fn parse(media_type: &'a str) -> Result<Self, Error> {
+        let invalid = |reason: &str| Error::Parse {
+            reason: format!("invalid media type `{media_type}`: {reason}"),
+        };
+        let (essence, mut rest) = media_type.split_once(';').unwrap_or((media_type, ""));
+        let (type_, subtype) = essence
+            .trim()
+            .split_once('/')
+            .ok_or_else(|| invalid("expected a `type/subtype`"))?;
+        if !is_token(type_) {
+            return Err(invalid("the type is not a token"));
+        }
+        if !is_token(subtype) {
+            return Err(invalid("the subtype is not a token"));
+        }
+        let mut parameters = Vec::new();
+        loop {
+            rest = rest.trim_start();
+            if let Some(next) = rest.strip_prefix(';') {
+                rest = next;
+                continue;
+            }
+            if rest.is_empty() {
+                break;
+            }
+            let (name, value) = rest
+                .split_once('=')
+                .ok_or_else(|| invalid("expected a `name=value` parameter"))?;
+            let length = if value.starts_with('"') {
+                quoted_string_length(value)
+                    .ok_or_else(|| invalid("a quoted string is not terminated"))?
+            } else {
+                value.find(';').unwrap_or(value.len())
+            };
+            let (value, next) = value.split_at(length);
+            let value = value.trim_end();
+            if !is_token(name) || !(is_token(value) || is_quoted_string(value)) {
+                return Err(invalid("the parameters are malformed"));
+            }
+            parameters.push((name, value));
+            rest = next.trim_start();
+            if !rest.is_empty() && !rest.starts_with(';') {
+                return Err(invalid("the parameters are malformed"));
+            }
+        }
+        Ok(Self {
+            type_,
+            subtype,
+            parameters,
+        })
+    }
+
+    /// Returns the lowercase `type/subtype`, without parameters.
+    pub fn essence(&self) -> String {
+        format!("{}/{}", self.type_, self.subtype).to_ascii_lowercase()
+    }
+}
+
+/// Returns `true` for a non-empty sequence of RFC 9110 token characters.
+fn is_token(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c))
+}
+
+/// Returns the length of the double quoted string starting `value`, up to its closing quote.
+fn quoted_string_length(value: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (index, c) in value.char_indices().skip(1) {
+        match (escaped, c) {
+            (true, _) => escaped = false,
+            (false, '\\') => escaped = true,
+            (false, '"') => return Some(index + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Returns `true` for a double quoted string, whose inner quotes are escaped.
+fn is_quoted_string(value: &str) -> bool {
+    let Some(content) = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+    else {
+        return false;
+    };
+    let mut escaped = false;
+    for c in content.chars() {
+        match (escaped, c) {
+            (true, _) => escaped = false,
+            (false, '\\') => escaped = true,
+            (false, '"') => return false,
+            _ => {}
+        }
+    }
+    !escaped
+}
+
+/// Returns the serialization told by a content type, `None` if it is not recognized.
+pub(crate) fn content_type_format(content_type: &str) -> Option<PayloadFormat> {
+    let media_type = MediaType::parse(content_type).ok()?.essence();
+    match media_type.as_str() {
+        "application/json" | "text/json" => Some(PayloadFormat::Json),
+        "application/avro" | "application/x-avro" | "avro/binary" => Some(PayloadFormat::Avro {
+            schema_id_location: None,
+        }),
+        "application/protobuf" | "application/x-protobuf" => Some(PayloadFormat::Protobuf),
+        "application/octet-stream" => Some(PayloadFormat::Binary),
+        _ if media_type.starts_with("application/vnd.apache.avro") => Some(PayloadFormat::Avro {
+            schema_id_location: None,
+        }),
+        _ if media_type.starts_with("application/vnd.google.protobuf") => {
+            Some(PayloadFormat::Protobuf)
+        }
+        "application/xml" | "application/x-www-form-urlencoded" => Some(PayloadFormat::Text),
+        _ if media_type.ends_with("+json") => Some(PayloadFormat::Json),
+        _ if media_type.ends_with("+xml") => Some(PayloadFormat::Text),
+        _ if media_type.starts_with("text/") => Some(PayloadFormat::Text),
+        _ => None,
+    }
+}
+
+fn schema_format_kind(schema_format: &str) -> SchemaFormatKind {
+    let schema_format = schema_format.trim().to_ascii_lowercase();
+    if schema_format.starts_with("application/vnd.apache.avro") {
+        SchemaFormatKind::Encoding(PayloadFormat::Avro {
+            schema_id_location: None,
+        })
+    } else if schema_format.starts_with("application/vnd.google.protobuf") {
+        SchemaFormatKind::Encoding(PayloadFormat::Protobuf)
+    } else if [
+        "application/vnd.aai.asyncapi",
+        "application/schema+json",
+        "application/schema+yaml",
+        "application/vnd.oai.openapi",
+        "application/raml+yaml",
+    ]
+    .iter()
+    .any(|prefix| schema_format.starts_with(prefix))
+    {
+        SchemaFormatKind::Structural
+    } else {
+        SchemaFormatKind::Unrecognized
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::parse_spec;
+
+    #[test]
+    fn media_type_parameters() {
+        let media_type =
+            MediaType::parse(r#"text/plain; charset="utf-8;x"; ; format=flowed;"#).unwrap();
+        assert_eq!(media_type.essence(), "text/plain");
+        assert_eq!(
+            media_type.parameters,
+            [("charset", r#""utf-8;x""#), ("format", "flowed")]
+        );
+        assert!(MediaType::parse("text/plain;")
+            .unwrap()
+            .parameters
+            .is_empty());
+
+        let errors = [
+            r#"text/plain; charset="utf-8"#,
+            r#"text/plain; charset="utf-8" x"#,
+            "text/plain; charset",
+            "application//json",
+        ]
+        .map(|media_type| MediaType::parse(media_type).unwrap_err().to_string());
+        assert_eq!(
+            errors,
+            [
+                r#"Cannot parse document: invalid media type `text/plain; charset="utf-8`: a quoted string is not terminated"#,
+                r#"Cannot parse document: invalid media type `text/plain; charset="utf-8" x`: the parameters are malformed"#,
+                "Cannot parse document: invalid media type `text/plain; charset`: expected a `name=value` parameter",
+                "Cannot parse document: invalid media type `application//json`: the subtype is not a token",
+            ]
+        );
+    }
+
+    fn hint(yaml: &str, message: &str) -> SerializationHint {
+        let spec = parse_spec(yaml);
+        spec.components.messages[message]
+            .resolve(&spec)
+            .unwrap()
+            .serialization_hint(&spec)
+    }
+
+    const SPEC: &str = r#"
+asyncapi: 3.0.0
+info:
+  title: Orders
+  version: 1.0.0
+defaultContentType: application/json
+components:
+  messages:
+    orderPlaced:
+      contentType: application/vnd.apache.avro+binary
+      payload:
+        schemaFormat: application/vnd.apache.avro;version=1.9.0
+        schema:
+          type: record
+          name: Order
+          fields: []
+      bindings:
+        kafka:
+          schemaIdLocation: header
+    orderShipped:
+      payload:
+        type: object
+    orderCancelled:
+      payload:
+        schemaFormat: application/vnd.apache.avro;version=1.9.0
+        schema:
+          type: record
+          name: Cancellation
+          fields: []
+"#;
+
+    #[test]
+    fn avro_with_schema_id_in_headers() {
+        assert_eq!(
+            hint(SPEC, "orderPlaced"),
+            SerializationHint {
+                format: PayloadFormat::Avro {
+                    schema_id_location: Some(SchemaIdLocation::Header),
+                },
+                provenance: HintProvenance::Both,
+            }
+        );
+    }
+
+    #[test]
+    fn plain_json() {
+        assert_eq!(
+            hint(SPEC, "orderShipped"),
+            SerializationHint {
+                format: PayloadFormat::Json,
+                provenance: HintProvenance::ContentType,
+            }
+        );
+    }
+
+    #[test]
+    fn conflicting_content_type_and_schema_format() {
+        assert_eq!(
+            hint(SPEC, "orderCancelled"),
+            SerializationHint {
+                format: PayloadFormat::Avro {
+                    schema_id_location: None,
+                },
+                provenance: HintProvenance::Conflict {
+                    content_type: String::from("application/json"),
+                    schema_format: String::from("application/vnd.apache.avro;version=1.9.0"),
+                },
+            }
+        );
+    }
+}
@@ -1,10 +1,21 @@
 //! Contains types related to the [servers
 //! field](https://www.asyncapi.com/docs/concepts/asyncapi-document/structure#servers-field)
-use crate::spec::common::{ExternalDocumentation, RefOr, Tag};
-use crate::spec::security::SecurityScheme;
-use std::collections::HashMap;
+use crate::editor::{DocumentEditor, RemovalPolicy};
+use crate::error::Error;
+use crate::spec::channel::Channel;
+use crate::spec::common::{
+    is_none_or_empty, non_null_bindings, to_generic_bindings, BindingPatch, ExternalDocumentation,
+    GenericBindings, JsonPointer, RefOr, ReferenceObject, Tag,
+};
+use crate::spec::security::{SaslMechanism, SecurityScheme};
+use crate::spec::AsyncApiV3Spec;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use serde_json::{Map, Value};
 
-pub type Servers = HashMap<String, RefOr<Server>>;
+pub type Servers = BTreeMap<String, RefOr<Server>>;
 
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -14,7 +25,11 @@ pub struct Server {
     /// The protocol this server supports for connection.
     pub protocol: String,
     /// The version of the protocol used for connection. For instance: AMQP 0.9.1, HTTP 2.0, Kafka 1.0.0, etc.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "string_or_number"
+    )]
     pub protocol_version: Option<String>,
     /// The path to a resource in the host. This field supports Server Variables. Variable substitutions will be made when a variable is named in {braces}.
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -29,13 +44,13 @@ pub struct Server {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub summary: Option<String>,
     /// A map between a variable name and its value. The value is used for substitution in the server's host and pathname template.
-    #[serde(default)]
-    pub variables: HashMap<String, RefOr<Variable>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub variables: BTreeMap<String, RefOr<Variable>>,
     /// A declaration of which security schemes can be used with this server. The list of values includes alternative security scheme objects that can be used. Only one of the security scheme objects need to be satisfied to authorize a connection or operation.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub security: Vec<RefOr<SecurityScheme>>,
     /// Tags Object A list of tags for logical grouping and categorization of servers.
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tags: Vec<Tag>,
     /// Additional external documentation for this server.
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -45,12 +60,347 @@ pub struct Server {
     pub bindings: Option<RefOr<ServerBindings>>,
 }
 
+/// Deserializes a version written either as a string or, unquoted in YAML, as a number.
+fn string_or_number<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    match <Option<Value> as serde::Deserialize>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(Value::String(version)) => Ok(Some(version)),
+        Some(Value::Number(version)) => Ok(Some(version.to_string())),
+        Some(other) => Err(serde::de::Error::custom(format!(
+            "expected a string or a number, found {other}"
+        ))),
+    }
+}
+
+/// A protocol a server supports, see [Server::protocol_kind].
+///
+/// Protocols are ordered as listed here, the known ones before the [Protocol::Other] ones, which
+/// are ordered by name.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Protocol {
+    Amqp,
+    Amqps,
+    AnypointMq,
+    GooglePubSub,
+    Http,
+    Https,
+    IbmMq,
+    Jms,
+    Kafka,
+    KafkaSecure,
+    Mercure,
+    Mqtt,
+    Pulsar,
+    SecureMqtt,
+    Solace,
+    Stomp,
+    Stomps,
+    Ws,
+    Wss,
+    /// A protocol absent from the specification, by name.
+    Other(String),
+}
+
+impl Protocol {
+    const KNOWN: [(&'static str, Protocol); 19] = [
+        ("amqp", Protocol::Amqp),
+        ("amqps", Protocol::Amqps),
+        ("anypointmq", Protocol::AnypointMq),
+        ("googlepubsub", Protocol::GooglePubSub),
+        ("http", Protocol::Http),
+        ("https", Protocol::Https),
+        ("ibmmq", Protocol::IbmMq),
+        ("jms", Protocol::Jms),
+        ("kafka", Protocol::Kafka),
+        ("kafka-secure", Protocol::KafkaSecure),
+        ("mercure", Protocol::Mercure),
+        ("mqtt", Protocol::Mqtt),
+        ("pulsar", Protocol::Pulsar),
+        ("secure-mqtt", Protocol::SecureMqtt),
+        ("solace", Protocol::Solace),
+        ("stomp", Protocol::Stomp),
+        ("stomps", Protocol::Stomps),
+        ("ws", Protocol::Ws),
+        ("wss", Protocol::Wss),
+    ];
+
+    /// Returns the protocol of a `protocol` field.
+    pub fn parse(name: &str) -> Protocol {
+        Self::KNOWN
+            .iter()
+            .find(|(known, _)| *known == name)
+            .map_or_else(
+                || Protocol::Other(String::from(name)),
+                |(_, protocol)| protocol.clone(),
+            )
+    }
+
+    /// Returns the name of the protocol, as written in a `protocol` field.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Protocol::Other(name) => name,
+            known => Self::KNOWN
+                .iter()
+                .find(|(_, protocol)| protocol == known)
+                .map(|(name, _)| *name)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl core::fmt::Display for Protocol {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Server {
+    /// Returns the protocol of the server.
+    pub fn protocol_kind(&self) -> Protocol {
+        Protocol::parse(&self.protocol)
+    }
+
+    /// Returns a copy of the server whose variables default to the given values, e.g. to derive
+    /// environment specific servers from a template.
+    ///
+    /// Overrides of variables the server does not declare are ignored. Referenced variables
+    /// being overridden are resolved in `spec` and inlined with the new default, keeping their
+    /// `enum`, description and examples.
+    ///
+    /// Fails with [Error::InvalidVariableValue] if a value is not one of the `enum` of its
+    /// variable, or with the error of the resolution of a referenced variable.
+    pub fn with_variable_overrides(
+        &self,
+        spec: &AsyncApiV3Spec,
+        overrides: BTreeMap<String, String>,
+    ) -> Result<Server, Error> {
+        let mut server = self.clone();
+        for (name, value) in overrides {
+            let Some(variable) = server.variables.get_mut(&name) else {
+                continue;
+            };
+            let mut inline = variable.resolve(spec)?.clone();
+            if let Some(allowed) = inline
+                .enum_values
+                .as_ref()
+                .filter(|allowed| !allowed.is_empty() && !allowed.contains(&value))
+            {
+                return Err(Error::InvalidVariableValue {
+                    name,
+                    value,
+                    allowed: allowed.clone(),
+                });
+            }
+            inline.default = Some(value);
+            *variable = RefOr::Right(inline);
+        }
+        Ok(server)
+    }
+
+    /// Returns `true` if the servers are configured alike, comparing the host, the protocol and
+    /// its version, the pathname, the variables, the security and the bindings, but not the
+    /// title, summary, description, tags and external documentation.
+    pub fn eq_ignoring_docs(&self, other: &Server) -> bool {
+        // Destructured so that a new field must be classified as functional or documentation.
+        let Server {
+            host,
+            protocol,
+            protocol_version,
+            pathname,
+            description: _,
+            title: _,
+            summary: _,
+            variables,
+            security,
+            tags: _,
+            external_docs: _,
+            bindings,
+        } = self;
+        *host == other.host
+            && *protocol == other.protocol
+            && *protocol_version == other.protocol_version
+            && *pathname == other.pathname
+            && *variables == other.variables
+            && *security == other.security
+            && *bindings == other.bindings
+    }
+
+    /// Returns the settings of the server a client library needs to connect, as a flat map:
+    ///
+    /// - `protocol`, `protocolVersion` and `pathname` as declared,
+    /// - `host` and `port`, split from the host,
+    /// - `vhost` from the `amqp` binding,
+    /// - `schemaRegistryUrl` and `schemaRegistryVendor` from the `kafka` binding,
+    /// - `saslMechanism`, the [Kafka SASL mechanism](Server::kafka_sasl_mechanism), for Kafka
+    ///   servers,
+    /// - `securityScheme`, the type of the first security scheme.
+    ///
+    /// Server variables are replaced by their default value. Settings which are absent or
+    /// cannot be resolved are left out.
+    pub fn connection_config(&self, spec: &AsyncApiV3Spec) -> BTreeMap<String, String> {
+        let mut config = BTreeMap::new();
+        let mut set = |key: &str, value: String| {
+            config.insert(String::from(key), value);
+        };
+
+        let host = self.substitute_variables(&self.host, spec);
+        match host.rsplit_once(':') {
+            Some((name, port)) if !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit()) => {
+                set("host", String::from(name));
+                set("port", String::from(port));
+            }
+            _ => set("host", host),
+        }
+        set("protocol", self.protocol.clone());
+        if let Some(version) = &self.protocol_version {
+            set("protocolVersion", version.clone());
+        }
+        if let Some(pathname) = &self.pathname {
+            set("pathname", self.substitute_variables(pathname, spec));
+        }
+
+        let bindings = self
+            .bindings
+            .as_ref()
+            .and_then(|bindings| bindings.resolve(spec).ok())
+            .map(ServerBindings::as_generic)
+            .unwrap_or_default();
+        let binding_fields = [
+            ("amqp", "vhost"),
+            ("kafka", "schemaRegistryUrl"),
+            ("kafka", "schemaRegistryVendor"),
+        ];
+        for (protocol, field) in binding_fields {
+            if let Some(Value::String(value)) = bindings.get(protocol).and_then(|b| b.get(field)) {
+                set(field, value.clone());
+            }
+        }
+
+        if self.protocol.starts_with("kafka") {
+            if let Ok(Some(mechanism)) = self.kafka_sasl_mechanism(spec) {
+                set("saslMechanism", String::from(mechanism.as_str()));
+            }
+        }
+        if let Some(scheme) = self
+            .security
+            .iter()
+            .find_map(|scheme| scheme.resolve(spec).ok())
+        {
+            set("securityScheme", String::from(scheme.kind().as_str()));
+        }
+        config
+    }
+
+    /// Replaces the `{variables}` of a template by their default value, keeping the variables
+    /// without one.
+    fn substitute_variables(&self, template: &str, spec: &AsyncApiV3Spec) -> String {
+        self.variables
+            .iter()
+            .filter_map(|(name, variable)| {
+                Some((name, variable.resolve(spec).ok()?.default.as_ref()?))
+            })
+            .fold(String::from(template), |template, (name, default)| {
+                template.replace(&format!("{{{name}}}"), default)
+            })
+    }
+
+    /// Returns the Kafka SASL mechanism required by the security schemes of the server, or
+    /// `None` if none of them is SASL based. Fails if the schemes declare different mechanisms,
+    /// since a Kafka client can be configured with one only.
+    pub fn kafka_sasl_mechanism(
+        &self,
+        spec: &AsyncApiV3Spec,
+    ) -> Result<Option<SaslMechanism>, Error> {
+        let mut mechanisms = Vec::new();
+        for scheme in &self.security {
+            if let Some(mechanism) = scheme.resolve(spec)?.sasl_mechanism() {
+                if !mechanisms.contains(&mechanism) {
+                    mechanisms.push(mechanism);
+                }
+            }
+        }
+        match mechanisms.as_slice() {
+            [] => Ok(None),
+            [mechanism] => Ok(Some(*mechanism)),
+            _ => Err(Error::ConflictingSaslMechanisms {
+                mechanisms: mechanisms.iter().map(ToString::to_string).collect(),
+            }),
+        }
+    }
+}
+
+impl AsyncApiV3Spec {
+    /// Returns the document deployed on the root server `name` alone, e.g. to publish a document
+    /// per environment: the other root servers are removed and the channels defined in the
+    /// document list `name` as their only server.
+    ///
+    /// Channels restricted to other servers are not available on `name`: they are removed,
+    /// along with the operations using them and the channels and operations referencing them
+    /// in turn, as by [RemovalPolicy::Cascade].
+    ///
+    /// Fails with [Error::ServerNotFound] if there is no root server `name`.
+    pub fn with_single_server(&self, name: &str) -> Result<AsyncApiV3Spec, Error> {
+        let (name, server) =
+            self.servers
+                .get_key_value(name)
+                .ok_or_else(|| Error::ServerNotFound {
+                    name: String::from(name),
+                })?;
+        let reference = ReferenceObject::new(format!(
+            "#{}",
+            JsonPointer::from_iter(["servers", name.as_str()])
+        ));
+        let elsewhere = |channels: &BTreeMap<String, RefOr<Channel>>| {
+            channels
+                .iter()
+                .filter(|(_, channel)| {
+                    channel.as_item().is_some_and(|channel| {
+                        !channel.servers.is_empty()
+                            && !channel
+                                .servers
+                                .iter()
+                                .any(|server| server.reference == reference.reference)
+                    })
+                })
+                .map(|(key, _)| key.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let mut spec = self.clone();
+        spec.servers = Servers::from([(name.clone(), server.clone())]);
+        let components = elsewhere(&spec.components.channels);
+        let root = elsewhere(&spec.channels);
+        let mut editor = DocumentEditor::new(&mut spec);
+        for key in components {
+            editor.remove_component("channels", &key, RemovalPolicy::Cascade)?;
+        }
+        for key in root {
+            // Already gone if it referenced a removed component channel.
+            if editor.spec().channels.contains_key(&key) {
+                editor.remove_channel(&key, RemovalPolicy::Cascade)?;
+            }
+        }
+        for channel in spec
+            .channels
+            .values_mut()
+            .chain(spec.components.channels.values_mut())
+        {
+            if let RefOr::Right(channel) = channel {
+                channel.servers = Vec::from([reference.clone()]);
+            }
+        }
+        Ok(spec)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Variable {
     /// An enumeration of string values to be used if the substitution options are from a limited set.
     #[serde(rename = "enum")]
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "is_none_or_empty")]
     pub enum_values: Option<Vec<String>>,
     /// The default value to use for substitution, and to send, if an alternate value is not supplied.
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -73,16 +423,550 @@ pub struct ServerBindings {
     pub nats: Option<NatsServerBinding>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub http: Option<HttpServerBinding>,
+    /// Bindings of protocols without a typed representation, keyed by protocol name.
+    #[serde(flatten, deserialize_with = "non_null_bindings")]
+    pub other: BTreeMap<String, serde_json::Value>,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
-pub struct WebSocketServerBinding;
+impl BindingPatch for ServerBindings {}
+
+impl ServerBindings {
+    /// Returns an untyped view of all the bindings, including the typed ones, keyed by
+    /// protocol name. Useful to read bindings of protocols this crate does not model yet.
+    pub fn as_generic(&self) -> GenericBindings {
+        to_generic_bindings(self)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebSocketServerBinding {
+    /// Fields of newer binding versions this crate does not model yet.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NatsServerBinding {
+    /// Fields of newer binding versions this crate does not model yet.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpServerBinding {
+    /// Fields of newer binding versions this crate does not model yet.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::spec::common::from_pairs;
+    use crate::test_util::parse_spec;
+    use alloc::collections::BTreeSet;
+
+    #[test]
+    fn servers_from_pairs() {
+        let server = |host: &str| {
+            serde_yaml::from_str::<Server>(&format!("host: {host}\nprotocol: kafka")).unwrap()
+        };
+
+        let servers: Servers = from_pairs([
+            ("production", RefOr::item(server("kafka.example.com"))),
+            ("staging", RefOr::reference("#/components/servers/staging")),
+        ]);
+        let collected = [("production", server("kafka.example.com"))]
+            .into_iter()
+            .map(|(name, server)| (String::from(name), RefOr::item(server)))
+            .collect::<Servers>();
 
-#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
-pub struct NatsServerBinding;
+        assert_eq!(servers.len(), 2);
+        assert_eq!(servers["production"], collected["production"]);
+        assert_eq!(
+            servers["staging"].as_reference().unwrap().reference,
+            "#/components/servers/staging"
+        );
+        assert_eq!(
+            from_pairs::<Server, _, _>([("production", server("kafka.example.com"))]),
+            collected
+        );
+    }
 
-#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
-pub struct HttpServerBinding;
+    #[test]
+    fn reduce_to_a_single_server() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Orders
+  version: 1.0.0
+servers:
+  production:
+    host: kafka.example.com
+    protocol: kafka
+  staging:
+    host: kafka.staging.example.com
+    protocol: kafka
+channels:
+  orders:
+    address: orders
+    messages: {}
+  audit:
+    address: audit
+    servers:
+      - $ref: '#/servers/production'
+    messages: {}
+  replay:
+    $ref: '#/components/channels/replay'
+operations:
+  placeOrder:
+    action: send
+    channel:
+      $ref: '#/channels/orders'
+  recordAudit:
+    action: send
+    channel:
+      $ref: '#/channels/audit'
+  replayOrders:
+    action: receive
+    channel:
+      $ref: '#/channels/replay'
+components:
+  channels:
+    replay:
+      address: replay
+      servers:
+        - $ref: '#/servers/production'
+      messages: {}
+"#,
+        );
+
+        let staging = spec.with_single_server("staging").unwrap();
+
+        assert_eq!(staging.servers.keys().collect::<Vec<_>>(), ["staging"]);
+        assert_eq!(staging.servers["staging"], spec.servers["staging"]);
+        assert_eq!(staging.channels.keys().collect::<Vec<_>>(), ["orders"]);
+        assert!(staging.components.channels.is_empty());
+        assert_eq!(
+            staging.operations.keys().collect::<Vec<_>>(),
+            ["placeOrder"]
+        );
+        for channel in staging.channels.values() {
+            let channel = channel.as_item().unwrap();
+            assert_eq!(channel.servers.len(), 1);
+            assert_eq!(channel.servers[0].reference, "#/servers/staging");
+        }
+        let production = spec.with_single_server("production").unwrap();
+        assert_eq!(
+            production.channels.keys().collect::<Vec<_>>(),
+            ["audit", "orders", "replay"]
+        );
+        assert_eq!(production.operations.len(), 3);
+        assert!(matches!(
+            spec.with_single_server("development"),
+            Err(Error::ServerNotFound { name }) if name == "development"
+        ));
+    }
+
+    #[test]
+    fn sort_protocols() {
+        let protocols = ["mqtt", "x-custom", "amqp", "kafka-secure", "mqtt", "nats"]
+            .into_iter()
+            .map(Protocol::parse)
+            .collect::<BTreeSet<_>>();
+
+        assert_eq!(
+            protocols.into_iter().collect::<Vec<_>>(),
+            [
+                Protocol::Amqp,
+                Protocol::KafkaSecure,
+                Protocol::Mqtt,
+                Protocol::Other(String::from("nats")),
+                Protocol::Other(String::from("x-custom")),
+            ]
+        );
+        assert_eq!(Protocol::KafkaSecure.to_string(), "kafka-secure");
+    }
+
+    #[test]
+    fn protocol_version_as_string_or_number() {
+        let server = |version: &str| {
+            serde_yaml::from_str::<Server>(&format!(
+                "host: example.com\nprotocol: http\nprotocolVersion: {version}"
+            ))
+        };
+
+        assert_eq!(
+            server("'2.0'").unwrap().protocol_version.as_deref(),
+            Some("2.0")
+        );
+        assert_eq!(
+            server("2.0").unwrap().protocol_version.as_deref(),
+            Some("2.0")
+        );
+        assert_eq!(server("1").unwrap().protocol_version.as_deref(), Some("1"));
+        assert!(server("[2]").is_err());
+    }
+
+    #[test]
+    fn null_binding_is_no_binding() {
+        let bindings = serde_yaml::from_str::<ServerBindings>("kafka: null\nhttp: null").unwrap();
+
+        assert_eq!(bindings.http, None);
+        assert!(bindings.other.is_empty());
+        assert!(bindings.as_generic().is_empty());
+    }
+
+    const KAFKA_SPEC: &str = r#"
+asyncapi: 3.0.0
+info:
+  title: Kafka
+  version: 1.0.0
+servers:
+  scram:
+    host: broker.example.com:9093
+    protocol: kafka-secure
+    security:
+      - $ref: '#/components/securitySchemes/scram256'
+      - $ref: '#/components/securitySchemes/certs'
+  kerberos:
+    host: broker.example.com:9094
+    protocol: kafka-secure
+    security:
+      - type: gssapi
+  conflicting:
+    host: broker.example.com:9095
+    protocol: kafka-secure
+    security:
+      - $ref: '#/components/securitySchemes/scram256'
+      - type: plain
+  plaintext:
+    host: broker.example.com:9092
+    protocol: kafka
+components:
+  securitySchemes:
+    scram256:
+      type: scramSha256
+    certs:
+      type: X509
+"#;
+
+    fn server<'a>(spec: &'a AsyncApiV3Spec, name: &str) -> &'a Server {
+        spec.servers[name].resolve(spec).unwrap()
+    }
+
+    #[test]
+    fn kafka_sasl_mechanism_scram_256() {
+        let spec = parse_spec(KAFKA_SPEC);
+
+        let mechanism = server(&spec, "scram").kafka_sasl_mechanism(&spec).unwrap();
+
+        assert_eq!(mechanism, Some(SaslMechanism::ScramSha256));
+        assert_eq!(mechanism.unwrap().as_str(), "SCRAM-SHA-256");
+    }
+
+    #[test]
+    fn kafka_sasl_mechanism_gssapi() {
+        let spec = parse_spec(KAFKA_SPEC);
+
+        let mechanism = server(&spec, "kerberos")
+            .kafka_sasl_mechanism(&spec)
+            .unwrap();
+
+        assert_eq!(mechanism.map(|m| m.as_str()), Some("GSSAPI"));
+    }
+
+    #[test]
+    fn kafka_sasl_mechanism_none_or_conflicting() {
+        let spec = parse_spec(KAFKA_SPEC);
+
+        assert_eq!(
+            server(&spec, "plaintext")
+                .kafka_sasl_mechanism(&spec)
+                .unwrap(),
+            None
+        );
+        assert!(matches!(
+            server(&spec, "conflicting").kafka_sasl_mechanism(&spec),
+            Err(Error::ConflictingSaslMechanisms { .. })
+        ));
+    }
+
+    #[test]
+    fn kafka_connection_config() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Kafka
+  version: 1.0.0
+servers:
+  production:
+    host: '{cluster}.broker.example.com:9093'
+    protocol: kafka-secure
+    protocolVersion: 3.6.0
+    variables:
+      cluster:
+        default: eu-west
+    security:
+      - $ref: '#/components/securitySchemes/scram512'
+    bindings:
+      kafka:
+        schemaRegistryUrl: https://registry.example.com
+        schemaRegistryVendor: confluent
+components:
+  securitySchemes:
+    scram512:
+      type: scramSha512
+"#,
+        );
+
+        let config = server(&spec, "production").connection_config(&spec);
+
+        assert_eq!(
+            config,
+            BTreeMap::from(
+                [
+                    ("host", "eu-west.broker.example.com"),
+                    ("port", "9093"),
+                    ("protocol", "kafka-secure"),
+                    ("protocolVersion", "3.6.0"),
+                    ("saslMechanism", "SCRAM-SHA-512"),
+                    ("schemaRegistryUrl", "https://registry.example.com"),
+                    ("schemaRegistryVendor", "confluent"),
+                    ("securityScheme", "scramSha512"),
+                ]
+                .map(|(key, value)| (String::from(key), String::from(value)))
+            )
+        );
+    }
+
+    #[test]
+    fn amqp_connection_config() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: AMQP
+  version: 1.0.0
+servers:
+  rabbit:
+    host: rabbitmq.example.com
+    protocol: amqp
+    security:
+      - type: userPassword
+    bindings:
+      amqp:
+        vhost: orders
+"#,
+        );
+
+        let config = server(&spec, "rabbit").connection_config(&spec);
+
+        assert_eq!(
+            config,
+            BTreeMap::from(
+                [
+                    ("host", "rabbitmq.example.com"),
+                    ("protocol", "amqp"),
+                    ("securityScheme", "userPassword"),
+                    ("vhost", "orders"),
+                ]
+                .map(|(key, value)| (String::from(key), String::from(value)))
+            )
+        );
+    }
+
+    #[test]
+    fn read_untyped_binding_through_generic_view() {
+        let bindings: ServerBindings = serde_json::from_value(serde_json::json!({
+            "kafka": {
+                "schemaRegistryUrl": "https://registry.example.com",
+                "bindingVersion": "0.5.0"
+            }
+        }))
+        .unwrap();
+
+        let generic = bindings.as_generic();
+
+        assert_eq!(
+            generic["kafka"]["schemaRegistryUrl"],
+            "https://registry.example.com"
+        );
+        assert_eq!(generic["kafka"]["bindingVersion"], "0.5.0");
+    }
+
+    #[test]
+    fn derive_prod_server_from_template() {
+        let template = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Templates
+  version: 1.0.0
+servers:
+  template:
+    host: '{environment}.broker.example.com:{port}'
+    protocol: kafka
+    variables:
+      environment:
+        enum: [dev, staging, prod]
+        default: dev
+      port:
+        $ref: '#/components/serverVariables/port'
+components:
+  serverVariables:
+    port:
+      enum: ['9092', '9093']
+      default: '9092'
+      description: Port of the listener.
+      examples: ['9092']
+"#,
+        );
+        let base = server(&template, "template");
+
+        let prod = base
+            .with_variable_overrides(
+                &template,
+                BTreeMap::from([
+                    (String::from("environment"), String::from("prod")),
+                    (String::from("port"), String::from("9093")),
+                    (String::from("region"), String::from("eu")),
+                ]),
+            )
+            .unwrap();
+
+        let default = |name: &str| match &prod.variables[name] {
+            RefOr::Right(variable) => variable.default.as_deref(),
+            RefOr::Left(_) => None,
+        };
+        assert_eq!(default("environment"), Some("prod"));
+        assert_eq!(default("port"), Some("9093"));
+        assert!(!prod.variables.contains_key("region"));
+        let RefOr::Right(environment) = &prod.variables["environment"] else {
+            panic!("environment should be inline");
+        };
+        assert_eq!(environment.enum_values.as_ref().map(Vec::len), Some(3));
+        let RefOr::Right(port) = &prod.variables["port"] else {
+            panic!("port should be inline");
+        };
+        assert_eq!(port.description.as_deref(), Some("Port of the listener."));
+        assert_eq!(port.enum_values.as_ref().map(Vec::len), Some(2));
+        assert_eq!(port.examples, vec![String::from("9092")]);
+        assert_eq!(prod.host, base.host);
+        assert_eq!(
+            base.variables["environment"]
+                .resolve(&template)
+                .unwrap()
+                .default
+                .as_deref(),
+            Some("dev")
+        );
+    }
+
+    #[test]
+    fn overrides_outside_the_enum() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Templates
+  version: 1.0.0
+servers:
+  template:
+    host: '{environment}.broker.example.com'
+    protocol: kafka
+    variables:
+      environment:
+        $ref: '#/components/serverVariables/environment'
+components:
+  serverVariables:
+    environment:
+      enum: [dev, prod]
+"#,
+        );
+
+        let err = server(&spec, "template")
+            .with_variable_overrides(
+                &spec,
+                BTreeMap::from([(String::from("environment"), String::from("qa"))]),
+            )
+            .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "Value 'qa' of server variable 'environment' is not one of dev, prod"
+        );
+    }
+
+    #[test]
+    fn servers_differing_only_in_description() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Brokers
+  version: 1.0.0
+servers:
+  production:
+    host: broker.example.com
+    protocol: kafka
+    description: The production broker.
+  documented:
+    host: broker.example.com
+    protocol: kafka
+    description: The broker of the production environment.
+    tags:
+      - name: production
+  staging:
+    host: staging.example.com
+    protocol: kafka
+    description: The production broker.
+"#,
+        );
+        let production = server(&spec, "production");
+
+        assert_ne!(production, server(&spec, "documented"));
+        assert!(production.eq_ignoring_docs(server(&spec, "documented")));
+        assert!(!production.eq_ignoring_docs(server(&spec, "staging")));
+    }
+
+    #[test]
+    fn empty_tags_and_variables_are_not_serialized() {
+        let spec = parse_spec(KAFKA_SPEC);
+        let server = server(&spec, "plaintext");
+
+        let json = serde_json::to_value(server).unwrap();
+
+        assert!(json.get("tags").is_none());
+        assert!(json.get("variables").is_none());
+        assert_eq!(&serde_json::from_value::<Server>(json).unwrap(), server);
+    }
+
+    #[test]
+    fn variable_enum_round_trips_under_its_name() {
+        let variable = serde_yaml::from_str::<Variable>("enum: [eu, us]").unwrap();
+        let empty = serde_yaml::from_str::<Variable>("enum: []").unwrap();
+        let absent = serde_yaml::from_str::<Variable>("default: eu").unwrap();
+
+        assert_eq!(
+            variable.enum_values,
+            Some(vec![String::from("eu"), String::from("us")])
+        );
+        assert_eq!(
+            serde_json::to_value(&variable).unwrap()["enum"],
+            serde_json::json!(["eu", "us"])
+        );
+        assert_eq!(empty.enum_values, Some(Vec::new()));
+        assert_eq!(absent.enum_values, None);
+        for skipped in [empty, absent] {
+            let value = serde_json::to_value(&skipped).unwrap();
+            assert!(value.get("enum").is_none(), "{value}");
+            assert!(value.get("enumValues").is_none(), "{value}");
+        }
+    }
+}
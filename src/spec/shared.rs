@@ -0,0 +1,158 @@
+//! Documents shared between threads.
+use crate::error::Error;
+use crate::spec::common::ReferenceObject;
+use crate::spec::resolve::Referenceable;
+use crate::spec::AsyncApiV3Spec;
+use alloc::sync::Arc;
+use core::fmt;
+use core::marker::PhantomData;
+use core::ops::Deref;
+
+/// A document shared between threads, whose resolutions do not borrow from it.
+///
+/// Resolved objects are either cloned or returned as [SharedNode]s, which keep the document
+/// alive and can be moved to other threads or held across `.await` points.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SharedDoc(pub Arc<AsyncApiV3Spec>);
+
+impl SharedDoc {
+    /// Moves a document behind an [Arc], so that clones of the [SharedDoc] share it.
+    pub fn new(spec: AsyncApiV3Spec) -> Self {
+        Self(Arc::new(spec))
+    }
+
+    /// Resolves a reference and returns a copy of the referenced object.
+    pub fn resolve<T: Referenceable + Clone>(
+        &self,
+        reference: &ReferenceObject,
+    ) -> Result<T, Error> {
+        reference.resolve::<T>(&self.0).cloned()
+    }
+
+    /// Resolves a reference into a node sharing the document.
+    pub fn node<T: Referenceable>(
+        &self,
+        reference: ReferenceObject,
+    ) -> Result<SharedNode<T>, Error> {
+        reference.resolve::<T>(&self.0)?;
+        Ok(SharedNode {
+            doc: self.0.clone(),
+            reference,
+            target: PhantomData,
+        })
+    }
+}
+
+impl From<AsyncApiV3Spec> for SharedDoc {
+    fn from(spec: AsyncApiV3Spec) -> Self {
+        Self::new(spec)
+    }
+}
+
+impl Deref for SharedDoc {
+    type Target = AsyncApiV3Spec;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// An object of a [SharedDoc], dereferencing to the resolved object.
+///
+/// The reference is resolved again on each access, which cannot fail since the document is
+/// immutable.
+pub struct SharedNode<T> {
+    doc: Arc<AsyncApiV3Spec>,
+    reference: ReferenceObject,
+    target: PhantomData<fn() -> T>,
+}
+
+impl<T> SharedNode<T> {
+    /// Returns the reference the node was resolved from.
+    pub fn reference(&self) -> &ReferenceObject {
+        &self.reference
+    }
+
+    /// Returns the document the node belongs to.
+    pub fn doc(&self) -> SharedDoc {
+        SharedDoc(self.doc.clone())
+    }
+}
+
+impl<T: Referenceable> Deref for SharedNode<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.reference
+            .resolve(&self.doc)
+            .expect("shared nodes are resolved when created")
+    }
+}
+
+impl<T> Clone for SharedNode<T> {
+    fn clone(&self) -> Self {
+        Self {
+            doc: self.doc.clone(),
+            reference: self.reference.clone(),
+            target: PhantomData,
+        }
+    }
+}
+
+impl<T: Referenceable + fmt::Debug> fmt::Debug for SharedNode<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::spec::channel::Channel;
+    use crate::spec::message::Message;
+    use crate::test_util::parse_spec;
+    use std::thread;
+
+    fn reference(reference: &str) -> ReferenceObject {
+        ReferenceObject::new(reference)
+    }
+
+    #[test]
+    fn resolve_from_threads() {
+        let doc = SharedDoc::new(parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Lights
+  version: 1.0.0
+channels:
+  lights:
+    address: lights
+    messages:
+      lightMeasured:
+        $ref: '#/components/messages/lightMeasured'
+components:
+  messages:
+    lightMeasured:
+      contentType: application/json
+"#,
+        ));
+
+        let message = doc.clone();
+        let message = thread::spawn(move || {
+            message.resolve::<Message>(&reference("#/channels/lights/messages/lightMeasured"))
+        });
+        let channel = doc.node::<Channel>(reference("#/channels/lights")).unwrap();
+        let channel = thread::spawn(move || channel.address.clone());
+
+        assert_eq!(
+            message.join().unwrap().unwrap().content_type.as_deref(),
+            Some("application/json")
+        );
+        assert_eq!(channel.join().unwrap().as_deref(), Some("lights"));
+        assert!(matches!(
+            doc.node::<Channel>(reference("#/channels/missing")),
+            Err(Error::UnresolvedReference { .. })
+        ));
+    }
+}
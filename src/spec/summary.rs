@@ -0,0 +1,276 @@
+//! Compact, single-line summaries of documents and their items, meant for logging.
+//!
+//! The [Display](fmt::Display) output is stable so it can be parsed by log-based alerting, and
+//! bounded: the texts taken from the document are shortened to [MAX_TEXT_LENGTH] characters and
+//! at most [MAX_PROTOCOLS] protocols are listed, so a summary line is never longer than a few
+//! hundred characters whatever the document.
+use crate::spec::common::RefOr;
+use crate::spec::operation::{Operation, OperationAction};
+use crate::spec::server::Server;
+use crate::spec::AsyncApiV3Spec;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Maximum number of characters of the titles, versions, hosts and names shown by the
+/// summaries, longer ones being cut with a trailing `…`.
+pub const MAX_TEXT_LENGTH: usize = 64;
+
+/// Maximum number of protocols listed by a [DocumentSummary], further ones being elided with
+/// `…`.
+pub const MAX_PROTOCOLS: usize = 4;
+
+/// Overview of a document: its identity and how many items it defines.
+///
+/// Displays as `Orders v1.2.0 — 3 servers (kafka, wss), 12 channels, 18 operations, 25 messages`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DocumentSummary<'a> {
+    /// The title of the application.
+    pub title: &'a str,
+    /// The version of the application API.
+    pub version: &'a str,
+    /// Name and protocol of every server, sorted by name. Servers defined through a reference
+    /// which cannot be resolved have no protocol.
+    pub servers: Vec<(&'a str, Option<&'a str>)>,
+    /// The number of channels.
+    pub channels: usize,
+    /// The number of operations.
+    pub operations: usize,
+    /// The number of messages defined either inline in a channel or in the components.
+    pub messages: usize,
+}
+
+impl DocumentSummary<'_> {
+    /// Returns the distinct protocols used by the servers, sorted.
+    pub fn protocols(&self) -> Vec<&str> {
+        let mut protocols = self
+            .servers
+            .iter()
+            .filter_map(|(_, protocol)| *protocol)
+            .collect::<Vec<_>>();
+        protocols.sort_unstable();
+        protocols.dedup();
+        protocols
+    }
+}
+
+impl fmt::Display for DocumentSummary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} v{} — {}",
+            Shortened(self.title),
+            Shortened(self.version),
+            Count(self.servers.len(), "server")
+        )?;
+        let protocols = self.protocols();
+        if !protocols.is_empty() {
+            f.write_str(" (")?;
+            for (index, protocol) in protocols.iter().take(MAX_PROTOCOLS).enumerate() {
+                if index > 0 {
+                    f.write_str(", ")?;
+                }
+                write!(f, "{}", Shortened(protocol))?;
+            }
+            if protocols.len() > MAX_PROTOCOLS {
+                f.write_str(", …")?;
+            }
+            f.write_str(")")?;
+        }
+        write!(
+            f,
+            ", {}, {}, {}",
+            Count(self.channels, "channel"),
+            Count(self.operations, "operation"),
+            Count(self.messages, "message")
+        )
+    }
+}
+
+struct Count(usize, &'static str);
+
+impl fmt::Display for Count {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Count(count, noun) = self;
+        let plural = if *count == 1 { "" } else { "s" };
+        write!(f, "{count} {noun}{plural}")
+    }
+}
+
+/// A text of the document, cut after [MAX_TEXT_LENGTH] characters.
+struct Shortened<'a>(&'a str);
+
+impl fmt::Display for Shortened<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.char_indices().nth(MAX_TEXT_LENGTH) {
+            Some((end, _)) => write!(f, "{}…", &self.0[..end]),
+            None => f.write_str(self.0),
+        }
+    }
+}
+
+impl AsyncApiV3Spec {
+    /// Returns a compact overview of the document, suitable for logging.
+    pub fn summary(&self) -> DocumentSummary<'_> {
+        let mut servers = self
+            .servers
+            .iter()
+            .map(|(name, server)| {
+                let protocol = server.resolve(self).ok().map(|s| s.protocol.as_str());
+                (name.as_str(), protocol)
+            })
+            .collect::<Vec<_>>();
+        servers.sort_unstable();
+
+        let inline_messages = self
+            .channels
+            .values()
+            .filter_map(|channel| match channel {
+                RefOr::Right(channel) => Some(channel),
+                RefOr::Left(_) => None,
+            })
+            .flat_map(|channel| channel.messages.values())
+            .filter(|message| matches!(message, RefOr::Right(_)))
+            .count();
+
+        DocumentSummary {
+            title: &self.info.title,
+            version: &self.info.version,
+            servers,
+            channels: self.channels.len(),
+            operations: self.operations.len(),
+            messages: inline_messages + self.components.messages.len(),
+        }
+    }
+}
+
+impl Server {
+    /// Returns a single line describing the server, e.g. `kafka://broker.example.com:9092` or
+    /// `mqtt://broker.example.com/v1 (protocol version 5)`.
+    pub fn summary_line(&self) -> impl fmt::Display + '_ {
+        ServerSummaryLine(self)
+    }
+}
+
+struct ServerSummaryLine<'a>(&'a Server);
+
+impl fmt::Display for ServerSummaryLine<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let server = self.0;
+        write!(
+            f,
+            "{}://{}",
+            Shortened(&server.protocol),
+            Shortened(&server.host)
+        )?;
+        if let Some(pathname) = &server.pathname {
+            write!(f, "{}", Shortened(pathname))?;
+        }
+        if let Some(version) = &server.protocol_version {
+            write!(f, " (protocol version {})", Shortened(version))?;
+        }
+        Ok(())
+    }
+}
+
+impl Operation {
+    /// Returns a single line describing the operation, e.g. `send lightsDim (1 message, reply)`.
+    ///
+    /// The channel is named by the last segment of its reference.
+    pub fn summary_line(&self) -> impl fmt::Display + '_ {
+        OperationSummaryLine(self)
+    }
+}
+
+struct OperationSummaryLine<'a>(&'a Operation);
+
+impl fmt::Display for OperationSummaryLine<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operation = self.0;
+        let action = match operation.action {
+            OperationAction::Send => "send",
+            OperationAction::Receive => "receive",
+        };
+        let reference = &operation.channel.reference;
+        let channel = reference.rsplit('/').next().unwrap_or(reference);
+        write!(f, "{action} {}", Shortened(channel))?;
+        match (&operation.messages, &operation.reply) {
+            (Some(messages), Some(_)) => {
+                write!(f, " ({}, reply)", Count(messages.len(), "message"))
+            }
+            (Some(messages), None) => write!(f, " ({})", Count(messages.len(), "message")),
+            (None, Some(_)) => write!(f, " (reply)"),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::spec::common::RefOr;
+    use crate::test_util::streetlights;
+
+    #[test]
+    fn document_summary_line() {
+        let spec = streetlights();
+
+        assert_eq!(
+            spec.summary().to_string(),
+            "Streetlights Kafka API v1.0.0 — 2 servers (kafka-secure), 4 channels, 4 operations, 3 messages"
+        );
+    }
+
+    #[test]
+    fn server_summary_line() {
+        let spec = streetlights();
+        let RefOr::Right(server) = &spec.servers["scram-connections"] else {
+            panic!("inline server expected");
+        };
+
+        assert_eq!(
+            server.summary_line().to_string(),
+            "kafka-secure://test.mykafkacluster.org:18092"
+        );
+    }
+
+    #[test]
+    fn operation_summary_line() {
+        let spec = streetlights();
+        let RefOr::Right(operation) = &spec.operations["dimLight"] else {
+            panic!("inline operation expected");
+        };
+
+        assert_eq!(
+            operation.summary_line().to_string(),
+            "send lightsDim (1 message)"
+        );
+    }
+
+    #[test]
+    fn summary_lines_are_bounded() {
+        let mut spec = streetlights();
+        spec.info.title = "Streetlights ".repeat(100);
+        let RefOr::Right(server) = spec.servers.get_mut("scram-connections").unwrap() else {
+            panic!("inline server expected");
+        };
+        server.host = "a".repeat(1000);
+        let protocols = ["amqp", "http", "kafka", "mqtt", "ws"];
+        for (index, protocol) in protocols.into_iter().enumerate() {
+            let mut server = spec.servers["scram-connections"].clone();
+            if let RefOr::Right(server) = &mut server {
+                server.protocol = String::from(protocol);
+            }
+            spec.servers.insert(format!("server{index}"), server);
+        }
+
+        let summary = spec.summary().to_string();
+        let RefOr::Right(server) = &spec.servers["scram-connections"] else {
+            panic!("inline server expected");
+        };
+        let server = server.summary_line().to_string();
+
+        assert!(summary.starts_with(&format!("{}… v1.0.0", &spec.info.title[..64])));
+        assert!(summary.contains("7 servers (amqp, http, kafka, kafka-secure, …)"));
+        assert!(summary.chars().count() < 256, "{summary}");
+        assert_eq!(server, format!("kafka-secure://{}…", "a".repeat(64)));
+    }
+}
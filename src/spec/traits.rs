@@ -0,0 +1,152 @@
+//! Application of message and operation traits.
+//!
+//! Traits are merged into the object applying them with the JSON Merge Patch algorithm, in
+//! the order they are listed, and the fields of the object take precedence over the ones of
+//! its traits.
+use crate::error::Error;
+use crate::spec::common::RefOr;
+use crate::spec::message::Message;
+use crate::spec::operation::Operation;
+use crate::spec::resolve::Referenceable;
+use crate::spec::AsyncApiV3Spec;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+impl Message {
+    /// Returns the message with its traits applied and removed. Referenced traits are resolved
+    /// against the document first.
+    ///
+    /// Fails with [Error::UnresolvedReference] if a trait reference does not resolve, or with
+    /// [Error::InvalidDocument] if the merged message is not a valid message.
+    pub fn apply_traits(&self, spec: &AsyncApiV3Spec) -> Result<Message, Error> {
+        apply_traits(self, &self.traits, spec)
+    }
+}
+
+impl Operation {
+    /// Returns the operation with its traits applied and removed. Referenced traits are
+    /// resolved against the document first.
+    ///
+    /// Fails with [Error::UnresolvedReference] if a trait reference does not resolve, or with
+    /// [Error::InvalidDocument] if the merged operation is not a valid operation.
+    pub fn apply_traits(&self, spec: &AsyncApiV3Spec) -> Result<Operation, Error> {
+        apply_traits(self, &self.traits, spec)
+    }
+}
+
+fn apply_traits<O, T>(object: &O, traits: &[RefOr<T>], spec: &AsyncApiV3Spec) -> Result<O, Error>
+where
+    O: Serialize + DeserializeOwned,
+    T: Serialize + Referenceable,
+{
+    let parse_error = |err: serde_json::Error| Error::InvalidDocument {
+        reason: err.to_string(),
+        hints: Vec::new(),
+    };
+    let mut merged = Value::Object(Map::new());
+    for object_trait in traits {
+        let object_trait =
+            serde_json::to_value(object_trait.resolve(spec)?).map_err(parse_error)?;
+        merge_patch(&mut merged, object_trait);
+    }
+    let Value::Object(mut fields) = serde_json::to_value(object).map_err(parse_error)? else {
+        return Err(Error::InvalidDocument {
+            reason: "the object does not serialize to a JSON object".to_string(),
+            hints: Vec::new(),
+        });
+    };
+    fields.remove("traits");
+    // Fields left empty are serialized as empty arrays, which must not clear the ones of the
+    // traits.
+    fields.retain(|_, value| !matches!(value, Value::Array(items) if items.is_empty()));
+    merge_patch(&mut merged, Value::Object(fields));
+    serde_json::from_value(merged).map_err(parse_error)
+}
+
+/// Applies a JSON Merge Patch (RFC 7386) to a value.
+fn merge_patch(target: &mut Value, patch: Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch;
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    let Value::Object(target) = target else {
+        unreachable!("the target was just made an object");
+    };
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(&key);
+        } else {
+            merge_patch(target.entry(key).or_insert(Value::Null), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::parse_spec;
+
+    #[test]
+    fn apply_referenced_trait_to_two_messages() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Orders
+  version: 1.0.0
+components:
+  messages:
+    orderPlaced:
+      summary: An order was placed.
+      traits:
+        - $ref: '#/components/messageTraits/commonHeaders'
+    orderShipped:
+      contentType: application/cloudevents+json
+      traits:
+        - $ref: '#/components/messageTraits/commonHeaders'
+    orderCancelled:
+      traits:
+        - $ref: '#/components/messageTraits/missing'
+  messageTraits:
+    commonHeaders:
+      contentType: application/json
+      summary: An order event.
+      headers:
+        type: object
+        properties:
+          correlationId:
+            type: string
+"#,
+        );
+        let applied = |name: &str| {
+            spec.components.messages[name]
+                .resolve(&spec)
+                .unwrap()
+                .apply_traits(&spec)
+        };
+
+        let placed = applied("orderPlaced").unwrap();
+        let shipped = applied("orderShipped").unwrap();
+
+        assert_eq!(placed.summary.as_deref(), Some("An order was placed."));
+        assert_eq!(placed.content_type.as_deref(), Some("application/json"));
+        assert!(placed.headers.is_some() && placed.traits.is_empty());
+        assert_eq!(shipped.summary.as_deref(), Some("An order event."));
+        assert_eq!(
+            shipped.content_type.as_deref(),
+            Some("application/cloudevents+json")
+        );
+        assert_eq!(placed.headers, shipped.headers);
+        assert!(matches!(
+            applied("orderCancelled"),
+            Err(Error::UnresolvedReference { reference })
+                if reference == "#/components/messageTraits/missing"
+        ));
+    }
+}
@@ -0,0 +1,200 @@
+//! Stable identifiers of channels, operations and messages, kept across renames.
+//!
+//! The specification has no identifier besides the keys of the entities, which change when an
+//! entity is renamed. The [UID_EXTENSION] gives an entity an identifier of its own, which the
+//! [diff](crate::diff::diff) of two documents uses to recognize renamed entities.
+use crate::error::Error;
+use crate::spec::channel::Channel;
+use crate::spec::common::{Either, Extensions, JsonPointer};
+use crate::spec::message::Message;
+use crate::spec::operation::Operation;
+use crate::spec::AsyncApiV3Spec;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use serde_json::Value;
+
+/// Extension holding the stable identifier of a channel, an operation or a message.
+pub const UID_EXTENSION: &str = "x-uid";
+
+impl Extensions {
+    /// Returns the [UID_EXTENSION], if it is a string.
+    pub fn uid(&self) -> Option<&str> {
+        self.get(UID_EXTENSION).and_then(Value::as_str)
+    }
+}
+
+impl Channel {
+    /// Returns the stable identifier of the channel, see [UID_EXTENSION].
+    pub fn uid(&self) -> Option<&str> {
+        self.extensions.uid()
+    }
+}
+
+impl Operation {
+    /// Returns the stable identifier of the operation, see [UID_EXTENSION].
+    pub fn uid(&self) -> Option<&str> {
+        self.extensions.uid()
+    }
+}
+
+impl Message {
+    /// Returns the stable identifier of the message, see [UID_EXTENSION].
+    pub fn uid(&self) -> Option<&str> {
+        self.extensions.uid()
+    }
+}
+
+/// Derives an identifier from the content of an entity, the hexadecimal FNV-1a hash of its
+/// JSON serialization. Identical contents get the same identifier.
+pub fn content_uid(content: &Value) -> String {
+    fnv1a_hex(content.to_string().as_bytes())
+}
+
+/// Returns the hexadecimal FNV-1a hash of bytes.
+pub(crate) fn fnv1a_hex(bytes: &[u8]) -> String {
+    let hash = bytes.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("{hash:016x}")
+}
+
+impl AsyncApiV3Spec {
+    /// Returns the location and the extensions of the channels, operations and messages
+    /// defined in the document, leaving out references, in document order.
+    pub(crate) fn identifiable_entities(&self) -> Vec<(JsonPointer, &Extensions)> {
+        let mut entities = Vec::new();
+        let root = JsonPointer::default();
+        let components = root.join("components");
+        for (section, channels) in [
+            (&root, &self.channels),
+            (&components, &self.components.channels),
+        ] {
+            for (name, channel) in channels {
+                let Either::Right(channel) = channel else {
+                    continue;
+                };
+                let pointer = section.join("channels").join(name.as_str());
+                for (key, message) in &channel.messages {
+                    if let Either::Right(message) = message {
+                        let pointer = pointer.join("messages").join(key.as_str());
+                        entities.push((pointer, &message.extensions));
+                    }
+                }
+                entities.push((pointer, &channel.extensions));
+            }
+        }
+        for (name, message) in &self.components.messages {
+            if let Either::Right(message) = message {
+                let pointer = components.join("messages").join(name.as_str());
+                entities.push((pointer, &message.extensions));
+            }
+        }
+        for (section, operations) in [
+            (&root, &self.operations),
+            (&components, &self.components.operations),
+        ] {
+            for (name, operation) in operations {
+                if let Either::Right(operation) = operation {
+                    let pointer = section.join("operations").join(name.as_str());
+                    entities.push((pointer, &operation.extensions));
+                }
+            }
+        }
+        entities
+    }
+
+    /// Gives a [stable identifier](UID_EXTENSION) to the channels, operations and messages
+    /// lacking one, computed by the generator from the content of the entity, e.g.
+    /// [content_uid]. The content of a channel includes its messages. A generated identifier
+    /// already in use gets a `-2`, `-3`... suffix, so entities with the same content get
+    /// distinct identifiers. Returns the location of every entity given an identifier.
+    ///
+    /// Fails with [Error::Parse] if the document cannot be read back, in which case the
+    /// document is left untouched.
+    pub fn assign_uids(
+        &mut self,
+        mut generator: impl FnMut(&Value) -> String,
+    ) -> Result<Vec<JsonPointer>, Error> {
+        let parse_error = |err: serde_json::Error| Error::Parse {
+            reason: err.to_string(),
+        };
+        let entities = self.identifiable_entities();
+        let mut used = entities
+            .iter()
+            .filter_map(|(_, extensions)| extensions.uid())
+            .map(String::from)
+            .collect::<Vec<_>>();
+        let missing = entities
+            .into_iter()
+            .filter(|(_, extensions)| extensions.uid().is_none())
+            .map(|(pointer, _)| pointer)
+            .collect::<Vec<_>>();
+        if missing.is_empty() {
+            return Ok(missing);
+        }
+
+        let mut document = serde_json::to_value(&*self).map_err(parse_error)?;
+        for pointer in &missing {
+            let Some(Value::Object(entity)) = pointer.evaluate_mut(&mut document) else {
+                continue;
+            };
+            let generated = generator(&Value::Object(entity.clone()));
+            let uid = (1..)
+                .map(|n| match n {
+                    1 => generated.clone(),
+                    n => format!("{generated}-{n}"),
+                })
+                .find(|uid| !used.contains(uid))
+                .expect("the suffixes are unbounded");
+            entity.insert(String::from(UID_EXTENSION), Value::String(uid.clone()));
+            used.push(uid);
+        }
+        *self = serde_json::from_value(document).map_err(parse_error)?;
+        Ok(missing)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::parse_spec;
+
+    #[test]
+    fn assign_deterministic_distinct_uids() {
+        let yaml = r#"
+asyncapi: 3.0.0
+info:
+  title: Orders
+  version: 1.0.0
+components:
+  messages:
+    orderPlaced:
+      payload:
+        type: object
+    orderCopied:
+      payload:
+        type: object
+    orderShipped:
+      x-uid: shipped
+      payload:
+        type: string
+"#;
+        let mut spec = parse_spec(yaml);
+        let mut again = parse_spec(yaml);
+
+        let assigned = spec.assign_uids(content_uid).unwrap();
+        again.assign_uids(content_uid).unwrap();
+
+        assert_eq!(assigned.len(), 2);
+        assert_eq!(spec, again);
+        let uid = |name: &str| {
+            let Either::Right(message) = &spec.components.messages[name] else {
+                panic!("the message is inline");
+            };
+            message.uid().unwrap()
+        };
+        assert_eq!(uid("orderPlaced"), format!("{}-2", uid("orderCopied")));
+        assert_eq!(uid("orderShipped"), "shipped");
+    }
+}
@@ -0,0 +1,543 @@
+//! Low-memory parsing of large YAML documents, e.g. specs bundling thousands of channels.
+//!
+//! [AsyncApiV3Spec::parse_yaml_filtered] reads the YAML event stream and only builds the root
+//! sections a [SectionFilter] selects. The other sections are skipped as they are read, without
+//! buffering them, and recorded in [PartialDocument::skipped].
+//!
+//! ```
+//! # use asyncapiv3::spec::partial::ParseOptions;
+//! # use asyncapiv3::spec::AsyncApiV3Spec;
+//! # use asyncapiv3::streaming::SectionFilter;
+//! let yaml = "
+//! asyncapi: 3.0.0
+//! info: { title: Lights, version: 1.0.0 }
+//! channels:
+//!   lightsOn: { address: lights.on }
+//! ";
+//! let filter = SectionFilter::new().section("info").keys("channels");
+//! let (document, issues) =
+//!     AsyncApiV3Spec::parse_yaml_filtered(yaml.as_bytes(), &filter, &ParseOptions::default())
+//!         .unwrap();
+//! assert!(issues.is_empty());
+//! assert!(document.channels.is_empty());
+//! assert_eq!(document.skipped[0].to_string(), "/channels/lightsOn");
+//! ```
+use crate::error::Error;
+use crate::spec::common::JsonPointer;
+use crate::spec::partial::{ParseIssue, ParseOptions, PartialDocument, PartialParser};
+use crate::spec::AsyncApiV3Spec;
+use serde_json::{Map, Number, Value};
+use std::collections::BTreeMap;
+use std::ffi::{c_void, CStr};
+use std::io::Read;
+use std::mem::MaybeUninit;
+use std::slice;
+
+/// The root fields always read, whatever the filter.
+const ROOT_SCALARS: [&str; 3] = ["asyncapi", "id", "defaultContentType"];
+
+/// How much of a root section a [SectionFilter] reads.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SectionMode {
+    /// The whole section.
+    Full,
+    /// Only the keys of the section, each entry being recorded as skipped.
+    Keys,
+}
+
+/// The root sections read by [AsyncApiV3Spec::parse_yaml_filtered], besides `asyncapi`, `id`
+/// and `defaultContentType` which are always read.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SectionFilter {
+    sections: BTreeMap<String, SectionMode>,
+}
+
+impl SectionFilter {
+    /// A filter reading no section.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the whole section.
+    pub fn section(mut self, name: &str) -> Self {
+        self.sections.insert(String::from(name), SectionMode::Full);
+        self
+    }
+
+    /// Reads the keys of the section only, e.g. the names of the channels.
+    pub fn keys(mut self, name: &str) -> Self {
+        self.sections.insert(String::from(name), SectionMode::Keys);
+        self
+    }
+
+    /// Returns how much of the section is read, if anything.
+    pub fn mode(&self, name: &str) -> Option<SectionMode> {
+        self.sections.get(name).copied()
+    }
+}
+
+impl AsyncApiV3Spec {
+    /// Parses a YAML document from a reader, only building the sections selected by `filter`.
+    ///
+    /// Like [AsyncApiV3Spec::parse_partial], nodes which cannot be deserialized are replaced by
+    /// placeholders. The skipped nodes are listed in [PartialDocument::skipped], so the
+    /// document only converts into an [AsyncApiV3Spec] when nothing was skipped.
+    ///
+    /// Fails with [Error::Parse] if the input cannot be read, is not YAML, is not a mapping, or if a read section
+    /// contains an alias of a node outside of the read sections.
+    pub fn parse_yaml_filtered<R: Read>(
+        reader: R,
+        filter: &SectionFilter,
+        options: &ParseOptions,
+    ) -> Result<(PartialDocument, Vec<ParseIssue>), Error> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("parse", streaming = true).entered();
+        let mut reader = Reader {
+            events: EventParser::new(Box::new(reader))?,
+            anchors: BTreeMap::new(),
+        };
+        let (root, skipped) = reader.root(filter)?;
+        let mut parser = PartialParser::default();
+        let document = parser.document(&root, options, skipped);
+        Ok((document, parser.issues))
+    }
+}
+
+/// Builds the selected sections out of the events.
+struct Reader<'r> {
+    events: EventParser<'r>,
+    /// The anchored nodes of the read sections.
+    anchors: BTreeMap<String, Value>,
+}
+
+impl Reader<'_> {
+    fn root(
+        &mut self,
+        filter: &SectionFilter,
+    ) -> Result<(Map<String, Value>, Vec<JsonPointer>), Error> {
+        let mut event = self.events.next()?;
+        while matches!(event, Event::StreamStart | Event::DocumentStart) {
+            event = self.events.next()?;
+        }
+        if !matches!(event, Event::MappingStart(_)) {
+            return Err(self.events.error("expected a mapping"));
+        }
+
+        let mut root = Map::new();
+        let mut skipped = Vec::new();
+        loop {
+            let key = match self.events.next()? {
+                Event::MappingEnd => break,
+                Event::Scalar { value, .. } => value,
+                _ => return Err(self.events.error("expected a string key")),
+            };
+            let pointer = JsonPointer::default().join(key.as_str());
+            let event = self.events.next()?;
+            let mode = if ROOT_SCALARS.contains(&key.as_str()) {
+                Some(SectionMode::Full)
+            } else {
+                filter.mode(&key)
+            };
+            match (mode, event) {
+                (Some(SectionMode::Full), event) => {
+                    let value = self.build(event)?;
+                    root.insert(key, value);
+                }
+                (Some(SectionMode::Keys), Event::MappingStart(_)) => loop {
+                    match self.events.next()? {
+                        Event::MappingEnd => break,
+                        Event::Scalar { value, .. } => {
+                            skipped.push(pointer.join(value));
+                            let event = self.events.next()?;
+                            self.skip(event)?;
+                        }
+                        _ => return Err(self.events.error("expected a string key")),
+                    }
+                },
+                (_, event) => {
+                    self.skip(event)?;
+                    skipped.push(pointer);
+                }
+            }
+        }
+        Ok((root, skipped))
+    }
+
+    /// Builds the node starting with `event`.
+    fn build(&mut self, event: Event) -> Result<Value, Error> {
+        let (anchor, value) = match event {
+            Event::Alias(anchor) => {
+                return match self.anchors.get(&anchor) {
+                    Some(value) => Ok(value.clone()),
+                    None => Err(self.events.error(format!(
+                        "the alias `{anchor}` refers to a node which was not read"
+                    ))),
+                };
+            }
+            Event::Scalar {
+                anchor,
+                value,
+                plain,
+            } => (
+                anchor,
+                if plain {
+                    resolve(value)
+                } else {
+                    Value::String(value)
+                },
+            ),
+            Event::SequenceStart(anchor) => {
+                let mut items = Vec::new();
+                loop {
+                    match self.events.next()? {
+                        Event::SequenceEnd => break,
+                        event => items.push(self.build(event)?),
+                    }
+                }
+                (anchor, Value::Array(items))
+            }
+            Event::MappingStart(anchor) => {
+                let mut entries = Map::new();
+                loop {
+                    let key = match self.events.next()? {
+                        Event::MappingEnd => break,
+                        Event::Scalar { value, .. } => value,
+                        _ => return Err(self.events.error("expected a string key")),
+                    };
+                    let event = self.events.next()?;
+                    entries.insert(key, self.build(event)?);
+                }
+                (anchor, Value::Object(entries))
+            }
+            _ => return Err(self.events.error("expected a node")),
+        };
+        if let Some(anchor) = anchor {
+            self.anchors.insert(anchor, value.clone());
+        }
+        Ok(value)
+    }
+
+    /// Reads past the node starting with `event`, keeping nothing of it.
+    fn skip(&mut self, mut event: Event) -> Result<(), Error> {
+        let mut depth = 0usize;
+        loop {
+            match event {
+                Event::SequenceStart(_) | Event::MappingStart(_) => depth += 1,
+                Event::SequenceEnd | Event::MappingEnd => depth -= 1,
+                Event::Scalar { .. } | Event::Alias(_) => {}
+                _ => return Err(self.events.error("expected a node")),
+            }
+            if depth == 0 {
+                return Ok(());
+            }
+            event = self.events.next()?;
+        }
+    }
+}
+
+/// Resolves a plain scalar following the YAML 1.2 core schema. Numbers JSON cannot represent,
+/// such as `.inf`, are kept as strings.
+fn resolve(value: String) -> Value {
+    match value.as_str() {
+        "" | "~" | "null" | "Null" | "NULL" => return Value::Null,
+        "true" | "True" | "TRUE" => return Value::Bool(true),
+        "false" | "False" | "FALSE" => return Value::Bool(false),
+        _ => {}
+    }
+    let integer = if let Some(hex) = value.strip_prefix("0x") {
+        u64::from_str_radix(hex, 16).ok().map(Number::from)
+    } else if let Some(octal) = value.strip_prefix("0o") {
+        u64::from_str_radix(octal, 8).ok().map(Number::from)
+    } else if let Ok(unsigned) = value.parse::<u64>() {
+        Some(Number::from(unsigned))
+    } else {
+        value.parse::<i64>().ok().map(Number::from)
+    };
+    if let Some(integer) = integer {
+        return Value::Number(integer);
+    }
+    // Rust also parses `inf` and `NaN`, which YAML spells `.inf` and `.nan`.
+    let numeric = value
+        .trim_start_matches(['-', '+'])
+        .starts_with(|c: char| c.is_ascii_digit() || c == '.');
+    match value.parse::<f64>().ok().filter(|_| numeric) {
+        Some(float) => Number::from_f64(float).map_or(Value::String(value), Value::Number),
+        None => Value::String(value),
+    }
+}
+
+/// The events of the YAML stream which matter to the reader.
+#[derive(Debug)]
+enum Event {
+    StreamStart,
+    StreamEnd,
+    DocumentStart,
+    DocumentEnd,
+    Alias(String),
+    Scalar {
+        anchor: Option<String>,
+        value: String,
+        /// Plain scalars are resolved to null, booleans and numbers, other ones are strings.
+        plain: bool,
+    },
+    SequenceStart(Option<String>),
+    SequenceEnd,
+    MappingStart(Option<String>),
+    MappingEnd,
+}
+
+/// A libyaml parser reading its input in chunks from a reader.
+struct EventParser<'r> {
+    /// libyaml keeps a pointer to the parser, which is boxed so it does not move. It is
+    /// initialized before the [EventParser] is built, which deletes it on drop.
+    parser: Box<MaybeUninit<unsafe_libyaml::yaml_parser_t>>,
+    /// The input, handed to libyaml through a pointer to the box.
+    input: Box<Input<'r>>,
+    /// The line and column of the last event, from 0.
+    mark: (u64, u64),
+}
+
+/// The reader of an [EventParser], along with the error it last failed with.
+struct Input<'r> {
+    reader: Box<dyn Read + 'r>,
+    error: Option<std::io::Error>,
+}
+
+impl<'r> EventParser<'r> {
+    fn new(reader: Box<dyn Read + 'r>) -> Result<Self, Error> {
+        let mut parser = Box::new(MaybeUninit::<unsafe_libyaml::yaml_parser_t>::uninit());
+        let mut input = Box::new(Input {
+            reader,
+            error: None,
+        });
+        // SAFETY: the parser is initialized before use and only moved into the EventParser,
+        // which deletes it on drop, once initialized. Both the parser and the input are boxed
+        // so the pointers libyaml keeps stay valid until then.
+        unsafe {
+            let parser = parser.as_mut_ptr();
+            if unsafe_libyaml::yaml_parser_initialize(parser).fail {
+                return Err(Error::Parse {
+                    reason: String::from("cannot initialize the YAML parser"),
+                });
+            }
+            unsafe_libyaml::yaml_parser_set_encoding(parser, unsafe_libyaml::YAML_UTF8_ENCODING);
+            let data = &mut *input as *mut Input<'r> as *mut c_void;
+            unsafe_libyaml::yaml_parser_set_input(parser, read_handler, data);
+        }
+        Ok(Self {
+            parser,
+            input,
+            mark: (0, 0),
+        })
+    }
+
+    /// Parses the next event, failing at the end of the document.
+    fn next(&mut self) -> Result<Event, Error> {
+        let mut event = MaybeUninit::<unsafe_libyaml::yaml_event_t>::uninit();
+        // SAFETY: the parser was initialized in `new`, and the event is deleted once converted.
+        let next = unsafe {
+            let event = event.as_mut_ptr();
+            if unsafe_libyaml::yaml_parser_parse(self.parser.as_mut_ptr(), event).fail {
+                return Err(match self.input.error.take() {
+                    Some(err) => Error::Parse {
+                        reason: format!("cannot read the document: {err}"),
+                    },
+                    None => self.error("invalid YAML"),
+                });
+            }
+            let next = convert(&*event);
+            self.mark = ((*event).start_mark.line, (*event).start_mark.column);
+            unsafe_libyaml::yaml_event_delete(event);
+            next
+        };
+        if matches!(next, Event::StreamEnd | Event::DocumentEnd) {
+            return Err(self.error("unexpected end of the document"));
+        }
+        Ok(next)
+    }
+
+    fn error(&self, message: impl core::fmt::Display) -> Error {
+        Error::Parse {
+            reason: format!(
+                "{message} at line {} column {}",
+                self.mark.0 + 1,
+                self.mark.1 + 1
+            ),
+        }
+    }
+}
+
+impl Drop for EventParser<'_> {
+    fn drop(&mut self) {
+        // SAFETY: the parser is initialized before the EventParser is built in `new`.
+        unsafe { unsafe_libyaml::yaml_parser_delete(self.parser.as_mut_ptr()) }
+    }
+}
+
+/// Copies the content of an event.
+///
+/// # Safety
+///
+/// The event must have been produced by `yaml_parser_parse` and not deleted yet.
+unsafe fn convert(event: &unsafe_libyaml::yaml_event_t) -> Event {
+    let string = |pointer: *const u8| {
+        (!pointer.is_null()).then(|| {
+            String::from_utf8_lossy(CStr::from_ptr(pointer.cast()).to_bytes()).into_owned()
+        })
+    };
+    match event.type_ {
+        unsafe_libyaml::YAML_STREAM_START_EVENT => Event::StreamStart,
+        unsafe_libyaml::YAML_DOCUMENT_START_EVENT => Event::DocumentStart,
+        unsafe_libyaml::YAML_DOCUMENT_END_EVENT => Event::DocumentEnd,
+        unsafe_libyaml::YAML_ALIAS_EVENT => {
+            Event::Alias(string(event.data.alias.anchor).unwrap_or_default())
+        }
+        unsafe_libyaml::YAML_SCALAR_EVENT => {
+            let scalar = event.data.scalar;
+            let value = slice::from_raw_parts(scalar.value, scalar.length as usize);
+            Event::Scalar {
+                anchor: string(scalar.anchor),
+                value: String::from_utf8_lossy(value).into_owned(),
+                plain: scalar.style == unsafe_libyaml::YAML_PLAIN_SCALAR_STYLE,
+            }
+        }
+        unsafe_libyaml::YAML_SEQUENCE_START_EVENT => {
+            Event::SequenceStart(string(event.data.sequence_start.anchor))
+        }
+        unsafe_libyaml::YAML_SEQUENCE_END_EVENT => Event::SequenceEnd,
+        unsafe_libyaml::YAML_MAPPING_START_EVENT => {
+            Event::MappingStart(string(event.data.mapping_start.anchor))
+        }
+        unsafe_libyaml::YAML_MAPPING_END_EVENT => Event::MappingEnd,
+        _ => Event::StreamEnd,
+    }
+}
+
+/// Fills libyaml's buffer from the reader.
+///
+/// # Safety
+///
+/// `data` must point to the boxed input of an [EventParser], `buffer` to `size` writable bytes.
+unsafe fn read_handler(data: *mut c_void, buffer: *mut u8, size: u64, size_read: *mut u64) -> i32 {
+    let input = &mut *(data as *mut Input);
+    let buffer = slice::from_raw_parts_mut(buffer, size as usize);
+    loop {
+        match input.reader.read(buffer) {
+            Ok(read) => {
+                *size_read = read as u64;
+                return 1;
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(err) => {
+                input.error = Some(err);
+                return 0;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::spec::partial::Node;
+    use crate::spec::AsyncApiSpec;
+
+    const DOCUMENT: &str = r#"
+asyncapi: 3.0.0
+info:
+  title: Lights
+  version: &version 1.0.0
+  description: 'true'
+servers:
+  production:
+    host: broker.example.com
+    protocol: mqtt
+    protocolVersion: *version
+channels:
+  lightsOn:
+    address: lights.on
+    messages: {}
+  lightsOff:
+    address: lights.off
+    messages: {}
+components:
+  schemas:
+    level: { type: integer, maximum: 0x64 }
+"#;
+
+    fn parse(filter: &SectionFilter) -> Result<(PartialDocument, Vec<ParseIssue>), Error> {
+        AsyncApiV3Spec::parse_yaml_filtered(DOCUMENT.as_bytes(), filter, &ParseOptions::default())
+    }
+
+    #[test]
+    fn filtered_sections_are_skipped() {
+        let filter = SectionFilter::new()
+            .section("info")
+            .section("servers")
+            .keys("channels");
+
+        let (document, issues) = parse(&filter).unwrap();
+
+        assert!(issues.is_empty(), "{issues:?}");
+        let Node::Parsed(info) = &document.info else {
+            panic!("info is read");
+        };
+        assert_eq!(info.description.as_deref(), Some("true"));
+        assert!(document.servers["production"].parsed().is_some());
+        assert!(document.channels.is_empty());
+        let skipped = document
+            .skipped
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            skipped,
+            ["/channels/lightsOn", "/channels/lightsOff", "/components"]
+        );
+        assert!(AsyncApiV3Spec::try_from(document).is_err());
+    }
+
+    #[test]
+    fn read_everything() {
+        let filter = ["info", "servers", "channels", "components"]
+            .into_iter()
+            .fold(SectionFilter::new(), SectionFilter::section);
+
+        let (document, _) = parse(&filter).unwrap();
+
+        let spec = AsyncApiV3Spec::try_from(document).unwrap();
+        let AsyncApiSpec::V3_0_0(expected) = serde_yaml::from_str(DOCUMENT).unwrap();
+        assert_eq!(spec, expected);
+    }
+
+    #[test]
+    fn aliases_of_skipped_nodes_fail() {
+        let filter = SectionFilter::new().section("servers");
+
+        let error = parse(&filter).unwrap_err();
+
+        assert!(matches!(error, Error::Parse { reason } if reason.contains("`version`")));
+    }
+
+    #[test]
+    fn read_errors_are_reported() {
+        struct Failing;
+        impl Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("connection reset"))
+            }
+        }
+
+        let error = AsyncApiV3Spec::parse_yaml_filtered(
+            Failing,
+            &SectionFilter::new(),
+            &ParseOptions::default(),
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Cannot parse document: cannot read the document: connection reset"
+        );
+    }
+}
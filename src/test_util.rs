@@ -0,0 +1,15 @@
+//! Helpers shared by the unit tests.
+use crate::spec::{AsyncApiSpec, AsyncApiV3Spec};
+
+/// Parses an AsyncAPI 3.0.0 document written in YAML, panicking if it is invalid.
+pub(crate) fn parse_spec(yaml: &str) -> AsyncApiV3Spec {
+    let AsyncApiSpec::V3_0_0(spec) = serde_yaml::from_str(yaml).unwrap();
+    spec
+}
+
+/// Reads the Kafka flavour of the streetlights example.
+pub(crate) fn streetlights() -> AsyncApiV3Spec {
+    let file = std::fs::File::open("test-res/3.0.0/streetlights-kafka-asyncapi.yml").unwrap();
+    let AsyncApiSpec::V3_0_0(spec) = serde_yaml::from_reader(file).unwrap();
+    spec
+}
@@ -0,0 +1,733 @@
+//! In-place transformations of documents.
+use crate::error::Error;
+use crate::spec::channel::{Channel, ChannelBindings};
+use crate::spec::common::{JsonPointer, RefOr};
+use crate::spec::message::{Message, MessageBindings, MessageTrait};
+use crate::spec::operation::{Operation, OperationBindings, OperationTrait};
+use crate::spec::server::{Server, ServerBindings};
+use crate::spec::AsyncApiV3Spec;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+#[cfg(feature = "anonymize")]
+mod anonymize;
+#[cfg(feature = "anonymize")]
+#[cfg_attr(docsrs, doc(cfg(feature = "anonymize")))]
+pub use anonymize::{anonymize, AnonymizeCategory, AnonymizeReport, AnonymizeRules};
+
+/// Binding values to fill in when a document leaves them unset, keyed by protocol name for each
+/// kind of bindings object.
+///
+/// Values are partial bindings, e.g. `{"bindingVersion": "0.5.0", "topicConfiguration":
+/// {"cleanup.policy": ["delete"]}}` for the `kafka` channel bindings. Nested objects are merged
+/// field by field.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BindingDefaults {
+    /// Defaults of the server bindings.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub server: BTreeMap<String, Value>,
+    /// Defaults of the channel bindings.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub channel: BTreeMap<String, Value>,
+    /// Defaults of the operation bindings, including the ones of operation traits.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub operation: BTreeMap<String, Value>,
+    /// Defaults of the message bindings, including the ones of message traits.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub message: BTreeMap<String, Value>,
+}
+
+/// Fills the unset fields of the bindings of a document with the given defaults.
+///
+/// Defaults only apply to the bindings of the protocols a bindings object already declares:
+/// no binding is added for other protocols and explicit values are never overridden.
+/// Referenced bindings are updated where they are defined. Returns the location of every field
+/// which was filled in.
+///
+/// Fails with [Error::InvalidBindingDefaults] if the defaults do not fit a binding this crate
+/// models, in which case the document is left untouched.
+pub fn apply_binding_defaults(
+    spec: &mut AsyncApiV3Spec,
+    defaults: &BindingDefaults,
+) -> Result<Vec<JsonPointer>, Error> {
+    let mut updated = spec.clone();
+    let mut applier = Applier {
+        defaults,
+        filled: Vec::new(),
+    };
+    let root = JsonPointer::default();
+    let components_pointer = root.join("components");
+
+    for (key, server) in &mut updated.servers {
+        applier.server(server, root.join("servers").join(key.as_str()))?;
+    }
+    for (key, channel) in &mut updated.channels {
+        applier.channel(channel, root.join("channels").join(key.as_str()))?;
+    }
+    for (key, operation) in &mut updated.operations {
+        applier.operation(operation, root.join("operations").join(key.as_str()))?;
+    }
+
+    let components = &mut updated.components;
+    for (key, server) in &mut components.servers {
+        applier.server(
+            server,
+            components_pointer.join("servers").join(key.as_str()),
+        )?;
+    }
+    for (key, channel) in &mut components.channels {
+        applier.channel(
+            channel,
+            components_pointer.join("channels").join(key.as_str()),
+        )?;
+    }
+    for (key, operation) in &mut components.operations {
+        applier.operation(
+            operation,
+            components_pointer.join("operations").join(key.as_str()),
+        )?;
+    }
+    for (key, message) in &mut components.messages {
+        applier.message(
+            message,
+            components_pointer.join("messages").join(key.as_str()),
+        )?;
+    }
+    for (key, operation_trait) in &mut components.operation_traits {
+        applier.operation_trait(
+            operation_trait,
+            components_pointer
+                .join("operationTraits")
+                .join(key.as_str()),
+        )?;
+    }
+    for (key, message_trait) in &mut components.message_traits {
+        applier.message_trait(
+            message_trait,
+            components_pointer.join("messageTraits").join(key.as_str()),
+        )?;
+    }
+    for (key, bindings) in &mut components.server_bindings {
+        let pointer = components_pointer.join("serverBindings").join(key.as_str());
+        applier.bindings_entry(bindings, pointer)?;
+    }
+    for (key, bindings) in &mut components.channel_bindings {
+        let pointer = components_pointer
+            .join("channelBindings")
+            .join(key.as_str());
+        applier.bindings_entry(bindings, pointer)?;
+    }
+    for (key, bindings) in &mut components.operation_bindings {
+        let pointer = components_pointer
+            .join("operationBindings")
+            .join(key.as_str());
+        applier.bindings_entry(bindings, pointer)?;
+    }
+    for (key, bindings) in &mut components.message_bindings {
+        let pointer = components_pointer
+            .join("messageBindings")
+            .join(key.as_str());
+        applier.bindings_entry(bindings, pointer)?;
+    }
+
+    *spec = updated;
+    Ok(applier.filled)
+}
+
+/// A bindings object, keyed by protocol name once serialized.
+trait Bindings: Serialize + DeserializeOwned {
+    /// Returns the defaults applying to this kind of bindings.
+    fn defaults(defaults: &BindingDefaults) -> &BTreeMap<String, Value>;
+}
+
+macro_rules! bindings {
+    ($($ty:ty => $field:ident;)*) => {
+        $(
+            impl Bindings for $ty {
+                fn defaults(defaults: &BindingDefaults) -> &BTreeMap<String, Value> {
+                    &defaults.$field
+                }
+            }
+        )*
+    };
+}
+
+bindings! {
+    ServerBindings => server;
+    ChannelBindings => channel;
+    OperationBindings => operation;
+    MessageBindings => message;
+}
+
+struct Applier<'a> {
+    defaults: &'a BindingDefaults,
+    filled: Vec<JsonPointer>,
+}
+
+impl Applier<'_> {
+    fn server(&mut self, server: &mut RefOr<Server>, pointer: JsonPointer) -> Result<(), Error> {
+        match server {
+            RefOr::Right(server) => self.bindings(&mut server.bindings, pointer),
+            RefOr::Left(_) => Ok(()),
+        }
+    }
+
+    fn channel(&mut self, channel: &mut RefOr<Channel>, pointer: JsonPointer) -> Result<(), Error> {
+        let RefOr::Right(channel) = channel else {
+            return Ok(());
+        };
+        for (key, message) in &mut channel.messages {
+            self.message(message, pointer.join("messages").join(key.as_str()))?;
+        }
+        self.bindings(&mut channel.bindings, pointer)
+    }
+
+    fn operation(
+        &mut self,
+        operation: &mut RefOr<Operation>,
+        pointer: JsonPointer,
+    ) -> Result<(), Error> {
+        let RefOr::Right(operation) = operation else {
+            return Ok(());
+        };
+        for (index, operation_trait) in operation.traits.iter_mut().enumerate() {
+            let trait_pointer = pointer.join("traits").join(index.to_string());
+            self.operation_trait(operation_trait, trait_pointer)?;
+        }
+        self.bindings(&mut operation.bindings, pointer)
+    }
+
+    fn operation_trait(
+        &mut self,
+        operation_trait: &mut RefOr<OperationTrait>,
+        pointer: JsonPointer,
+    ) -> Result<(), Error> {
+        match operation_trait {
+            RefOr::Right(operation_trait) => self.bindings(&mut operation_trait.bindings, pointer),
+            RefOr::Left(_) => Ok(()),
+        }
+    }
+
+    fn message(&mut self, message: &mut RefOr<Message>, pointer: JsonPointer) -> Result<(), Error> {
+        let RefOr::Right(message) = message else {
+            return Ok(());
+        };
+        for (index, message_trait) in message.traits.iter_mut().enumerate() {
+            let trait_pointer = pointer.join("traits").join(index.to_string());
+            self.message_trait(message_trait, trait_pointer)?;
+        }
+        self.bindings(&mut message.bindings, pointer)
+    }
+
+    fn message_trait(
+        &mut self,
+        message_trait: &mut RefOr<MessageTrait>,
+        pointer: JsonPointer,
+    ) -> Result<(), Error> {
+        match message_trait {
+            RefOr::Right(message_trait) => self.bindings(&mut message_trait.bindings, pointer),
+            RefOr::Left(_) => Ok(()),
+        }
+    }
+
+    /// Applies the defaults to the `bindings` field of an object.
+    fn bindings<B: Bindings>(
+        &mut self,
+        bindings: &mut Option<RefOr<B>>,
+        pointer: JsonPointer,
+    ) -> Result<(), Error> {
+        match bindings {
+            Some(bindings) => self.bindings_entry(bindings, pointer.join("bindings")),
+            None => Ok(()),
+        }
+    }
+
+    fn bindings_entry<B: Bindings>(
+        &mut self,
+        bindings: &mut RefOr<B>,
+        pointer: JsonPointer,
+    ) -> Result<(), Error> {
+        let RefOr::Right(bindings) = bindings else {
+            return Ok(());
+        };
+        let defaults = B::defaults(self.defaults);
+        if defaults.is_empty() {
+            return Ok(());
+        }
+
+        let invalid = |protocol: &str, reason: String| Error::InvalidBindingDefaults {
+            protocol: String::from(protocol),
+            reason,
+        };
+        let Value::Object(mut protocols) =
+            serde_json::to_value(&*bindings).map_err(|err| invalid("", err.to_string()))?
+        else {
+            return Ok(());
+        };
+        for (protocol, binding) in protocols.iter_mut() {
+            let Some(default) = defaults.get(protocol) else {
+                continue;
+            };
+            let filled = self.filled.len();
+            fill(
+                binding,
+                default,
+                &pointer.join(protocol.as_str()),
+                &mut self.filled,
+            );
+            if self.filled.len() > filled {
+                // Check each protocol on its own to blame the right defaults.
+                let single = Value::Object(serde_json::Map::from_iter([(
+                    protocol.clone(),
+                    binding.clone(),
+                )]));
+                B::deserialize(&single).map_err(|err| invalid(protocol, err.to_string()))?;
+            }
+        }
+        *bindings = B::deserialize(&Value::Object(protocols))
+            .map_err(|err| invalid("", err.to_string()))?;
+        Ok(())
+    }
+}
+
+/// Copies the fields of `default` missing from `target`, recursing into nested objects.
+fn fill(target: &mut Value, default: &Value, pointer: &JsonPointer, filled: &mut Vec<JsonPointer>) {
+    let (Value::Object(target), Value::Object(default)) = (target, default) else {
+        return;
+    };
+    for (key, default) in default {
+        let field_pointer = pointer.join(key.as_str());
+        match target.get_mut(key) {
+            Some(value) => fill(value, default, &field_pointer, filled),
+            None => {
+                target.insert(key.clone(), default.clone());
+                filled.push(field_pointer);
+            }
+        }
+    }
+}
+
+/// How [truncate_descriptions] shortens a description exceeding the budget.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TruncateStrategy {
+    /// Cuts at the last character fitting the budget.
+    HardCut,
+    /// Cuts after the last sentence or line fitting the budget, or as [HardCut](Self::HardCut)
+    /// if the first sentence does not fit.
+    SentenceBoundary,
+    /// Cuts as [HardCut](Self::HardCut) and appends an ellipsis (`…`).
+    Ellipsis,
+}
+
+/// A description shortened by [truncate_descriptions].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TruncatedDescription {
+    /// Location of the description.
+    pub pointer: JsonPointer,
+    /// Length in bytes of the description before truncation.
+    pub original_len: usize,
+}
+
+/// Fields whose values are user data rather than parts of the document.
+pub(crate) const DATA_FIELDS: [&str; 5] = ["const", "default", "enum", "example", "examples"];
+
+/// Shortens every description of a document to at most `max_bytes` bytes of UTF-8.
+///
+/// Descriptions are never cut inside a character, and a CommonMark code fence left open by the
+/// cut is closed within the budget. Descriptions inside examples, default values and
+/// specification extensions are left untouched. Returns the location of every shortened
+/// description, in document order.
+///
+/// Fails with [Error::InvalidDocument] if the document cannot be serialized or the shortened
+/// document cannot be read back, in which case the document is left untouched.
+pub fn truncate_descriptions(
+    spec: &mut AsyncApiV3Spec,
+    max_bytes: usize,
+    strategy: TruncateStrategy,
+) -> Result<Vec<TruncatedDescription>, Error> {
+    let invalid_document = |err: serde_json::Error| Error::InvalidDocument {
+        reason: err.to_string(),
+        hints: Vec::new(),
+    };
+    let mut document = serde_json::to_value(&*spec).map_err(invalid_document)?;
+    let mut truncated = Vec::new();
+    truncate_value(
+        &mut document,
+        &JsonPointer::default(),
+        max_bytes,
+        strategy,
+        &mut truncated,
+    );
+    if !truncated.is_empty() {
+        *spec = serde_json::from_value(document).map_err(invalid_document)?;
+    }
+    Ok(truncated)
+}
+
+fn truncate_value(
+    value: &mut Value,
+    pointer: &JsonPointer,
+    max_bytes: usize,
+    strategy: TruncateStrategy,
+    truncated: &mut Vec<TruncatedDescription>,
+) {
+    match value {
+        Value::Object(fields) => {
+            for (key, value) in fields.iter_mut() {
+                if DATA_FIELDS.contains(&key.as_str()) || key.starts_with("x-") {
+                    continue;
+                }
+                let field_pointer = pointer.join(key.as_str());
+                match value {
+                    Value::String(description) if key == "description" => {
+                        if let Some(shortened) = truncate(description, max_bytes, strategy) {
+                            truncated.push(TruncatedDescription {
+                                pointer: field_pointer,
+                                original_len: description.len(),
+                            });
+                            *description = shortened;
+                        }
+                    }
+                    _ => truncate_value(value, &field_pointer, max_bytes, strategy, truncated),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (index, item) in items.iter_mut().enumerate() {
+                let item_pointer = pointer.join(index.to_string());
+                truncate_value(item, &item_pointer, max_bytes, strategy, truncated);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Returns the text shortened to `max_bytes`, or `None` if it already fits.
+fn truncate(text: &str, max_bytes: usize, strategy: TruncateStrategy) -> Option<String> {
+    if text.len() <= max_bytes {
+        return None;
+    }
+    let ellipsis = match strategy {
+        TruncateStrategy::Ellipsis if max_bytes >= '…'.len_utf8() => "…",
+        _ => "",
+    };
+    let mut budget = max_bytes - ellipsis.len();
+    loop {
+        let mut cut = floor_char_boundary(text, budget);
+        if strategy == TruncateStrategy::SentenceBoundary {
+            cut = sentence_end(text, cut).unwrap_or(cut);
+        }
+        let kept = text[..cut].trim_end();
+        let mut shortened = String::from(kept);
+        let fence = open_fence(kept);
+        shortened.push_str(ellipsis);
+        if let Some(fence) = fence {
+            shortened.push('\n');
+            shortened.push_str(fence);
+        }
+        if shortened.len() <= max_bytes {
+            return Some(shortened);
+        }
+        // Make room for the closing fence, which may leave no fence open at all.
+        budget = budget.saturating_sub(shortened.len() - max_bytes);
+    }
+}
+
+/// Returns the largest index not greater than `index` which is a character boundary.
+fn floor_char_boundary(text: &str, index: usize) -> usize {
+    (0..=index.min(text.len()))
+        .rev()
+        .find(|&index| text.is_char_boundary(index))
+        .unwrap_or(0)
+}
+
+/// Returns the end of the last sentence or paragraph ending before `cut` outside of a code
+/// fence.
+fn sentence_end(text: &str, cut: usize) -> Option<usize> {
+    text[..cut]
+        .char_indices()
+        .rev()
+        .filter_map(|(index, c)| match c {
+            '\n' if text[..index].ends_with('\n') => Some(index),
+            '.' | '!' | '?' => {
+                let end = index + 1;
+                let followed_by_space = text[end..].chars().next().is_none_or(char::is_whitespace);
+                followed_by_space.then_some(end)
+            }
+            _ => None,
+        })
+        .find(|&end| open_fence(&text[..end]).is_none())
+}
+
+/// Returns the marker closing the CommonMark code fence left open at the end of the text.
+fn open_fence(text: &str) -> Option<&str> {
+    let mut open: Option<&str> = None;
+    for line in text.lines() {
+        let indent = line.len() - line.trim_start_matches(' ').len();
+        if indent > 3 {
+            continue;
+        }
+        let line = &line[indent..];
+        let Some(marker) = ['`', '~'].into_iter().find(|&c| line.starts_with(c)) else {
+            continue;
+        };
+        let run = &line[..line.len() - line.trim_start_matches(marker).len()];
+        if run.len() < 3 {
+            continue;
+        }
+        match open {
+            None => open = Some(run),
+            Some(fence)
+                if fence.starts_with(marker)
+                    && run.len() >= fence.len()
+                    && line[run.len()..].trim().is_empty() =>
+            {
+                open = None;
+            }
+            Some(_) => {}
+        }
+    }
+    open
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::parse_spec;
+    use serde_json::json;
+
+    const SPEC: &str = r#"
+asyncapi: 3.0.0
+info:
+  title: Bindings
+  version: 1.0.0
+servers:
+  kafka:
+    host: broker.example.com
+    protocol: kafka
+    bindings:
+      kafka:
+        schemaRegistryUrl: https://registry.example.com
+channels:
+  orders:
+    address: orders
+    messages:
+      order:
+        $ref: '#/components/messages/order'
+    bindings:
+      kafka:
+        bindingVersion: 0.4.0
+        partitions: 3
+  legacyOrders:
+    address: legacy.orders
+    messages: {}
+    bindings:
+      kafka:
+        topicConfiguration:
+          cleanup.policy: [compact]
+  lights:
+    address: lights
+    messages: {}
+    bindings:
+      mqtt:
+        bindingVersion: 0.2.0
+operations:
+  publishLights:
+    action: send
+    channel:
+      $ref: '#/channels/lights'
+    bindings:
+      mqtt:
+        retain: true
+  publishDimmed:
+    action: send
+    channel:
+      $ref: '#/channels/lights'
+    bindings:
+      $ref: '#/components/operationBindings/mqttExactlyOnce'
+components:
+  messages:
+    order:
+      bindings:
+        kafka:
+          key:
+            type: string
+  operationBindings:
+    mqttExactlyOnce:
+      mqtt:
+        qos: 2
+"#;
+
+    fn policy() -> BindingDefaults {
+        serde_json::from_value(json!({
+            "channel": {
+                "kafka": {
+                    "bindingVersion": "0.5.0",
+                    "topicConfiguration": { "cleanup.policy": ["delete"] }
+                }
+            },
+            "operation": { "mqtt": { "qos": 1 } },
+            "message": { "kafka": { "bindingVersion": "0.5.0" } }
+        }))
+        .unwrap()
+    }
+
+    fn generic(bindings: &Option<RefOr<impl Serialize>>) -> Value {
+        match bindings {
+            Some(RefOr::Right(bindings)) => serde_json::to_value(bindings).unwrap(),
+            _ => Value::Null,
+        }
+    }
+
+    fn channel<'a>(spec: &'a AsyncApiV3Spec, name: &str) -> &'a Channel {
+        spec.channels[name].resolve(spec).unwrap()
+    }
+
+    #[test]
+    fn fill_kafka_channel_defaults_without_overriding() {
+        let mut spec = parse_spec(SPEC);
+
+        apply_binding_defaults(&mut spec, &policy()).unwrap();
+
+        let orders = generic(&channel(&spec, "orders").bindings);
+        assert_eq!(orders["kafka"]["bindingVersion"], "0.4.0");
+        assert_eq!(orders["kafka"]["partitions"], 3);
+        assert_eq!(
+            orders["kafka"]["topicConfiguration"]["cleanup.policy"],
+            json!(["delete"])
+        );
+        let legacy = generic(&channel(&spec, "legacyOrders").bindings);
+        assert_eq!(legacy["kafka"]["bindingVersion"], "0.5.0");
+        assert_eq!(
+            legacy["kafka"]["topicConfiguration"]["cleanup.policy"],
+            json!(["compact"])
+        );
+        // Bindings of other protocols are left alone.
+        let lights = generic(&channel(&spec, "lights").bindings);
+        assert_eq!(lights, json!({ "mqtt": { "bindingVersion": "0.2.0" } }));
+    }
+
+    #[test]
+    fn fill_mqtt_operation_and_kafka_message_defaults() {
+        let mut spec = parse_spec(SPEC);
+
+        apply_binding_defaults(&mut spec, &policy()).unwrap();
+
+        let operation = spec.operations["publishLights"].resolve(&spec).unwrap();
+        assert_eq!(
+            generic(&operation.bindings),
+            json!({ "mqtt": { "retain": true, "qos": 1 } })
+        );
+        let referenced = &spec.components.operation_bindings["mqttExactlyOnce"];
+        assert_eq!(
+            serde_json::to_value(referenced).unwrap(),
+            json!({ "mqtt": { "qos": 2 } })
+        );
+        let message = spec.components.messages["order"].resolve(&spec).unwrap();
+        assert_eq!(
+            generic(&message.bindings)["kafka"]["bindingVersion"],
+            "0.5.0"
+        );
+        let server = spec.servers["kafka"].resolve(&spec).unwrap();
+        assert_eq!(
+            generic(&server.bindings),
+            json!({ "kafka": { "schemaRegistryUrl": "https://registry.example.com" } })
+        );
+    }
+
+    #[test]
+    fn report_filled_fields() {
+        let mut spec = parse_spec(SPEC);
+
+        let filled = apply_binding_defaults(&mut spec, &policy()).unwrap();
+
+        let filled = filled.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            filled,
+            vec![
+                "/channels/legacyOrders/bindings/kafka/bindingVersion",
+                "/channels/orders/bindings/kafka/topicConfiguration",
+                "/operations/publishLights/bindings/mqtt/qos",
+                "/components/messages/order/bindings/kafka/bindingVersion",
+            ]
+        );
+    }
+
+    #[test]
+    fn reject_defaults_not_fitting_typed_bindings() {
+        let mut spec = parse_spec(SPEC);
+        let RefOr::Right(lights) = spec.channels.get_mut("lights").unwrap() else {
+            panic!("lights should be inline");
+        };
+        lights.bindings = Some(RefOr::Right(
+            serde_json::from_value(json!({ "ws": { "method": "GET" } })).unwrap(),
+        ));
+        let original = spec.clone();
+        let defaults = BindingDefaults {
+            channel: BTreeMap::from([(String::from("ws"), json!({ "query": 42 }))]),
+            ..BindingDefaults::default()
+        };
+
+        let result = apply_binding_defaults(&mut spec, &defaults);
+
+        assert!(matches!(
+            result,
+            Err(Error::InvalidBindingDefaults { protocol, .. }) if protocol == "ws"
+        ));
+        assert_eq!(spec, original);
+    }
+
+    #[test]
+    fn truncate_before_multi_byte_character() {
+        let mut spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Lights
+  version: 1.0.0
+  description: Lights 💡 everywhere
+channels:
+  lights:
+    address: lights
+    description: Short.
+    messages: {}
+"#,
+        );
+
+        let truncated = truncate_descriptions(&mut spec, 9, TruncateStrategy::HardCut).unwrap();
+
+        assert_eq!(spec.info.description.as_deref(), Some("Lights"));
+        assert_eq!(
+            truncated,
+            vec![TruncatedDescription {
+                pointer: JsonPointer::from_iter(["info", "description"]),
+                original_len: 22,
+            }]
+        );
+        assert_eq!(
+            truncate("Lights 💡 everywhere", 13, TruncateStrategy::Ellipsis).as_deref(),
+            Some("Lights…")
+        );
+        assert_eq!(
+            truncate("Lights 💡 everywhere", 14, TruncateStrategy::Ellipsis).as_deref(),
+            Some("Lights 💡…")
+        );
+    }
+
+    #[test]
+    fn truncate_closes_code_fences() {
+        let description = "Sends orders. Example:\n```json\n{\"id\": 1, \"items\": []}\n```";
+
+        let hard_cut = truncate(description, 40, TruncateStrategy::HardCut).unwrap();
+        let sentence = truncate(description, 40, TruncateStrategy::SentenceBoundary).unwrap();
+
+        assert_eq!(hard_cut, "Sends orders. Example:\n```json\n{\"id\"\n```");
+        assert!(hard_cut.len() <= 40);
+        assert_eq!(sentence, "Sends orders.");
+    }
+}
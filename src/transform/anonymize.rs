@@ -0,0 +1,306 @@
+//! Scrubbing of documents before sharing them outside of the organization.
+use super::DATA_FIELDS;
+use crate::error::Error;
+use crate::spec::common::JsonPointer;
+use crate::spec::AsyncApiV3Spec;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use regex::{Captures, Regex};
+use serde_json::Value;
+
+/// The parts of a document [anonymize] applies replacements to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum AnonymizeCategory {
+    /// The `host` of the servers.
+    Host,
+    /// The `description` and `summary` fields.
+    Description,
+    /// The strings inside `example` and `examples` fields, e.g. the message examples.
+    Example,
+}
+
+/// Replacements and removals applied by [anonymize].
+#[derive(Clone, Debug, Default)]
+pub struct AnonymizeRules {
+    replacements: Vec<(Vec<AnonymizeCategory>, Regex, String)>,
+    strip_extensions: Vec<String>,
+    pseudonymize: bool,
+}
+
+impl AnonymizeRules {
+    /// Creates rules leaving documents unchanged, to be completed with the builder methods.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the matches of a pattern in the given categories. The replacement can refer to
+    /// capture groups, e.g. `$1`, except when [pseudonymizing](Self::pseudonymize).
+    pub fn replace(
+        mut self,
+        categories: &[AnonymizeCategory],
+        pattern: Regex,
+        replacement: impl Into<String>,
+    ) -> Self {
+        self.replacements
+            .push((categories.to_vec(), pattern, replacement.into()));
+        self
+    }
+
+    /// Removes the specification extension with the given key, e.g. `x-owner`, wherever it
+    /// appears.
+    pub fn strip_extension(mut self, key: impl Into<String>) -> Self {
+        self.strip_extensions.push(key.into());
+        self
+    }
+
+    /// Replaces every distinct match of a pattern with a placeholder made of the replacement
+    /// and a number, e.g. `host-1`, `host-2`. The same text gets the same placeholder
+    /// everywhere in the document, so references to it in prose stay consistent.
+    pub fn pseudonymize(mut self, enabled: bool) -> Self {
+        self.pseudonymize = enabled;
+        self
+    }
+}
+
+/// What [anonymize] changed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AnonymizeReport {
+    /// Number of matches replaced, by category.
+    pub replacements: BTreeMap<AnonymizeCategory, usize>,
+    /// Number of specification extensions removed.
+    pub stripped_extensions: usize,
+}
+
+/// Applies replacements to the server hosts, the descriptions and the examples of a document,
+/// and removes specification extensions, according to the rules.
+///
+/// Pseudonymization numbers the distinct matches in document order, so the same document and
+/// rules always give the same placeholders. Fails with [Error::InvalidDocument] if the document
+/// cannot be serialized or the anonymized document cannot be read back, in which case the
+/// document is left untouched.
+pub fn anonymize(
+    spec: &mut AsyncApiV3Spec,
+    rules: &AnonymizeRules,
+) -> Result<AnonymizeReport, Error> {
+    let invalid_document = |err: serde_json::Error| Error::InvalidDocument {
+        reason: err.to_string(),
+        hints: Vec::new(),
+    };
+    let mut document = serde_json::to_value(&*spec).map_err(invalid_document)?;
+    let mut anonymizer = Anonymizer {
+        rules,
+        placeholders: BTreeMap::new(),
+        counters: vec![0; rules.replacements.len()],
+        report: AnonymizeReport::default(),
+    };
+    anonymizer.visit(&mut document, &JsonPointer::default(), false);
+    *spec = serde_json::from_value(document).map_err(invalid_document)?;
+    Ok(anonymizer.report)
+}
+
+struct Anonymizer<'a> {
+    rules: &'a AnonymizeRules,
+    /// Placeholders keyed by replacement index and replaced text.
+    placeholders: BTreeMap<(usize, String), String>,
+    /// Number of placeholders handed out by each replacement.
+    counters: Vec<usize>,
+    report: AnonymizeReport,
+}
+
+impl Anonymizer<'_> {
+    fn visit(&mut self, value: &mut Value, pointer: &JsonPointer, in_example: bool) {
+        match value {
+            Value::Object(fields) => {
+                if !in_example {
+                    let before = fields.len();
+                    fields.retain(|key, _| !self.rules.strip_extensions.contains(key));
+                    self.report.stripped_extensions += before - fields.len();
+                }
+                for (key, value) in fields.iter_mut() {
+                    let field = pointer.join(key.as_str());
+                    let example = in_example || key == "example" || key == "examples";
+                    if !example && (DATA_FIELDS.contains(&key.as_str()) || key.starts_with("x-")) {
+                        continue;
+                    }
+                    match value {
+                        Value::String(host) if !example && is_host(&field) => {
+                            self.replace(host, AnonymizeCategory::Host)
+                        }
+                        Value::String(text)
+                            if !example && (key == "description" || key == "summary") =>
+                        {
+                            self.replace(text, AnonymizeCategory::Description)
+                        }
+                        _ => self.visit(value, &field, example),
+                    }
+                }
+            }
+            Value::Array(items) => {
+                for (index, item) in items.iter_mut().enumerate() {
+                    self.visit(item, &pointer.join(index.to_string()), in_example);
+                }
+            }
+            Value::String(text) if in_example => self.replace(text, AnonymizeCategory::Example),
+            _ => {}
+        }
+    }
+
+    fn replace(&mut self, text: &mut String, category: AnonymizeCategory) {
+        let replacements = self.rules.replacements.iter().enumerate();
+        for (index, (categories, pattern, replacement)) in replacements {
+            if !categories.contains(&category) {
+                continue;
+            }
+            let mut count = 0;
+            let replaced = if self.rules.pseudonymize {
+                let placeholders = &mut self.placeholders;
+                let counter = &mut self.counters[index];
+                pattern.replace_all(text, |captures: &Captures| {
+                    count += 1;
+                    placeholders
+                        .entry((index, String::from(&captures[0])))
+                        .or_insert_with(|| {
+                            *counter += 1;
+                            format!("{replacement}-{counter}")
+                        })
+                        .clone()
+                })
+            } else {
+                count = pattern.find_iter(text).count();
+                pattern.replace_all(text, replacement.as_str())
+            };
+            if count > 0 {
+                *text = replaced.into_owned();
+                *self.report.replacements.entry(category).or_default() += count;
+            }
+        }
+    }
+}
+
+fn is_host(pointer: &JsonPointer) -> bool {
+    match pointer.segments() {
+        [servers, _, host] => servers == "servers" && host == "host",
+        [components, servers, _, host] => {
+            components == "components" && servers == "servers" && host == "host"
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[cfg(feature = "validation")]
+    use crate::spec::common::Either;
+    use crate::test_util::parse_spec;
+    #[cfg(feature = "validation")]
+    use crate::validation::{validate, Severity};
+
+    const SPEC: &str = r#"
+asyncapi: 3.0.0
+info:
+  title: Orders
+  version: 1.0.0
+  description: Owned by the Platform Team, reach kafka.internal.acme.com for support.
+servers:
+  kafka:
+    host: kafka.internal.acme.com:9092
+    protocol: kafka
+    description: Primary broker, mirrored by mqtt.internal.acme.com.
+  mqtt:
+    host: mqtt.internal.acme.com
+    protocol: mqtt
+channels:
+  orders:
+    address: orders
+    messages:
+      orderPlaced:
+        payload:
+          type: object
+        examples:
+          - payload:
+              callback: https://kafka.internal.acme.com/orders
+operations:
+  placeOrder:
+    action: send
+    channel:
+      $ref: '#/channels/orders'
+    x-owner: platform-team
+"#;
+
+    fn rules() -> AnonymizeRules {
+        AnonymizeRules::new()
+            .replace(
+                &[
+                    AnonymizeCategory::Host,
+                    AnonymizeCategory::Description,
+                    AnonymizeCategory::Example,
+                ],
+                Regex::new(r"[a-z0-9-]+\.internal\.acme\.com").unwrap(),
+                "host",
+            )
+            .replace(
+                &[AnonymizeCategory::Description],
+                Regex::new(r"(?i)platform team").unwrap(),
+                "team",
+            )
+            .strip_extension("x-owner")
+            .pseudonymize(true)
+    }
+
+    #[test]
+    #[cfg(feature = "validation")]
+    fn replace_hosts_in_servers_and_descriptions() {
+        let mut spec = parse_spec(SPEC);
+
+        let report = anonymize(&mut spec, &rules()).unwrap();
+
+        let host = |name: &str| match &spec.servers[name] {
+            Either::Right(server) => server.host.clone(),
+            Either::Left(_) => panic!("inline server"),
+        };
+        let description = |name: &str| match &spec.servers[name] {
+            Either::Right(server) => server.description.clone().unwrap(),
+            Either::Left(_) => panic!("inline server"),
+        };
+        assert_eq!(
+            spec.info.description.as_deref(),
+            Some("Owned by the team-1, reach host-1 for support.")
+        );
+        assert_eq!(host("kafka"), "host-1:9092");
+        assert_eq!(host("mqtt"), "host-2");
+        assert_eq!(description("kafka"), "Primary broker, mirrored by host-2.");
+        assert_eq!(
+            report.replacements,
+            BTreeMap::from([
+                (AnonymizeCategory::Host, 2),
+                (AnonymizeCategory::Description, 3),
+                (AnonymizeCategory::Example, 1),
+            ])
+        );
+        assert_eq!(report.stripped_extensions, 1);
+        let document = serde_json::to_string(&spec).unwrap();
+        assert!(!document.contains("acme") && !document.contains("x-owner"));
+        assert!(validate(&spec)
+            .iter()
+            .all(|issue| issue.severity != Severity::Error));
+    }
+
+    #[test]
+    fn pseudonymization_is_deterministic() {
+        let mut first = parse_spec(SPEC);
+        let mut second = parse_spec(SPEC);
+
+        anonymize(&mut first, &rules()).unwrap();
+        anonymize(&mut second, &rules()).unwrap();
+
+        assert_eq!(first, second);
+        let example = serde_json::to_value(&first.channels["orders"]).unwrap();
+        assert_eq!(
+            example["messages"]["orderPlaced"]["examples"][0]["payload"]["callback"],
+            "https://host-1/orders"
+        );
+    }
+}
@@ -0,0 +1,1975 @@
+//! Validation of documents against the rules of the specification and good practices.
+//!
+//! The validator executes the rules of a single table, which [rule_catalogue] exports as data,
+//! e.g. to document the rules or to configure them from other languages.
+//!
+//! House rules are added through [CustomRule]s, run by [validate_with] after the built-in
+//! rules:
+//!
+//! ```
+//! # use asyncapiv3::spec::common::JsonPointer;
+//! # use asyncapiv3::spec::{AsyncApiSpec, AsyncApiV3Spec};
+//! # use asyncapiv3::validation::*;
+//! /// Channel addresses must be kebab-case.
+//! struct KebabCaseAddresses;
+//!
+//! impl CustomRule for KebabCaseAddresses {
+//!     fn code(&self) -> &str {
+//!         "kebab-case-address"
+//!     }
+//!
+//!     fn check(&self, spec: &AsyncApiV3Spec, _: &RuleContext) -> Vec<ValidationIssue> {
+//!         let kebab_case = |address: &str| {
+//!             address
+//!                 .split('-')
+//!                 .all(|word| !word.is_empty() && word.bytes().all(|c| c.is_ascii_lowercase()))
+//!         };
+//!         spec.channels
+//!             .iter()
+//!             .filter_map(|(name, channel)| Some((name, channel.as_item()?.address.as_deref()?)))
+//!             .filter(|(_, address)| !kebab_case(address))
+//!             .map(|(name, address)| ValidationIssue {
+//!                 code: String::from(self.code()),
+//!                 severity: Severity::Warning,
+//!                 pointer: JsonPointer::from_iter(["channels", name.as_str(), "address"]),
+//!                 message: format!("address `{address}` is not kebab-case"),
+//!             })
+//!             .collect()
+//!     }
+//! }
+//!
+//! let AsyncApiSpec::V3_0_0(spec) = r#"{
+//!     "asyncapi": "3.0.0",
+//!     "info": { "title": "Lights", "version": "1.0.0" },
+//!     "channels": {
+//!         "lightsOn": { "address": "lights-on", "messages": {} },
+//!         "lightsOff": { "address": "lightsOff", "messages": {} }
+//!     }
+//! }"#
+//! .parse()
+//! .unwrap();
+//! let options = ValidateOptions::new()
+//!     .with_rule(Box::new(KebabCaseAddresses))
+//!     .unwrap();
+//!
+//! let issues = validate_with(&spec, &options).unwrap();
+//!
+//! assert_eq!(
+//!     issues.last().unwrap().to_string(),
+//!     "warning[kebab-case-address] /channels/lightsOff/address: address `lightsOff` is not kebab-case"
+//! );
+//! ```
+use crate::error::Error;
+use crate::spec::channel::Channel;
+use crate::spec::common::{
+    collect_references, local_pointer, Either, JsonPointer, RefOr, ReferenceObject,
+};
+use crate::spec::expression::{MessageSource, RuntimeExpression};
+use crate::spec::index::DocumentIndex;
+use crate::spec::message::{Message, MultiFormatSchema};
+use crate::spec::operation::OperationTrait;
+use crate::spec::schema::{json_schema, SchemaValidator};
+use crate::spec::security::SecurityScheme;
+use crate::spec::serialization::{HintProvenance, MediaType};
+use crate::spec::uid::UID_EXTENSION;
+use crate::spec::AsyncApiV3Spec;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use serde_json::{Map, Value};
+
+/// How serious a [ValidationIssue] is.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum Severity {
+    /// The document violates the specification.
+    Error,
+    /// The document is valid but likely incorrect or incomplete.
+    Warning,
+}
+
+/// An option changing the behaviour of a rule.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleOption {
+    pub name: &'static str,
+    pub summary: &'static str,
+}
+
+/// Description of a validation rule.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleInfo {
+    /// Identifier of the rule, reported by its issues.
+    pub code: &'static str,
+    /// What the rule checks.
+    pub summary: &'static str,
+    /// Severity of the issues reported by the rule.
+    pub default_severity: Severity,
+    /// Options configuring the rule.
+    pub options: &'static [RuleOption],
+    /// Version of the crate which introduced the rule.
+    pub since: &'static str,
+}
+
+/// A problem found in a document by a validation rule.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationIssue {
+    /// Code of the rule reporting the issue.
+    pub code: String,
+    pub severity: Severity,
+    /// Location of the issue in the document.
+    pub pointer: JsonPointer,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(
+            f,
+            "{severity}[{}] {}: {}",
+            self.code, self.pointer, self.message
+        )
+    }
+}
+
+type Check = fn(&AsyncApiV3Spec, &mut Vec<(JsonPointer, String)>);
+
+struct Rule {
+    info: RuleInfo,
+    check: Check,
+}
+
+/// The rules executed by [validate].
+const RULES: &[Rule] = &[
+    Rule {
+        info: RuleInfo {
+            code: "unresolved-reference",
+            summary: "Local references must point at an existing object.",
+            default_severity: Severity::Error,
+            options: &[],
+            since: "0.2.0",
+        },
+        check: unresolved_references,
+    },
+    Rule {
+        info: RuleInfo {
+            code: "operation-channel",
+            summary: "Root operations must reference a channel of the root channels.",
+            default_severity: Severity::Error,
+            options: &[],
+            since: "0.2.0",
+        },
+        check: operation_channels,
+    },
+    Rule {
+        info: RuleInfo {
+            code: "operation-messages",
+            summary: "The messages of a root operation must be messages of its channel.",
+            default_severity: Severity::Error,
+            options: &[],
+            since: "0.2.0",
+        },
+        check: operation_messages,
+    },
+    Rule {
+        info: RuleInfo {
+            code: "oauth2-flows",
+            summary: "OAuth2 security schemes must declare at least one flow.",
+            default_severity: Severity::Error,
+            options: &[],
+            since: "0.2.0",
+        },
+        check: empty_oauth2_flows,
+    },
+    Rule {
+        info: RuleInfo {
+            code: "example-enum",
+            summary: "The examples of server variables and channel parameters declaring an enum must be values of the enum.",
+            default_severity: Severity::Error,
+            options: &[],
+            since: "0.2.0",
+        },
+        check: examples_outside_enum,
+    },
+    Rule {
+        info: RuleInfo {
+            code: "duplicate-uid",
+            summary: "Stable identifiers (`x-uid`) of channels, operations and messages must be unique.",
+            default_severity: Severity::Error,
+            options: &[],
+            since: "0.2.0",
+        },
+        check: duplicate_uids,
+    },
+    Rule {
+        info: RuleInfo {
+            code: "duplicate-tag",
+            summary: "The tags of the application must have distinct names.",
+            default_severity: Severity::Error,
+            options: &[],
+            since: "0.2.0",
+        },
+        check: duplicate_info_tags,
+    },
+    Rule {
+        info: RuleInfo {
+            code: "missing-description",
+            summary:
+                "Servers, channels, operations and channel messages should have a description.",
+            default_severity: Severity::Warning,
+            options: &[],
+            since: "0.2.0",
+        },
+        check: missing_descriptions,
+    },
+    Rule {
+        info: RuleInfo {
+            code: "example-shape",
+            summary: "The examples of channel parameters should not contain `/` and the examples of host variables should not contain whitespace.",
+            default_severity: Severity::Warning,
+            options: &[],
+            since: "0.2.0",
+        },
+        check: misshapen_examples,
+    },
+    Rule {
+        info: RuleInfo {
+            code: "example-default",
+            summary: "The examples of server variables and channel parameters should not repeat the default.",
+            default_severity: Severity::Warning,
+            options: &[],
+            since: "0.2.0",
+        },
+        check: examples_repeating_default,
+    },
+    Rule {
+        info: RuleInfo {
+            code: "deprecated-reference",
+            summary: "Operations, channels and messages which are not deprecated should not use deprecated channels, messages or schemas.",
+            default_severity: Severity::Warning,
+            options: &[],
+            since: "0.2.0",
+        },
+        check: deprecated_references,
+    },
+    Rule {
+        info: RuleInfo {
+            code: "serialization-conflict",
+            summary: "The content type of a message should tell the serialization of its payload schema format.",
+            default_severity: Severity::Warning,
+            options: &[],
+            since: "0.2.0",
+        },
+        check: serialization_conflicts,
+    },
+    Rule {
+        info: RuleInfo {
+            code: "message-example",
+            summary: "The headers of message examples should match the headers schema and hold the correlation id of the message.",
+            default_severity: Severity::Warning,
+            options: &[],
+            since: "0.2.0",
+        },
+        check: inconsistent_message_examples,
+    },
+    Rule {
+        info: RuleInfo {
+            code: "reply-security",
+            summary: "The servers of a reply channel should not require a kind of security scheme the request side does not use.",
+            default_severity: Severity::Warning,
+            options: &[],
+            since: "0.2.0",
+        },
+        check: reply_security_gaps,
+    },
+    Rule {
+        info: RuleInfo {
+            code: "content-type",
+            summary: "Content types must be valid media types.",
+            default_severity: Severity::Error,
+            options: &[],
+            since: "0.2.0",
+        },
+        check: malformed_content_types,
+    },
+];
+
+/// Returns the description of every rule executed by [validate].
+pub fn rule_catalogue() -> Vec<RuleInfo> {
+    RULES.iter().map(|rule| rule.info).collect()
+}
+
+/// A validation rule defined outside of the crate, e.g. a house rule of an organisation.
+pub trait CustomRule {
+    /// Identifier of the rule, which must differ from the codes of the built-in rules.
+    fn code(&self) -> &str;
+
+    /// Returns the issues found in a document.
+    fn check(&self, spec: &AsyncApiV3Spec, context: &RuleContext) -> Vec<ValidationIssue>;
+}
+
+/// What custom rules share instead of building it each: the document as JSON, its
+/// [DocumentIndex] and its local references.
+pub struct RuleContext<'a> {
+    document: Value,
+    index: DocumentIndex<'a>,
+    references: BTreeMap<JsonPointer, JsonPointer>,
+}
+
+impl<'a> RuleContext<'a> {
+    fn new(spec: &'a AsyncApiV3Spec) -> Result<Self, Error> {
+        let document = serde_json::to_value(spec).map_err(|err| Error::InvalidDocument {
+            reason: err.to_string(),
+            hints: Vec::new(),
+        })?;
+        let references = collect_references(&document, &JsonPointer::default())
+            .into_iter()
+            .filter_map(|(pointer, reference)| Some((pointer, local_pointer(reference)?)))
+            .collect();
+        Ok(Self {
+            document,
+            index: DocumentIndex::build(spec),
+            references,
+        })
+    }
+
+    /// Returns the document as JSON, e.g. to evaluate pointers.
+    pub fn document(&self) -> &Value {
+        &self.document
+    }
+
+    /// Returns the index of the document, e.g. to look up the operations of a channel.
+    pub fn index(&self) -> &DocumentIndex<'a> {
+        &self.index
+    }
+
+    /// Returns the targets of the local references, by the location of each reference, e.g.
+    /// `/components/channels/dimmed` for `/operations/turnOn/channel`.
+    pub fn references(&self) -> &BTreeMap<JsonPointer, JsonPointer> {
+        &self.references
+    }
+}
+
+/// Options of [validate_with].
+#[derive(Default)]
+pub struct ValidateOptions {
+    rules: Vec<Box<dyn CustomRule>>,
+}
+
+impl ValidateOptions {
+    /// Creates options without custom rules, validating like [validate].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a custom rule, run after the built-in rules and the custom rules added before it.
+    ///
+    /// Fails with [Error::DuplicateRule] if the code of the rule is the code of a built-in rule
+    /// or of a custom rule already added.
+    pub fn with_rule(mut self, rule: Box<dyn CustomRule>) -> Result<Self, Error> {
+        let code = rule.code();
+        if RULES.iter().any(|built_in| built_in.info.code == code)
+            || self.rules.iter().any(|custom| custom.code() == code)
+        {
+            return Err(Error::DuplicateRule {
+                code: String::from(code),
+            });
+        }
+        self.rules.push(rule);
+        Ok(self)
+    }
+}
+
+/// Validates a document, returning the issues found by every rule, rule by rule.
+pub fn validate(spec: &AsyncApiV3Spec) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    for rule in RULES {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("rule", code = rule.info.code).entered();
+        let mut findings = Vec::new();
+        (rule.check)(spec, &mut findings);
+        issues.extend(
+            findings
+                .into_iter()
+                .map(|(pointer, message)| ValidationIssue {
+                    code: String::from(rule.info.code),
+                    severity: rule.info.default_severity,
+                    pointer,
+                    message,
+                }),
+        );
+    }
+    issues
+}
+
+/// Validates a document like [validate], then with the custom rules of the options.
+///
+/// Fails with [Error::Parse] if the document cannot be converted to JSON for the custom rules.
+pub fn validate_with(
+    spec: &AsyncApiV3Spec,
+    options: &ValidateOptions,
+) -> Result<Vec<ValidationIssue>, Error> {
+    let mut issues = validate(spec);
+    if options.rules.is_empty() {
+        return Ok(issues);
+    }
+    let context = RuleContext::new(spec)?;
+    for rule in &options.rules {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("rule", code = rule.code()).entered();
+        issues.extend(rule.check(spec, &context));
+    }
+    Ok(issues)
+}
+
+/// Validates the object at a JSON pointer, e.g. `/servers/production`, returning the issues
+/// located inside it. Meant for editors giving feedback on every change.
+///
+/// The rules run over a document reduced to the entry holding the object, the entries its
+/// local references point at and the root fields such as `info`, rather than over the whole
+/// document. Pointers which do not point at a value have no issue.
+pub fn validate_node(spec: &AsyncApiV3Spec, pointer: &str) -> Vec<ValidationIssue> {
+    let Ok(node) = JsonPointer::parse(pointer) else {
+        return Vec::new();
+    };
+    let Ok(document) = serde_json::to_value(spec) else {
+        return Vec::new();
+    };
+    let (Some(value), Value::Object(root)) = (node.evaluate(&document), &document) else {
+        return Vec::new();
+    };
+    if node.segments().is_empty() {
+        return validate(spec);
+    }
+
+    let mut entries = vec![enclosing_entry(&node)];
+    entries.extend(
+        collect_references(value, &JsonPointer::default())
+            .into_iter()
+            .filter_map(|(_, reference)| local_pointer(reference))
+            .map(|target| enclosing_entry(&target)),
+    );
+    let mut reduced = root
+        .iter()
+        .filter(|(key, _)| !SECTIONS.contains(&key.as_str()))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect::<Map<_, _>>();
+    for entry in entries {
+        let Some(value) = entry.evaluate(&document) else {
+            continue;
+        };
+        let Some((key, parents)) = entry.segments().split_last() else {
+            continue;
+        };
+        let mut parent = &mut reduced;
+        for segment in parents {
+            let Value::Object(child) = parent
+                .entry(segment.clone())
+                .or_insert_with(|| Value::Object(Map::new()))
+            else {
+                unreachable!("the sections are objects");
+            };
+            parent = child;
+        }
+        parent.insert(key.clone(), value.clone());
+    }
+
+    let issues = match serde_json::from_value(Value::Object(reduced)) {
+        Ok(reduced) => validate(&reduced),
+        Err(_) => validate(spec),
+    };
+    issues
+        .into_iter()
+        .filter(|issue| issue.pointer.starts_with(&node))
+        .collect()
+}
+
+/// Sections of the document whose entries are kept by [validate_node] only when needed.
+const SECTIONS: [&str; 4] = ["servers", "channels", "operations", "components"];
+
+/// Returns the entry of a section of the document holding the value at a pointer, e.g.
+/// `/components/messages/orderPlaced` for `/components/messages/orderPlaced/payload`, or the
+/// root field holding it outside of the sections.
+fn enclosing_entry(pointer: &JsonPointer) -> JsonPointer {
+    let depth = match pointer.segments().first().map(String::as_str) {
+        Some("components") => 3,
+        Some(section) if SECTIONS.contains(&section) => 2,
+        _ => 1,
+    };
+    pointer.segments().iter().take(depth).cloned().collect()
+}
+
+fn unresolved_references(spec: &AsyncApiV3Spec, findings: &mut Vec<(JsonPointer, String)>) {
+    let Ok(document) = serde_json::to_value(spec) else {
+        return;
+    };
+    for (pointer, reference) in collect_references(&document, &JsonPointer::default()) {
+        let resolved = !reference.starts_with('#')
+            || local_pointer(reference)
+                .and_then(|target| target.evaluate(&document))
+                .is_some();
+        if !resolved {
+            findings.push((pointer, format!("reference `{reference}` does not resolve")));
+        }
+    }
+}
+
+/// Returns the name of the root channel a reference points at.
+fn root_channel(reference: &ReferenceObject) -> Option<String> {
+    match reference.local_pointer()?.segments() {
+        [channels, name] if channels == "channels" => Some(name.clone()),
+        _ => None,
+    }
+}
+
+fn operation_channels(spec: &AsyncApiV3Spec, findings: &mut Vec<(JsonPointer, String)>) {
+    for (name, operation) in &spec.operations {
+        let RefOr::Right(operation) = operation else {
+            continue;
+        };
+        let is_root = root_channel(&operation.channel)
+            .is_some_and(|channel| spec.channels.contains_key(&channel));
+        if !is_root {
+            findings.push((
+                JsonPointer::from_iter(["operations", name.as_str(), "channel"]),
+                format!(
+                    "channel `{}` is not a channel of the root channels",
+                    operation.channel.reference
+                ),
+            ));
+        }
+    }
+}
+
+fn operation_messages(spec: &AsyncApiV3Spec, findings: &mut Vec<(JsonPointer, String)>) {
+    for (name, operation) in &spec.operations {
+        let RefOr::Right(operation) = operation else {
+            continue;
+        };
+        let Some(channel) = root_channel(&operation.channel) else {
+            continue;
+        };
+        for (index, message) in operation.messages.iter().flatten().enumerate() {
+            let in_channel = message.local_pointer().is_some_and(|pointer| {
+                matches!(
+                    pointer.segments(),
+                    [channels, c, messages, _]
+                        if channels == "channels" && *c == channel && messages == "messages"
+                )
+            });
+            if !in_channel {
+                findings.push((
+                    JsonPointer::from_iter([
+                        String::from("operations"),
+                        name.clone(),
+                        String::from("messages"),
+                        format!("{index}"),
+                    ]),
+                    format!(
+                        "message `{}` is not a message of the channel `{channel}`",
+                        message.reference
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+fn empty_oauth2_flows(spec: &AsyncApiV3Spec, findings: &mut Vec<(JsonPointer, String)>) {
+    let root = JsonPointer::default();
+    let components = root.join("components");
+    let mut schemes = spec
+        .components
+        .security_schemes
+        .iter()
+        .map(|(name, scheme)| {
+            (
+                components.join("securitySchemes").join(name.as_str()),
+                scheme,
+            )
+        })
+        .collect::<Vec<_>>();
+    for (section, servers) in [
+        (&root, &spec.servers),
+        (&components, &spec.components.servers),
+    ] {
+        let section = section.join("servers");
+        for (name, server) in servers {
+            if let RefOr::Right(server) = server {
+                schemes.extend(listed_schemes(
+                    section.join(name.as_str()),
+                    &server.security,
+                ));
+            }
+        }
+    }
+    for (section, operations) in [
+        (&root, &spec.operations),
+        (&components, &spec.components.operations),
+    ] {
+        let section = section.join("operations");
+        for (name, operation) in operations {
+            let RefOr::Right(operation) = operation else {
+                continue;
+            };
+            let pointer = section.join(name.as_str());
+            schemes.extend(listed_schemes(pointer.clone(), &operation.security));
+            for (index, operation_trait) in operation.traits.iter().enumerate() {
+                if let RefOr::Right(OperationTrait {
+                    security: Some(scheme),
+                    ..
+                }) = operation_trait
+                {
+                    let pointer = pointer.join("traits").join(format!("{index}"));
+                    schemes.push((pointer.join("security"), scheme));
+                }
+            }
+        }
+    }
+    let operation_traits = components.join("operationTraits");
+    for (name, operation_trait) in &spec.components.operation_traits {
+        if let RefOr::Right(OperationTrait {
+            security: Some(scheme),
+            ..
+        }) = operation_trait
+        {
+            schemes.push((
+                operation_traits.join(name.as_str()).join("security"),
+                scheme,
+            ));
+        }
+    }
+    for (pointer, scheme) in schemes {
+        if let RefOr::Right(SecurityScheme::Oauth2(scheme)) = scheme {
+            if scheme.flows.iter().next().is_none() {
+                findings.push((
+                    pointer.join("flows"),
+                    String::from("no OAuth2 flow is declared"),
+                ));
+            }
+        }
+    }
+}
+
+/// Locates the security schemes listed by the server or operation at `pointer`.
+fn listed_schemes(
+    pointer: JsonPointer,
+    security: &[RefOr<SecurityScheme>],
+) -> impl Iterator<Item = (JsonPointer, &RefOr<SecurityScheme>)> {
+    let pointer = pointer.join("security");
+    security
+        .iter()
+        .enumerate()
+        .map(move |(index, scheme)| (pointer.join(format!("{index}")), scheme))
+}
+
+/// A server variable or a channel parameter, substituted in a server URL or a channel address.
+struct Substitution<'a> {
+    /// Location of the variable or the parameter.
+    pointer: JsonPointer,
+    /// What is substituted, e.g. "variable `port` of the server `production`".
+    label: String,
+    enum_values: Option<&'a [String]>,
+    default: Option<&'a str>,
+    examples: &'a [String],
+    /// Where the values are substituted, if it constrains them.
+    usage: Option<Usage>,
+}
+
+#[derive(Clone, Copy)]
+enum Usage {
+    Host,
+    AddressSegment,
+}
+
+impl Usage {
+    /// Returns `true` if a value substituted at this place cannot contain the character.
+    fn forbids(self, c: char) -> bool {
+        match self {
+            Self::Host => c.is_whitespace(),
+            Self::AddressSegment => c == '/',
+        }
+    }
+}
+
+impl fmt::Display for Usage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Host => "host",
+            Self::AddressSegment => "address segment",
+        })
+    }
+}
+
+/// Lists the server variables and channel parameters defined in the root sections and the
+/// components, following references to the variables and parameters.
+fn substitutions(spec: &AsyncApiV3Spec) -> Vec<Substitution<'_>> {
+    let mut substitutions = Vec::new();
+    let root = JsonPointer::default();
+    let components = root.join("components");
+    for (section, servers) in [
+        (&root, &spec.servers),
+        (&components, &spec.components.servers),
+    ] {
+        for (name, server) in servers {
+            let RefOr::Right(server) = server else {
+                continue;
+            };
+            let pointer = section.join("servers").join(name.as_str());
+            for (variable_name, variable) in &server.variables {
+                let Ok(variable) = variable.resolve(spec) else {
+                    continue;
+                };
+                let in_host = server.host.contains(&format!("{{{variable_name}}}"));
+                substitutions.push(Substitution {
+                    pointer: pointer.join("variables").join(variable_name.as_str()),
+                    label: format!("variable `{variable_name}` of the server `{name}`"),
+                    enum_values: variable.enum_values.as_deref(),
+                    default: variable.default.as_deref(),
+                    examples: &variable.examples,
+                    usage: in_host.then_some(Usage::Host),
+                });
+            }
+        }
+    }
+    for (section, channels) in [
+        (&root, &spec.channels),
+        (&components, &spec.components.channels),
+    ] {
+        for (name, channel) in channels {
+            let RefOr::Right(channel) = channel else {
+                continue;
+            };
+            let pointer = section.join("channels").join(name.as_str());
+            for (parameter_name, parameter) in &channel.parameters {
+                let Ok(parameter) = parameter.resolve(spec) else {
+                    continue;
+                };
+                let in_address = channel
+                    .address
+                    .as_deref()
+                    .is_some_and(|address| address.contains(&format!("{{{parameter_name}}}")));
+                substitutions.push(Substitution {
+                    pointer: pointer.join("parameters").join(parameter_name.as_str()),
+                    label: format!("parameter `{parameter_name}` of the channel `{name}`"),
+                    enum_values: parameter.enum_values.as_deref(),
+                    default: parameter.default.as_deref(),
+                    examples: &parameter.examples,
+                    usage: in_address.then_some(Usage::AddressSegment),
+                });
+            }
+        }
+    }
+    substitutions
+}
+
+fn examples_outside_enum(spec: &AsyncApiV3Spec, findings: &mut Vec<(JsonPointer, String)>) {
+    for substitution in substitutions(spec) {
+        let Some(enum_values) = substitution.enum_values else {
+            continue;
+        };
+        for (index, example) in substitution.examples.iter().enumerate() {
+            if !enum_values.contains(example) {
+                findings.push((
+                    substitution
+                        .pointer
+                        .join("examples")
+                        .join(format!("{index}")),
+                    format!(
+                        "example `{example}` of the {} is not a value of its enum",
+                        substitution.label
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+fn misshapen_examples(spec: &AsyncApiV3Spec, findings: &mut Vec<(JsonPointer, String)>) {
+    for substitution in substitutions(spec) {
+        let Some(usage) = substitution.usage else {
+            continue;
+        };
+        for (index, example) in substitution.examples.iter().enumerate() {
+            if example.contains(|c| usage.forbids(c)) {
+                findings.push((
+                    substitution
+                        .pointer
+                        .join("examples")
+                        .join(format!("{index}")),
+                    format!(
+                        "example `{example}` of the {} cannot be substituted in a {usage}",
+                        substitution.label
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+fn examples_repeating_default(spec: &AsyncApiV3Spec, findings: &mut Vec<(JsonPointer, String)>) {
+    for substitution in substitutions(spec) {
+        let Some(default) = substitution.default else {
+            continue;
+        };
+        for (index, example) in substitution.examples.iter().enumerate() {
+            if example == default {
+                findings.push((
+                    substitution
+                        .pointer
+                        .join("examples")
+                        .join(format!("{index}")),
+                    format!("example of the {} repeats its default", substitution.label),
+                ));
+            }
+        }
+    }
+}
+
+fn deprecated_references(spec: &AsyncApiV3Spec, findings: &mut Vec<(JsonPointer, String)>) {
+    let root = JsonPointer::default();
+    let components = root.join("components");
+    for (section, operations) in [
+        (&root, &spec.operations),
+        (&components, &spec.components.operations),
+    ] {
+        let section = section.join("operations");
+        for (name, operation) in operations {
+            let RefOr::Right(operation) = operation else {
+                continue;
+            };
+            if operation.is_deprecated() {
+                continue;
+            }
+            let pointer = section.join(name.as_str());
+            deprecated_channel(spec, &operation.channel, pointer.join("channel"), findings);
+            deprecated_messages(
+                spec,
+                operation.messages.iter().flatten(),
+                &pointer.join("messages"),
+                findings,
+            );
+            if let Some(RefOr::Right(reply)) = &operation.reply {
+                let pointer = pointer.join("reply");
+                if let Some(channel) = &reply.channel {
+                    deprecated_channel(spec, channel, pointer.join("channel"), findings);
+                }
+                deprecated_messages(spec, &reply.messages, &pointer.join("messages"), findings);
+            }
+        }
+    }
+    for (section, channels) in [
+        (&root, &spec.channels),
+        (&components, &spec.components.channels),
+    ] {
+        let section = section.join("channels");
+        for (name, channel) in channels {
+            let RefOr::Right(channel) = channel else {
+                continue;
+            };
+            if channel.is_deprecated() {
+                continue;
+            }
+            let pointer = section.join(name.as_str()).join("messages");
+            for (key, message) in &channel.messages {
+                let RefOr::Left(reference) = message else {
+                    continue;
+                };
+                if reference
+                    .resolve::<Message>(spec)
+                    .is_ok_and(Message::is_deprecated)
+                {
+                    findings.push((
+                        pointer.join(key.as_str()),
+                        format!(
+                            "channel uses the deprecated message `{}`",
+                            reference.reference
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+    for (pointer, message) in spec.defined_messages() {
+        if message.is_deprecated() {
+            continue;
+        }
+        for (field, schema) in [("headers", &message.headers), ("payload", &message.payload)] {
+            let Some(RefOr::Left(reference)) = schema else {
+                continue;
+            };
+            let deprecated = reference
+                .resolve::<Either<schemars::Schema, MultiFormatSchema>>(spec)
+                .is_ok_and(|schema| match schema {
+                    Either::Left(schema) => schema.get("deprecated") == Some(&Value::Bool(true)),
+                    Either::Right(_) => false,
+                });
+            if deprecated {
+                findings.push((
+                    pointer.join(field),
+                    format!(
+                        "message uses the deprecated schema `{}`",
+                        reference.reference
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+/// Reports the channel an operation or its reply uses if it is deprecated.
+fn deprecated_channel(
+    spec: &AsyncApiV3Spec,
+    channel: &ReferenceObject,
+    pointer: JsonPointer,
+    findings: &mut Vec<(JsonPointer, String)>,
+) {
+    if channel
+        .resolve::<Channel>(spec)
+        .is_ok_and(Channel::is_deprecated)
+    {
+        findings.push((
+            pointer,
+            format!(
+                "operation uses the deprecated channel `{}`",
+                channel.reference
+            ),
+        ));
+    }
+}
+
+/// Reports the deprecated messages among those an operation or its reply uses.
+fn deprecated_messages<'a>(
+    spec: &AsyncApiV3Spec,
+    messages: impl IntoIterator<Item = &'a ReferenceObject>,
+    pointer: &JsonPointer,
+    findings: &mut Vec<(JsonPointer, String)>,
+) {
+    for (index, message) in messages.into_iter().enumerate() {
+        if message
+            .resolve::<Message>(spec)
+            .is_ok_and(Message::is_deprecated)
+        {
+            findings.push((
+                pointer.join(format!("{index}")),
+                format!(
+                    "operation uses the deprecated message `{}`",
+                    message.reference
+                ),
+            ));
+        }
+    }
+}
+
+fn serialization_conflicts(spec: &AsyncApiV3Spec, findings: &mut Vec<(JsonPointer, String)>) {
+    for (pointer, message) in spec.defined_messages() {
+        if let HintProvenance::Conflict {
+            content_type,
+            schema_format,
+        } = message.serialization_hint(spec).provenance
+        {
+            findings.push((
+                pointer,
+                format!(
+                    "content type `{content_type}` conflicts with the schema format `{schema_format}` of the payload"
+                ),
+            ));
+        }
+    }
+}
+
+fn inconsistent_message_examples(spec: &AsyncApiV3Spec, findings: &mut Vec<(JsonPointer, String)>) {
+    let validator = SchemaValidator::new(spec);
+    for (pointer, message) in spec.defined_messages() {
+        let headers_schema = message
+            .headers
+            .as_ref()
+            .and_then(|schema| json_schema(schema, spec));
+        let correlation_id = message
+            .correlation_id
+            .as_ref()
+            .and_then(|correlation_id| correlation_id.resolve(spec).ok())
+            .and_then(|correlation_id| correlation_id.location.parse::<RuntimeExpression>().ok());
+        for (index, example) in message.examples.iter().enumerate() {
+            let example_pointer = pointer.join("examples").join(format!("{index}"));
+            let label = match &example.name {
+                Some(name) => format!("example `{name}`"),
+                None => format!("example {index}"),
+            };
+            let headers = Value::Object(example.headers.clone().into_iter().collect());
+            if let (Some(schema), false) = (headers_schema, example.headers.is_empty()) {
+                for violation in validator.validate(schema, &headers) {
+                    findings.push((
+                        violation
+                            .pointer
+                            .segments()
+                            .iter()
+                            .fold(example_pointer.join("headers"), |pointer, segment| {
+                                pointer.join(segment.as_str())
+                            }),
+                        format!(
+                            "headers of the {label} do not match the headers schema at `{}`: {}",
+                            violation.pointer, violation.reason
+                        ),
+                    ));
+                }
+            }
+            let Some(correlation_id) = &correlation_id else {
+                continue;
+            };
+            let mut instance = serde_json::Map::new();
+            instance.insert(String::from("headers"), headers);
+            if let Ok(Some(payload)) = example.resolve_payload(spec) {
+                instance.insert(String::from("payload"), payload.into_owned());
+            }
+            if correlation_id.evaluate(&Value::Object(instance)).is_none() {
+                let source = match correlation_id.source {
+                    MessageSource::Header => "headers",
+                    MessageSource::Payload => "payload",
+                };
+                findings.push((
+                    example_pointer,
+                    format!(
+                        "the {label} has no value at `{}` of its {source}, the location of the correlation id",
+                        correlation_id.pointer
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+fn duplicate_uids(spec: &AsyncApiV3Spec, findings: &mut Vec<(JsonPointer, String)>) {
+    let mut first_uses = BTreeMap::new();
+    for (pointer, extensions) in spec.identifiable_entities() {
+        let Some(uid) = extensions.uid() else {
+            continue;
+        };
+        match first_uses.get(uid) {
+            Some(first) => findings.push((
+                pointer.join(UID_EXTENSION),
+                format!("uid `{uid}` is already used by `{first}`"),
+            )),
+            None => {
+                first_uses.insert(uid, pointer);
+            }
+        }
+    }
+}
+
+fn duplicate_info_tags(spec: &AsyncApiV3Spec, findings: &mut Vec<(JsonPointer, String)>) {
+    let mut names = Vec::new();
+    for (index, tag) in spec.info.tags.iter().enumerate() {
+        let Ok(tag) = tag.resolve(spec) else {
+            continue;
+        };
+        if names.contains(&tag.name.as_str()) {
+            findings.push((
+                JsonPointer::from_iter(["info", "tags"]).join(format!("{index}")),
+                format!("tag `{}` is listed more than once", tag.name),
+            ));
+        } else {
+            names.push(&tag.name);
+        }
+    }
+}
+
+fn missing_descriptions(spec: &AsyncApiV3Spec, findings: &mut Vec<(JsonPointer, String)>) {
+    findings.extend(spec.undocumented().into_iter().filter_map(|pointer| {
+        let pointer = JsonPointer::parse(&pointer).ok()?;
+        Some((pointer, String::from("no description")))
+    }));
+}
+
+fn reply_security_gaps(spec: &AsyncApiV3Spec, findings: &mut Vec<(JsonPointer, String)>) {
+    for (key, operation) in &spec.operations {
+        let Ok(operation) = operation.resolve(spec) else {
+            continue;
+        };
+        let Some(reply) = operation
+            .reply
+            .as_ref()
+            .and_then(|reply| reply.resolve(spec).ok())
+        else {
+            continue;
+        };
+        let request = operation.security_requirements(spec).kinds();
+        let request_side = match request.is_empty() {
+            true => String::from("none"),
+            false => request
+                .iter()
+                .map(|kind| format!("`{kind}`"))
+                .collect::<Vec<_>>()
+                .join(", "),
+        };
+        let pointer = JsonPointer::from_iter(["operations", key.as_str(), "reply", "channel"]);
+        for kind in reply
+            .security_requirements(spec)
+            .kinds()
+            .difference(&request)
+        {
+            findings.push((
+                pointer.clone(),
+                format!(
+                    "the servers of the reply channel require a `{kind}` security scheme, while the request side uses {request_side}"
+                ),
+            ));
+        }
+    }
+}
+
+fn malformed_content_types(spec: &AsyncApiV3Spec, findings: &mut Vec<(JsonPointer, String)>) {
+    let default = spec
+        .default_content_type
+        .as_ref()
+        .map(|content_type| (JsonPointer::from_iter(["defaultContentType"]), content_type));
+    let messages = spec
+        .defined_messages()
+        .into_iter()
+        .filter_map(|(pointer, message)| {
+            Some((pointer.join("contentType"), message.content_type.as_ref()?))
+        });
+    for (pointer, content_type) in default.into_iter().chain(messages) {
+        if let Err(Error::Parse { reason }) = MediaType::parse(content_type) {
+            findings.push((pointer, reason));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::parse_spec;
+    use alloc::collections::BTreeSet;
+
+    const INVALID_SPEC: &str = r#"
+asyncapi: 3.0.0
+info:
+  title: Lights
+  version: 1.0.0
+  tags:
+    - name: lights
+    - name: lights
+servers:
+  production:
+    host: '{region}.example.com'
+    protocol: mqtt
+    description: Production broker.
+    variables:
+      region:
+        enum: [eu, us]
+        default: eu
+        examples: [eu, ap south]
+  replies:
+    host: replies.example.com
+    protocol: mqtt
+    description: Reply broker.
+    security:
+      - type: X509
+channels:
+  lights:
+    address: lights
+    description: Light measurements.
+    x-deprecated: true
+    servers:
+      - $ref: '#/servers/production'
+    messages:
+      lightMeasured:
+        description: A measurement.
+        payload:
+          $ref: '#/components/schemas/missing'
+  lightReplies:
+    address: lights.replies
+    messages: {}
+    description: Replies to light commands.
+    servers:
+      - $ref: '#/servers/replies'
+components:
+  channels:
+    dimmed:
+      address: dimmed
+      messages:
+        dimmed:
+          description: A dimming.
+  messages:
+    lightLogged:
+      description: A logged measurement.
+      contentType: application/json
+      correlationId:
+        location: '$message.header#/correlationId'
+      examples:
+        - name: logged
+          payload: {}
+      payload:
+        schemaFormat: application/vnd.apache.avro;version=1.9.0
+        schema:
+          type: record
+          name: Measurement
+          fields: []
+operations:
+  turnOn:
+    action: send
+    x-uid: lights-switch
+    channel:
+      $ref: '#/components/channels/dimmed'
+    messages:
+      - $ref: '#/components/channels/dimmed/messages/dimmed'
+  turnOff:
+    action: send
+    description: Turns the lights off.
+    x-uid: lights-switch
+    channel:
+      $ref: '#/channels/lights'
+    messages:
+      - $ref: '#/components/channels/dimmed/messages/dimmed'
+    security:
+      - type: oauth2
+        flows: {}
+    reply:
+      channel:
+        $ref: '#/channels/lightReplies'
+"#;
+
+    #[test]
+    fn every_reported_rule_is_catalogued() {
+        let spec = parse_spec(INVALID_SPEC);
+
+        let issues = validate(&spec);
+        // The content types are checked on a fixture of their own.
+        let content_types = validate(&parse_spec(MALFORMED_CONTENT_TYPES));
+
+        let reported = issues
+            .iter()
+            .chain(&content_types)
+            .map(|issue| issue.code.as_str())
+            .collect::<BTreeSet<_>>();
+        let catalogued = rule_catalogue()
+            .into_iter()
+            .map(|rule| rule.code)
+            .collect::<BTreeSet<_>>();
+        assert_eq!(reported, catalogued);
+        assert_eq!(
+            issues
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec![
+                "error[unresolved-reference] /channels/lights/messages/lightMeasured/payload: reference `#/components/schemas/missing` does not resolve",
+                "error[operation-channel] /operations/turnOn/channel: channel `#/components/channels/dimmed` is not a channel of the root channels",
+                "error[operation-messages] /operations/turnOff/messages/0: message `#/components/channels/dimmed/messages/dimmed` is not a message of the channel `lights`",
+                "error[oauth2-flows] /operations/turnOff/security/0/flows: no OAuth2 flow is declared",
+                "error[example-enum] /servers/production/variables/region/examples/1: example `ap south` of the variable `region` of the server `production` is not a value of its enum",
+                "error[duplicate-uid] /operations/turnOn/x-uid: uid `lights-switch` is already used by `/operations/turnOff`",
+                "error[duplicate-tag] /info/tags/1: tag `lights` is listed more than once",
+                "warning[missing-description] /operations/turnOn: no description",
+                "warning[example-shape] /servers/production/variables/region/examples/1: example `ap south` of the variable `region` of the server `production` cannot be substituted in a host",
+                "warning[example-default] /servers/production/variables/region/examples/0: example of the variable `region` of the server `production` repeats its default",
+                "warning[deprecated-reference] /operations/turnOff/channel: operation uses the deprecated channel `#/channels/lights`",
+                "warning[serialization-conflict] /components/messages/lightLogged: content type `application/json` conflicts with the schema format `application/vnd.apache.avro;version=1.9.0` of the payload",
+                "warning[message-example] /components/messages/lightLogged/examples/0: the example `logged` has no value at `/correlationId` of its headers, the location of the correlation id",
+                "warning[reply-security] /operations/turnOff/reply/channel: the servers of the reply channel require a `X509` security scheme, while the request side uses `oauth2`",
+            ]
+        );
+    }
+
+    #[test]
+    fn operation_messages_outside_channel() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Lights
+  version: 1.0.0
+channels:
+  lights:
+    address: lights
+    description: Light measurements.
+    messages:
+      lightMeasured:
+        description: A measurement.
+  dimmed:
+    address: dimmed
+    description: Dimmed lights.
+    messages:
+      dimmed:
+        description: A dimming.
+operations:
+  turnOn:
+    action: send
+    description: Turns the lights on.
+    channel:
+      $ref: '#/channels/lights'
+    messages:
+      - $ref: '#/channels/lights/messages/lightMeasured'
+      - $ref: '#/channels/dimmed/messages/dimmed'
+"#,
+        );
+
+        let issues = validate(&spec);
+
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].code, "operation-messages");
+        assert_eq!(
+            issues[0].pointer.to_string(),
+            "/operations/turnOn/messages/1"
+        );
+    }
+
+    #[test]
+    fn duplicate_info_tag_through_reference() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Lights
+  version: 1.0.0
+  tags:
+    - name: lights
+    - name: sensors
+    - $ref: '#/components/tags/lights'
+components:
+  tags:
+    lights:
+      name: lights
+"#,
+        );
+
+        let issues = validate(&spec);
+
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].code, "duplicate-tag");
+        assert_eq!(issues[0].pointer.to_string(), "/info/tags/2");
+    }
+
+    #[test]
+    fn oauth2_scheme_without_flows() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Lights
+  version: 1.0.0
+components:
+  securitySchemes:
+    empty:
+      type: oauth2
+      flows: {}
+    clientCredentials:
+      type: oauth2
+      flows:
+        clientCredentials:
+          tokenUrl: https://auth.example.com/token
+          availableScopes: {}
+"#,
+        );
+
+        let issues = validate(&spec);
+
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].code, "oauth2-flows");
+        assert_eq!(
+            issues[0].pointer.to_string(),
+            "/components/securitySchemes/empty/flows"
+        );
+    }
+
+    #[test]
+    fn oauth2_schemes_of_components_without_flows() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Lights
+  version: 1.0.0
+components:
+  servers:
+    production:
+      host: lights.example.com
+      protocol: mqtt
+      security:
+        - type: oauth2
+          flows: {}
+  channels:
+    lights:
+      address: lights
+      messages: {}
+  operations:
+    turnOn:
+      action: send
+      channel:
+        $ref: '#/components/channels/lights'
+      security:
+        - type: oauth2
+          flows: {}
+      traits:
+        - security:
+            type: oauth2
+            flows: {}
+  operationTraits:
+    secured:
+      security:
+        type: oauth2
+        flows: {}
+"#,
+        );
+
+        let issues = validate(&spec);
+
+        assert_eq!(
+            issues
+                .iter()
+                .filter(|issue| issue.code == "oauth2-flows")
+                .map(|issue| issue.pointer.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "/components/servers/production/security/0/flows",
+                "/components/operations/turnOn/security/0/flows",
+                "/components/operations/turnOn/traits/0/security/flows",
+                "/components/operationTraits/secured/security/flows",
+            ]
+        );
+    }
+
+    #[test]
+    fn server_variable_examples_within_enum() {
+        let yaml = |examples: &str| {
+            format!(
+                r#"
+asyncapi: 3.0.0
+info:
+  title: Lights
+  version: 1.0.0
+servers:
+  production:
+    host: 'broker.example.com:{{port}}'
+    protocol: mqtt
+    description: Production broker.
+    variables:
+      port:
+        enum: ['1883', '8883']
+        examples: {examples}
+"#
+            )
+        };
+
+        let coherent = validate(&parse_spec(&yaml("['1883', '8883']")));
+        let incoherent = validate(&parse_spec(&yaml("['8883', '443']")));
+
+        assert!(coherent.is_empty(), "{coherent:?}");
+        assert_eq!(incoherent.len(), 1);
+        assert_eq!(incoherent[0].code, "example-enum");
+        assert_eq!(
+            incoherent[0].message,
+            "example `443` of the variable `port` of the server `production` is not a value of its enum"
+        );
+    }
+
+    #[test]
+    fn channel_parameter_examples() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Lights
+  version: 1.0.0
+channels:
+  lights:
+    address: 'lights/{room}/{level}'
+    description: Light measurements.
+    messages: {}
+    parameters:
+      room:
+        examples: [kitchen, hall/upstairs]
+      level:
+        enum: ['0', '1']
+        default: '0'
+        examples: ['1', '2']
+"#,
+        );
+
+        let issues = validate(&spec)
+            .into_iter()
+            .map(|issue| (issue.code, issue.pointer.to_string()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            issues,
+            vec![
+                (
+                    String::from("example-enum"),
+                    String::from("/channels/lights/parameters/level/examples/1")
+                ),
+                (
+                    String::from("example-shape"),
+                    String::from("/channels/lights/parameters/room/examples/1")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn deprecated_message_of_current_operation() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Lights
+  version: 1.0.0
+channels:
+  lights:
+    address: lights
+    description: Light measurements.
+    messages:
+      lightMeasured:
+        $ref: '#/components/messages/lightMeasured'
+operations:
+  measure:
+    action: send
+    description: Measures the lights.
+    channel:
+      $ref: '#/channels/lights'
+    messages:
+      - $ref: '#/channels/lights/messages/lightMeasured'
+  legacyMeasure:
+    action: send
+    description: Measures the lights, the old way.
+    x-deprecated: true
+    channel:
+      $ref: '#/channels/lights'
+    messages:
+      - $ref: '#/channels/lights/messages/lightMeasured'
+components:
+  messages:
+    lightMeasured:
+      description: A measurement.
+      x-deprecated: true
+"#,
+        );
+
+        let issues = validate(&spec);
+
+        assert_eq!(
+            issues
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec![
+                "warning[deprecated-reference] /operations/measure/messages/0: operation uses the deprecated message `#/channels/lights/messages/lightMeasured`",
+                "warning[deprecated-reference] /channels/lights/messages/lightMeasured: channel uses the deprecated message `#/components/messages/lightMeasured`",
+            ]
+        );
+    }
+
+    #[test]
+    fn deprecated_references_of_components() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Lights
+  version: 1.0.0
+components:
+  channels:
+    lights:
+      address: lights
+      description: Light measurements.
+      x-deprecated: true
+      messages:
+        lightMeasured:
+          $ref: '#/components/messages/lightMeasured'
+    dimmed:
+      address: dimmed
+      description: Dimmed lights.
+      messages: {}
+  operations:
+    measure:
+      action: send
+      description: Measures the lights.
+      channel:
+        $ref: '#/components/channels/dimmed'
+      reply:
+        channel:
+          $ref: '#/components/channels/lights'
+        messages:
+          - $ref: '#/components/channels/lights/messages/lightMeasured'
+  messages:
+    lightMeasured:
+      description: A measurement.
+      payload:
+        $ref: '#/components/schemas/measurement'
+  schemas:
+    measurement:
+      type: object
+      deprecated: true
+"#,
+        );
+
+        let issues = validate(&spec);
+
+        assert_eq!(
+            issues
+                .iter()
+                .filter(|issue| issue.code == "deprecated-reference")
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec![
+                "warning[deprecated-reference] /components/operations/measure/reply/channel: operation uses the deprecated channel `#/components/channels/lights`",
+                "warning[deprecated-reference] /components/messages/lightMeasured/payload: message uses the deprecated schema `#/components/schemas/measurement`",
+            ]
+        );
+    }
+
+    #[test]
+    fn uids_must_be_unique() {
+        let yaml = |second_uid: &str| {
+            format!(
+                r#"
+asyncapi: 3.0.0
+info:
+  title: Lights
+  version: 1.0.0
+components:
+  messages:
+    lightMeasured:
+      x-uid: light-measured
+    lightDimmed:
+      x-uid: {second_uid}
+"#
+            )
+        };
+
+        let unique = validate(&parse_spec(&yaml("light-dimmed")));
+        let colliding = validate(&parse_spec(&yaml("light-measured")));
+
+        assert!(unique.is_empty(), "{unique:?}");
+        assert_eq!(colliding.len(), 1);
+        assert_eq!(colliding[0].code, "duplicate-uid");
+        assert_eq!(
+            colliding[0].pointer.to_string(),
+            "/components/messages/lightMeasured/x-uid"
+        );
+    }
+
+    #[test]
+    fn message_examples_hold_the_correlation_id() {
+        let yaml = |second_headers: &str| {
+            format!(
+                r#"
+asyncapi: 3.0.0
+info:
+  title: Lights
+  version: 1.0.0
+components:
+  messages:
+    lightMeasured:
+      headers:
+        type: object
+        properties:
+          correlationId:
+            type: string
+      correlationId:
+        location: '$message.header#/correlationId'
+      examples:
+        - name: first
+          headers:
+            correlationId: '1'
+        - name: second
+          headers: {second_headers}
+          payload:
+            lumens: 3
+"#
+            )
+        };
+
+        let consistent = validate(&parse_spec(&yaml("{correlationId: '2'}")));
+        let missing = validate(&parse_spec(&yaml("{traceId: '2'}")));
+        let mistyped = validate(&parse_spec(&yaml("{correlationId: 2}")));
+
+        assert!(consistent.is_empty(), "{consistent:?}");
+        assert_eq!(
+            missing
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            ["warning[message-example] /components/messages/lightMeasured/examples/1: the example `second` has no value at `/correlationId` of its headers, the location of the correlation id"]
+        );
+        assert_eq!(mistyped.len(), 1);
+        assert_eq!(
+            mistyped[0].pointer.to_string(),
+            "/components/messages/lightMeasured/examples/1/headers/correlationId"
+        );
+    }
+
+    const MALFORMED_CONTENT_TYPES: &str = r#"
+asyncapi: 3.0.0
+info:
+  title: Lights
+  version: 1.0.0
+defaultContentType: application//json
+channels:
+  lights:
+    address: lights
+    messages:
+      lightMeasured:
+        contentType: application/json
+      lightDimmed:
+        contentType: application/json; charset="utf-8"
+      lightLogged:
+        contentType: application//json
+"#;
+
+    #[test]
+    fn content_types_are_media_types() {
+        let spec = parse_spec(MALFORMED_CONTENT_TYPES);
+
+        let issues = validate(&spec)
+            .into_iter()
+            .filter(|issue| issue.code == "content-type")
+            .map(|issue| issue.to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            issues,
+            [
+                "error[content-type] /defaultContentType: invalid media type `application//json`: the subtype is not a token",
+                "error[content-type] /channels/lights/messages/lightLogged/contentType: invalid media type `application//json`: the subtype is not a token",
+            ]
+        );
+    }
+
+    #[test]
+    fn reply_security_on_another_server() {
+        let yaml = |reply_scheme: &str| {
+            format!(
+                r#"
+asyncapi: 3.0.0
+info:
+  title: Lights
+  version: 1.0.0
+servers:
+  requests:
+    host: requests.example.com
+    protocol: mqtt
+    security:
+      - $ref: '#/components/securitySchemes/oauth'
+  replies:
+    host: replies.example.com
+    protocol: mqtt
+    security:
+      - {reply_scheme}
+channels:
+  commands:
+    address: commands
+    servers:
+      - $ref: '#/servers/requests'
+    messages: {{}}
+  results:
+    address: results
+    servers:
+      - $ref: '#/servers/replies'
+    messages: {{}}
+operations:
+  dim:
+    action: send
+    channel:
+      $ref: '#/channels/commands'
+    reply:
+      channel:
+        $ref: '#/channels/results'
+components:
+  securitySchemes:
+    oauth:
+      type: oauth2
+      flows:
+        clientCredentials:
+          tokenUrl: https://auth.example.com/token
+          availableScopes: {{}}
+"#
+            )
+        };
+        let reply_security = |spec: &AsyncApiV3Spec| {
+            validate(spec)
+                .into_iter()
+                .filter(|issue| issue.code == "reply-security")
+                .map(|issue| issue.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let matching = parse_spec(&yaml("$ref: '#/components/securitySchemes/oauth'"));
+        let certificate = parse_spec(&yaml("type: X509"));
+
+        assert!(reply_security(&matching).is_empty());
+        assert_eq!(
+            reply_security(&certificate),
+            ["warning[reply-security] /operations/dim/reply/channel: the servers of the reply channel require a `X509` security scheme, while the request side uses `oauth2`"]
+        );
+        let RefOr::Right(operation) = &certificate.operations["dim"] else {
+            panic!("the operation is inline");
+        };
+        let Some(RefOr::Right(reply)) = &operation.reply else {
+            panic!("the reply is inline");
+        };
+        let requirements = reply.security_requirements(&certificate);
+        assert_eq!(
+            requirements.servers.keys().collect::<Vec<_>>(),
+            [&"replies"]
+        );
+        assert_eq!(
+            operation
+                .security_requirements(&certificate)
+                .servers
+                .keys()
+                .collect::<Vec<_>>(),
+            [&"requests"]
+        );
+    }
+
+    #[test]
+    fn custom_rules_share_the_context() {
+        /// Every reference must point at the components.
+        struct ComponentReferences;
+
+        impl CustomRule for ComponentReferences {
+            fn code(&self) -> &str {
+                "component-reference"
+            }
+
+            fn check(&self, _: &AsyncApiV3Spec, context: &RuleContext) -> Vec<ValidationIssue> {
+                let components = JsonPointer::from_iter(["components"]);
+                context
+                    .references()
+                    .iter()
+                    .filter(|(_, target)| !target.starts_with(&components))
+                    .map(|(location, target)| ValidationIssue {
+                        code: String::from(self.code()),
+                        severity: Severity::Warning,
+                        pointer: location.clone(),
+                        message: format!("`{target}` is not a component"),
+                    })
+                    .collect()
+            }
+        }
+        struct Named(&'static str);
+
+        impl CustomRule for Named {
+            fn code(&self) -> &str {
+                self.0
+            }
+
+            fn check(&self, _: &AsyncApiV3Spec, _: &RuleContext) -> Vec<ValidationIssue> {
+                Vec::new()
+            }
+        }
+        let spec = parse_spec(INVALID_SPEC);
+        let options = ValidateOptions::new()
+            .with_rule(Box::new(ComponentReferences))
+            .unwrap();
+
+        let issues = validate_with(&spec, &options).unwrap();
+
+        let custom = issues
+            .iter()
+            .skip_while(|issue| issue.code != "component-reference")
+            .map(|issue| issue.pointer.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(issues.len() - custom.len(), validate(&spec).len());
+        assert_eq!(
+            custom,
+            [
+                "/channels/lightReplies/servers/0",
+                "/channels/lights/servers/0",
+                "/operations/turnOff/channel",
+                "/operations/turnOff/reply/channel",
+            ]
+        );
+        assert!(matches!(
+            options.with_rule(Box::new(Named("component-reference"))),
+            Err(Error::DuplicateRule { code }) if code == "component-reference"
+        ));
+        assert!(matches!(
+            ValidateOptions::new().with_rule(Box::new(Named("duplicate-uid"))),
+            Err(Error::DuplicateRule { .. })
+        ));
+    }
+
+    #[test]
+    fn catalogue_serialization() {
+        let catalogue = serde_json::to_value(rule_catalogue()).unwrap();
+
+        assert_eq!(
+            catalogue[0],
+            serde_json::json!({
+                "code": "unresolved-reference",
+                "summary": "Local references must point at an existing object.",
+                "defaultSeverity": "error",
+                "options": [],
+                "since": "0.2.0"
+            })
+        );
+    }
+
+    #[test]
+    fn validate_a_single_server() {
+        let spec = parse_spec(
+            r#"
+asyncapi: 3.0.0
+info:
+  title: Lights
+  version: 1.0.0
+servers:
+  production:
+    host: '{region}.example.com'
+    protocol: mqtt
+    description: Production broker.
+    security:
+      - $ref: '#/components/securitySchemes/oauth'
+    variables:
+      region:
+        enum: [eu, us]
+        examples: [ap]
+  staging:
+    host: staging.example.com
+    protocol: mqtt
+channels:
+  lights:
+    address: lights
+    messages: {}
+components:
+  securitySchemes:
+    oauth:
+      type: oauth2
+      flows: {}
+"#,
+        );
+
+        let issues = validate_node(&spec, "/servers/production");
+
+        assert_eq!(
+            issues
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            ["error[example-enum] /servers/production/variables/region/examples/0: example `ap` of the variable `region` of the server `production` is not a value of its enum"]
+        );
+        let staging = validate_node(&spec, "/servers/staging");
+        assert_eq!(staging.len(), 1);
+        assert_eq!(staging[0].code, "missing-description");
+        assert_eq!(
+            validate_node(&spec, "/components/securitySchemes/oauth").len(),
+            1
+        );
+        assert!(validate_node(&spec, "/servers/missing").is_empty());
+    }
+}
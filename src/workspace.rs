@@ -0,0 +1,823 @@
+//! Sets of related documents referencing each other.
+//!
+//! A [Workspace] holds named documents, e.g. one per service, and resolves references between
+//! them such as `billing.yaml#/components/messages/invoiceRequested`: the part before the `#`
+//! names the document, relative to the directory of the document containing the reference,
+//! which is looked up among the workspace members before being loaded through the workspace
+//! [Loader].
+use crate::error::Error;
+use crate::spec::common::{JsonPointer, RefOr, ReferenceObject};
+use crate::spec::resolve::Referenceable;
+use crate::spec::AsyncApiV3Spec;
+#[cfg(feature = "validation")]
+use crate::validation::{Severity, ValidationIssue};
+use alloc::boxed::Box;
+#[cfg(feature = "validation")]
+use alloc::collections::btree_map::Entry;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+#[cfg(feature = "validation")]
+use core::fmt;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// Sections of the root of a document whose entries can be moved to the components.
+const ROOT_SECTIONS: [&str; 3] = ["servers", "channels", "operations"];
+
+/// Loads the documents of a workspace which are not members yet.
+pub trait Loader {
+    /// Loads the document with the given name, as written before the `#` of references.
+    fn load(&self, name: &str) -> Result<AsyncApiV3Spec, Error>;
+}
+
+impl<F: Fn(&str) -> Result<AsyncApiV3Spec, Error>> Loader for F {
+    fn load(&self, name: &str) -> Result<AsyncApiV3Spec, Error> {
+        self(name)
+    }
+}
+
+/// Loads documents from the files of a directory, in YAML when their extension is `.yaml` or
+/// `.yml` (requires the `yaml` feature) and in JSON otherwise.
+///
+/// Fails with [Error::OutsideRoot] for the names leading out of the directory, e.g.
+/// `../secrets.yaml` or absolute paths.
+#[cfg(feature = "loader-fs")]
+#[cfg_attr(docsrs, doc(cfg(feature = "loader-fs")))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileLoader {
+    pub root: std::path::PathBuf,
+}
+
+#[cfg(feature = "loader-fs")]
+impl Loader for FileLoader {
+    fn load(&self, name: &str) -> Result<AsyncApiV3Spec, Error> {
+        use crate::spec::AsyncApiSpec;
+        use std::path::Component;
+
+        let mut path = self.root.clone();
+        for component in std::path::Path::new(name).components() {
+            match component {
+                Component::Normal(segment) => path.push(segment),
+                Component::CurDir => {}
+                Component::ParentDir if path != self.root => {
+                    path.pop();
+                }
+                _ => {
+                    return Err(Error::OutsideRoot {
+                        name: String::from(name),
+                    })
+                }
+            }
+        }
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("load", path = %path.display()).entered();
+        let document = std::fs::read_to_string(&path).map_err(|err| Error::Parse {
+            reason: format!("{}: {err}", path.display()),
+        })?;
+        let is_yaml = path
+            .extension()
+            .is_some_and(|extension| extension == "yaml" || extension == "yml");
+        let spec = if is_yaml {
+            #[cfg(feature = "yaml")]
+            {
+                AsyncApiSpec::from_yaml(&document)?
+            }
+            #[cfg(not(feature = "yaml"))]
+            return Err(Error::Parse {
+                reason: format!("{}: YAML requires the `yaml` feature", path.display()),
+            });
+        } else {
+            document.parse::<AsyncApiSpec>()?
+        };
+        let AsyncApiSpec::V3_0_0(spec) = spec;
+        Ok(spec)
+    }
+}
+
+/// A problem found by [Workspace::validate] in one of the documents.
+#[cfg(feature = "validation")]
+#[cfg_attr(docsrs, doc(cfg(feature = "validation")))]
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceIssue {
+    /// Name of the document the issue is located in.
+    pub document: String,
+    #[serde(flatten)]
+    pub issue: ValidationIssue,
+}
+
+#[cfg(feature = "validation")]
+impl fmt::Display for WorkspaceIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.document, self.issue)
+    }
+}
+
+/// A set of named documents, resolving the references between them.
+#[derive(Default)]
+pub struct Workspace {
+    documents: BTreeMap<String, AsyncApiV3Spec>,
+    loader: Option<Box<dyn Loader>>,
+}
+
+impl Workspace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the loader of the documents referenced but not added to the workspace.
+    pub fn with_loader(mut self, loader: impl Loader + 'static) -> Self {
+        self.loader = Some(Box::new(loader));
+        self
+    }
+
+    /// Adds a document, replacing the document with the same name if any.
+    pub fn insert(&mut self, name: &str, spec: AsyncApiV3Spec) -> Option<AsyncApiV3Spec> {
+        self.documents.insert(String::from(name), spec)
+    }
+
+    /// Returns the document with the given name.
+    pub fn get(&self, name: &str) -> Option<&AsyncApiV3Spec> {
+        self.documents.get(name)
+    }
+
+    /// Returns the documents of the workspace, keyed by name.
+    pub fn documents(&self) -> &BTreeMap<String, AsyncApiV3Spec> {
+        &self.documents
+    }
+
+    /// Resolves a reference written in the given document, following references through the
+    /// documents of the workspace. Returns the name of the document defining the object along
+    /// with the object.
+    ///
+    /// Referenced documents which are not members of the workspace are loaded and added to it.
+    /// Fails with [Error::UnresolvedReference] if there is no such document and no loader, or
+    /// if the object does not exist, and with [Error::CyclicReference] if the references loop.
+    pub fn resolve<T: Referenceable>(
+        &mut self,
+        document: &str,
+        reference: &ReferenceObject,
+    ) -> Result<(&str, &T), Error> {
+        while let Err(Missing::Document(name)) = self.locate::<T>(document, reference) {
+            let loader = self
+                .loader
+                .as_ref()
+                .ok_or_else(|| Error::UnresolvedReference {
+                    reference: reference.reference.clone(),
+                })?;
+            let spec = loader.load(&name)?;
+            self.documents.insert(name, spec);
+        }
+        self.locate(document, reference)
+            .map_err(|missing| match missing {
+                Missing::Document(_) => Error::UnresolvedReference {
+                    reference: reference.reference.clone(),
+                },
+                Missing::Object(err) => err,
+            })
+    }
+
+    fn locate<T: Referenceable>(
+        &self,
+        document: &str,
+        reference: &ReferenceObject,
+    ) -> Result<(&str, &T), Missing> {
+        let mut document = String::from(document);
+        let mut current = reference;
+        let mut visited: Vec<(String, &str)> = Vec::new();
+        loop {
+            let (name, fragment) = split_reference(&current.reference, &document);
+            let unresolved = || {
+                Missing::Object(Error::UnresolvedReference {
+                    reference: current.reference.clone(),
+                })
+            };
+            let Some((name, spec)) = self.documents.get_key_value(&name) else {
+                return Err(Missing::Document(name));
+            };
+            let pointer = JsonPointer::parse(fragment).map_err(|_| unresolved())?;
+            match T::lookup(spec, &pointer).ok_or_else(unresolved)? {
+                RefOr::Right(object) => return Ok((name, object)),
+                RefOr::Left(next) => {
+                    if visited.contains(&(name.clone(), &next.reference)) {
+                        return Err(Missing::Object(Error::CyclicReference {
+                            reference: next.reference.clone(),
+                        }));
+                    }
+                    visited.push((name.clone(), &next.reference));
+                    document = name.clone();
+                    current = next;
+                }
+            }
+        }
+    }
+
+    /// Checks the documents of the workspace together, reporting:
+    ///
+    /// - `dangling-cross-reference` errors for references to other documents which are not
+    ///   members of the workspace or do not contain the referenced value,
+    /// - `duplicate-channel-address` warnings for root channels of different documents using
+    ///   the same address, reported on every channel but the first one.
+    ///
+    /// Documents are not loaded: only the members of the workspace are considered.
+    #[cfg(feature = "validation")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "validation")))]
+    pub fn validate(&self) -> Vec<WorkspaceIssue> {
+        let values = self
+            .documents
+            .iter()
+            .filter_map(|(name, spec)| Some((name.as_str(), serde_json::to_value(spec).ok()?)))
+            .collect::<BTreeMap<_, _>>();
+        let mut issues = Vec::new();
+        for (name, value) in &values {
+            for (pointer, reference) in
+                crate::spec::common::collect_references(value, &JsonPointer::default())
+            {
+                let (target, fragment) = split_reference(reference, name);
+                if target == *name {
+                    continue;
+                }
+                let resolved = values.get(target.as_str()).is_some_and(|document| {
+                    JsonPointer::parse(fragment)
+                        .ok()
+                        .and_then(|fragment| fragment.evaluate(document))
+                        .is_some()
+                });
+                if !resolved {
+                    issues.push(issue(
+                        name,
+                        "dangling-cross-reference",
+                        Severity::Error,
+                        pointer,
+                        format!("reference `{reference}` does not resolve in the workspace"),
+                    ));
+                }
+            }
+        }
+
+        let mut addresses = BTreeMap::<&str, (&str, &str)>::new();
+        for (document, spec) in &self.documents {
+            for (channel_name, channel) in &spec.channels {
+                let Some(address) = channel
+                    .resolve(spec)
+                    .ok()
+                    .and_then(|channel| channel.address.as_deref())
+                else {
+                    continue;
+                };
+                match addresses.entry(address) {
+                    Entry::Vacant(entry) => {
+                        entry.insert((document, channel_name));
+                    }
+                    Entry::Occupied(entry) => {
+                        let (first_document, first_channel) = entry.get();
+                        if first_document == document {
+                            continue;
+                        }
+                        issues.push(issue(
+                            document,
+                            "duplicate-channel-address",
+                            Severity::Warning,
+                            JsonPointer::from_iter(["channels", channel_name.as_str(), "address"]),
+                            format!(
+                                "address `{address}` is also used by channel `{first_channel}` of `{first_document}`"
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+        issues
+    }
+
+    /// Produces a single document from a member of the workspace, importing every object it
+    /// references from other documents into its components.
+    ///
+    /// Imported objects keep their key unless it is already taken, in which case it is
+    /// prefixed by the name of their document, e.g. `billing_invoice` for an `invoice` of
+    /// `billing.yaml`. Root servers, channels and operations of other documents are imported
+    /// into the matching components section. Fails with [Error::UnresolvedReference] if a
+    /// reference to another document does not resolve among the workspace members.
+    pub fn bundle(&self, root_name: &str) -> Result<AsyncApiV3Spec, Error> {
+        let invalid = |err: serde_json::Error| Error::InvalidDocument {
+            reason: err.to_string(),
+            hints: Vec::new(),
+        };
+        let values = self
+            .documents
+            .iter()
+            .map(|(name, spec)| Ok((name.as_str(), serde_json::to_value(spec).map_err(invalid)?)))
+            .collect::<Result<BTreeMap<_, _>, Error>>()?;
+        let mut root =
+            values
+                .get(root_name)
+                .cloned()
+                .ok_or_else(|| Error::UnresolvedReference {
+                    reference: String::from(root_name),
+                })?;
+
+        let components = match root
+            .as_object_mut()
+            .and_then(|root| root.remove("components"))
+        {
+            Some(Value::Object(components)) => components,
+            _ => Map::new(),
+        };
+        // The components of the root keep their key, and their references are rewritten like
+        // the ones of the imported objects.
+        let pending = components
+            .iter()
+            .filter_map(|(section, entries)| Some((section, entries.as_object()?)))
+            .flat_map(|(section, entries)| {
+                entries
+                    .iter()
+                    .map(|(key, value)| (root_name, section.clone(), key.clone(), value.clone()))
+            })
+            .collect();
+        let mut bundler = Bundler {
+            root_name,
+            documents: &values,
+            components,
+            imported: BTreeMap::new(),
+            pending,
+        };
+        bundler.rewrite(&mut root, root_name)?;
+        while let Some((document, section, key, mut value)) = bundler.pending.pop() {
+            bundler.rewrite(&mut value, document)?;
+            bundler.section(&section).insert(key, value);
+        }
+        if let Value::Object(root) = &mut root {
+            root.insert(
+                String::from("components"),
+                Value::Object(bundler.components),
+            );
+        }
+        AsyncApiV3Spec::deserialize(&root).map_err(invalid)
+    }
+}
+
+/// Why a reference could not be located among the workspace members.
+enum Missing {
+    /// The document is not a member of the workspace.
+    Document(String),
+    Object(Error),
+}
+
+/// Splits a reference into the name of its document, `current` for local references, and its
+/// JSON pointer.
+/// Splits a reference written in the document named `current` into the name of the referenced
+/// document and the fragment.
+fn split_reference<'a>(reference: &'a str, current: &str) -> (String, &'a str) {
+    match reference.split_once('#') {
+        Some(("", fragment)) => (String::from(current), fragment),
+        Some((document, fragment)) => (document_name(current, document), fragment),
+        None => (document_name(current, reference), ""),
+    }
+}
+
+/// Returns the name of a document referenced from the document named `current`, resolving
+/// relative paths against the directory of `current`, e.g. `shared/common.yaml` for
+/// `../shared/common.yaml` referenced from `services/billing.yaml`.
+///
+/// The `..` leading out of the directory of the workspace are kept, for the loader to reject.
+fn document_name(current: &str, path: &str) -> String {
+    if path.contains("://") {
+        return String::from(path);
+    }
+    let mut segments: Vec<&str> = current.split('/').collect();
+    segments.pop();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." if matches!(segments.last(), Some(last) if *last != "..") => {
+                segments.pop();
+            }
+            _ => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+#[cfg(feature = "validation")]
+fn issue(
+    document: &str,
+    code: &str,
+    severity: Severity,
+    pointer: JsonPointer,
+    message: String,
+) -> WorkspaceIssue {
+    WorkspaceIssue {
+        document: String::from(document),
+        issue: ValidationIssue {
+            code: String::from(code),
+            severity,
+            pointer,
+            message,
+        },
+    }
+}
+
+struct Bundler<'a> {
+    root_name: &'a str,
+    documents: &'a BTreeMap<&'a str, Value>,
+    /// The components of the bundled document.
+    components: Map<String, Value>,
+    /// Key of each imported object in its components section, by document and pointer.
+    imported: BTreeMap<(&'a str, JsonPointer), String>,
+    /// Imported objects whose references still have to be rewritten.
+    pending: Vec<(&'a str, String, String, Value)>,
+}
+
+impl<'a> Bundler<'a> {
+    fn section(&mut self, section: &str) -> &mut Map<String, Value> {
+        let section = self
+            .components
+            .entry(section)
+            .or_insert_with(|| Value::Object(Map::new()));
+        if !section.is_object() {
+            *section = Value::Object(Map::new());
+        }
+        section.as_object_mut().expect("section is an object")
+    }
+
+    /// Rewrites the references of a value of the given document into references to the
+    /// bundled document, importing the objects of other documents.
+    fn rewrite(&mut self, value: &mut Value, document: &'a str) -> Result<(), Error> {
+        match value {
+            Value::Object(fields) => {
+                if let Some(Value::String(reference)) = fields.get_mut("$ref") {
+                    *reference = self.import(reference, document)?;
+                }
+                for (key, value) in fields.iter_mut() {
+                    if key != "$ref" {
+                        self.rewrite(value, document)?;
+                    }
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.rewrite(item, document)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Returns the reference to use in the bundled document, importing the referenced object.
+    fn import(&mut self, reference: &str, document: &'a str) -> Result<String, Error> {
+        let (target, fragment) = split_reference(reference, document);
+        if target == self.root_name {
+            return Ok(format!("#{fragment}"));
+        }
+        let unresolved = || Error::UnresolvedReference {
+            reference: String::from(reference),
+        };
+        let (target, value) = self
+            .documents
+            .get_key_value(target.as_str())
+            .ok_or_else(unresolved)?;
+        let pointer = JsonPointer::parse(fragment).map_err(|_| unresolved())?;
+        let (section, entry, rest) = match pointer.segments() {
+            [components, section, _, rest @ ..] if components == "components" => {
+                (section.clone(), &pointer.segments()[..3], rest)
+            }
+            [section, _, rest @ ..] if ROOT_SECTIONS.contains(&section.as_str()) => {
+                (section.clone(), &pointer.segments()[..2], rest)
+            }
+            _ => return Err(unresolved()),
+        };
+        let entry = entry.iter().cloned().collect::<JsonPointer>();
+        let key = match self.imported.get(&(*target, entry.clone())) {
+            Some(key) => key.clone(),
+            None => {
+                let object = entry.evaluate(value).ok_or_else(unresolved)?.clone();
+                let name = entry.segments().last().cloned().unwrap_or_default();
+                let key = self.free_key(&section, &name, target);
+                self.section(&section).insert(key.clone(), Value::Null);
+                self.imported.insert((*target, entry), key.clone());
+                self.pending
+                    .push((*target, section.clone(), key.clone(), object));
+                key
+            }
+        };
+        Ok(format!(
+            "#{}",
+            ["components", section.as_str(), key.as_str()]
+                .into_iter()
+                .map(String::from)
+                .chain(rest.iter().cloned())
+                .collect::<JsonPointer>()
+        ))
+    }
+
+    /// Returns a key of the components section not used yet.
+    fn free_key(&mut self, section: &str, name: &str, document: &str) -> String {
+        let taken = |bundler: &mut Self, key: &str| bundler.section(section).contains_key(key);
+        if !taken(self, name) {
+            return String::from(name);
+        }
+        let stem = document
+            .split('.')
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect::<String>();
+        let prefixed = format!("{stem}_{name}");
+        let mut key = prefixed.clone();
+        let mut suffix = 2;
+        while taken(self, &key) {
+            key = format!("{prefixed}_{suffix}");
+            suffix += 1;
+        }
+        key
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::spec::component::ComponentSection;
+    use crate::spec::library::ComponentSelection;
+    use crate::spec::message::Message;
+    use crate::test_util::parse_spec;
+
+    const ORDERS: &str = r#"
+asyncapi: 3.0.0
+info:
+  title: Orders
+  version: 1.0.0
+channels:
+  orders:
+    address: orders
+    messages:
+      invoiceRequested:
+        $ref: 'billing.yaml#/components/messages/invoiceRequested'
+      orderPlaced:
+        $ref: '#/components/messages/orderPlaced'
+components:
+  messages:
+    orderPlaced:
+      payload:
+        $ref: '#/components/schemas/invoice'
+  schemas:
+    invoice:
+      type: string
+"#;
+
+    const BILLING: &str = r#"
+asyncapi: 3.0.0
+info:
+  title: Billing
+  version: 1.0.0
+channels:
+  invoices:
+    address: orders
+    messages:
+      orderPlaced:
+        $ref: 'orders.yaml#/components/messages/orderPlaced'
+components:
+  messages:
+    invoiceRequested:
+      contentType: application/json
+      payload:
+        $ref: '#/components/schemas/invoice'
+  schemas:
+    invoice:
+      type: object
+"#;
+
+    fn workspace() -> Workspace {
+        let mut workspace = Workspace::new();
+        workspace.insert("orders.yaml", parse_spec(ORDERS));
+        workspace.insert("billing.yaml", parse_spec(BILLING));
+        workspace
+    }
+
+    fn reference(reference: &str) -> ReferenceObject {
+        ReferenceObject::new(reference)
+    }
+
+    #[test]
+    fn resolve_across_documents() {
+        let mut workspace =
+            workspace().with_loader(|name: &str| -> Result<AsyncApiV3Spec, Error> {
+                panic!("{name} is a member and should not be loaded")
+            });
+
+        let (document, message) = workspace
+            .resolve::<Message>(
+                "orders.yaml",
+                &reference("#/channels/orders/messages/invoiceRequested"),
+            )
+            .unwrap();
+
+        assert_eq!(document, "billing.yaml");
+        assert_eq!(message.content_type.as_deref(), Some("application/json"));
+    }
+
+    #[test]
+    fn load_missing_documents() {
+        let mut workspace = Workspace::new().with_loader(|name: &str| match name {
+            "billing.yaml" => Ok(parse_spec(BILLING)),
+            _ => Err(Error::Parse {
+                reason: format!("no document {name}"),
+            }),
+        });
+        workspace.insert("orders.yaml", parse_spec(ORDERS));
+
+        let (document, _) = workspace
+            .resolve::<Message>(
+                "orders.yaml",
+                &reference("billing.yaml#/components/messages/invoiceRequested"),
+            )
+            .unwrap();
+
+        assert_eq!(document, "billing.yaml");
+        assert!(workspace.get("billing.yaml").is_some());
+        assert!(matches!(
+            workspace.resolve::<Message>("orders.yaml", &reference("shipping.yaml#/x")),
+            Err(Error::Parse { .. })
+        ));
+    }
+
+    #[test]
+    fn resolve_relative_to_the_referencing_document() {
+        let billing = BILLING.replace(
+            "      contentType: application/json\n      payload:\n        $ref: '#/components/schemas/invoice'\n",
+            "      $ref: 'common.yaml#/components/messages/invoice'\n",
+        );
+        let mut workspace = Workspace::new().with_loader(move |name: &str| match name {
+            "shared/billing.yaml" => Ok(parse_spec(&billing)),
+            "shared/common.yaml" => Ok(parse_spec(
+                BILLING.replace("invoiceRequested:", "invoice:").as_str(),
+            )),
+            _ => Err(Error::Parse {
+                reason: format!("no document {name}"),
+            }),
+        });
+        workspace.insert("services/orders.yaml", parse_spec(ORDERS));
+
+        let (document, message) = workspace
+            .resolve::<Message>(
+                "services/orders.yaml",
+                &reference("../shared/./billing.yaml#/components/messages/invoiceRequested"),
+            )
+            .unwrap();
+
+        assert_eq!(document, "shared/common.yaml");
+        assert_eq!(message.content_type.as_deref(), Some("application/json"));
+        assert_eq!(
+            document_name("services/orders.yaml", "../../etc/passwd"),
+            "../etc/passwd"
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "loader-fs", feature = "yaml"))]
+    fn file_loader_stays_inside_its_root() {
+        let loader = FileLoader {
+            root: std::path::PathBuf::from("test-res/3.0.0"),
+        };
+
+        let spec = loader
+            .load("./nested/../streetlights-kafka-asyncapi.yml")
+            .unwrap();
+
+        assert_eq!(spec.info.title, "Streetlights Kafka API");
+        for name in [
+            "../../etc/x",
+            "../3.0.0/streetlights-kafka-asyncapi.yml",
+            "/etc/x",
+        ] {
+            assert!(matches!(
+                loader.load(name),
+                Err(Error::OutsideRoot { name: outside }) if outside == name
+            ));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "validation")]
+    fn duplicate_addresses_and_dangling_references() {
+        let mut workspace = workspace();
+
+        assert_eq!(
+            workspace
+                .validate()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec!["orders.yaml: warning[duplicate-channel-address] /channels/orders/address: address `orders` is also used by channel `invoices` of `billing.yaml`"]
+        );
+
+        workspace.documents.remove("billing.yaml");
+
+        let issues = workspace.validate();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].issue.code, "dangling-cross-reference");
+        assert_eq!(
+            issues[0].issue.pointer.to_string(),
+            "/channels/orders/messages/invoiceRequested"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "validation")]
+    fn bundle_imports_referenced_objects() {
+        let workspace = workspace();
+
+        let bundled = workspace.bundle("orders.yaml").unwrap();
+
+        let channel = bundled.channels["orders"].resolve(&bundled).unwrap();
+        let RefOr::Left(imported) = &channel.messages["invoiceRequested"] else {
+            panic!("imported message should be referenced");
+        };
+        assert_eq!(imported.reference, "#/components/messages/invoiceRequested");
+        let message = imported.resolve::<Message>(&bundled).unwrap();
+        assert_eq!(
+            serde_json::to_value(message.payload.as_ref().unwrap()).unwrap(),
+            serde_json::json!({ "$ref": "#/components/schemas/billing_invoice" })
+        );
+        assert_eq!(
+            serde_json::to_value(&bundled.components.schemas).unwrap(),
+            serde_json::json!({
+                "invoice": { "type": "string" },
+                "billing_invoice": { "type": "object" }
+            })
+        );
+        assert!(crate::validation::validate(&bundled)
+            .iter()
+            .all(|issue| issue.code != "unresolved-reference"));
+    }
+
+    #[test]
+    fn bundle_rewrites_references_of_root_components() {
+        let mut workspace = workspace();
+        workspace.insert(
+            "orders.yaml",
+            parse_spec(&ORDERS.replace(
+                "  schemas:\n",
+                "    invoiceSent:\n      payload:\n        $ref: 'billing.yaml#/components/schemas/invoice'\n  schemas:\n",
+            )),
+        );
+
+        let bundled = workspace.bundle("orders.yaml").unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&bundled.components.messages).unwrap()["invoiceSent"]["payload"],
+            serde_json::json!({ "$ref": "#/components/schemas/billing_invoice" })
+        );
+        assert_eq!(
+            serde_json::to_value(&bundled.components.schemas).unwrap()["billing_invoice"],
+            serde_json::json!({ "type": "object" })
+        );
+    }
+
+    const LIBRARY_ORDERS: &str = include_str!("../test-res/library/orders.yaml");
+
+    #[test]
+    fn bundle_extracted_library() {
+        let spec = parse_spec(LIBRARY_ORDERS);
+        let selection = ComponentSelection::new()
+            .section(ComponentSection::Messages)
+            .entry(ComponentSection::Schemas, "order")
+            .entry(ComponentSection::Schemas, "address");
+
+        let (library, rewritten) = spec.extract_components(&selection, "common.yaml").unwrap();
+
+        assert_eq!(library.info.title, "Orders components");
+        assert_eq!(
+            serde_json::to_value(&library.components).unwrap(),
+            serde_json::json!({
+                "messages": spec.components.messages,
+                "schemas": spec.components.schemas,
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(&rewritten.channels).unwrap()["orders"]["messages"]["orderPlaced"]
+                ["$ref"],
+            "common.yaml#/components/messages/orderPlaced"
+        );
+        assert!(rewritten.components.schemas.is_empty());
+        assert_eq!(rewritten.components.operation_traits.len(), 1);
+
+        let mut workspace = Workspace::new();
+        workspace.insert("app.yaml", rewritten);
+        workspace.insert("common.yaml", library);
+        let bundled = workspace.bundle("app.yaml").unwrap();
+        assert_eq!(
+            serde_json::to_value(&bundled).unwrap(),
+            serde_json::to_value(&spec).unwrap()
+        );
+    }
+}
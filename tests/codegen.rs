@@ -0,0 +1,84 @@
+use asyncapiv3::codegen::{conformance_tests, ConformanceGenOptions};
+use asyncapiv3::error::Error;
+use asyncapiv3::spec::{AsyncApiSpec, AsyncApiV3Spec};
+use serde_json::{json, Value};
+
+/// The consumer the generated tests hand the examples to.
+pub trait Handler {
+    fn on_light_measured(&mut self, payload: &Value);
+}
+
+#[derive(Default)]
+pub struct Dashboard {
+    lumens: Vec<u64>,
+}
+
+impl Handler for Dashboard {
+    fn on_light_measured(&mut self, payload: &Value) {
+        self.lumens.push(payload["lumens"].as_u64().unwrap());
+        assert!(self.lumens.len() <= 2);
+    }
+}
+
+fn handler() -> Dashboard {
+    Dashboard::default()
+}
+
+/// Generated from `codegen/lights.json` with [options], compiled and run with these tests.
+#[path = "codegen/generated.rs"]
+#[rustfmt::skip]
+mod generated;
+
+fn options() -> ConformanceGenOptions {
+    ConformanceGenOptions {
+        ignore: false,
+        ..ConformanceGenOptions::default()
+    }
+}
+
+#[test]
+fn generated_tests_are_up_to_date() {
+    let AsyncApiSpec::V3_0_0(spec) = include_str!("codegen/lights.json").parse().unwrap();
+
+    let code = conformance_tests(&spec, &options()).unwrap();
+
+    assert_eq!(code, include_str!("codegen/generated.rs"));
+}
+
+fn lights_with_operation(key: &str, operation: Value) -> AsyncApiV3Spec {
+    let mut document = serde_json::from_str::<Value>(include_str!("codegen/lights.json")).unwrap();
+    document["operations"][key] = operation;
+    let AsyncApiSpec::V3_0_0(spec) = serde_json::from_value(document).unwrap();
+    spec
+}
+
+#[test]
+fn conflicting_identifiers_are_rejected() {
+    let spec = lights_with_operation(
+        "on_light_measured",
+        json!({ "action": "receive", "channel": { "$ref": "#/channels/lightMeasured" } }),
+    );
+
+    let err = conformance_tests(&spec, &options()).unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        "Conflicting identifier 'on_light_measured' of onLightMeasured, on_light_measured"
+    );
+}
+
+#[test]
+fn operations_of_component_channels_are_rejected() {
+    let spec = lights_with_operation(
+        "onDimmed",
+        json!({ "action": "receive", "channel": { "$ref": "#/components/channels/dimmed" } }),
+    );
+
+    let err = conformance_tests(&spec, &options()).unwrap_err();
+
+    assert!(matches!(
+        err,
+        Error::NotRootChannel { operation, reference }
+            if operation == "onDimmed" && reference == "#/components/channels/dimmed"
+    ));
+}
@@ -0,0 +1,33 @@
+// Generated by asyncapiv3::codegen::conformance_tests, do not edit.
+use asyncapiv3::spec::schema::SchemaValidator;
+use asyncapiv3::spec::AsyncApiV3Spec;
+
+const DOCUMENT: &str = r##"{"info":{"title":"Lights","version":"1.0.0","tags":[]},"servers":{},"channels":{"lightMeasured":{"address":"lights.measured","messages":{"measurement":{"payload":{"$ref":"#/components/schemas/measurement"},"tags":[],"examples":[{"payload":{"lumens":3,"note":"a \"quoted\"# note"},"name":"dim"},{"payload":{"lumens":800},"name":"bright"}],"traits":[]}},"servers":[],"parameters":{},"tags":[]},"lightsOn":{"address":"lights.on","messages":{"turnOn":{"payload":{"type":"object"},"tags":[],"examples":[],"traits":[]}},"servers":[],"parameters":{},"tags":[]}},"operations":{"onLightMeasured":{"action":"receive","channel":{"$ref":"#/channels/lightMeasured"}},"turnOn":{"action":"send","channel":{"$ref":"#/channels/lightsOn"}}},"components":{"schemas":{"measurement":{"type":"object","properties":{"lumens":{"type":"integer","minimum":0}},"required":["lumens"]}}}}"##;
+
+fn spec() -> AsyncApiV3Spec {
+    serde_json::from_str(DOCUMENT).unwrap()
+}
+
+/// Consumers of the channel `lightMeasured`.
+mod light_measured {
+    use super::*;
+
+    /// Receives the examples of the operation `onLightMeasured`.
+    #[test]
+    fn on_light_measured() {
+        let examples: [(&str, &str); 2] = [
+            (r#"{"properties":{"lumens":{"minimum":0,"type":"integer"}},"required":["lumens"],"type":"object"}"#, r##"{"lumens":3,"note":"a \"quoted\"# note"}"##),
+            (r#"{"properties":{"lumens":{"minimum":0,"type":"integer"}},"required":["lumens"],"type":"object"}"#, r#"{"lumens":800}"#),
+        ];
+        let spec = spec();
+        let validator = SchemaValidator::new(&spec);
+        let mut handler = crate::handler();
+        for (schema, payload) in examples {
+            let schema = serde_json::from_str(schema).unwrap();
+            let payload = serde_json::from_str(payload).unwrap();
+            let violations = validator.validate(&schema, &payload);
+            assert!(violations.is_empty(), "{violations:?}");
+            crate::Handler::on_light_measured(&mut handler, &payload);
+        }
+    }
+}
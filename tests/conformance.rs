@@ -0,0 +1,21 @@
+use asyncapiv3::conformance;
+use std::env;
+use std::fs;
+
+/// Runs the corpus given by `ASYNCAPI_EXAMPLES_DIR`, e.g. the `examples` directory of a checkout
+/// of the AsyncAPI specification repository, or the vendored examples, and fails when a
+/// document of the baseline no longer passes.
+#[test]
+fn official_examples_conformance() {
+    let dir = env::var("ASYNCAPI_EXAMPLES_DIR").unwrap_or_else(|_| String::from("test-res/3.0.0"));
+    let report = conformance::run(&dir).unwrap();
+    println!("{report}");
+    assert!(!report.files.is_empty(), "no document found in {dir}");
+
+    let baseline = fs::read_to_string("test-res/conformance/passing.txt").unwrap();
+    let regressions = report.regressions(baseline.lines().filter(|line| !line.is_empty()));
+    assert!(
+        regressions.is_empty(),
+        "documents no longer passing: {regressions:?}"
+    );
+}
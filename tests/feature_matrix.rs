@@ -0,0 +1,50 @@
+use std::path::Path;
+use std::process::Command;
+
+/// Feature combinations the library must build with, each on top of `--no-default-features`.
+const COMBINATIONS: &[&str] = &[
+    "",
+    "std",
+    "writer",
+    "validation",
+    "yaml",
+    "loader-fs",
+    "validation,yaml",
+    "protobuf,borrowed",
+    "tracing",
+    "anonymize",
+    "conformance",
+    "docgen",
+    "codegen",
+    "macros",
+    "axum",
+    "actix",
+    "streaming",
+    "fuzzing",
+];
+
+/// Builds the library with every combination, catching code of a feature relying on another
+/// one. Slow, so only run on demand: `cargo test --test feature_matrix -- --ignored`.
+#[test]
+#[ignore = "builds the crate once per feature combination"]
+fn features_build_independently() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let failures = COMBINATIONS
+        .iter()
+        .filter(|features| {
+            let status = Command::new(env!("CARGO"))
+                .current_dir(manifest_dir)
+                .args(["check", "--lib", "--no-default-features", "--features"])
+                .arg(features)
+                .env(
+                    "CARGO_TARGET_DIR",
+                    manifest_dir.join("target/feature-matrix"),
+                )
+                .env("RUSTFLAGS", "-D warnings")
+                .status()
+                .unwrap();
+            !status.success()
+        })
+        .collect::<Vec<_>>();
+    assert!(failures.is_empty(), "failing combinations: {failures:?}");
+}
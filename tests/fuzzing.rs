@@ -0,0 +1,32 @@
+use asyncapiv3::fuzzing::{check_lenient, fuzz_documents, fuzz_lenient};
+use proptest::collection::vec;
+use proptest::prelude::*;
+use std::fs;
+use std::path::Path;
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(512))]
+
+    #[test]
+    fn generated_documents_round_trip(data in vec(any::<u8>(), 0..4096)) {
+        fuzz_documents(&data);
+    }
+
+    #[test]
+    fn lenient_parsing_agrees_with_strict_parsing(data in vec(any::<u8>(), 0..4096)) {
+        fuzz_lenient(&data);
+    }
+}
+
+/// The counterexamples found by the harness, kept as regression fixtures.
+#[test]
+fn regression_fixtures() {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fuzzing");
+    for entry in fs::read_dir(fixtures).unwrap() {
+        let path = entry.unwrap().path();
+        let input = fs::read_to_string(&path).unwrap();
+        if let Err(err) = check_lenient(&input) {
+            panic!("{}: {err}", path.display());
+        }
+    }
+}
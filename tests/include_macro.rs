@@ -0,0 +1,55 @@
+#![cfg(feature = "macros")]
+
+use asyncapiv3::asyncapi_include;
+use asyncapiv3::spec::AsyncApiV3Spec;
+use std::sync::LazyLock;
+
+static STREETLIGHTS: LazyLock<AsyncApiV3Spec> =
+    asyncapi_include!("../test-res/3.0.0/streetlights-kafka-asyncapi.yml");
+
+#[test]
+fn include_valid_spec() {
+    assert_eq!(STREETLIGHTS.info.title, "Streetlights Kafka API");
+    assert!(STREETLIGHTS
+        .operations
+        .contains_key("receiveLightMeasurement"));
+}
+
+#[test]
+fn include_examples() {
+    // The examples are all valid, so none may be rejected by the checks of the macro.
+    let specs: [LazyLock<AsyncApiV3Spec>; 19] = [
+        asyncapi_include!("../test-res/3.0.0/adeo-kafka-request-reply-asyncapi.yml"),
+        asyncapi_include!("../test-res/3.0.0/anyof-asyncapi.yml"),
+        asyncapi_include!("../test-res/3.0.0/application-headers-asyncapi.yml"),
+        asyncapi_include!("../test-res/3.0.0/correlation-id-asyncapi.yml"),
+        asyncapi_include!("../test-res/3.0.0/gitter-streaming-asyncapi.yml"),
+        asyncapi_include!(
+            "../test-res/3.0.0/kraken-websocket-request-reply-message-filter-in-reply-asyncapi.yml"
+        ),
+        asyncapi_include!(
+            "../test-res/3.0.0/kraken-websocket-request-reply-multiple-channels-asyncapi.yml"
+        ),
+        asyncapi_include!("../test-res/3.0.0/mercure-asyncapi.yml"),
+        asyncapi_include!("../test-res/3.0.0/not-asyncapi.yml"),
+        asyncapi_include!("../test-res/3.0.0/oneof-asyncapi.yml"),
+        asyncapi_include!("../test-res/3.0.0/operation-security-asyncapi.yml"),
+        asyncapi_include!("../test-res/3.0.0/rpc-client-asyncapi.yml"),
+        asyncapi_include!("../test-res/3.0.0/rpc-server-asyncapi.yml"),
+        asyncapi_include!("../test-res/3.0.0/simple-asyncapi.yml"),
+        asyncapi_include!("../test-res/3.0.0/slack-rtm-asyncapi.yml"),
+        asyncapi_include!("../test-res/3.0.0/streetlights-kafka-asyncapi.yml"),
+        asyncapi_include!("../test-res/3.0.0/streetlights-mqtt-asyncapi.yml"),
+        asyncapi_include!("../test-res/3.0.0/streetlights-operation-security-asyncapi.yml"),
+        asyncapi_include!("../test-res/3.0.0/websocket-gemini-asyncapi.yml"),
+    ];
+
+    for spec in &specs {
+        LazyLock::force(spec);
+    }
+}
+
+#[test]
+fn reject_invalid_spec_at_compile_time() {
+    trybuild::TestCases::new().compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,77 @@
+use actix_web::body::to_bytes;
+use actix_web::http::{header, StatusCode};
+use actix_web::test::TestRequest;
+use actix_web::web::Bytes;
+use actix_web::{HttpRequest, HttpResponse};
+use asyncapiv3::serve::actix::spec_handler;
+use asyncapiv3::spec::AsyncApiSpec;
+use std::fs;
+use std::future::Future;
+use std::pin::pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+
+fn handler() -> impl Fn(HttpRequest) -> HttpResponse {
+    let document = fs::read_to_string("test-res/3.0.0/streetlights-kafka-asyncapi.yml").unwrap();
+    let handler = spec_handler(Arc::new(serde_yaml::from_str(&document).unwrap()));
+    move |request| handler(request).into_inner()
+}
+
+/// Reads the body of a response, which the handler builds in memory.
+fn body(response: HttpResponse) -> Bytes {
+    let mut read = pin!(to_bytes(response.into_body()));
+    match read.as_mut().poll(&mut Context::from_waker(Waker::noop())) {
+        Poll::Ready(Ok(body)) => body,
+        Poll::Ready(Err(err)) => panic!("{err}"),
+        Poll::Pending => panic!("the body is not in memory"),
+    }
+}
+
+#[test]
+fn negotiate_json_and_yaml() {
+    let handler = handler();
+
+    let json = handler(TestRequest::get().uri("/asyncapi").to_http_request());
+    let yaml = handler(
+        TestRequest::get()
+            .uri("/asyncapi")
+            .insert_header((header::ACCEPT, "application/yaml"))
+            .to_http_request(),
+    );
+
+    assert_eq!(json.status(), StatusCode::OK);
+    assert_eq!(
+        json.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/json"
+    );
+    serde_json::from_slice::<AsyncApiSpec>(&body(json)).unwrap();
+    assert_eq!(
+        yaml.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/yaml"
+    );
+    serde_yaml::from_slice::<AsyncApiSpec>(&body(yaml)).unwrap();
+}
+
+#[test]
+fn unchanged_document_is_not_sent_again() {
+    let handler = handler();
+
+    let first = handler(TestRequest::get().uri("/asyncapi").to_http_request());
+    let etag = first
+        .headers()
+        .get(header::ETAG)
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_owned();
+    let second = handler(
+        TestRequest::get()
+            .uri("/asyncapi")
+            .insert_header((header::IF_NONE_MATCH, etag.as_str()))
+            .to_http_request(),
+    );
+
+    assert_eq!(second.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(second.headers().get(header::ETAG).unwrap(), etag.as_str());
+    assert!(body(second).is_empty());
+}
@@ -0,0 +1,63 @@
+use asyncapiv3::serve::axum::spec_handler;
+use asyncapiv3::spec::AsyncApiSpec;
+use axum::body::{to_bytes, Bytes};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::Response;
+use std::fs;
+use std::future::Future;
+use std::pin::pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+
+fn handler() -> impl Fn(HeaderMap) -> Response {
+    let document = fs::read_to_string("test-res/3.0.0/streetlights-kafka-asyncapi.yml").unwrap();
+    let spec = serde_yaml::from_str::<AsyncApiSpec>(&document).unwrap();
+    let handler = spec_handler(Arc::new(spec));
+    move |headers| handler(headers).into_inner()
+}
+
+fn headers(headers: &[(header::HeaderName, &str)]) -> HeaderMap {
+    headers
+        .iter()
+        .map(|(name, value)| (name.clone(), HeaderValue::from_str(value).unwrap()))
+        .collect()
+}
+
+/// Reads the body of a response, which the handler builds in memory.
+fn body(response: Response) -> Bytes {
+    let mut read = pin!(to_bytes(response.into_body(), usize::MAX));
+    match read.as_mut().poll(&mut Context::from_waker(Waker::noop())) {
+        Poll::Ready(body) => body.unwrap(),
+        Poll::Pending => panic!("the body is not in memory"),
+    }
+}
+
+#[test]
+fn negotiate_json_and_yaml() {
+    let handler = handler();
+
+    let json = handler(headers(&[]));
+    let yaml = handler(headers(&[(
+        header::ACCEPT,
+        "text/yaml, application/json;q=0.5",
+    )]));
+
+    assert_eq!(json.status(), StatusCode::OK);
+    assert_eq!(json.headers()[header::CONTENT_TYPE], "application/json");
+    serde_json::from_slice::<AsyncApiSpec>(&body(json)).unwrap();
+    assert_eq!(yaml.headers()[header::CONTENT_TYPE], "text/yaml");
+    serde_yaml::from_slice::<AsyncApiSpec>(&body(yaml)).unwrap();
+}
+
+#[test]
+fn unchanged_document_is_not_sent_again() {
+    let handler = handler();
+
+    let first = handler(headers(&[]));
+    let etag = first.headers()[header::ETAG].to_str().unwrap().to_owned();
+    let second = handler(headers(&[(header::IF_NONE_MATCH, &etag)]));
+
+    assert_eq!(second.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(second.headers()[header::ETAG], etag.as_str());
+    assert!(body(second).is_empty());
+}
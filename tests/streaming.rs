@@ -0,0 +1,88 @@
+use asyncapiv3::spec::partial::ParseOptions;
+use asyncapiv3::spec::AsyncApiV3Spec;
+use asyncapiv3::streaming::SectionFilter;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Tracks the bytes allocated at any time and the peak since the last reset.
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let pointer = System.alloc(layout);
+        if !pointer.is_null() {
+            let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            PEAK.fetch_max(allocated, Ordering::SeqCst);
+        }
+        pointer
+    }
+
+    unsafe fn dealloc(&self, pointer: *mut u8, layout: Layout) {
+        System.dealloc(pointer, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// A document bundling thousands of channels, each with a message and its payload schema.
+fn large_document(channels: usize) -> String {
+    let mut yaml = String::from(
+        "asyncapi: 3.0.0\ninfo:\n  title: Fleet\n  version: 1.0.0\nservers:\n  production:\n    \
+         host: broker.example.com\n    protocol: kafka\nchannels:\n",
+    );
+    for channel in 0..channels {
+        writeln!(
+            yaml,
+            "  vehicle{channel}:\n    address: fleet.vehicle.{channel}"
+        )
+        .unwrap();
+        writeln!(yaml, "    messages:\n      position:\n        payload:").unwrap();
+        writeln!(yaml, "          type: object\n          properties:").unwrap();
+        for property in 0..40 {
+            writeln!(
+                yaml,
+                "            sensor{property}:\n              type: number\n              \
+                 description: Reading of the sensor {property} of the vehicle {channel}."
+            )
+            .unwrap();
+        }
+    }
+    yaml
+}
+
+#[test]
+fn filtered_parsing_stays_under_the_allocation_threshold() {
+    let input = large_document(2_000);
+    let filter = SectionFilter::new()
+        .section("info")
+        .section("servers")
+        .keys("channels");
+
+    let baseline = ALLOCATED.load(Ordering::SeqCst);
+    PEAK.store(baseline, Ordering::SeqCst);
+    let (document, issues) =
+        AsyncApiV3Spec::parse_yaml_filtered(input.as_bytes(), &filter, &ParseOptions::default())
+            .unwrap();
+    let peak = PEAK.load(Ordering::SeqCst) - baseline;
+
+    assert!(issues.is_empty(), "{issues:?}");
+    assert_eq!(document.info.parsed().unwrap().title, "Fleet");
+    assert_eq!(document.servers.len(), 1);
+    assert_eq!(document.skipped.len(), 2_000);
+    assert!(
+        input.len() > 8 * 1024 * 1024,
+        "the fixture is {} bytes",
+        input.len()
+    );
+    assert!(
+        peak < 1024 * 1024,
+        "parsing {} bytes allocated up to {peak} bytes",
+        input.len()
+    );
+}
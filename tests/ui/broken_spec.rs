@@ -0,0 +1,9 @@
+use asyncapiv3::asyncapi_include;
+use asyncapiv3::spec::AsyncApiV3Spec;
+use std::sync::LazyLock;
+
+static BROKEN: LazyLock<AsyncApiV3Spec> = asyncapi_include!("broken_spec.yaml");
+
+fn main() {
+    println!("{}", BROKEN.info.title);
+}
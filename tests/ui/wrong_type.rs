@@ -0,0 +1,9 @@
+use asyncapiv3::asyncapi_include;
+use asyncapiv3::spec::AsyncApiV3Spec;
+use std::sync::LazyLock;
+
+static WRONG_TYPE: LazyLock<AsyncApiV3Spec> = asyncapi_include!("wrong_type.yaml");
+
+fn main() {
+    println!("{}", WRONG_TYPE.info.title);
+}